@@ -0,0 +1,211 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+//!
+//! Reference external driver: serves the tables stored in a JSON file (`{ "table": [{ ...row }] }`),
+//! the connection string is the file's path. Only a small subset of SQL is understood:
+//! - `SELECT * FROM table [WHERE column = ?]`
+//! - `INSERT INTO table (column, ...) VALUES (?, ...)`
+//! - `DELETE FROM table WHERE column = ?`
+//!
+//! The schema is inferred from the rows, the `id` column (if present) is considered the primary key.
+//! Build it with `cargo build --example json_driver` and reference it from the `project.toml`:
+//! ```toml
+//! [databases.connection.ExternalDBConnectionConfig]
+//! id = "json"
+//! connection = "./data.json"
+//! command = "./target/debug/examples/json_driver"
+//! ```
+//!
+
+use waveless_commons::{databases::external::*, schema::*, *};
+
+use rustyrosetta::*;
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::{Result, anyhow, bail};
+use async_trait::async_trait;
+use compact_str::*;
+use serde_json::{Map, Value};
+use tokio::sync::RwLock;
+
+#[derive(Default)]
+struct JsonDriver {
+    path: RwLock<Option<PathBuf>>,
+}
+
+impl JsonDriver {
+    async fn load(&self) -> Result<Map<String, Value>> {
+        let path = self.path.read().await.to_owned();
+        let path = path.ok_or(anyhow!("The driver hasn't been initialized."))?;
+
+        match tokio::fs::read(&path).await {
+            Ok(buffer) => Ok(serde_json::from_slice(&buffer)?),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Map::new()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn store(&self, tables: &Map<String, Value>) -> Result<()> {
+        let path = self.path.read().await.to_owned();
+        let path = path.ok_or(anyhow!("The driver hasn't been initialized."))?;
+
+        tokio::fs::write(path, serde_json::to_vec_pretty(tables)?).await?;
+
+        Ok(())
+    }
+}
+
+/// Splits `column = ?` returning the column's name.
+fn where_column(clause: &str) -> Result<&str> {
+    let (column, placeholder) = clause
+        .split_once('=')
+        .ok_or(anyhow!("Only `column = ?` conditions are supported."))?;
+
+    if placeholder.trim() != "?" {
+        bail!("Only `column = ?` conditions are supported.")
+    }
+
+    Ok(column.trim())
+}
+
+/// Compares a stored value with a bound one, bound values are usually strings.
+fn matches(stored: Option<&Value>, bound: Option<&Value>) -> bool {
+    match (stored, bound) {
+        (Some(Value::String(stored)), Some(Value::String(bound))) => stored == bound,
+        (Some(stored), Some(Value::String(bound))) => stored.to_string() == *bound,
+        (stored, bound) => stored == bound,
+    }
+}
+
+#[async_trait]
+impl ExternalDriverHandler for JsonDriver {
+    async fn initialize(&self, connection: CompactString) -> Result<()> {
+        *self.path.write().await = Some(PathBuf::from(connection.as_str()));
+        Ok(())
+    }
+
+    async fn execute(&self, query: CompactString, values: Vec<Value>) -> Result<Vec<Value>> {
+        let query = query.trim().trim_end_matches(';');
+        let mut tables = self.load().await?;
+
+        if let Some(rest) = query.strip_prefix("SELECT * FROM ") {
+            let (table, condition) = match rest.split_once(" WHERE ") {
+                Some((table, condition)) => (table.trim(), Some(where_column(condition)?)),
+                None => (rest.trim(), None),
+            };
+
+            let rows = tables
+                .get(table)
+                .and_then(|rows| rows.as_array())
+                .ok_or(anyhow!("Table '{}' doesn't exist.", table))?;
+
+            Ok(rows
+                .iter()
+                .filter(|row| {
+                    condition
+                        .map(|column| matches(row.get(column), values.first()))
+                        .unwrap_or(true)
+                })
+                .cloned()
+                .collect())
+        } else if let Some(rest) = query.strip_prefix("INSERT INTO ") {
+            let (table, rest) = rest
+                .split_once('(')
+                .ok_or(anyhow!("Malformed INSERT statement."))?;
+            let (columns, _) = rest
+                .split_once(')')
+                .ok_or(anyhow!("Malformed INSERT statement."))?;
+
+            let row = columns
+                .split(',')
+                .map(|column| column.trim().to_string())
+                .zip(values)
+                .collect::<Map<String, Value>>();
+
+            tables
+                .entry(table.trim())
+                .or_insert(Value::Array(Vec::new()))
+                .as_array_mut()
+                .ok_or(anyhow!("Table '{}' is malformed.", table.trim()))?
+                .push(Value::Object(row));
+
+            self.store(&tables).await?;
+
+            Ok(Vec::new())
+        } else if let Some(rest) = query.strip_prefix("DELETE FROM ") {
+            let (table, condition) = rest
+                .split_once(" WHERE ")
+                .ok_or(anyhow!("DELETE statements require a WHERE clause."))?;
+            let column = where_column(condition)?;
+
+            tables
+                .get_mut(table.trim())
+                .and_then(|rows| rows.as_array_mut())
+                .ok_or(anyhow!("Table '{}' doesn't exist.", table.trim()))?
+                .retain(|row| !matches(row.get(column), values.first()));
+
+            self.store(&tables).await?;
+
+            Ok(Vec::new())
+        } else {
+            bail!("Unsupported statement: {}", query)
+        }
+    }
+
+    async fn schema(
+        &self,
+        _connection: CompactString,
+        _config: HashMap<CompactString, Bytes>,
+    ) -> Result<DataSchema> {
+        let tables = self.load().await?;
+
+        let mut schema = CheapVec::<TableSchema, 0>::new();
+
+        for (name, rows) in tables.iter() {
+            let Some(Value::Object(row)) = rows.as_array().and_then(|rows| rows.first()) else {
+                continue;
+            };
+
+            let columns = row
+                .iter()
+                .map(|(column, value)| {
+                    ColumnSchema::new(
+                        column.to_compact_string(),
+                        match value {
+                            Value::Bool(_) => "boolean",
+                            Value::Number(_) => "number",
+                            Value::String(_) => "text",
+                            _ => "json",
+                        }
+                        .to_compact_string(),
+                        value.is_null(),
+                        column == "id",
+                        false,
+                    )
+                })
+                .collect();
+
+            schema.push(TableSchema::new(
+                name.to_compact_string(),
+                false,
+                columns,
+                CheapVec::new(),
+            ));
+        }
+
+        Ok(DataSchema::new(schema))
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    serve_driver(
+        JsonDriver::default(),
+        tokio::io::stdin(),
+        tokio::io::stdout(),
+    )
+    .await
+}
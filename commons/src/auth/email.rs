@@ -0,0 +1,59 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+//!
+//! Flows proving the ownership of the users' emails through the links sent to them, which
+//! carry single-use tokens kept on the embedded store (see `mail::Mailing`).
+//!
+
+use crate::*;
+
+/// Password reset settings of the project.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display("Password reset (tokens expire after {}s)", max_age)]
+#[getset(get = "pub")]
+pub struct PasswordResetSettings {
+    /// Link sent to the users, where `{token}` is replaced by the reset token.
+    /// NOTE: it's usually a frontend's page, which sends the token along with the new `password`
+    /// to the `password-reset` endpoint.
+    link: CompactString,
+
+    /// Max age of the reset tokens (in seconds).
+    max_age: usize,
+}
+
+impl Default for PasswordResetSettings {
+    fn default() -> Self {
+        Self {
+            link: "http://127.0.0.1:8080/reset-password?token={token}".to_compact_string(),
+            max_age: 3600,
+        }
+    }
+}
+
+/// Email verification settings of the project.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display("Email verification (tokens expire after {}s)", max_age)]
+#[getset(get = "pub")]
+pub struct EmailVerificationSettings {
+    /// Link sent to the users on signup, where `{token}` is replaced by the verification token.
+    link: CompactString,
+
+    /// Max age of the verification tokens (in seconds).
+    max_age: usize,
+
+    /// Whether users must have verified their email to log in.
+    #[serde(default)]
+    required: bool,
+}
+
+impl Default for EmailVerificationSettings {
+    fn default() -> Self {
+        Self {
+            link: "http://127.0.0.1:8080/api/internal/email/verify?token={token}"
+                .to_compact_string(),
+            max_age: 86400,
+            required: false,
+        }
+    }
+}
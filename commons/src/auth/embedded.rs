@@ -0,0 +1,244 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+//!
+//! Sessions kept on the embedded store, for single-node deployments without a sessions table.
+//! Tokens are stored hashed, so they cannot be recovered from the store's file.
+//!
+
+use crate::*;
+
+use super::*;
+
+use store::*;
+
+/// The stored session, which keeps its own expiration.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+struct SessionEntry {
+    user_id: UserId,
+    expires_at: i64,
+}
+
+/// Sessions stored on the embedded store.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display("Embedded sessions on {} ({})", path, table_name)]
+#[getset(get = "pub")]
+pub struct EmbeddedSession {
+    /// Store file, relative to the project's workspace.
+    path: CompactString,
+
+    table_name: CompactString,
+
+    /// Max age of sessions.
+    max_age: usize,
+}
+
+boxed_any!(EmbeddedSession);
+
+impl Default for EmbeddedSession {
+    fn default() -> Self {
+        Self {
+            path: "waveless.redb".to_compact_string(),
+            table_name: "sessions".to_compact_string(),
+            max_age: 86400,
+        }
+    }
+}
+
+impl EmbeddedSession {
+    fn store(&self) -> Result<EmbeddedStore<[u8; 32], SessionEntry>> {
+        EmbeddedStore::open(&self.path, &self.table_name)
+    }
+
+    fn token_key(token: &str) -> [u8; 32] {
+        *blake3::hash(token.as_bytes()).as_bytes()
+    }
+}
+
+#[typetag::serde(name = "Embedded")]
+#[async_trait]
+impl AnySessionMethod for EmbeddedSession {
+    fn name(&self) -> &'static str {
+        "embedded"
+    }
+
+    fn db_id(&self) -> Option<CompactString> {
+        None
+    }
+
+    fn max_age(&self) -> Option<usize> {
+        Some(self.max_age)
+    }
+
+    async fn check(
+        &self,
+        _db_conn: Arc<dyn AnyDatabaseConnection>,
+        token: CompactString,
+    ) -> Result<Option<UserId>> {
+        let Some(entry) = self.store()?.get(Self::token_key(&token)).await? else {
+            return Ok(None);
+        };
+
+        if entry.expires_at <= Utc::now().timestamp() {
+            return Ok(None);
+        }
+
+        Ok(Some(entry.user_id))
+    }
+
+    async fn new(
+        &self,
+        _db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+    ) -> Result<CompactString> {
+        let token = Alphanumeric
+            .sample_string(&mut rand::rng(), 32)
+            .to_compact_string();
+
+        self.store()?
+            .insert(
+                Self::token_key(&token),
+                SessionEntry {
+                    user_id,
+                    expires_at: Utc::now().timestamp() + self.max_age as i64,
+                },
+            )
+            .await?;
+
+        Ok(token)
+    }
+
+    async fn invalidate(
+        &self,
+        _db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        token: Option<CompactString>,
+    ) -> Result<()> {
+        let store = self.store()?;
+
+        match token {
+            Some(token) => {
+                // Invalidate a given token, only if it belongs to the user.
+                let key = Self::token_key(&token);
+
+                if store
+                    .get(key)
+                    .await?
+                    .is_some_and(|entry| entry.user_id == user_id)
+                {
+                    store.remove(key).await?;
+                }
+            }
+            None => {
+                // Invalidate all tokens from a given user.
+                // NOTE: sessions are indexed by their token, so the whole table is scanned.
+                store
+                    .retain(move |_, entry| entry.user_id != user_id)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn remove_expired(&self, _db_conn: Arc<dyn AnyDatabaseConnection>) -> Result<()> {
+        let now = Utc::now().timestamp();
+
+        let removed = self
+            .store()?
+            .retain(move |_, entry| entry.expires_at > now)
+            .await?;
+
+        debug!("{} expired sessions have been removed.", removed);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use databases::sqlite::*;
+
+    #[tokio::test]
+    async fn sessions_lifecycle() -> Result<()> {
+        let (db_conn, _) = SQLiteDBConnectionConfig::new(":memory:".to_compact_string(), false)
+            .new_conn("embedded_test".to_compact_string(), None, None)
+            .await?;
+
+        let path = std::env::temp_dir().join(format!(
+            "waveless_sessions_{}.redb",
+            Alphanumeric.sample_string(&mut rand::rng(), 8)
+        ));
+
+        let session = |max_age| {
+            EmbeddedSession::new(
+                path.to_str().unwrap().to_compact_string(),
+                "sessions_test".to_compact_string(),
+                max_age,
+            )
+        };
+
+        let (sessions, expired) = (session(3600), session(0));
+
+        let first = sessions.new(db_conn.to_owned(), 1).await?;
+        let second = sessions.new(db_conn.to_owned(), 1).await?;
+        let other = sessions.new(db_conn.to_owned(), 2).await?;
+
+        assert_eq!(
+            sessions.check(db_conn.to_owned(), first.to_owned()).await?,
+            Some(1)
+        );
+        assert_eq!(
+            sessions.check(db_conn.to_owned(), other.to_owned()).await?,
+            Some(2)
+        );
+        assert_eq!(
+            sessions
+                .check(db_conn.to_owned(), "unknown".to_compact_string())
+                .await?,
+            None
+        );
+
+        // Tokens can only be invalidated by their user.
+        sessions
+            .invalidate(db_conn.to_owned(), 2, Some(first.to_owned()))
+            .await?;
+        assert_eq!(
+            sessions.check(db_conn.to_owned(), first.to_owned()).await?,
+            Some(1)
+        );
+
+        sessions
+            .invalidate(db_conn.to_owned(), 1, Some(first.to_owned()))
+            .await?;
+        assert_eq!(sessions.check(db_conn.to_owned(), first).await?, None);
+        assert_eq!(
+            sessions
+                .check(db_conn.to_owned(), second.to_owned())
+                .await?,
+            Some(1)
+        );
+
+        // Invalidating all the user's sessions leaves the others.
+        sessions.invalidate(db_conn.to_owned(), 1, None).await?;
+        assert_eq!(sessions.check(db_conn.to_owned(), second).await?, None);
+        assert_eq!(
+            sessions.check(db_conn.to_owned(), other.to_owned()).await?,
+            Some(2)
+        );
+
+        // Expired sessions are rejected, then removed.
+        let stale = expired.new(db_conn.to_owned(), 3).await?;
+        assert_eq!(expired.check(db_conn.to_owned(), stale).await?, None);
+
+        sessions.remove_expired(db_conn.to_owned()).await?;
+        assert_eq!(sessions.store()?.entries(|_, _| true).await?.len(), 1);
+        assert_eq!(sessions.check(db_conn, other).await?, Some(2));
+
+        std::fs::remove_file(path)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,685 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+//!
+//! Stateless sessions using signed JWTs, which are checked without any database lookup.
+//! Keys are identified by their `kid`, so new keys can be introduced while the retired ones
+//! keep verifying the tokens they signed until they expire. Logouts are backed by an optional
+//! revocation list stored on a SQL table, which is cached in memory and synced periodically.
+//!
+
+use crate::*;
+
+use super::*;
+
+use sql::*;
+
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, decode_header, encode,
+};
+
+use std::sync::LazyLock;
+use std::time::Instant;
+
+/// Supported signing algorithms.
+#[derive(Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Display, Debug)]
+pub enum JwtAlgorithm {
+    HS256,
+    EdDSA,
+}
+
+/// A signing key, identified by its `kid`.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display("{} key '{}'", algorithm, kid)]
+#[getset(get = "pub")]
+pub struct JwtKey {
+    kid: CompactString,
+
+    algorithm: JwtAlgorithm,
+
+    /// HS256's shared secret, at least 32 bytes long.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    secret: Option<CompactString>,
+
+    /// EdDSA's PEM encoded private key, retired keys don't need it.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    private_key: Option<CompactString>,
+
+    /// EdDSA's PEM encoded public key.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    public_key: Option<CompactString>,
+}
+
+impl JwtKey {
+    fn algorithm_header(&self) -> Algorithm {
+        match self.algorithm {
+            JwtAlgorithm::HS256 => Algorithm::HS256,
+            JwtAlgorithm::EdDSA => Algorithm::EdDSA,
+        }
+    }
+
+    fn encoding_key(&self) -> Result<EncodingKey> {
+        match self.algorithm {
+            JwtAlgorithm::HS256 => Ok(EncodingKey::from_secret(self.secret()?.as_bytes())),
+            JwtAlgorithm::EdDSA => Ok(EncodingKey::from_ed_pem(
+                self.private_key
+                    .as_ref()
+                    .ok_or(anyhow!("The private key of {} is not set.", self))?
+                    .as_bytes(),
+            )?),
+        }
+    }
+
+    fn decoding_key(&self) -> Result<DecodingKey> {
+        match self.algorithm {
+            JwtAlgorithm::HS256 => Ok(DecodingKey::from_secret(self.secret()?.as_bytes())),
+            JwtAlgorithm::EdDSA => Ok(DecodingKey::from_ed_pem(
+                self.public_key
+                    .as_ref()
+                    .ok_or(anyhow!("The public key of {} is not set.", self))?
+                    .as_bytes(),
+            )?),
+        }
+    }
+
+    fn secret(&self) -> Result<&CompactString> {
+        let secret = self
+            .secret
+            .as_ref()
+            .ok_or(anyhow!("The secret of {} is not set.", self))?;
+
+        if secret.len() < 32 {
+            bail!("The secret of {} must be at least 32 bytes long.", self)
+        }
+
+        Ok(secret)
+    }
+}
+
+/// Revoked tokens stored on a SQL table, with one row per revoked token and one row per
+/// logout of all the user's sessions (whose `token_id` is `*`).
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display("JWT revocation list on {}", table_name)]
+#[getset(get = "pub")]
+pub struct JwtRevocation {
+    table_name: CompactString,
+
+    token_id_field: CompactString,
+
+    user_id_field: CompactString,
+
+    /// Should keep microseconds (e.g. `DATETIME(6)`), as the tokens issued right after logging out
+    /// of all the sessions are told apart by their issue instant.
+    revoked_field: CompactString,
+
+    /// Once expired, the revoked tokens would be rejected anyway, so the rows can be removed.
+    expires_field: CompactString,
+
+    /// How often the revocation list is synced from the table (in seconds).
+    /// NOTE: revocations made by other instances may be accepted until the next sync.
+    refresh_interval: usize,
+}
+
+impl Default for JwtRevocation {
+    fn default() -> Self {
+        Self {
+            table_name: "revocations_auth".to_compact_string(),
+            token_id_field: "token_id".to_compact_string(),
+            user_id_field: "user_id".to_compact_string(),
+            revoked_field: "revoked_at".to_compact_string(),
+            expires_field: "expires_at".to_compact_string(),
+            refresh_interval: 10,
+        }
+    }
+}
+
+/// The cached revocation list of a table.
+#[derive(Clone, Debug)]
+struct RevocationList {
+    /// Revoked token ids.
+    tokens: HashMap<CompactString, i64>,
+
+    /// Tokens issued before the given instant (in microseconds) are revoked, by user.
+    users: HashMap<UserId, i64>,
+
+    synced_at: Instant,
+}
+
+/// Revocation lists by their table.
+static REVOCATION_LISTS: LazyLock<DashMap<CompactString, RevocationList>> =
+    LazyLock::new(DashMap::new);
+
+const ALL_TOKENS_ID: &str = "*";
+
+/// Claims of the session tokens, the role is set under the configured claim.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+struct JwtClaims {
+    sub: CompactString,
+    jti: CompactString,
+    /// NumericDate with microseconds, see `issued_at`.
+    iat: f64,
+    exp: i64,
+
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    iss: Option<CompactString>,
+
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    aud: Option<CompactString>,
+
+    #[serde(flatten)]
+    other: HashMap<CompactString, serde_json::Value>,
+}
+
+impl JwtClaims {
+    /// The instant (in microseconds) the token was issued, precise enough to tell apart the tokens
+    /// issued right before logging out of all the sessions from those issued right after.
+    fn issued_at(&self) -> i64 {
+        (self.iat * 1_000_000.0).round() as i64
+    }
+}
+
+/// Stateless sessions using signed JWTs.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display("JWT sessions")]
+#[getset(get = "pub")]
+pub struct JwtSession {
+    /// Only used by the revocation list, will use the primary database by default.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    database_id: Option<DatabaseId>,
+
+    /// The first key signs the new tokens, while all of them are used to verify.
+    keys: CheapVec<JwtKey, 1>,
+
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    issuer: Option<CompactString>,
+
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    audience: Option<CompactString>,
+
+    /// Max age of sessions.
+    max_age: usize,
+
+    /// Claim where the user's roles are embedded, so they're not looked up on every request.
+    /// NOTE: role changes won't apply until the user logs in again.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    role_claim: Option<CompactString>,
+
+    /// Without a revocation list, logouts only remove the session's cookie.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    revocation: Option<JwtRevocation>,
+}
+
+boxed_any!(JwtSession);
+
+impl JwtSession {
+    /// Verifies the token's signature, expiration, issuer and audience.
+    fn verify(&self, token: &str, validate_exp: bool) -> Option<JwtClaims> {
+        let header = decode_header(token).ok()?;
+
+        let key = self
+            .keys
+            .iter()
+            .find(|key| Some(key.kid.as_str()) == header.kid.as_deref())?;
+
+        // Tokens must be signed with the key's algorithm.
+        if key.algorithm_header() != header.alg {
+            return None;
+        }
+
+        let mut validation = Validation::new(header.alg);
+        validation.validate_exp = validate_exp;
+        validation.set_required_spec_claims(&["exp", "sub"]);
+
+        if let Some(issuer) = &self.issuer {
+            validation.set_issuer(&[issuer]);
+        }
+
+        match &self.audience {
+            Some(audience) => validation.set_audience(&[audience]),
+            None => validation.validate_aud = false,
+        }
+
+        let decoding_key = match key.decoding_key() {
+            Ok(decoding_key) => decoding_key,
+            Err(err) => {
+                error!("Cannot load JWT {}. {}", key, err);
+                return None;
+            }
+        };
+
+        decode::<JwtClaims>(token, &decoding_key, &validation)
+            .ok()
+            .map(|token| token.claims)
+    }
+
+    /// Returns the roles embedded in the token's claims.
+    fn roles(&self, claims: &JwtClaims) -> Option<CheapVec<CompactString, 0>> {
+        // Tokens issued before multiple roles were supported embed a single one.
+        let roles = match claims.other.get(self.role_claim.as_ref()?.as_str())? {
+            serde_json::Value::String(role) => CheapVec::from_vec(vec![role.to_compact_string()]),
+            serde_json::Value::Array(roles) => roles
+                .iter()
+                .filter_map(|role| role.as_str())
+                .map(|role| role.to_compact_string())
+                .collect(),
+            _ => return None,
+        };
+
+        Some(roles)
+    }
+
+    /// Returns the revocation list, syncing it from the table when it's outdated.
+    async fn revocation_list(
+        &self,
+        revocation: &JwtRevocation,
+        db_conn: &Arc<dyn AnyDatabaseConnection>,
+    ) -> Result<RevocationList> {
+        if let Some(list) = REVOCATION_LISTS.get(&revocation.table_name) {
+            if list.synced_at.elapsed() < Duration::from_secs(revocation.refresh_interval as u64) {
+                return Ok(list.to_owned());
+            }
+        }
+
+        let dialect = sql_dialect("Jwt", db_conn)?;
+
+        let res = sql_query(
+            db_conn,
+            format!(
+                "SELECT {}, {}, {} FROM {} WHERE {} > {}",
+                dialect.quote(&revocation.token_id_field),
+                dialect.quote(&revocation.user_id_field),
+                dialect.quote(&revocation.revoked_field),
+                dialect.quote(&revocation.table_name),
+                dialect.quote(&revocation.expires_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![dialect.datetime(Utc::now().naive_utc())]),
+        )
+        .await?;
+
+        let mut list = RevocationList {
+            tokens: HashMap::new(),
+            users: HashMap::new(),
+            synced_at: Instant::now(),
+        };
+
+        for entry in &res {
+            let (Ok(token_id), Some(user_id), Some(revoked_at)) = (
+                entry.try_get::<String>("", &revocation.token_id_field),
+                sql_user_id(entry, &revocation.user_id_field),
+                sql_datetime(entry, &revocation.revoked_field),
+            ) else {
+                bail!(
+                    "Unexpected fields' data types in '{}' table.",
+                    revocation.table_name
+                )
+            };
+
+            let revoked_at = revoked_at.and_utc().timestamp_micros();
+
+            if token_id == ALL_TOKENS_ID {
+                let latest = list.users.entry(user_id).or_insert(revoked_at);
+                *latest = (*latest).max(revoked_at);
+            } else {
+                list.tokens.insert(token_id.to_compact_string(), revoked_at);
+            }
+        }
+
+        REVOCATION_LISTS.insert(revocation.table_name.to_owned(), list.to_owned());
+
+        Ok(list)
+    }
+
+    /// Stores the revocation and applies it to the cached list right away.
+    async fn revoke(
+        &self,
+        revocation: &JwtRevocation,
+        db_conn: &Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        token_id: &str,
+        expires_at: i64,
+    ) -> Result<()> {
+        let dialect = sql_dialect("Jwt", db_conn)?;
+
+        let now = Utc::now();
+
+        sql_query(
+            db_conn,
+            format!(
+                "INSERT INTO {} ({}, {}, {}, {}) VALUES ({})",
+                dialect.quote(&revocation.table_name),
+                dialect.quote(&revocation.token_id_field),
+                dialect.quote(&revocation.user_id_field),
+                dialect.quote(&revocation.revoked_field),
+                dialect.quote(&revocation.expires_field),
+                dialect.placeholders(1, 4)
+            ),
+            CheapVec::from_vec(vec![
+                sea_orm::Value::from(token_id.to_string()),
+                sea_orm::Value::from(user_id as i64),
+                dialect.datetime_micros(now.naive_utc()),
+                dialect.datetime(
+                    chrono::DateTime::from_timestamp(expires_at, 0)
+                        .unwrap_or(now)
+                        .naive_utc(),
+                ),
+            ]),
+        )
+        .await?;
+
+        if let Some(mut list) = REVOCATION_LISTS.get_mut(&revocation.table_name) {
+            if token_id == ALL_TOKENS_ID {
+                list.users.insert(user_id, now.timestamp_micros());
+            } else {
+                list.tokens
+                    .insert(token_id.to_compact_string(), now.timestamp_micros());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[typetag::serde(name = "Jwt")]
+#[async_trait]
+impl AnySessionMethod for JwtSession {
+    fn name(&self) -> &'static str {
+        "jwt"
+    }
+
+    fn db_id(&self) -> Option<CompactString> {
+        self.database_id.to_owned()
+    }
+
+    fn max_age(&self) -> Option<usize> {
+        Some(self.max_age)
+    }
+
+    fn embeds_roles(&self) -> bool {
+        self.role_claim.is_some()
+    }
+
+    async fn check(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        token: CompactString,
+    ) -> Result<Option<UserId>> {
+        Ok(self
+            .check_with_roles(db_conn, token)
+            .await?
+            .map(|(user_id, _)| user_id))
+    }
+
+    async fn check_with_roles(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        token: CompactString,
+    ) -> Result<Option<(UserId, Option<CheapVec<CompactString, 0>>)>> {
+        let Some(claims) = self.verify(&token, true) else {
+            return Ok(None);
+        };
+
+        let Ok(user_id) = claims.sub.parse::<UserId>() else {
+            return Ok(None);
+        };
+
+        if let Some(revocation) = &self.revocation {
+            let list = self.revocation_list(revocation, &db_conn).await?;
+
+            if list.tokens.contains_key(&claims.jti)
+                || list
+                    .users
+                    .get(&user_id)
+                    .is_some_and(|revoked_at| claims.issued_at() < *revoked_at)
+            {
+                return Ok(None);
+            }
+        }
+
+        Ok(Some((user_id, self.roles(&claims))))
+    }
+
+    async fn new(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+    ) -> Result<CompactString> {
+        self.new_with_roles(db_conn, user_id, CheapVec::new()).await
+    }
+
+    async fn new_with_roles(
+        &self,
+        _db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        roles: CheapVec<CompactString, 0>,
+    ) -> Result<CompactString> {
+        let key = self
+            .keys
+            .first()
+            .ok_or(anyhow!("There are no JWT keys to sign the session."))?;
+
+        let now = Utc::now();
+
+        let mut other = HashMap::new();
+
+        if let Some(role_claim) = &self.role_claim {
+            if !roles.is_empty() {
+                other.insert(role_claim.to_owned(), json!(roles.as_slice()));
+            }
+        }
+
+        let claims = JwtClaims {
+            sub: user_id.to_compact_string(),
+            jti: Alphanumeric
+                .sample_string(&mut rand::rng(), 16)
+                .to_compact_string(),
+            iat: now.timestamp_micros() as f64 / 1_000_000.0,
+            exp: now.timestamp() + self.max_age as i64,
+            iss: self.issuer.to_owned(),
+            aud: self.audience.to_owned(),
+            other,
+        };
+
+        let mut header = Header::new(key.algorithm_header());
+        header.kid = Some(key.kid.to_string());
+
+        Ok(encode(&header, &claims, &key.encoding_key()?)?.to_compact_string())
+    }
+
+    async fn invalidate(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        token: Option<CompactString>,
+    ) -> Result<()> {
+        let Some(revocation) = &self.revocation else {
+            warn!("Sessions cannot be revoked as there is no JWT revocation list.");
+            return Ok(());
+        };
+
+        match token {
+            Some(token) => {
+                let Some(claims) = self.verify(&token, false) else {
+                    return Ok(());
+                };
+
+                if claims.sub.parse::<UserId>().ok() != Some(user_id) {
+                    return Ok(());
+                }
+
+                self.revoke(revocation, &db_conn, user_id, &claims.jti, claims.exp)
+                    .await
+            }
+            None => {
+                // Every token issued until now will have expired after the max age.
+                self.revoke(
+                    revocation,
+                    &db_conn,
+                    user_id,
+                    ALL_TOKENS_ID,
+                    Utc::now().timestamp() + self.max_age as i64,
+                )
+                .await
+            }
+        }
+    }
+
+    async fn remove_expired(&self, db_conn: Arc<dyn AnyDatabaseConnection>) -> Result<()> {
+        let Some(revocation) = &self.revocation else {
+            return Ok(());
+        };
+
+        let dialect = sql_dialect("Jwt", &db_conn)?;
+
+        sql_query(
+            &db_conn,
+            format!(
+                "DELETE FROM {} WHERE {} <= {}",
+                dialect.quote(&revocation.table_name),
+                dialect.quote(&revocation.expires_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![dialect.datetime(Utc::now().naive_utc())]),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use databases::sqlite::*;
+
+    fn key(kid: &str, secret: &str) -> JwtKey {
+        JwtKey::new(
+            kid.to_compact_string(),
+            JwtAlgorithm::HS256,
+            Some(secret.to_compact_string()),
+            None,
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn sign_rotate_and_revoke() -> Result<()> {
+        let (db_conn, _) = SQLiteDBConnectionConfig::new(":memory:".to_compact_string(), false)
+            .new_conn("jwt_test".to_compact_string(), None, None)
+            .await?;
+
+        db_conn
+            .execute(DatabaseInput::Query(
+                "CREATE TABLE jwt_test_revocations (token_id TEXT, user_id INTEGER, revoked_at DATETIME, expires_at DATETIME)"
+                    .to_compact_string(),
+            ))
+            .await?;
+
+        let old_key = key("old", "an old secret which is long enough!");
+        let new_key = key("new", "a new secret which is long enough!!");
+
+        let session = |keys: Vec<JwtKey>| {
+            JwtSession::new(
+                None,
+                CheapVec::from_vec(keys),
+                Some("waveless".to_compact_string()),
+                None,
+                3600,
+                Some("role".to_compact_string()),
+                Some(JwtRevocation::new(
+                    "jwt_test_revocations".to_compact_string(),
+                    "token_id".to_compact_string(),
+                    "user_id".to_compact_string(),
+                    "revoked_at".to_compact_string(),
+                    "expires_at".to_compact_string(),
+                    0,
+                )),
+            )
+        };
+
+        let before = session(vec![old_key.to_owned()]);
+        let token = before
+            .new_with_roles(
+                db_conn.to_owned(),
+                7,
+                CheapVec::from_vec(vec![
+                    "admin".to_compact_string(),
+                    "editor".to_compact_string(),
+                ]),
+            )
+            .await?;
+
+        assert_eq!(
+            before
+                .check_with_roles(db_conn.to_owned(), token.to_owned())
+                .await?,
+            Some((
+                7,
+                Some(CheapVec::from_vec(vec![
+                    "admin".to_compact_string(),
+                    "editor".to_compact_string()
+                ]))
+            ))
+        );
+
+        // Once rotated, tokens signed by the old key are still valid.
+        let after = session(vec![new_key, old_key]);
+        let new_token = after.new(db_conn.to_owned(), 7).await?;
+
+        assert_eq!(
+            after.check(db_conn.to_owned(), token.to_owned()).await?,
+            Some(7)
+        );
+        assert_eq!(
+            after
+                .check_with_roles(db_conn.to_owned(), new_token.to_owned())
+                .await?,
+            Some((7, None))
+        );
+
+        // Tampered and unknown tokens are rejected.
+        assert_eq!(
+            after
+                .check(
+                    db_conn.to_owned(),
+                    format!("{}x", token).to_compact_string()
+                )
+                .await?,
+            None
+        );
+        assert_eq!(
+            before
+                .check(db_conn.to_owned(), new_token.to_owned())
+                .await?,
+            None
+        );
+
+        // Revoking a token doesn't affect the others.
+        after
+            .invalidate(db_conn.to_owned(), 7, Some(token.to_owned()))
+            .await?;
+
+        assert_eq!(after.check(db_conn.to_owned(), token).await?, None);
+        assert_eq!(
+            after
+                .check(db_conn.to_owned(), new_token.to_owned())
+                .await?,
+            Some(7)
+        );
+
+        // Logging out everywhere revokes the tokens issued before, even within the same second,
+        // but not the ones issued right after.
+        let last_token = after.new(db_conn.to_owned(), 7).await?;
+
+        after.invalidate(db_conn.to_owned(), 7, None).await?;
+
+        let next_token = after.new(db_conn.to_owned(), 7).await?;
+
+        assert_eq!(after.check(db_conn.to_owned(), new_token).await?, None);
+        assert_eq!(after.check(db_conn.to_owned(), last_token).await?, None);
+        assert_eq!(after.check(db_conn, next_token).await?, Some(7));
+
+        Ok(())
+    }
+}
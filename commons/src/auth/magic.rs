@@ -0,0 +1,862 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+//!
+//! Passwordless login through single-use links (or codes) mailed to the user's address.
+//! Users are stored on a SQL table, which can be shared with other authentication methods,
+//! while the pending logins are kept on the embedded store, where only the hashes of their
+//! tokens and codes are stored. Logins are completed through `check` given either the link's
+//! `token` or the user's name along with the `code`.
+//!
+
+use crate::*;
+
+use super::*;
+
+use sql::*;
+use store::*;
+
+use sea_orm::QueryResult;
+
+/// A login waiting for its link (or code) to be used.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+struct MagicLoginEntry {
+    /// Not set for accounts which don't exist yet, as they're created once the login is completed.
+    user_id: Option<UserId>,
+    /// Fields of the account to create, including its name.
+    signup_fields: HashMap<CompactString, CompactString>,
+    account_key: [u8; 32],
+    code_hash: CompactString,
+    expires_at: i64,
+    /// Attempts with a code, whether they failed or not.
+    attempts: usize,
+}
+
+/// A login's link token and code, sent together so the user can use either of them.
+#[derive(Clone, PartialEq, Constructor, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct MagicLogin {
+    token: CompactString,
+    code: CompactString,
+}
+
+/// Passwordless login through mailed links on a SQL table.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display("Magic link login on SQL using table {}", table_name)]
+#[getset(get = "pub")]
+pub struct SqlMagicLinkAuthenticationMethod {
+    /// Will use the primary database by default.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    database_id: Option<DatabaseId>,
+
+    /// The users' table, which may be shared with other authentication methods.
+    table_name: CompactString,
+
+    /// Generated by the database (`AUTO_INCREMENT`, `SERIAL`, `INTEGER PRIMARY KEY`...).
+    user_id_field: CompactString,
+
+    /// The user's email, where the links are sent, must be unique.
+    name_field: CompactString,
+
+    /// Other fields filled on signup when given, they must be nullable as accounts may be created on login.
+    #[serde(default, skip_serializing_if = "CheapVec::is_empty")]
+    extra_fields: CheapVec<CompactString>,
+
+    /// Flags disabled accounts, must be a nullable boolean column.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    disabled_field: Option<CompactString>,
+
+    /// Link sent to the users, where `{token}` is replaced by the login token.
+    link: CompactString,
+
+    /// Max age of the links and codes (in seconds).
+    max_age: usize,
+
+    /// Number of digits of the codes.
+    code_length: usize,
+
+    /// Max failed attempts before a code is discarded, as codes are short.
+    max_attempts: usize,
+
+    /// Max links requested for the same address during the `rate_window`.
+    max_requests: usize,
+
+    /// Window of the requests' rate limit (in seconds).
+    rate_window: usize,
+
+    /// Store file keeping the pending logins, relative to the project's workspace.
+    path: CompactString,
+
+    logins_table: CompactString,
+}
+
+boxed_any!(SqlMagicLinkAuthenticationMethod);
+
+impl Default for SqlMagicLinkAuthenticationMethod {
+    fn default() -> Self {
+        Self {
+            database_id: None,
+            table_name: "users_auth".to_compact_string(),
+            user_id_field: "user_id".to_compact_string(),
+            name_field: "email".to_compact_string(),
+            extra_fields: CheapVec::new_const(),
+            disabled_field: None,
+            link: "http://127.0.0.1:8080/api/internal/login/magic-link/verify?token={token}"
+                .to_compact_string(),
+            max_age: 600,
+            code_length: 6,
+            max_attempts: 5,
+            max_requests: 3,
+            rate_window: 900,
+            path: "waveless.redb".to_compact_string(),
+            logins_table: "magic_logins".to_compact_string(),
+        }
+    }
+}
+
+impl SqlMagicLinkAuthenticationMethod {
+    fn store(&self) -> Result<EmbeddedStore<[u8; 32], MagicLoginEntry>> {
+        EmbeddedStore::open(&self.path, &self.logins_table)
+    }
+
+    /// Indexes the latest login of each account, so codes are checked without scanning the logins.
+    fn account_store(&self) -> Result<EmbeddedStore<[u8; 32], [u8; 32]>> {
+        EmbeddedStore::open(&self.path, &format!("{}_by_account", self.logins_table))
+    }
+
+    fn token_key(token: &str) -> [u8; 32] {
+        *blake3::hash(token.as_bytes()).as_bytes()
+    }
+
+    fn account_key(account: &str) -> [u8; 32] {
+        *blake3::hash(account.trim().to_lowercase().as_bytes()).as_bytes()
+    }
+
+    /// Issues a new login for the account, its previous ones stop being valid.
+    /// NOTE: accounts which don't exist yet are only created once their login is completed,
+    /// using the given signup fields.
+    pub async fn issue(
+        &self,
+        account: &str,
+        user_id: Option<UserId>,
+        signup_fields: HashMap<CompactString, CompactString>,
+    ) -> Result<MagicLogin> {
+        let (store, account_store) = (self.store()?, self.account_store()?);
+
+        let token = Alphanumeric
+            .sample_string(&mut rand::rng(), 40)
+            .to_compact_string();
+
+        let code_length = self.code_length.clamp(4, 12);
+
+        let code = format!(
+            "{:0width$}",
+            rand::random::<u64>() % 10u64.pow(code_length as u32),
+            width = code_length
+        )
+        .to_compact_string();
+
+        let (key, account_key) = (Self::token_key(&token), Self::account_key(account));
+
+        store
+            .insert(
+                key,
+                MagicLoginEntry {
+                    user_id,
+                    signup_fields,
+                    account_key,
+                    code_hash: sql_token_hash(&code),
+                    expires_at: Utc::now().timestamp() + self.max_age as i64,
+                    attempts: 0,
+                },
+            )
+            .await?;
+
+        // The previous login is replaced on the index, and removed as it cannot be used anymore.
+        let previous = account_store.get(account_key).await?;
+
+        account_store.insert(account_key, key).await?;
+
+        if let Some(previous) = previous {
+            store.remove(previous).await?;
+        }
+
+        Ok(MagicLogin::new(token, code))
+    }
+
+    /// Whether the login is still the latest one of its account.
+    async fn is_latest(&self, key: [u8; 32], entry: &MagicLoginEntry) -> Result<bool> {
+        Ok(self.account_store()?.get(entry.account_key).await? == Some(key))
+    }
+
+    /// Completes the login given the link's token, which is consumed.
+    async fn check_token(&self, token: &str) -> Result<Option<MagicLoginEntry>> {
+        let store = self.store()?;
+
+        let key = Self::token_key(token);
+
+        let Some(entry) = store.get(key).await? else {
+            return Ok(None);
+        };
+
+        // Concurrent requests with the same token fail but for the one which removes it.
+        if !store.remove(key).await?
+            || entry.expires_at <= Utc::now().timestamp()
+            || !self.is_latest(key, &entry).await?
+        {
+            return Ok(None);
+        }
+
+        Ok(Some(entry))
+    }
+
+    /// Completes the account's login given its code, which is discarded after the max failed attempts.
+    async fn check_code(&self, account: &str, code: &str) -> Result<Option<MagicLoginEntry>> {
+        let store = self.store()?;
+
+        let Some(key) = self
+            .account_store()?
+            .get(Self::account_key(account))
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        // The attempt is counted before comparing the code, so concurrent guesses cannot exceed the max attempts.
+        let max_attempts = self.max_attempts;
+
+        let Some(entry) = store
+            .update(vec![key], move |_, entry| match entry {
+                Some(entry) => MagicLoginEntry {
+                    attempts: entry.attempts + 1,
+                    ..entry
+                },
+                // Removed meanwhile, so it's recreated already discarded.
+                None => MagicLoginEntry {
+                    user_id: None,
+                    signup_fields: HashMap::new(),
+                    account_key: [0; 32],
+                    code_hash: CompactString::default(),
+                    expires_at: 0,
+                    attempts: max_attempts + 1,
+                },
+            })
+            .await?
+            .into_iter()
+            .next()
+        else {
+            return Ok(None);
+        };
+
+        if entry.attempts > max_attempts || entry.expires_at <= Utc::now().timestamp() {
+            store.remove(key).await?;
+            return Ok(None);
+        }
+
+        if sql_token_hash(code) == entry.code_hash {
+            return Ok(store.remove(key).await?.then_some(entry));
+        }
+
+        if entry.attempts >= max_attempts {
+            store.remove(key).await?;
+        }
+
+        Ok(None)
+    }
+
+    /// Completes the login given the link's `token`, or the user's name along with the `code`,
+    /// returning the user and whether its account has just been created.
+    /// NOTE: logins of accounts which don't exist yet only create them when `signup` is set,
+    /// otherwise they fail without being consumed.
+    pub async fn redeem(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        entries: HashMap<CompactString, CompactString>,
+        signup: bool,
+    ) -> Result<Option<(UserId, bool)>> {
+        let dialect = sql_dialect("SqlMagicLink", &db_conn)?;
+
+        // Pending signups are skipped before being consumed.
+        let pending_signup = |entry: Option<MagicLoginEntry>| {
+            entry.is_some_and(|entry| entry.user_id.is_none()) && !signup
+        };
+
+        let entry = match (entries.get("token"), entries.get("code")) {
+            (Some(token), _) => {
+                if pending_signup(self.store()?.get(Self::token_key(token)).await?) {
+                    return Ok(None);
+                }
+
+                self.check_token(token).await?
+            }
+            (None, Some(code)) => {
+                let account = entries
+                    .get(&self.name_field)
+                    .ok_or(anyhow!("'{}' field not found.", self.name_field))?;
+
+                if let Some(key) = self
+                    .account_store()?
+                    .get(Self::account_key(account))
+                    .await?
+                {
+                    if pending_signup(self.store()?.get(key).await?) {
+                        return Ok(None);
+                    }
+                }
+
+                self.check_code(account, code).await?
+            }
+            (None, None) => bail!("Either the 'token' or the 'code' field is required."),
+        };
+
+        let Some(entry) = entry else {
+            return Ok(None);
+        };
+
+        let (user_id, created) = match entry.user_id {
+            Some(user_id) => (user_id, false),
+            // The account may have been created meanwhile, by another login or a regular signup.
+            None => match self
+                .new(db_conn.to_owned(), entry.signup_fields.to_owned())
+                .await
+            {
+                Ok(user_id) => (user_id, true),
+                Err(err) => {
+                    let Some(name) = entry.signup_fields.get(&self.name_field) else {
+                        return Err(err);
+                    };
+
+                    let Some(user_id) = self
+                        .find_user(dialect, &db_conn, name)
+                        .await?
+                        .and_then(|entry| sql_user_id(&entry, &self.user_id_field))
+                    else {
+                        return Err(err);
+                    };
+
+                    (user_id, false)
+                }
+            },
+        };
+
+        // Disabled users fail as if the login were wrong, so their accounts aren't disclosed.
+        if self.is_disabled(dialect, &db_conn, user_id).await? {
+            return Ok(None);
+        }
+
+        Ok(Some((user_id, created)))
+    }
+
+    /// Removes the expired logins, along with their index entries, returning how many were removed.
+    pub async fn sweep(&self) -> Result<usize> {
+        let (store, account_store) = (self.store()?, self.account_store()?);
+
+        let now = Utc::now().timestamp();
+
+        let removed = store.retain(move |_, entry| entry.expires_at > now).await?;
+
+        let live = store
+            .entries(|_, _| true)
+            .await?
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect::<std::collections::HashSet<_>>();
+
+        account_store
+            .retain(move |_, key| live.contains(&key))
+            .await?;
+
+        Ok(removed)
+    }
+
+    async fn find_user(
+        &self,
+        dialect: SqlDialect,
+        db_conn: &Arc<dyn AnyDatabaseConnection>,
+        name: &str,
+    ) -> Result<Option<QueryResult>> {
+        let res = sql_query(
+            db_conn,
+            format!(
+                "SELECT {}{} FROM {} WHERE {} = {}",
+                dialect.quote(&self.user_id_field),
+                self.disabled_field
+                    .as_ref()
+                    .map(|disabled_field| format!(", {}", dialect.quote(disabled_field)))
+                    .unwrap_or_default(),
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.name_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(name.to_string())]),
+        )
+        .await?;
+
+        Ok(res.into_iter().next())
+    }
+
+    /// Whether the user's account is disabled.
+    async fn is_disabled(
+        &self,
+        dialect: SqlDialect,
+        db_conn: &Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+    ) -> Result<bool> {
+        let Some(disabled_field) = &self.disabled_field else {
+            return Ok(false);
+        };
+
+        let res = sql_query(
+            db_conn,
+            format!(
+                "SELECT {} FROM {} WHERE {} = {}",
+                dialect.quote(disabled_field),
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(user_id as i64)]),
+        )
+        .await?;
+
+        Ok(res
+            .first()
+            .is_some_and(|entry| sql_flag(entry, disabled_field)))
+    }
+}
+
+#[typetag::serde(name = "SqlMagicLink")]
+#[async_trait]
+impl AnyAuthenticationMethod for SqlMagicLinkAuthenticationMethod {
+    fn name(&self) -> &'static str {
+        "sqlmagiclink"
+    }
+
+    fn db_id(&self) -> Option<CompactString> {
+        self.database_id.to_owned()
+    }
+
+    /// Only the logins with a code are for a named account, as tokens are long enough.
+    fn login_account(
+        &self,
+        entries: &HashMap<CompactString, CompactString>,
+    ) -> Option<CompactString> {
+        if entries.contains_key("token") {
+            None
+        } else {
+            entries.get(&self.name_field).cloned()
+        }
+    }
+
+    /// Completes the login given the link's `token`, or the user's name along with the `code`.
+    /// NOTE: logins of accounts which don't exist yet are completed through the magic link
+    /// endpoint, which assigns their default role.
+    async fn check(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        entries: HashMap<CompactString, CompactString>,
+    ) -> Result<Option<UserId>> {
+        Ok(self
+            .redeem(db_conn, entries, false)
+            .await?
+            .map(|(user_id, _)| user_id))
+    }
+
+    async fn new(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        entries: HashMap<CompactString, CompactString>,
+    ) -> Result<UserId> {
+        let dialect = sql_dialect("SqlMagicLink", &db_conn)?;
+
+        let name_field = entries
+            .get(&self.name_field)
+            .ok_or(anyhow!("'{}' field not found.", self.name_field))?;
+
+        let mut fields = CheapVec::<_, 8>::from_vec(vec![dialect.quote(&self.name_field)]);
+        let mut values =
+            CheapVec::<_, 8>::from_vec(vec![sea_orm::Value::from(name_field.to_string())]);
+
+        for extra_field in &self.extra_fields {
+            if let Some(value) = entries.get(extra_field) {
+                fields.push(dialect.quote(extra_field));
+                values.push(sea_orm::Value::from(value.to_string()));
+            }
+        }
+
+        let res = match sql_query(
+            &db_conn,
+            format!(
+                "INSERT INTO {} ({}) VALUES ({}){}",
+                dialect.quote(&self.table_name),
+                fields.join(", "),
+                dialect.placeholders(1, values.len()),
+                if dialect.returning() {
+                    format!(" RETURNING {}", dialect.quote(&self.user_id_field))
+                } else {
+                    String::new()
+                }
+            ),
+            values,
+        )
+        .await
+        {
+            Ok(res) => res,
+            Err(err) => {
+                if dialect.is_unique_violation(&err) {
+                    return Err(anyhow!(
+                        "Signup failed, an account with the same unique fields already exists."
+                    ));
+                } else {
+                    return Err(err);
+                }
+            }
+        };
+
+        // Backends without `RETURNING` require querying the new user.
+        let entry = if dialect.returning() {
+            res.into_iter().next()
+        } else {
+            self.find_user(dialect, &db_conn, name_field).await?
+        };
+
+        entry
+            .and_then(|entry| sql_user_id(&entry, &self.user_id_field))
+            .ok_or(anyhow!(
+                "Field '{}' expected but not returned in '{}' table. Maybe it exists but the associated data type is not an integer.",
+                self.user_id_field,
+                self.table_name
+            ))
+    }
+
+    async fn active(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+    ) -> Result<Option<bool>> {
+        sql_user_active(
+            "SqlMagicLink",
+            &db_conn,
+            &self.table_name,
+            &self.user_id_field,
+            self.disabled_field.as_ref(),
+            user_id,
+        )
+        .await
+    }
+
+    async fn delete(&self, db_conn: Arc<dyn AnyDatabaseConnection>, user_id: UserId) -> Result<()> {
+        let dialect = sql_dialect("SqlMagicLink", &db_conn)?;
+
+        let email = self.email(db_conn.to_owned(), user_id).await?;
+
+        sql_query(
+            &db_conn,
+            format!(
+                "DELETE FROM {} WHERE {} = {}",
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(user_id as i64)]),
+        )
+        .await?;
+
+        // Its pending login must not outlive it.
+        if let Some(email) = email {
+            if let Some(key) = self.account_store()?.get(Self::account_key(&email)).await? {
+                self.store()?.remove(key).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn set_disabled(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        disabled: bool,
+    ) -> Result<()> {
+        let Some(disabled_field) = &self.disabled_field else {
+            bail!(
+                "Users cannot be disabled on '{}' as `disabled_field` is not set.",
+                self.table_name
+            )
+        };
+
+        let dialect = sql_dialect("SqlMagicLink", &db_conn)?;
+
+        sql_query(
+            &db_conn,
+            format!(
+                "UPDATE {} SET {} = {} WHERE {} = {}",
+                dialect.quote(&self.table_name),
+                dialect.quote(disabled_field),
+                dialect.placeholder(1),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(2)
+            ),
+            CheapVec::from_vec(vec![
+                sea_orm::Value::from(disabled),
+                sea_orm::Value::from(user_id as i64),
+            ]),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        entries: HashMap<CompactString, CompactString>,
+    ) -> Result<Option<(UserId, CompactString)>> {
+        let dialect = sql_dialect("SqlMagicLink", &db_conn)?;
+
+        let name_field = entries
+            .get(&self.name_field)
+            .ok_or(anyhow!("'{}' field not found.", self.name_field))?;
+
+        Ok(self
+            .find_user(dialect, &db_conn, name_field)
+            .await?
+            .and_then(|entry| sql_user_id(&entry, &self.user_id_field))
+            .map(|user_id| (user_id, name_field.to_owned())))
+    }
+
+    async fn email(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+    ) -> Result<Option<CompactString>> {
+        let dialect = sql_dialect("SqlMagicLink", &db_conn)?;
+
+        let res = sql_query(
+            &db_conn,
+            format!(
+                "SELECT {} FROM {} WHERE {} = {}",
+                dialect.quote(&self.name_field),
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(user_id as i64)]),
+        )
+        .await?;
+
+        Ok(res
+            .first()
+            .and_then(|entry| entry.try_get::<Option<String>>("", &self.name_field).ok())
+            .flatten()
+            .map(|email| email.to_compact_string()))
+    }
+
+    /// Logging in proves the email's ownership, so it's always verified.
+    async fn email_verified(
+        &self,
+        _db_conn: Arc<dyn AnyDatabaseConnection>,
+        _user_id: UserId,
+    ) -> Result<bool> {
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use databases::sqlite::*;
+    use mail::*;
+
+    #[tokio::test]
+    async fn login_through_the_mail_sink() -> Result<()> {
+        let (db_conn, _) = SQLiteDBConnectionConfig::new(":memory:".to_compact_string(), false)
+            .new_conn("sql_magic_link_test".to_compact_string(), None, None)
+            .await?;
+
+        db_conn
+            .execute(DatabaseInput::Query(
+                "CREATE TABLE users_auth (user_id INTEGER PRIMARY KEY, email TEXT UNIQUE)"
+                    .to_compact_string(),
+            ))
+            .await?;
+
+        let scratch = std::env::temp_dir().join(format!(
+            "waveless_magic_{}",
+            Alphanumeric.sample_string(&mut rand::rng(), 8)
+        ));
+
+        let mut method = SqlMagicLinkAuthenticationMethod::default();
+        method.path = scratch
+            .join("store.redb")
+            .to_string_lossy()
+            .to_compact_string();
+
+        std::fs::create_dir_all(&scratch)?;
+
+        let address = HashMap::from([(
+            "email".to_compact_string(),
+            "ada@example.com".to_compact_string(),
+        )]);
+
+        let user_id = method.new(db_conn.to_owned(), address.to_owned()).await?;
+
+        assert_eq!(
+            method.find(db_conn.to_owned(), address.to_owned()).await?,
+            Some((user_id, "ada@example.com".to_compact_string()))
+        );
+
+        // The code is read back from the mail delivered to the sink.
+        let mailing = Mailing::new(
+            Arc::new(MaildirMailer::new(
+                scratch.join("mail").to_string_lossy().to_compact_string(),
+            )),
+            "Example <no-reply@example.com>".to_compact_string(),
+            "missing_templates".to_compact_string(),
+            "waveless.redb".to_compact_string(),
+            "mail_tokens".to_compact_string(),
+        );
+
+        let login = method
+            .issue("ada@example.com", Some(user_id), HashMap::new())
+            .await?;
+
+        let mail = mailing
+            .render(
+                MailTemplate::MagicLink,
+                "ada@example.com",
+                &[
+                    ("project", "Example"),
+                    ("link", &method.link().replace("{token}", login.token())),
+                    ("code", login.code().as_str()),
+                    ("minutes", "10"),
+                ],
+            )
+            .await?;
+
+        mailing.send(mail).await?;
+
+        let delivered = std::fs::read_dir(scratch.join("mail").join("new"))?
+            .next()
+            .unwrap()?;
+
+        let message = std::fs::read_to_string(delivered.path())?;
+
+        assert!(message.contains(login.code().as_str()));
+
+        let with_code = |code: &str| {
+            let mut entries = address.to_owned();
+            entries.insert("code".to_compact_string(), code.to_compact_string());
+            entries
+        };
+
+        // Wrong codes fail, while the right one logs in once.
+        assert_eq!(
+            method.check(db_conn.to_owned(), with_code("x")).await?,
+            None
+        );
+        assert_eq!(
+            method
+                .check(db_conn.to_owned(), with_code(login.code().as_str()))
+                .await?,
+            Some(user_id)
+        );
+        assert_eq!(
+            method
+                .check(db_conn.to_owned(), with_code(login.code().as_str()))
+                .await?,
+            None
+        );
+
+        // Issuing a new login invalidates the previous one.
+        let first = method
+            .issue("ada@example.com", Some(user_id), HashMap::new())
+            .await?;
+        let second = method
+            .issue("ada@example.com", Some(user_id), HashMap::new())
+            .await?;
+
+        let with_token =
+            |token: &str| HashMap::from([("token".to_compact_string(), token.to_compact_string())]);
+
+        assert_eq!(
+            method
+                .check(db_conn.to_owned(), with_token(first.token().as_str()))
+                .await?,
+            None
+        );
+        assert_eq!(
+            method
+                .check(db_conn.to_owned(), with_token(second.token().as_str()))
+                .await?,
+            Some(user_id)
+        );
+
+        // Codes are discarded after the max failed attempts.
+        let login = method
+            .issue("ada@example.com", Some(user_id), HashMap::new())
+            .await?;
+
+        for _ in 0..*method.max_attempts() {
+            method.check(db_conn.to_owned(), with_code("x")).await?;
+        }
+
+        assert_eq!(
+            method
+                .check(db_conn.to_owned(), with_code(login.code().as_str()))
+                .await?,
+            None
+        );
+
+        // New accounts are only created once their login is completed.
+        let grace = HashMap::from([(
+            "email".to_compact_string(),
+            "grace@example.com".to_compact_string(),
+        )]);
+
+        let signup = method
+            .issue("grace@example.com", None, grace.to_owned())
+            .await?;
+
+        assert_eq!(
+            method.find(db_conn.to_owned(), grace.to_owned()).await?,
+            None
+        );
+
+        let signup_token = with_token(signup.token().as_str());
+
+        assert_eq!(
+            method
+                .check(db_conn.to_owned(), signup_token.to_owned())
+                .await?,
+            None
+        );
+
+        let Some((grace_id, true)) = method
+            .redeem(db_conn.to_owned(), signup_token.to_owned(), true)
+            .await?
+        else {
+            panic!("The account should have been created on redemption.");
+        };
+
+        assert_eq!(
+            method.find(db_conn.to_owned(), grace).await?,
+            Some((grace_id, "grace@example.com".to_compact_string()))
+        );
+        assert_eq!(method.redeem(db_conn, signup_token, true).await?, None);
+
+        // Expired logins are swept on schedule.
+        method.max_age = 0;
+
+        method
+            .issue("ada@example.com", Some(user_id), HashMap::new())
+            .await?;
+
+        assert!(method.sweep().await? >= 1);
+
+        std::fs::remove_dir_all(scratch)?;
+
+        Ok(())
+    }
+}
@@ -1,11 +1,24 @@
 // Waveless
 // Copyright (C) 2026 Oscar Alvarez Gonzalez
 
+pub mod email;
+pub mod embedded;
+pub mod jwt;
+pub mod magic;
 pub mod mysql;
+pub mod oidc;
+pub mod passkey;
+pub mod password;
+pub mod roles;
+pub mod sql;
+pub mod sqlite;
+pub mod throttle;
+pub mod totp;
 
 use crate::*;
 
 use databases::*;
+use totp::*;
 
 /// Trait implemented for every user authentication mechanism.
 /// Note that the auth data does not have to live in a SQL database...
@@ -15,6 +28,11 @@ pub trait AnyAuthenticationMethod: Any + BoxedAny + DynClone + Send + Sync + Deb
     fn name(&self) -> &str;
     fn db_id(&self) -> Option<CompactString>;
 
+    /// Prepares what the method needs before serving any request, e.g. its decoy password hash.
+    async fn prepare(&self) -> Result<()> {
+        Ok(())
+    }
+
     /// Check whether the given credentials match for a given user.
     async fn check(
         &self,
@@ -22,6 +40,14 @@ pub trait AnyAuthenticationMethod: Any + BoxedAny + DynClone + Send + Sync + Deb
         entries: HashMap<CompactString, CompactString>,
     ) -> Result<Option<UserId>>;
 
+    /// The account the login's entries are for (e.g. the `email` entry), whose failed logins are throttled.
+    fn login_account(
+        &self,
+        _entries: &HashMap<CompactString, CompactString>,
+    ) -> Option<CompactString> {
+        None
+    }
+
     /// Signup a new user.
     async fn new(
         &self,
@@ -31,6 +57,143 @@ pub trait AnyAuthenticationMethod: Any + BoxedAny + DynClone + Send + Sync + Deb
 
     /// Deletes a user given it's id.
     async fn delete(&self, db_conn: Arc<dyn AnyDatabaseConnection>, user_id: UserId) -> Result<()>;
+
+    /// Hashes the passwords that are still stored in plaintext, returning how many were hashed.
+    /// NOTE: this is a one-off migration, methods which don't store passwords have nothing to do.
+    async fn hash_plaintext_passwords(
+        &self,
+        _db_conn: Arc<dyn AnyDatabaseConnection>,
+    ) -> Result<usize> {
+        Ok(0)
+    }
+
+    /// Gets the user's TOTP state, `None` if the user hasn't enabled TOTP.
+    async fn totp(
+        &self,
+        _db_conn: Arc<dyn AnyDatabaseConnection>,
+        _user_id: UserId,
+    ) -> Result<Option<TotpState>> {
+        Ok(None)
+    }
+
+    /// Enables (or updates) the user's TOTP state, `None` disables it.
+    async fn set_totp(
+        &self,
+        _db_conn: Arc<dyn AnyDatabaseConnection>,
+        _user_id: UserId,
+        _totp: Option<TotpState>,
+    ) -> Result<()> {
+        bail!("TOTP is not supported by '{}'.", self.name())
+    }
+
+    /// Replaces the user's TOTP state only if it's still the `previous` one, returning whether it
+    /// has been replaced. Codes are consumed this way, so concurrent requests cannot reuse them.
+    async fn replace_totp(
+        &self,
+        _db_conn: Arc<dyn AnyDatabaseConnection>,
+        _user_id: UserId,
+        _previous: &TotpState,
+        _totp: TotpState,
+    ) -> Result<bool> {
+        bail!("TOTP is not supported by '{}'.", self.name())
+    }
+
+    /// Whether the user may still authenticate, `None` if the method doesn't know the user.
+    /// NOTE: used to reject the API keys of disabled or deleted users.
+    async fn active(
+        &self,
+        _db_conn: Arc<dyn AnyDatabaseConnection>,
+        _user_id: UserId,
+    ) -> Result<Option<bool>> {
+        Ok(None)
+    }
+
+    /// Lists the users, optionally filtering those whose name contains the given text.
+    async fn users(
+        &self,
+        _db_conn: Arc<dyn AnyDatabaseConnection>,
+        _search: Option<CompactString>,
+        _offset: usize,
+        _limit: usize,
+    ) -> Result<CheapVec<UserInfo, 0>> {
+        bail!("Listing users is not supported by '{}'.", self.name())
+    }
+
+    /// Disables (or enables again) the user's account, disabled users cannot log in.
+    async fn set_disabled(
+        &self,
+        _db_conn: Arc<dyn AnyDatabaseConnection>,
+        _user_id: UserId,
+        _disabled: bool,
+    ) -> Result<()> {
+        bail!("Disabling users is not supported by '{}'.", self.name())
+    }
+
+    /// Replaces the user's password with an unknown one, so it must be reset before logging in again.
+    async fn force_password_reset(
+        &self,
+        _db_conn: Arc<dyn AnyDatabaseConnection>,
+        _user_id: UserId,
+    ) -> Result<()> {
+        bail!("Passwords are not managed by '{}'.", self.name())
+    }
+
+    /// Finds the user given its name (e.g. the `email` entry), returning its id and email.
+    async fn find(
+        &self,
+        _db_conn: Arc<dyn AnyDatabaseConnection>,
+        _entries: HashMap<CompactString, CompactString>,
+    ) -> Result<Option<(UserId, CompactString)>> {
+        bail!("Users' emails are not managed by '{}'.", self.name())
+    }
+
+    /// Gets the user's email.
+    async fn email(
+        &self,
+        _db_conn: Arc<dyn AnyDatabaseConnection>,
+        _user_id: UserId,
+    ) -> Result<Option<CompactString>> {
+        bail!("Users' emails are not managed by '{}'.", self.name())
+    }
+
+    /// Replaces the user's password.
+    async fn set_password(
+        &self,
+        _db_conn: Arc<dyn AnyDatabaseConnection>,
+        _user_id: UserId,
+        _password: CompactString,
+    ) -> Result<()> {
+        bail!("Passwords are not managed by '{}'.", self.name())
+    }
+
+    /// Whether the user has verified its email.
+    async fn email_verified(
+        &self,
+        _db_conn: Arc<dyn AnyDatabaseConnection>,
+        _user_id: UserId,
+    ) -> Result<bool> {
+        bail!("Email verification is not supported by '{}'.", self.name())
+    }
+
+    /// Marks the user's email as verified.
+    async fn set_email_verified(
+        &self,
+        _db_conn: Arc<dyn AnyDatabaseConnection>,
+        _user_id: UserId,
+    ) -> Result<()> {
+        bail!("Email verification is not supported by '{}'.", self.name())
+    }
+}
+
+/// A user, as listed to admins.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct UserInfo {
+    id: UserId,
+
+    name: CompactString,
+
+    disabled: bool,
 }
 
 /// Trait implemented for every session's storage backend.
@@ -44,6 +207,11 @@ pub trait AnySessionMethod: Any + BoxedAny + DynClone + Send + Sync + Debug {
         None
     }
 
+    /// Max age of the refresh tokens, `None` if they aren't issued.
+    fn refresh_max_age(&self) -> Option<usize> {
+        None
+    }
+
     /// Get whether a user is authenticated by the given session token.
     async fn check(
         &self,
@@ -58,6 +226,65 @@ pub trait AnySessionMethod: Any + BoxedAny + DynClone + Send + Sync + Debug {
         user_id: UserId,
     ) -> Result<CompactString>;
 
+    /// Whether the user's roles are embedded in the session token, so they must be given on creation.
+    fn embeds_roles(&self) -> bool {
+        false
+    }
+
+    /// Create a new session for the given user embedding its roles.
+    async fn new_with_roles(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        _roles: CheapVec<CompactString, 0>,
+    ) -> Result<CompactString> {
+        self.new(db_conn, user_id).await
+    }
+
+    /// Create a new session and, if supported, its refresh token.
+    async fn new_with_refresh(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        roles: CheapVec<CompactString, 0>,
+        _client: SessionClient,
+    ) -> Result<(CompactString, Option<CompactString>)> {
+        Ok((self.new_with_roles(db_conn, user_id, roles).await?, None))
+    }
+
+    /// Rotate the refresh token, returning a new session and refresh token.
+    /// NOTE: invalid, expired or reused refresh tokens return `None`.
+    async fn refresh(
+        &self,
+        _db_conn: Arc<dyn AnyDatabaseConnection>,
+        _refresh_token: CompactString,
+        _client: SessionClient,
+    ) -> Result<Option<(CompactString, CompactString)>> {
+        bail!("Refresh tokens are not supported by '{}'.", self.name())
+    }
+
+    /// The user the refresh token was issued to, so it can be checked before rotating the token.
+    /// NOTE: it doesn't tell whether the token is still valid, `refresh` does.
+    async fn refresh_user(
+        &self,
+        _db_conn: Arc<dyn AnyDatabaseConnection>,
+        _refresh_token: CompactString,
+    ) -> Result<Option<UserId>> {
+        bail!("Refresh tokens are not supported by '{}'.", self.name())
+    }
+
+    /// Check the session token, also returning the roles embedded in it, if any.
+    async fn check_with_roles(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        token: CompactString,
+    ) -> Result<Option<(UserId, Option<CheapVec<CompactString, 0>>)>> {
+        Ok(self
+            .check(db_conn, token)
+            .await?
+            .map(|user_id| (user_id, None)))
+    }
+
     /// Invalidate all session's of the given user.
     async fn invalidate(
         &self,
@@ -68,6 +295,56 @@ pub trait AnySessionMethod: Any + BoxedAny + DynClone + Send + Sync + Debug {
 
     /// Remove all the expired sessions.
     async fn remove_expired(&self, db_conn: Arc<dyn AnyDatabaseConnection>) -> Result<()>;
+
+    /// List the user's active sessions, marking the one of the given token as the current.
+    async fn sessions(
+        &self,
+        _db_conn: Arc<dyn AnyDatabaseConnection>,
+        _user_id: UserId,
+        _current_token: Option<CompactString>,
+    ) -> Result<CheapVec<SessionInfo, 0>> {
+        bail!("Listing sessions is not supported by '{}'.", self.name())
+    }
+
+    /// Revoke one of the user's sessions given its id, returning whether it existed.
+    async fn revoke_session(
+        &self,
+        _db_conn: Arc<dyn AnyDatabaseConnection>,
+        _user_id: UserId,
+        _session_id: CompactString,
+    ) -> Result<bool> {
+        bail!("Revoking sessions is not supported by '{}'.", self.name())
+    }
+}
+
+/// The client which starts a session.
+#[derive(Clone, PartialEq, Default, Constructor, Serialize, Deserialize, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct SessionClient {
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    user_agent: Option<CompactString>,
+
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    ip: Option<CompactString>,
+}
+
+/// An active session, as listed to its user.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct SessionInfo {
+    /// Identifies the session without exposing its token.
+    id: CompactString,
+
+    created_at: NaiveDateTime,
+
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    last_seen: Option<NaiveDateTime>,
+
+    #[serde(flatten)]
+    client: SessionClient,
+
+    /// Whether it's the session making the request.
+    current: bool,
 }
 
 /// Trait implemented for every role's storage backend.
@@ -77,14 +354,38 @@ pub trait AnyRoleMethod: Any + BoxedAny + DynClone + Send + Sync + Debug {
     fn name(&self) -> &str;
     fn db_id(&self) -> Option<CompactString>;
 
-    /// Get the role of the given user.
+    /// Get the role of the given user, the first one if it has many.
     async fn get(
         &self,
         db_conn: Arc<dyn AnyDatabaseConnection>,
         user_id: UserId,
     ) -> Result<Option<CompactString>>;
 
-    /// Set the role of the given user.
+    /// Get all the roles of the given user.
+    async fn get_all(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+    ) -> Result<CheapVec<CompactString, 0>> {
+        Ok(self.get(db_conn, user_id).await?.into_iter().collect())
+    }
+
+    /// Get all the roles of the given users at once.
+    async fn get_all_for(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_ids: &[UserId],
+    ) -> Result<HashMap<UserId, CheapVec<CompactString, 0>>> {
+        let mut roles = HashMap::with_capacity(user_ids.len());
+
+        for user_id in user_ids {
+            roles.insert(*user_id, self.get_all(db_conn.to_owned(), *user_id).await?);
+        }
+
+        Ok(roles)
+    }
+
+    /// Set the role of the given user, replacing all its roles.
     async fn set(
         &self,
         db_conn: Arc<dyn AnyDatabaseConnection>,
@@ -92,6 +393,134 @@ pub trait AnyRoleMethod: Any + BoxedAny + DynClone + Send + Sync + Debug {
         role: CompactString,
     ) -> Result<()>;
 
-    /// Remove the role of the given user.
+    /// Add a role to the given user, keeping the ones it already has.
+    /// NOTE: methods with a single role per user replace it.
+    async fn grant(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        role: CompactString,
+    ) -> Result<()> {
+        self.set(db_conn, user_id, role).await
+    }
+
+    /// Remove one of the roles of the given user.
+    async fn revoke(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        role: CompactString,
+    ) -> Result<()> {
+        if self
+            .get(db_conn.to_owned(), user_id)
+            .await?
+            .is_some_and(|current| current.eq_ignore_ascii_case(&role))
+        {
+            self.remove(db_conn, user_id).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Remove all the roles of the given user.
     async fn remove(&self, db_conn: Arc<dyn AnyDatabaseConnection>, user_id: UserId) -> Result<()>;
+
+    /// Grant the given roles to the user and revoke the `managed` ones it's no longer given,
+    /// leaving its other roles alone.
+    async fn sync(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        roles: &[CompactString],
+        managed: &[CompactString],
+    ) -> Result<()> {
+        let contains = |roles: &[CompactString], role: &str| {
+            roles.iter().any(|other| other.eq_ignore_ascii_case(role))
+        };
+
+        let current = self.get_all(db_conn.to_owned(), user_id).await?;
+
+        for role in &current {
+            if contains(managed, role) && !contains(roles, role) {
+                self.revoke(db_conn.to_owned(), user_id, role.to_owned())
+                    .await?;
+            }
+        }
+
+        for role in roles {
+            if !contains(&current, role) {
+                self.grant(db_conn.to_owned(), user_id, role.to_owned())
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Trait implemented for every API keys' storage backend.
+/// API keys authenticate machine clients on behalf of a user, restricted to the key's scopes.
+#[typetag::serde]
+#[async_trait]
+pub trait AnyApiKeyMethod: Any + BoxedAny + DynClone + Send + Sync + Debug {
+    fn name(&self) -> &str;
+    fn db_id(&self) -> Option<CompactString>;
+
+    /// Get the key if it's valid and not expired, recording its use.
+    async fn check(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        key: CompactString,
+    ) -> Result<Option<ApiKeyInfo>>;
+
+    /// Create a new key for the given user.
+    /// NOTE: the key is only returned here, as just its hash is stored.
+    async fn new(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        name: CompactString,
+        scopes: CheapVec<CompactString, 0>,
+        expires_at: Option<NaiveDateTime>,
+    ) -> Result<(CompactString, ApiKeyInfo)>;
+
+    /// List all the keys, or only the given user's ones.
+    async fn list(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: Option<UserId>,
+    ) -> Result<CheapVec<ApiKeyInfo, 0>>;
+
+    /// Revoke a key given its id, returning whether it existed.
+    async fn revoke(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        key_id: CompactString,
+    ) -> Result<bool>;
+
+    /// Revoke all the keys of the given user.
+    async fn remove(&self, db_conn: Arc<dyn AnyDatabaseConnection>, user_id: UserId) -> Result<()>;
+}
+
+/// An API key, without the key itself.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct ApiKeyInfo {
+    /// Identifies the key without exposing it.
+    id: CompactString,
+
+    user_id: UserId,
+
+    name: CompactString,
+
+    /// Roles or permissions (`resource:action`) granted to the key.
+    scopes: CheapVec<CompactString, 0>,
+
+    created_at: NaiveDateTime,
+
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    expires_at: Option<NaiveDateTime>,
+
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    last_used: Option<NaiveDateTime>,
 }
@@ -0,0 +1,1149 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+//!
+//! Passwordless login through passkeys (WebAuthn). Users are stored on a SQL table, which can
+//! be shared with other authentication methods, along with their credentials' public keys (as
+//! JSON in the `credentials_field` column). Each ceremony is started by the server, whose state
+//! is kept on the embedded store under the returned `challenge_id`, and finished by the client
+//! with the authenticator's response. Logins are completed through `check`.
+//!
+
+use crate::*;
+
+use super::*;
+
+use sql::*;
+use store::*;
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use sea_orm::QueryResult;
+use webauthn_rs::prelude::{
+    Passkey, PasskeyAuthentication, PasskeyRegistration, PublicKeyCredential,
+    RegisterPublicKeyCredential, Url, Uuid,
+};
+use webauthn_rs::{Webauthn, WebauthnBuilder};
+
+use std::sync::LazyLock;
+
+/// Derives the credential ids of the decoy logins, so they're the same for each name while
+/// they cannot be told apart from real ones.
+static DECOY_KEY: LazyLock<[u8; 32]> = LazyLock::new(rand::random);
+
+/// How many times a login retries storing its signature counter when another one stored it first.
+const COUNTER_UPDATE_RETRIES: usize = 3;
+
+/// Whom a registration ceremony is for.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub enum PasskeyOwner {
+    /// An existing user adding a new passkey.
+    User(UserId),
+
+    /// A new account, created with the given entries once its first passkey is registered.
+    Signup(HashMap<CompactString, CompactString>),
+}
+
+/// What a ceremony is for.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+enum PasskeyCeremonyKind {
+    /// The registration for the given owner, along with its user handle.
+    Registration(PasskeyOwner, Uuid),
+    /// The login of the given user.
+    Login(UserId),
+}
+
+/// A ceremony waiting for the authenticator's response.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+struct PasskeyCeremony {
+    kind: PasskeyCeremonyKind,
+    /// The ceremony's state as JSON, either a `PasskeyRegistration` or a `PasskeyAuthentication`.
+    state: String,
+    expires_at: i64,
+}
+
+/// A user's credential, along with the last signature counter seen.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct PasskeyCredential {
+    passkey: Passkey,
+
+    /// Given to the authenticators, it's random and shared by all the user's credentials.
+    user_handle: Uuid,
+
+    #[serde(default)]
+    counter: u32,
+}
+
+/// A started ceremony, whose `options` are given to the browser's WebAuthn API.
+#[derive(Clone, PartialEq, Constructor, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct PasskeyChallenge {
+    challenge_id: CompactString,
+    options: serde_json::Value,
+}
+
+/// Passkey login on a SQL table.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display(
+    "Passkey login on SQL using table {} (relying party {})",
+    table_name,
+    rp_id
+)]
+#[getset(get = "pub")]
+pub struct SqlPasskeyAuthenticationMethod {
+    /// Will use the primary database by default.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    database_id: Option<DatabaseId>,
+
+    /// The users' table, which may be shared with other authentication methods.
+    table_name: CompactString,
+
+    /// Generated by the database (`AUTO_INCREMENT`, `SERIAL`, `INTEGER PRIMARY KEY`...).
+    user_id_field: CompactString,
+
+    /// The user's name (e.g. its email), must be unique.
+    name_field: CompactString,
+
+    /// Other fields filled on signup when given.
+    #[serde(default, skip_serializing_if = "CheapVec::is_empty")]
+    extra_fields: CheapVec<CompactString>,
+
+    /// Flags disabled accounts, must be a nullable boolean column.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    disabled_field: Option<CompactString>,
+
+    /// Keeps the user's credentials as JSON, must be a nullable text column.
+    credentials_field: CompactString,
+
+    /// Flags verified emails (the `name_field`), must be a nullable boolean column.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    verified_field: Option<CompactString>,
+
+    /// The relying party's id, the domain (without scheme nor port) passkeys are bound to.
+    rp_id: CompactString,
+
+    /// The origin the ceremonies are run from, its host must be (or be within) the `rp_id`.
+    rp_origin: CompactString,
+
+    /// Shown by the authenticators, the `rp_id` by default.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    rp_name: Option<CompactString>,
+
+    /// Max age of the started ceremonies (in seconds).
+    challenge_max_age: usize,
+
+    /// Store file keeping the started ceremonies, relative to the project's workspace.
+    path: CompactString,
+
+    challenges_table: CompactString,
+}
+
+boxed_any!(SqlPasskeyAuthenticationMethod);
+
+impl Default for SqlPasskeyAuthenticationMethod {
+    fn default() -> Self {
+        Self {
+            database_id: None,
+            table_name: "users_auth".to_compact_string(),
+            user_id_field: "user_id".to_compact_string(),
+            name_field: "email".to_compact_string(),
+            extra_fields: CheapVec::new_const(),
+            disabled_field: None,
+            credentials_field: "passkeys".to_compact_string(),
+            verified_field: None,
+            rp_id: "localhost".to_compact_string(),
+            rp_origin: "http://localhost:8080".to_compact_string(),
+            rp_name: None,
+            challenge_max_age: 300,
+            path: "waveless.redb".to_compact_string(),
+            challenges_table: "passkey_challenges".to_compact_string(),
+        }
+    }
+}
+
+impl SqlPasskeyAuthenticationMethod {
+    fn webauthn(&self) -> Result<Webauthn> {
+        Ok(
+            WebauthnBuilder::new(&self.rp_id, &Url::parse(&self.rp_origin)?)?
+                .rp_name(self.rp_name.as_deref().unwrap_or(&self.rp_id))
+                .build()?,
+        )
+    }
+
+    fn store(&self) -> Result<EmbeddedStore<[u8; 32], PasskeyCeremony>> {
+        EmbeddedStore::open(&self.path, &self.challenges_table)
+    }
+
+    fn challenge_key(challenge_id: &str) -> [u8; 32] {
+        *blake3::hash(challenge_id.as_bytes()).as_bytes()
+    }
+
+    /// Keeps the ceremony's state, returning its challenge id.
+    async fn keep_ceremony(
+        &self,
+        kind: PasskeyCeremonyKind,
+        state: String,
+    ) -> Result<CompactString> {
+        let store = self.store()?;
+
+        let now = Utc::now().timestamp();
+
+        // Expired ceremonies are removed meanwhile.
+        store.retain(move |_, entry| entry.expires_at > now).await?;
+
+        let challenge_id = Alphanumeric
+            .sample_string(&mut rand::rng(), 32)
+            .to_compact_string();
+
+        store
+            .insert(
+                Self::challenge_key(&challenge_id),
+                PasskeyCeremony {
+                    kind,
+                    state,
+                    expires_at: now + self.challenge_max_age as i64,
+                },
+            )
+            .await?;
+
+        Ok(challenge_id)
+    }
+
+    /// Removes the ceremony, returning it if it was still valid.
+    async fn take_ceremony(&self, challenge_id: &str) -> Result<Option<PasskeyCeremony>> {
+        let store = self.store()?;
+
+        let key = Self::challenge_key(challenge_id);
+
+        let Some(entry) = store.get(key).await? else {
+            return Ok(None);
+        };
+
+        // Concurrent requests with the same challenge fail but for the one which removes it.
+        if !store.remove(key).await? || entry.expires_at <= Utc::now().timestamp() {
+            return Ok(None);
+        }
+
+        Ok(Some(entry))
+    }
+
+    async fn find_user(
+        &self,
+        dialect: SqlDialect,
+        db_conn: &Arc<dyn AnyDatabaseConnection>,
+        field: &str,
+        value: sea_orm::Value,
+    ) -> Result<Option<QueryResult>> {
+        let res = sql_query(
+            db_conn,
+            format!(
+                "SELECT {}, {}, {}{} FROM {} WHERE {} = {}",
+                dialect.quote(&self.user_id_field),
+                dialect.quote(&self.name_field),
+                dialect.quote(&self.credentials_field),
+                self.disabled_field
+                    .as_ref()
+                    .map(|disabled_field| format!(", {}", dialect.quote(disabled_field)))
+                    .unwrap_or_default(),
+                dialect.quote(&self.table_name),
+                dialect.quote(field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![value]),
+        )
+        .await?;
+
+        Ok(res.into_iter().next())
+    }
+
+    fn stored_credentials(&self, entry: &QueryResult) -> Result<Vec<PasskeyCredential>> {
+        let Ok(credentials) = entry.try_get::<Option<String>>("", &self.credentials_field) else {
+            bail!(
+                "Field '{}' expected but not returned in '{}' table. Maybe it exists but the associated data type is not a string.",
+                self.credentials_field,
+                self.table_name
+            )
+        };
+
+        credentials
+            .filter(|credentials| !credentials.is_empty())
+            .map(|credentials| {
+                serde_json::from_str::<Vec<PasskeyCredential>>(&credentials)
+                    .map_err(|err| anyhow!("Cannot deserialize the user's passkeys. {}", err))
+            })
+            .transpose()
+            .map(Option::unwrap_or_default)
+    }
+
+    async fn set_credentials(
+        &self,
+        dialect: SqlDialect,
+        db_conn: &Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        credentials: &[PasskeyCredential],
+    ) -> Result<()> {
+        sql_query(
+            db_conn,
+            format!(
+                "UPDATE {} SET {} = {} WHERE {} = {}",
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.credentials_field),
+                dialect.placeholder(1),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(2)
+            ),
+            CheapVec::from_vec(vec![
+                sea_orm::Value::from(serde_json::to_string(credentials)?),
+                sea_orm::Value::from(user_id as i64),
+            ]),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    /// Creates the user, which has no passkeys yet.
+    async fn create_user(
+        &self,
+        dialect: SqlDialect,
+        db_conn: &Arc<dyn AnyDatabaseConnection>,
+        entries: &HashMap<CompactString, CompactString>,
+    ) -> Result<UserId> {
+        let name_field = entries
+            .get(&self.name_field)
+            .ok_or(anyhow!("'{}' field not found.", self.name_field))?;
+
+        let mut fields = CheapVec::<_, 8>::from_vec(vec![dialect.quote(&self.name_field)]);
+        let mut values =
+            CheapVec::<_, 8>::from_vec(vec![sea_orm::Value::from(name_field.to_string())]);
+
+        for extra_field in &self.extra_fields {
+            if let Some(value) = entries.get(extra_field) {
+                fields.push(dialect.quote(extra_field));
+                values.push(sea_orm::Value::from(value.to_string()));
+            }
+        }
+
+        let res = match sql_query(
+            db_conn,
+            format!(
+                "INSERT INTO {} ({}) VALUES ({}){}",
+                dialect.quote(&self.table_name),
+                fields.join(", "),
+                dialect.placeholders(1, values.len()),
+                if dialect.returning() {
+                    format!(" RETURNING {}", dialect.quote(&self.user_id_field))
+                } else {
+                    String::new()
+                }
+            ),
+            values,
+        )
+        .await
+        {
+            Ok(res) => res,
+            Err(err) => {
+                if dialect.is_unique_violation(&err) {
+                    return Err(anyhow!(
+                        "Signup failed, an account with the same unique fields already exists."
+                    ));
+                } else {
+                    return Err(err);
+                }
+            }
+        };
+
+        // Backends without `RETURNING` require querying the new user.
+        let entry = if dialect.returning() {
+            res.into_iter().next()
+        } else {
+            self.find_user(
+                dialect,
+                db_conn,
+                &self.name_field,
+                sea_orm::Value::from(name_field.to_string()),
+            )
+            .await?
+        };
+
+        entry
+            .and_then(|entry| sql_user_id(&entry, &self.user_id_field))
+            .ok_or(anyhow!(
+                "Field '{}' expected but not returned in '{}' table. Maybe it exists but the associated data type is not an integer.",
+                self.user_id_field,
+                self.table_name
+            ))
+    }
+
+    /// Starts the registration of a new passkey, either for an existing user or for a new account.
+    pub async fn start_registration(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        owner: PasskeyOwner,
+    ) -> Result<PasskeyChallenge> {
+        let dialect = sql_dialect("SqlPasskey", &db_conn)?;
+
+        let (user_handle, name, excluded) = match &owner {
+            PasskeyOwner::User(user_id) => {
+                let entry = self
+                    .find_user(
+                        dialect,
+                        &db_conn,
+                        &self.user_id_field,
+                        sea_orm::Value::from(*user_id as i64),
+                    )
+                    .await?
+                    .ok_or(anyhow!("Cannot find the user {}.", user_id))?;
+
+                let name = entry
+                    .try_get::<Option<String>>("", &self.name_field)
+                    .ok()
+                    .flatten()
+                    .unwrap_or(user_id.to_string());
+
+                let credentials = self.stored_credentials(&entry)?;
+
+                // The authenticator refuses to register the same passkey twice.
+                let excluded = credentials
+                    .iter()
+                    .map(|credential| credential.passkey.cred_id().to_owned())
+                    .collect::<Vec<_>>();
+
+                // The user keeps its handle, so its passkeys are grouped by the authenticators.
+                let user_handle = credentials
+                    .first()
+                    .map(|credential| credential.user_handle)
+                    .unwrap_or(Uuid::from_bytes(rand::random()));
+
+                (user_handle, name, Some(excluded))
+            }
+            // Whether the name is taken is only known once the account is created, so the
+            // response doesn't disclose existing accounts.
+            PasskeyOwner::Signup(entries) => {
+                let name = entries
+                    .get(&self.name_field)
+                    .ok_or(anyhow!("'{}' field not found.", self.name_field))?;
+
+                (Uuid::from_bytes(rand::random()), name.to_string(), None)
+            }
+        };
+
+        let (options, state) =
+            self.webauthn()?
+                .start_passkey_registration(user_handle, &name, &name, excluded)?;
+
+        let challenge_id = self
+            .keep_ceremony(
+                PasskeyCeremonyKind::Registration(owner, user_handle),
+                serde_json::to_string(&state)?,
+            )
+            .await?;
+
+        Ok(PasskeyChallenge::new(
+            challenge_id,
+            serde_json::to_value(options)?,
+        ))
+    }
+
+    /// Finishes the registration given the authenticator's `credential` (as JSON), returning the
+    /// user the passkey was registered for and whether its account has just been created.
+    pub async fn finish_registration(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        challenge_id: &str,
+        credential: &str,
+    ) -> Result<Option<(UserId, bool)>> {
+        let dialect = sql_dialect("SqlPasskey", &db_conn)?;
+
+        let Some(PasskeyCeremony {
+            kind: PasskeyCeremonyKind::Registration(owner, user_handle),
+            state,
+            ..
+        }) = self.take_ceremony(challenge_id).await?
+        else {
+            return Ok(None);
+        };
+
+        let Ok(state) = serde_json::from_str::<PasskeyRegistration>(&state) else {
+            return Ok(None);
+        };
+
+        let credential = serde_json::from_str::<RegisterPublicKeyCredential>(credential)
+            .map_err(|err| anyhow!("Cannot deserialize the passkey's credential. {}", err))?;
+
+        let passkey = match self
+            .webauthn()?
+            .finish_passkey_registration(&credential, &state)
+        {
+            Ok(passkey) => passkey,
+            Err(err) => {
+                warn!("Passkey registration failed. {}", err);
+                return Ok(None);
+            }
+        };
+
+        let (user_id, created, mut credentials) = match owner {
+            PasskeyOwner::User(user_id) => {
+                let entry = self
+                    .find_user(
+                        dialect,
+                        &db_conn,
+                        &self.user_id_field,
+                        sea_orm::Value::from(user_id as i64),
+                    )
+                    .await?
+                    .ok_or(anyhow!("Cannot find the user {}.", user_id))?;
+
+                (user_id, false, self.stored_credentials(&entry)?)
+            }
+            PasskeyOwner::Signup(entries) => (
+                self.create_user(dialect, &db_conn, &entries).await?,
+                true,
+                Vec::new(),
+            ),
+        };
+
+        credentials.push(PasskeyCredential {
+            passkey,
+            user_handle,
+            counter: 0,
+        });
+
+        self.set_credentials(dialect, &db_conn, user_id, &credentials)
+            .await?;
+
+        Ok(Some((user_id, created)))
+    }
+
+    /// Starts the login of the user given its name.
+    /// NOTE: users which don't exist or have no passkeys get a decoy challenge, which cannot be
+    /// finished, so the response doesn't disclose existing accounts.
+    pub async fn start_login(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        entries: HashMap<CompactString, CompactString>,
+    ) -> Result<PasskeyChallenge> {
+        let dialect = sql_dialect("SqlPasskey", &db_conn)?;
+
+        let name = entries
+            .get(&self.name_field)
+            .ok_or(anyhow!("'{}' field not found.", self.name_field))?;
+
+        let entry = self
+            .find_user(
+                dialect,
+                &db_conn,
+                &self.name_field,
+                sea_orm::Value::from(name.to_string()),
+            )
+            .await?;
+
+        let passkeys = match &entry {
+            Some(entry) => self
+                .stored_credentials(entry)?
+                .into_iter()
+                .map(|credential| credential.passkey)
+                .collect::<Vec<_>>(),
+            None => Vec::new(),
+        };
+
+        let Some(user_id) = entry
+            .and_then(|entry| sql_user_id(&entry, &self.user_id_field))
+            .filter(|_| !passkeys.is_empty())
+        else {
+            return self.decoy_login(name);
+        };
+
+        let (options, state) = self.webauthn()?.start_passkey_authentication(&passkeys)?;
+
+        let challenge_id = self
+            .keep_ceremony(
+                PasskeyCeremonyKind::Login(user_id),
+                serde_json::to_string(&state)?,
+            )
+            .await?;
+
+        Ok(PasskeyChallenge::new(
+            challenge_id,
+            serde_json::to_value(options)?,
+        ))
+    }
+
+    /// A login challenge for a credential which doesn't exist, derived from the name.
+    fn decoy_login(&self, name: &str) -> Result<PasskeyChallenge> {
+        let (options, _) = self.webauthn()?.start_discoverable_authentication()?;
+
+        let mut options = serde_json::to_value(options)?;
+
+        let cred_id = blake3::keyed_hash(&DECOY_KEY, name.trim().to_lowercase().as_bytes());
+
+        options["publicKey"]["allowCredentials"] = json!([{
+            "type": "public-key",
+            "id": URL_SAFE_NO_PAD.encode(&cred_id.as_bytes()[..16]),
+        }]);
+
+        Ok(PasskeyChallenge::new(
+            Alphanumeric
+                .sample_string(&mut rand::rng(), 32)
+                .to_compact_string(),
+            options,
+        ))
+    }
+
+    /// Stores the login's signature counter only if the user's credentials haven't changed since
+    /// they were read, returning whether they have been stored.
+    async fn replace_credentials(
+        &self,
+        dialect: SqlDialect,
+        db_conn: &Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        previous: &str,
+        credentials: &[PasskeyCredential],
+    ) -> Result<bool> {
+        let updated = sql_execute(
+            db_conn,
+            format!(
+                "UPDATE {} SET {} = {} WHERE {} = {} AND {} = {}",
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.credentials_field),
+                dialect.placeholder(1),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(2),
+                dialect.quote(&self.credentials_field),
+                dialect.placeholder(3)
+            ),
+            CheapVec::from_vec(vec![
+                sea_orm::Value::from(serde_json::to_string(credentials)?),
+                sea_orm::Value::from(user_id as i64),
+                sea_orm::Value::from(previous.to_string()),
+            ]),
+        )
+        .await?;
+
+        Ok(updated > 0)
+    }
+
+    fn checked_verified_field(&self) -> Result<&CompactString> {
+        self.verified_field.as_ref().ok_or(anyhow!(
+            "Emails cannot be verified on '{}' as `verified_field` is not set.",
+            self.table_name
+        ))
+    }
+}
+
+#[typetag::serde(name = "SqlPasskey")]
+#[async_trait]
+impl AnyAuthenticationMethod for SqlPasskeyAuthenticationMethod {
+    fn name(&self) -> &'static str {
+        "sqlpasskey"
+    }
+
+    fn db_id(&self) -> Option<CompactString> {
+        self.database_id.to_owned()
+    }
+
+    /// Finishes the login given its `challenge_id` and the authenticator's `credential` (as JSON).
+    async fn check(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        entries: HashMap<CompactString, CompactString>,
+    ) -> Result<Option<UserId>> {
+        let dialect = sql_dialect("SqlPasskey", &db_conn)?;
+
+        let challenge_id = entries
+            .get("challenge_id")
+            .ok_or(anyhow!("'challenge_id' field not found."))?;
+        let credential = entries
+            .get("credential")
+            .ok_or(anyhow!("'credential' field not found."))?;
+
+        let Some(PasskeyCeremony {
+            kind: PasskeyCeremonyKind::Login(user_id),
+            state,
+            ..
+        }) = self.take_ceremony(challenge_id).await?
+        else {
+            return Ok(None);
+        };
+
+        let Ok(state) = serde_json::from_str::<PasskeyAuthentication>(&state) else {
+            return Ok(None);
+        };
+
+        let credential = serde_json::from_str::<PublicKeyCredential>(credential)
+            .map_err(|err| anyhow!("Cannot deserialize the passkey's credential. {}", err))?;
+
+        let res = match self
+            .webauthn()?
+            .finish_passkey_authentication(&credential, &state)
+        {
+            Ok(res) => res,
+            Err(err) => {
+                warn!("Passkey login failed. {}", err);
+                return Ok(None);
+            }
+        };
+
+        // Concurrent logins may store their counters meanwhile, so the counter is checked again
+        // against the stored one until it's stored without a change in between.
+        for _ in 0..COUNTER_UPDATE_RETRIES {
+            let Some(entry) = self
+                .find_user(
+                    dialect,
+                    &db_conn,
+                    &self.user_id_field,
+                    sea_orm::Value::from(user_id as i64),
+                )
+                .await?
+            else {
+                return Ok(None);
+            };
+
+            // Disabled users fail as if the login were wrong, so their accounts aren't disclosed.
+            if self
+                .disabled_field
+                .as_ref()
+                .is_some_and(|disabled_field| sql_flag(&entry, disabled_field))
+            {
+                return Ok(None);
+            }
+
+            let previous = entry
+                .try_get::<Option<String>>("", &self.credentials_field)
+                .ok()
+                .flatten()
+                .unwrap_or_default();
+
+            let mut credentials = self.stored_credentials(&entry)?;
+
+            let Some(credential) = credentials
+                .iter_mut()
+                .find(|credential| credential.passkey.cred_id() == res.cred_id())
+            else {
+                return Ok(None);
+            };
+
+            // A counter which doesn't increase means the authenticator may have been cloned, as
+            // authenticators without counters always report zero.
+            if (res.counter() != 0 || credential.counter != 0)
+                && res.counter() <= credential.counter
+            {
+                warn!(
+                    "Passkey login of user {} rejected, its signature counter didn't increase.",
+                    user_id
+                );
+                return Ok(None);
+            }
+
+            credential.counter = res.counter();
+            credential.passkey.update_credential(&res);
+
+            if self
+                .replace_credentials(dialect, &db_conn, user_id, &previous, &credentials)
+                .await?
+            {
+                return Ok(Some(user_id));
+            }
+        }
+
+        warn!(
+            "Passkey login of user {} rejected, its credentials kept changing meanwhile.",
+            user_id
+        );
+
+        Ok(None)
+    }
+
+    async fn new(
+        &self,
+        _db_conn: Arc<dyn AnyDatabaseConnection>,
+        _entries: HashMap<CompactString, CompactString>,
+    ) -> Result<UserId> {
+        bail!(
+            "Users of '{}' are created when registering their first passkey.",
+            self.name()
+        )
+    }
+
+    async fn active(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+    ) -> Result<Option<bool>> {
+        sql_user_active(
+            "SqlPasskey",
+            &db_conn,
+            &self.table_name,
+            &self.user_id_field,
+            self.disabled_field.as_ref(),
+            user_id,
+        )
+        .await
+    }
+
+    async fn delete(&self, db_conn: Arc<dyn AnyDatabaseConnection>, user_id: UserId) -> Result<()> {
+        let dialect = sql_dialect("SqlPasskey", &db_conn)?;
+
+        sql_query(
+            &db_conn,
+            format!(
+                "DELETE FROM {} WHERE {} = {}",
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(user_id as i64)]),
+        )
+        .await?;
+
+        // Its pending registrations must not outlive it.
+        self.store()?
+            .retain(move |_, entry| match entry.kind {
+                PasskeyCeremonyKind::Registration(PasskeyOwner::User(owner), _)
+                | PasskeyCeremonyKind::Login(owner) => owner != user_id,
+                PasskeyCeremonyKind::Registration(PasskeyOwner::Signup(_), _) => true,
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    async fn set_disabled(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        disabled: bool,
+    ) -> Result<()> {
+        let Some(disabled_field) = &self.disabled_field else {
+            bail!(
+                "Users cannot be disabled on '{}' as `disabled_field` is not set.",
+                self.table_name
+            )
+        };
+
+        let dialect = sql_dialect("SqlPasskey", &db_conn)?;
+
+        sql_query(
+            &db_conn,
+            format!(
+                "UPDATE {} SET {} = {} WHERE {} = {}",
+                dialect.quote(&self.table_name),
+                dialect.quote(disabled_field),
+                dialect.placeholder(1),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(2)
+            ),
+            CheapVec::from_vec(vec![
+                sea_orm::Value::from(disabled),
+                sea_orm::Value::from(user_id as i64),
+            ]),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        entries: HashMap<CompactString, CompactString>,
+    ) -> Result<Option<(UserId, CompactString)>> {
+        let dialect = sql_dialect("SqlPasskey", &db_conn)?;
+
+        let name = entries
+            .get(&self.name_field)
+            .ok_or(anyhow!("'{}' field not found.", self.name_field))?;
+
+        Ok(self
+            .find_user(
+                dialect,
+                &db_conn,
+                &self.name_field,
+                sea_orm::Value::from(name.to_string()),
+            )
+            .await?
+            .and_then(|entry| sql_user_id(&entry, &self.user_id_field))
+            .map(|user_id| (user_id, name.to_owned())))
+    }
+
+    async fn email(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+    ) -> Result<Option<CompactString>> {
+        let dialect = sql_dialect("SqlPasskey", &db_conn)?;
+
+        Ok(self
+            .find_user(
+                dialect,
+                &db_conn,
+                &self.user_id_field,
+                sea_orm::Value::from(user_id as i64),
+            )
+            .await?
+            .and_then(|entry| entry.try_get::<Option<String>>("", &self.name_field).ok())
+            .flatten()
+            .map(|email| email.to_compact_string()))
+    }
+
+    async fn email_verified(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+    ) -> Result<bool> {
+        let verified_field = self.checked_verified_field()?;
+
+        let dialect = sql_dialect("SqlPasskey", &db_conn)?;
+
+        let res = sql_query(
+            &db_conn,
+            format!(
+                "SELECT {} FROM {} WHERE {} = {}",
+                dialect.quote(verified_field),
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(user_id as i64)]),
+        )
+        .await?;
+
+        Ok(res
+            .first()
+            .is_some_and(|entry| sql_flag(entry, verified_field)))
+    }
+
+    async fn set_email_verified(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+    ) -> Result<()> {
+        let verified_field = self.checked_verified_field()?;
+
+        let dialect = sql_dialect("SqlPasskey", &db_conn)?;
+
+        sql_query(
+            &db_conn,
+            format!(
+                "UPDATE {} SET {} = {} WHERE {} = {}",
+                dialect.quote(&self.table_name),
+                dialect.quote(verified_field),
+                dialect.placeholder(1),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(2)
+            ),
+            CheapVec::from_vec(vec![
+                sea_orm::Value::from(true),
+                sea_orm::Value::from(user_id as i64),
+            ]),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use databases::sqlite::*;
+
+    use webauthn_authenticator_rs::WebauthnAuthenticator;
+    use webauthn_authenticator_rs::softpasskey::SoftPasskey;
+    use webauthn_rs::prelude::{CreationChallengeResponse, RequestChallengeResponse};
+
+    /// Runs the login ceremony, returning the entries to finish it with.
+    async fn login(
+        method: &SqlPasskeyAuthenticationMethod,
+        db_conn: &Arc<dyn AnyDatabaseConnection>,
+        address: &HashMap<CompactString, CompactString>,
+        origin: &Url,
+        authenticator: &mut WebauthnAuthenticator<SoftPasskey>,
+    ) -> Result<HashMap<CompactString, CompactString>> {
+        let challenge = method
+            .start_login(db_conn.to_owned(), address.to_owned())
+            .await?;
+
+        let credential = authenticator
+            .do_authentication(
+                origin.to_owned(),
+                serde_json::from_value::<RequestChallengeResponse>(challenge.options().to_owned())?,
+            )
+            .map_err(|err| anyhow!("{:?}", err))?;
+
+        Ok(HashMap::from([
+            (
+                "challenge_id".to_compact_string(),
+                challenge.challenge_id().to_owned(),
+            ),
+            (
+                "credential".to_compact_string(),
+                serde_json::to_string(&credential)?.to_compact_string(),
+            ),
+        ]))
+    }
+
+    #[tokio::test]
+    async fn register_and_login_with_a_software_authenticator() -> Result<()> {
+        let (db_conn, _) = SQLiteDBConnectionConfig::new(":memory:".to_compact_string(), false)
+            .new_conn("sql_passkey_test".to_compact_string(), None, None)
+            .await?;
+
+        db_conn
+            .execute(DatabaseInput::Query(
+                "CREATE TABLE users_auth (user_id INTEGER PRIMARY KEY, email TEXT UNIQUE, passkeys TEXT)"
+                    .to_compact_string(),
+            ))
+            .await?;
+
+        let scratch = std::env::temp_dir().join(format!(
+            "waveless_passkey_{}",
+            Alphanumeric.sample_string(&mut rand::rng(), 8)
+        ));
+
+        std::fs::create_dir_all(&scratch)?;
+
+        let mut method = SqlPasskeyAuthenticationMethod::default();
+        method.path = scratch
+            .join("store.redb")
+            .to_string_lossy()
+            .to_compact_string();
+
+        let origin = Url::parse(method.rp_origin())?;
+
+        let mut authenticator = WebauthnAuthenticator::new(SoftPasskey::new(true));
+
+        let address = HashMap::from([(
+            "email".to_compact_string(),
+            "ada@example.com".to_compact_string(),
+        )]);
+
+        // The account is created along with its first passkey.
+        let challenge = method
+            .start_registration(db_conn.to_owned(), PasskeyOwner::Signup(address.to_owned()))
+            .await?;
+
+        let credential = authenticator
+            .do_registration(
+                origin.to_owned(),
+                serde_json::from_value::<CreationChallengeResponse>(
+                    challenge.options().to_owned(),
+                )?,
+            )
+            .map_err(|err| anyhow!("{:?}", err))?;
+
+        let Some((user_id, true)) = method
+            .finish_registration(
+                db_conn.to_owned(),
+                challenge.challenge_id(),
+                &serde_json::to_string(&credential)?,
+            )
+            .await?
+        else {
+            panic!("The account should have been created.");
+        };
+
+        // The same name cannot sign up twice, which is only known once the account is created.
+        let challenge = method
+            .start_registration(db_conn.to_owned(), PasskeyOwner::Signup(address.to_owned()))
+            .await?;
+
+        let credential = WebauthnAuthenticator::new(SoftPasskey::new(true))
+            .do_registration(
+                origin.to_owned(),
+                serde_json::from_value::<CreationChallengeResponse>(
+                    challenge.options().to_owned(),
+                )?,
+            )
+            .map_err(|err| anyhow!("{:?}", err))?;
+
+        assert!(
+            method
+                .finish_registration(
+                    db_conn.to_owned(),
+                    challenge.challenge_id(),
+                    &serde_json::to_string(&credential)?,
+                )
+                .await
+                .is_err()
+        );
+
+        let entries = login(&method, &db_conn, &address, &origin, &mut authenticator).await?;
+
+        assert_eq!(
+            method.check(db_conn.to_owned(), entries.to_owned()).await?,
+            Some(user_id)
+        );
+
+        // Challenges are single-use.
+        assert_eq!(method.check(db_conn.to_owned(), entries).await?, None);
+
+        // Unknown users get a decoy challenge, the same one for each name, which cannot be finished.
+        let grace = HashMap::from([(
+            "email".to_compact_string(),
+            "grace@example.com".to_compact_string(),
+        )]);
+
+        let decoy = method
+            .start_login(db_conn.to_owned(), grace.to_owned())
+            .await?;
+
+        assert_eq!(
+            decoy.options()["publicKey"]["allowCredentials"],
+            method
+                .start_login(db_conn.to_owned(), grace)
+                .await?
+                .options()["publicKey"]["allowCredentials"]
+        );
+        assert_eq!(
+            method
+                .check(
+                    db_conn.to_owned(),
+                    HashMap::from([
+                        (
+                            "challenge_id".to_compact_string(),
+                            decoy.challenge_id().to_owned(),
+                        ),
+                        ("credential".to_compact_string(), "{}".to_compact_string()),
+                    ]),
+                )
+                .await?,
+            None
+        );
+
+        // Another authenticator's passkey is added to the existing user.
+        let mut other = WebauthnAuthenticator::new(SoftPasskey::new(true));
+
+        let challenge = method
+            .start_registration(db_conn.to_owned(), PasskeyOwner::User(user_id))
+            .await?;
+
+        let credential = other
+            .do_registration(
+                origin.to_owned(),
+                serde_json::from_value::<CreationChallengeResponse>(
+                    challenge.options().to_owned(),
+                )?,
+            )
+            .map_err(|err| anyhow!("{:?}", err))?;
+
+        assert_eq!(
+            method
+                .finish_registration(
+                    db_conn.to_owned(),
+                    challenge.challenge_id(),
+                    &serde_json::to_string(&credential)?,
+                )
+                .await?,
+            Some((user_id, false))
+        );
+
+        let entries = login(&method, &db_conn, &address, &origin, &mut other).await?;
+
+        assert_eq!(method.check(db_conn, entries).await?, Some(user_id));
+
+        std::fs::remove_dir_all(scratch)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,190 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+//!
+//! Password hashing shared by the name & password authentication methods.
+//! Passwords are hashed using Argon2id and stored in the PHC string format, which
+//! embeds the algorithm, its parameters and the salt, so hashes can be verified
+//! even after the configured parameters change.
+//!
+
+use crate::*;
+
+use std::sync::LazyLock;
+
+use argon2::{
+    Algorithm, Argon2, Params, PasswordHash, PasswordHasher, PasswordVerifier, Version,
+    password_hash::SaltString,
+};
+
+/// Hashes of an unguessable password for each set of parameters, verified when the user is missing.
+/// NOTE: they're made on startup (see `PasswordHashing::prepare_decoy`), as making one takes longer than verifying it.
+static DECOY_HASHES: LazyLock<DashMap<(u32, u32, u32), CompactString>> =
+    LazyLock::new(DashMap::new);
+
+/// Argon2id's parameters used when hashing new passwords.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display("Argon2id (m={}KiB, t={}, p={})", memory_cost, time_cost, parallelism)]
+#[getset(get = "pub")]
+pub struct PasswordHashing {
+    /// Memory size in KiB.
+    memory_cost: u32,
+
+    /// Number of iterations.
+    time_cost: u32,
+
+    /// Degree of parallelism.
+    parallelism: u32,
+}
+
+impl Default for PasswordHashing {
+    fn default() -> Self {
+        Self {
+            memory_cost: Params::DEFAULT_M_COST,
+            time_cost: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+impl PasswordHashing {
+    fn hasher(&self) -> Result<Argon2<'static>> {
+        let params = Params::new(self.memory_cost, self.time_cost, self.parallelism, None)
+            .map_err(|err| anyhow!("Invalid Argon2id parameters ({}). {}", self, err))?;
+
+        Ok(Argon2::new(Algorithm::Argon2id, Version::V0x13, params))
+    }
+
+    /// Hashes the password with a random salt, returning its PHC string.
+    /// NOTE: hashing is expensive by design, so it runs on the blocking thread pool.
+    pub async fn hash(&self, password: &str) -> Result<CompactString> {
+        let (hashing, password) = (self.to_owned(), password.to_owned());
+
+        tokio::task::spawn_blocking(move || hashing.hash_blocking(&password)).await?
+    }
+
+    /// Verifies the password against the stored PHC string on the blocking thread pool.
+    pub async fn verify(&self, password: &str, hash: &str) -> Result<bool> {
+        let (hashing, password, hash) = (self.to_owned(), password.to_owned(), hash.to_owned());
+
+        Ok(tokio::task::spawn_blocking(move || hashing.verify_blocking(&password, &hash)).await?)
+    }
+
+    /// Makes the decoy hash of the current parameters, if it isn't made yet.
+    pub async fn prepare_decoy(&self) -> Result<CompactString> {
+        let params = (self.memory_cost, self.time_cost, self.parallelism);
+
+        if let Some(decoy) = DECOY_HASHES.get(&params) {
+            return Ok(decoy.to_owned());
+        }
+
+        let decoy = self
+            .hash(&Alphanumeric.sample_string(&mut rand::rng(), 32))
+            .await?;
+
+        Ok(DECOY_HASHES.entry(params).or_insert(decoy).to_owned())
+    }
+
+    /// Verifies the password against a decoy hash made with the current parameters, so it takes
+    /// as long as verifying a stored one. Used when there is no stored hash to verify.
+    pub async fn verify_decoy(&self, password: &str) -> Result<()> {
+        let decoy = match DECOY_HASHES.get(&(self.memory_cost, self.time_cost, self.parallelism)) {
+            Some(decoy) => decoy.to_owned(),
+            None => {
+                warn!(
+                    "The decoy password hash wasn't prepared on startup, logins of missing users may be told apart."
+                );
+
+                self.prepare_decoy().await?
+            }
+        };
+
+        self.verify(password, &decoy).await?;
+
+        Ok(())
+    }
+
+    fn hash_blocking(&self, password: &str) -> Result<CompactString> {
+        let salt = SaltString::encode_b64(&rand::random::<[u8; 16]>())
+            .map_err(|err| anyhow!("Cannot encode the password's salt. {}", err))?;
+
+        Ok(self
+            .hasher()?
+            .hash_password(password.as_bytes(), &salt)
+            .map_err(|err| anyhow!("Cannot hash the password. {}", err))?
+            .to_compact_string())
+    }
+
+    /// Verifies the password using the parameters embedded in the PHC string.
+    /// NOTE: values which aren't a valid PHC string (like plaintext passwords) never match.
+    fn verify_blocking(&self, password: &str, hash: &str) -> bool {
+        let Ok(hash) = PasswordHash::new(hash) else {
+            warn!(
+                "The stored password is not a valid hash. HINT: run `waveless auth hash-passwords`."
+            );
+            return false;
+        };
+
+        Argon2::default()
+            .verify_password(password.as_bytes(), &hash)
+            .is_ok()
+    }
+
+    /// Whether the stored PHC string wasn't produced by Argon2id with the current parameters.
+    pub fn needs_rehash(&self, hash: &str) -> bool {
+        let Ok(hash) = PasswordHash::new(hash) else {
+            return true;
+        };
+
+        let Ok(params) = Params::try_from(&hash) else {
+            return true;
+        };
+
+        hash.algorithm != Algorithm::Argon2id.ident()
+            || hash.version != Some(Version::V0x13.into())
+            || params.m_cost() != self.memory_cost
+            || params.t_cost() != self.time_cost
+            || params.p_cost() != self.parallelism
+    }
+
+    /// Whether the stored value is a password hash, so plaintext passwords can be told apart.
+    pub fn is_hash(value: &str) -> bool {
+        PasswordHash::new(value).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn hash_verify_and_rehash() -> Result<()> {
+        let hashing = PasswordHashing::new(1024, 1, 1);
+
+        let hash = hashing.hash("hunter2").await?;
+
+        assert!(PasswordHashing::is_hash(&hash));
+        assert!(hashing.verify("hunter2", &hash).await?);
+        assert!(!hashing.verify("hunter3", &hash).await?);
+        assert!(!hashing.needs_rehash(&hash));
+
+        // Hashes made with other parameters must still be verified, but should be upgraded.
+        let stronger = PasswordHashing::new(2048, 2, 1);
+
+        assert!(stronger.verify("hunter2", &hash).await?);
+        assert!(stronger.needs_rehash(&hash));
+
+        // Plaintext passwords never match.
+        assert!(!PasswordHashing::is_hash("hunter2"));
+        assert!(!hashing.verify("hunter2", "hunter2").await?);
+
+        // The decoy is made once per parameters.
+        let decoy = hashing.prepare_decoy().await?;
+
+        assert!(PasswordHashing::is_hash(&decoy));
+        assert_eq!(hashing.prepare_decoy().await?, decoy);
+        assert_ne!(stronger.prepare_decoy().await?, decoy);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+//!
+//! Roles' hierarchy and permissions, declared on the project's authentication settings.
+//! Roles inherit the permissions of the roles they include (e.g. `admin` ⊃ `editor` ⊃ `viewer`),
+//! and permissions are `resource:action` pairs (e.g. `orders:write`) where either part may be `*`.
+//!
+
+use crate::*;
+
+use std::collections::HashSet;
+
+/// A role declared on the project, roles which are not declared have no permissions.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display("Role {}", name)]
+#[getset(get = "pub")]
+pub struct RoleDefinition {
+    name: CompactString,
+
+    /// Roles included by this one, so it's granted their permissions too.
+    #[serde(default, skip_serializing_if = "should_skip_cheapvec")]
+    inherits: CheapVec<CompactString, 0>,
+
+    #[serde(default, skip_serializing_if = "should_skip_cheapvec")]
+    permissions: CheapVec<CompactString, 0>,
+}
+
+/// The roles and permissions of a user once the hierarchy has been expanded.
+#[derive(Clone, PartialEq, Default, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct Grants {
+    /// Lowercased, including the inherited ones.
+    roles: HashSet<CompactString>,
+
+    permissions: HashSet<CompactString>,
+}
+
+impl Grants {
+    /// Expands the user's roles following the hierarchy.
+    /// NOTE: cycles in the hierarchy are tolerated, each role is only expanded once.
+    pub fn resolve(definitions: &[RoleDefinition], user_roles: &[CompactString]) -> Self {
+        let mut grants = Self::default();
+
+        let mut pending = user_roles
+            .iter()
+            .map(|role| role.to_lowercase().to_compact_string())
+            .collect::<Vec<_>>();
+
+        while let Some(role) = pending.pop() {
+            if grants.roles.contains(&role) {
+                continue;
+            }
+
+            if let Some(definition) = definitions
+                .iter()
+                .find(|definition| definition.name.eq_ignore_ascii_case(&role))
+            {
+                pending.extend(
+                    definition
+                        .inherits
+                        .iter()
+                        .map(|role| role.to_lowercase().to_compact_string()),
+                );
+
+                grants.permissions.extend(
+                    definition
+                        .permissions
+                        .iter()
+                        .map(|permission| permission.to_lowercase().to_compact_string()),
+                );
+            }
+
+            grants.roles.insert(role);
+        }
+
+        grants
+    }
+
+    /// Expands the scopes of an API key, which may be either roles or permissions.
+    pub fn resolve_scopes(definitions: &[RoleDefinition], scopes: &[CompactString]) -> Self {
+        let (permissions, roles): (Vec<_>, Vec<_>) = scopes
+            .iter()
+            .cloned()
+            .partition(|scope| scope.contains(':'));
+
+        let mut grants = Self::resolve(definitions, &roles);
+
+        grants.permissions.extend(
+            permissions
+                .iter()
+                .map(|permission| permission.to_lowercase().to_compact_string()),
+        );
+
+        grants
+    }
+
+    /// Whether the user has the role, either directly or inherited.
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.contains(role.to_lowercase().as_str())
+    }
+
+    /// Whether any of the user's permissions matches the required one.
+    pub fn has_permission(&self, required: &str) -> bool {
+        let required = required.to_lowercase();
+        let (resource, action) = required.split_once(':').unwrap_or((required.as_str(), ""));
+
+        self.permissions.iter().any(|permission| {
+            let (granted_resource, granted_action) = permission
+                .split_once(':')
+                .unwrap_or((permission.as_str(), "*"));
+
+            (granted_resource == "*" || granted_resource == resource)
+                && (granted_action == "*" || granted_action == action)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hierarchy_and_wildcards() {
+        let role = |name: &str, inherits: &[&str], permissions: &[&str]| {
+            RoleDefinition::new(
+                name.to_compact_string(),
+                inherits
+                    .iter()
+                    .map(|role| role.to_compact_string())
+                    .collect(),
+                permissions
+                    .iter()
+                    .map(|permission| permission.to_compact_string())
+                    .collect(),
+            )
+        };
+
+        let definitions = [
+            role("Admin", &["editor"], &["users:*"]),
+            role("editor", &["viewer"], &["orders:write"]),
+            role("viewer", &["admin"], &["orders:read", "products"]),
+        ];
+
+        let admin = Grants::resolve(&definitions, &["ADMIN".to_compact_string()]);
+
+        assert!(admin.has_role("viewer"));
+        assert!(admin.has_permission("orders:read"));
+        assert!(admin.has_permission("Users:delete"));
+        assert!(admin.has_permission("products:delete"));
+        assert!(!admin.has_permission("orders:delete"));
+
+        // Undeclared roles are kept, but they have no permissions.
+        let guest = Grants::resolve(&definitions, &["guest".to_compact_string()]);
+
+        assert!(guest.has_role("guest"));
+        assert!(!guest.has_permission("orders:read"));
+
+        // Scopes grant either roles or plain permissions.
+        let key = Grants::resolve_scopes(
+            &[role("viewer", &[], &["orders:read"])],
+            &[
+                "viewer".to_compact_string(),
+                "users:read".to_compact_string(),
+            ],
+        );
+
+        assert!(key.has_permission("orders:read"));
+        assert!(key.has_permission("users:read"));
+        assert!(!key.has_role("users:read"));
+        assert!(!key.has_permission("users:delete"));
+    }
+}
@@ -0,0 +1,3325 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+//!
+//! Authentication, session and role methods backed by any SQL database connection,
+//! the queries are built at runtime using the connection's `SqlDialect`.
+//!
+
+use crate::*;
+
+use super::*;
+
+use execute::cache::invalidate_written;
+use password::*;
+
+use sea_orm::QueryResult;
+
+/// Name & password authentication on a SQL table.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display("Name & password authentication on SQL using table {}", table_name)]
+#[getset(get = "pub")]
+pub struct SqlSimpleAuthenticationMethod {
+    /// Will use the primary database by default.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    database_id: Option<DatabaseId>,
+
+    table_name: CompactString,
+
+    /// Generated by the database (`AUTO_INCREMENT`, `SERIAL`, `INTEGER PRIMARY KEY`...).
+    user_id_field: CompactString,
+
+    /// This field references to the user's name, emails, IDs... and must not be primary key.
+    name_field: CompactString,
+
+    password_field: CompactString,
+
+    /// Specifies all other the fields the user table contains, useful for signing up new users.
+    #[serde(default, skip_serializing_if = "CheapVec::is_empty")]
+    extra_fields: CheapVec<CompactString>,
+
+    /// Stores the user's TOTP state as JSON, must be a nullable text column.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    totp_field: Option<CompactString>,
+
+    /// Parameters used to hash new passwords, stored hashes are upgraded on login when they change.
+    #[serde(default)]
+    hashing: PasswordHashing,
+
+    /// Flags disabled accounts, must be a nullable boolean column.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    disabled_field: Option<CompactString>,
+
+    /// The user's email, which mails are sent to, the `name_field` is used by default.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    email_field: Option<CompactString>,
+
+    /// Flags verified emails, must be a nullable boolean column.
+    /// NOTE: existing users are unverified until they verify their email.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    verified_field: Option<CompactString>,
+}
+
+boxed_any!(SqlSimpleAuthenticationMethod);
+
+impl Default for SqlSimpleAuthenticationMethod {
+    fn default() -> Self {
+        Self {
+            database_id: None,
+            table_name: "users_auth".to_compact_string(),
+            user_id_field: "user_id".to_compact_string(),
+            name_field: "email".to_compact_string(),
+            password_field: "password".to_compact_string(),
+            extra_fields: CheapVec::new_const(),
+            totp_field: None,
+            hashing: PasswordHashing::default(),
+            disabled_field: None,
+            email_field: None,
+            verified_field: None,
+        }
+    }
+}
+
+impl SqlSimpleAuthenticationMethod {
+    /// The field which mails are sent to.
+    fn recipient_field(&self) -> &CompactString {
+        self.email_field.as_ref().unwrap_or(&self.name_field)
+    }
+
+    fn checked_verified_field(&self) -> Result<&CompactString> {
+        self.verified_field.as_ref().ok_or(anyhow!(
+            "Emails cannot be verified on '{}' as `verified_field` is not set.",
+            self.table_name
+        ))
+    }
+
+    /// Replaces the stored password of the given user.
+    async fn store_password(
+        &self,
+        dialect: SqlDialect,
+        db_conn: &Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        hash: CompactString,
+    ) -> Result<()> {
+        sql_query(
+            db_conn,
+            format!(
+                "UPDATE {} SET {} = {} WHERE {} = {}",
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.password_field),
+                dialect.placeholder(1),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(2)
+            ),
+            CheapVec::from_vec(vec![
+                sea_orm::Value::from(hash.to_string()),
+                sea_orm::Value::from(user_id as i64),
+            ]),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Session tokens stored on a SQL table.
+/// NOTE: only the tokens' hashes are stored, so a leaked table doesn't expose live sessions.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display("SQL backed token on table {}", table_name)]
+#[getset(get = "pub")]
+pub struct SqlToken {
+    /// Will use the primary database by default.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    database_id: Option<DatabaseId>,
+
+    table_name: CompactString,
+
+    /// Stores the token's blake3 hash (64 hex characters).
+    token_field: CompactString,
+
+    /// Must not be primary key.
+    user_id_field: CompactString,
+
+    /// With sliding expiration, it's renewed while the session is used.
+    created_field: CompactString,
+
+    /// Max age of sessions.
+    max_age: usize,
+
+    /// Whether to extend the sessions while they're used, they're renewed once past half their max age.
+    #[serde(default)]
+    sliding_expiration: bool,
+
+    /// Issues refresh tokens along with the sessions.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    refresh_tokens: Option<SqlRefreshTokens>,
+
+    /// Records the sessions' clients and last activity, which are shown when listing them.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    activity: Option<SqlSessionActivity>,
+
+    /// Max concurrent sessions per user, the oldest ones are revoked when exceeded.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    max_sessions: Option<usize>,
+}
+
+boxed_any!(SqlToken);
+
+impl Default for SqlToken {
+    fn default() -> Self {
+        Self {
+            database_id: None,
+            table_name: "sessions_auth".to_compact_string(),
+            token_field: "session_id".to_compact_string(),
+            user_id_field: "user_id".to_compact_string(),
+            created_field: "created_at".to_compact_string(),
+            max_age: 86400,
+            sliding_expiration: false,
+            refresh_tokens: None,
+            activity: None,
+            max_sessions: None,
+        }
+    }
+}
+
+/// Columns of the sessions' table recording who uses each session.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display(
+    "Sessions' activity on {}, {} and {}",
+    user_agent_field,
+    ip_field,
+    last_seen_field
+)]
+#[getset(get = "pub")]
+pub struct SqlSessionActivity {
+    user_agent_field: CompactString,
+
+    ip_field: CompactString,
+
+    /// Updated at most once per minute, so it's not written on every request.
+    last_seen_field: CompactString,
+}
+
+impl Default for SqlSessionActivity {
+    fn default() -> Self {
+        Self {
+            user_agent_field: "user_agent".to_compact_string(),
+            ip_field: "ip".to_compact_string(),
+            last_seen_field: "last_seen".to_compact_string(),
+        }
+    }
+}
+
+/// Refresh tokens stored on a SQL table (on the sessions' database). They're rotated on every use,
+/// and reusing an already rotated token revokes its whole family (every token descending from the same login).
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display("SQL backed refresh tokens on table {}", table_name)]
+#[getset(get = "pub")]
+pub struct SqlRefreshTokens {
+    table_name: CompactString,
+
+    /// Stores the token's blake3 hash (64 hex characters).
+    token_field: CompactString,
+
+    family_field: CompactString,
+
+    user_id_field: CompactString,
+
+    created_field: CompactString,
+
+    /// Set once the token has been rotated, rotated tokens are kept to detect their reuse.
+    rotated_field: CompactString,
+
+    /// Column of the sessions' table storing the family, so the sessions it issued are revoked too.
+    session_family_field: CompactString,
+
+    /// Max age of refresh tokens, which is renewed on every rotation.
+    max_age: usize,
+}
+
+impl Default for SqlRefreshTokens {
+    fn default() -> Self {
+        Self {
+            table_name: "refresh_tokens_auth".to_compact_string(),
+            token_field: "token".to_compact_string(),
+            family_field: "family_id".to_compact_string(),
+            user_id_field: "user_id".to_compact_string(),
+            created_field: "created_at".to_compact_string(),
+            rotated_field: "rotated_at".to_compact_string(),
+            session_family_field: "family_id".to_compact_string(),
+            max_age: 2592000,
+        }
+    }
+}
+
+/// Users' roles stored on a SQL table.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display("SQL backed users' roles check on {}", table_name)]
+#[getset(get = "pub")]
+pub struct SqlRole {
+    /// Will use the primary database by default.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    database_id: Option<DatabaseId>,
+
+    table_name: CompactString,
+
+    /// Must be unique, as roles are upserted by user, unless multiple roles are enabled.
+    user_id_field: CompactString,
+
+    /// Must not be primary key.
+    role_field: CompactString,
+
+    /// Whether users may have several roles, stored as one row per role.
+    /// NOTE: the pair of user id and role must be unique, as granting relies on it.
+    /// Roles are stored in lowercase.
+    #[serde(default)]
+    multiple_roles: bool,
+}
+
+boxed_any!(SqlRole);
+
+impl Default for SqlRole {
+    fn default() -> Self {
+        Self {
+            database_id: None,
+            table_name: "roles_auth".to_compact_string(),
+            user_id_field: "user_id".to_compact_string(),
+            role_field: "role".to_compact_string(),
+            multiple_roles: false,
+        }
+    }
+}
+
+/// API keys stored on a SQL table.
+/// NOTE: only the keys' hashes are stored, which also identify them.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display("SQL backed API keys on table {}", table_name)]
+#[getset(get = "pub")]
+pub struct SqlApiKey {
+    /// Will use the primary database by default.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    database_id: Option<DatabaseId>,
+
+    table_name: CompactString,
+
+    /// Stores the key's blake3 hash (64 hex characters).
+    key_field: CompactString,
+
+    /// Must not be primary key.
+    user_id_field: CompactString,
+
+    name_field: CompactString,
+
+    /// Stores the key's scopes separated by spaces.
+    scopes_field: CompactString,
+
+    created_field: CompactString,
+
+    /// Nullable, keys without expiry last until they're revoked.
+    expires_field: CompactString,
+
+    /// Nullable, updated at most once per minute.
+    last_used_field: CompactString,
+}
+
+boxed_any!(SqlApiKey);
+
+impl Default for SqlApiKey {
+    fn default() -> Self {
+        Self {
+            database_id: None,
+            table_name: "api_keys_auth".to_compact_string(),
+            key_field: "key_hash".to_compact_string(),
+            user_id_field: "user_id".to_compact_string(),
+            name_field: "name".to_compact_string(),
+            scopes_field: "scopes".to_compact_string(),
+            created_field: "created_at".to_compact_string(),
+            expires_field: "expires_at".to_compact_string(),
+            last_used_field: "last_used".to_compact_string(),
+        }
+    }
+}
+
+/// Returns the connection's SQL dialect, failing if it isn't a SQL connection.
+pub fn sql_dialect(method: &str, db_conn: &Arc<dyn AnyDatabaseConnection>) -> Result<SqlDialect> {
+    db_conn.dialect().ok_or(anyhow!(
+        "Database connection for `{}` should be a SQL connection but it's '{}'.",
+        method,
+        db_conn.name()
+    ))
+}
+
+/// Runs the query on the given SQL connection.
+pub async fn sql_query(
+    db_conn: &Arc<dyn AnyDatabaseConnection>,
+    query: String,
+    values: CheapVec<sea_orm::Value, 8>,
+) -> Result<Vec<QueryResult>> {
+    let res = db_conn
+        .execute(DatabaseInput::QueryValues(
+            query.to_compact_string(),
+            values,
+        ))
+        .await
+        .map_err(|err| anyhow!("Query execution error: {}", err))?;
+
+    // Writes returning rows (e.g. `INSERT ... RETURNING`) outdate the cached results too.
+    invalidate_written(db_conn, &query).await;
+
+    let DatabaseOutput::Any(res) = res else {
+        bail!("Unexpected database's executor's output.");
+    };
+
+    let res = res
+        .downcast::<Vec<QueryResult>>()
+        .map_err(|err| anyhow!("Cannot downcast to SQL query result. {:?}", err))?;
+
+    Ok(*res)
+}
+
+/// Runs the statement on the given SQL connection, returning the number of affected rows.
+/// NOTE: MySQL counts the changed rows, so rows updated with their current values aren't counted.
+pub async fn sql_execute(
+    db_conn: &Arc<dyn AnyDatabaseConnection>,
+    query: String,
+    values: CheapVec<sea_orm::Value, 8>,
+) -> Result<u64> {
+    let res = db_conn
+        .execute(DatabaseInput::ExecuteValues(
+            query.to_compact_string(),
+            values,
+        ))
+        .await
+        .map_err(|err| anyhow!("Query execution error: {}", err))?;
+
+    invalidate_written(db_conn, &query).await;
+
+    let DatabaseOutput::Any(res) = res else {
+        bail!("Unexpected database's executor's output.");
+    };
+
+    let res = res
+        .downcast::<u64>()
+        .map_err(|err| anyhow!("Cannot downcast to the affected rows. {:?}", err))?;
+
+    Ok(*res)
+}
+
+/// Reads the user's id from the row, regardless of the integer type used by the backend.
+pub fn sql_user_id(row: &QueryResult, field: &str) -> Option<UserId> {
+    row.try_get::<u32>("", field)
+        .map(|user_id| user_id as usize)
+        .or_else(|_| {
+            row.try_get::<i64>("", field)
+                .map(|user_id| user_id as usize)
+        })
+        .or_else(|_| {
+            row.try_get::<i32>("", field)
+                .map(|user_id| user_id as usize)
+        })
+        .or_else(|_| {
+            row.try_get::<u64>("", field)
+                .map(|user_id| user_id as usize)
+        })
+        .ok()
+}
+
+/// Whether the user exists on the table and isn't disabled, `None` if it doesn't exist.
+pub async fn sql_user_active(
+    method: &str,
+    db_conn: &Arc<dyn AnyDatabaseConnection>,
+    table_name: &str,
+    user_id_field: &str,
+    disabled_field: Option<&CompactString>,
+    user_id: UserId,
+) -> Result<Option<bool>> {
+    let dialect = sql_dialect(method, db_conn)?;
+
+    let res = sql_query(
+        db_conn,
+        format!(
+            "SELECT {}{} FROM {} WHERE {} = {}",
+            dialect.quote(user_id_field),
+            disabled_field
+                .map(|disabled_field| format!(", {}", dialect.quote(disabled_field)))
+                .unwrap_or_default(),
+            dialect.quote(table_name),
+            dialect.quote(user_id_field),
+            dialect.placeholder(1)
+        ),
+        CheapVec::from_vec(vec![sea_orm::Value::from(user_id as i64)]),
+    )
+    .await?;
+
+    Ok(res
+        .first()
+        .map(|entry| disabled_field.is_none_or(|disabled_field| !sql_flag(entry, disabled_field))))
+}
+
+/// Reads a boolean flag from the row, backends without booleans store them as integers.
+/// NOTE: `NULL` values are read as `false`.
+pub fn sql_flag(row: &QueryResult, field: &str) -> bool {
+    row.try_get::<Option<bool>>("", field)
+        .map(|flag| flag.unwrap_or(false))
+        .or_else(|_| {
+            row.try_get::<Option<i64>>("", field)
+                .map(|flag| flag.is_some_and(|flag| flag != 0))
+        })
+        .unwrap_or(false)
+}
+
+/// Reads a UTC datetime from the row, regardless of whether the backend stores the timezone.
+pub fn sql_datetime(row: &QueryResult, field: &str) -> Option<NaiveDateTime> {
+    row.try_get::<NaiveDateTime>("", field)
+        .or_else(|_| {
+            row.try_get::<chrono::DateTime<Utc>>("", field)
+                .map(|datetime| datetime.naive_utc())
+        })
+        .ok()
+}
+
+/// Pattern matching the values which contain the text, whose wildcards are escaped by `!`.
+/// NOTE: must be used along with `ESCAPE '!'`.
+fn sql_like_contains(text: &str) -> String {
+    let escaped = text
+        .replace('!', "!!")
+        .replace('%', "!%")
+        .replace('_', "!_");
+
+    format!("%{}%", escaped)
+}
+
+/// Hashes the token, as only the hashes of the tokens are stored.
+pub fn sql_token_hash(token: &str) -> CompactString {
+    blake3::hash(token.as_bytes()).to_hex().to_compact_string()
+}
+
+impl SqlToken {
+    /// Stores a new session, optionally tagged with its refresh token's family.
+    async fn insert_session(
+        &self,
+        dialect: SqlDialect,
+        db_conn: &Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        family: Option<&str>,
+        client: &SessionClient,
+    ) -> Result<CompactString> {
+        let token = Alphanumeric
+            .sample_string(&mut rand::rng(), 32)
+            .to_compact_string();
+
+        let mut fields = CheapVec::<_, 4>::from_vec(vec![
+            dialect.quote(&self.token_field),
+            dialect.quote(&self.user_id_field),
+            dialect.quote(&self.created_field),
+        ]);
+
+        let mut values = CheapVec::<_, 8>::from_vec(vec![
+            sea_orm::Value::from(sql_token_hash(&token).to_string()),
+            sea_orm::Value::from(user_id as i64),
+            dialect.datetime(Utc::now().naive_utc()),
+        ]);
+
+        if let (Some(family), Some(refresh)) = (family, &self.refresh_tokens) {
+            fields.push(dialect.quote(&refresh.session_family_field));
+            values.push(sea_orm::Value::from(family.to_string()));
+        }
+
+        if let Some(activity) = &self.activity {
+            fields.extend([
+                dialect.quote(&activity.user_agent_field),
+                dialect.quote(&activity.ip_field),
+                dialect.quote(&activity.last_seen_field),
+            ]);
+            values.extend([
+                sea_orm::Value::from(client.user_agent().as_ref().map(|ua| ua.to_string())),
+                sea_orm::Value::from(client.ip().as_ref().map(|ip| ip.to_string())),
+                dialect.datetime(Utc::now().naive_utc()),
+            ]);
+        }
+
+        sql_query(
+            db_conn,
+            format!(
+                "INSERT INTO {} ({}) VALUES ({})",
+                dialect.quote(&self.table_name),
+                fields.join(", "),
+                dialect.placeholders(1, values.len())
+            ),
+            values,
+        )
+        .await?;
+
+        if let Some(max_sessions) = self.max_sessions {
+            self.revoke_oldest(dialect, db_conn, user_id, max_sessions)
+                .await?;
+        }
+
+        Ok(token)
+    }
+
+    /// Revokes the user's oldest sessions beyond the given amount.
+    async fn revoke_oldest(
+        &self,
+        dialect: SqlDialect,
+        db_conn: &Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        max_sessions: usize,
+    ) -> Result<()> {
+        // NOTE: fetched first as MySQL doesn't support `LIMIT` within `IN` subqueries.
+        let res = sql_query(
+            db_conn,
+            format!(
+                "SELECT {} FROM {} WHERE {} = {} ORDER BY {} DESC",
+                dialect.quote(&self.token_field),
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(1),
+                dialect.quote(&self.created_field)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(user_id as i64)]),
+        )
+        .await?;
+
+        for entry in res.iter().skip(max_sessions) {
+            if let Ok(token_hash) = entry.try_get::<String>("", &self.token_field) {
+                self.delete_session(dialect, db_conn, user_id, &token_hash)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Deletes one of the user's sessions given its token's hash, along with its refresh tokens' family
+    /// when refresh tokens are enabled. Returns whether the session existed.
+    async fn delete_session(
+        &self,
+        dialect: SqlDialect,
+        db_conn: &Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        token_hash: &str,
+    ) -> Result<bool> {
+        let res = sql_query(
+            db_conn,
+            format!(
+                "SELECT {} FROM {} WHERE {} = {} AND {} = {}",
+                self.refresh_tokens
+                    .as_ref()
+                    .map(|refresh| dialect.quote(&refresh.session_family_field))
+                    .unwrap_or_else(|| dialect.quote(&self.token_field)),
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.token_field),
+                dialect.placeholder(1),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(2)
+            ),
+            CheapVec::from_vec(vec![
+                sea_orm::Value::from(token_hash.to_string()),
+                sea_orm::Value::from(user_id as i64),
+            ]),
+        )
+        .await?;
+
+        let Some(entry) = res.first() else {
+            return Ok(false);
+        };
+
+        // Revokes the session's refresh tokens when the family is known.
+        if let Some((refresh, family)) = self.refresh_tokens.as_ref().and_then(|refresh| {
+            entry
+                .try_get::<String>("", &refresh.session_family_field)
+                .ok()
+                .map(|family| (refresh, family))
+        }) {
+            self.revoke_family(refresh, dialect, db_conn, &family)
+                .await?;
+        }
+
+        sql_query(
+            db_conn,
+            format!(
+                "DELETE FROM {} WHERE {} = {} AND {} = {}",
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.token_field),
+                dialect.placeholder(1),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(2)
+            ),
+            CheapVec::from_vec(vec![
+                sea_orm::Value::from(token_hash.to_string()),
+                sea_orm::Value::from(user_id as i64),
+            ]),
+        )
+        .await?;
+
+        Ok(true)
+    }
+
+    /// Stores a new refresh token of the given family.
+    async fn insert_refresh(
+        &self,
+        refresh: &SqlRefreshTokens,
+        dialect: SqlDialect,
+        db_conn: &Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        family: &str,
+    ) -> Result<CompactString> {
+        let token = Alphanumeric
+            .sample_string(&mut rand::rng(), 48)
+            .to_compact_string();
+
+        sql_query(
+            db_conn,
+            format!(
+                "INSERT INTO {} ({}, {}, {}, {}) VALUES ({})",
+                dialect.quote(&refresh.table_name),
+                dialect.quote(&refresh.token_field),
+                dialect.quote(&refresh.family_field),
+                dialect.quote(&refresh.user_id_field),
+                dialect.quote(&refresh.created_field),
+                dialect.placeholders(1, 4)
+            ),
+            CheapVec::from_vec(vec![
+                sea_orm::Value::from(sql_token_hash(&token).to_string()),
+                sea_orm::Value::from(family.to_string()),
+                sea_orm::Value::from(user_id as i64),
+                dialect.datetime(Utc::now().naive_utc()),
+            ]),
+        )
+        .await?;
+
+        Ok(token)
+    }
+
+    /// Revokes every refresh token of the family and the sessions it issued.
+    async fn revoke_family(
+        &self,
+        refresh: &SqlRefreshTokens,
+        dialect: SqlDialect,
+        db_conn: &Arc<dyn AnyDatabaseConnection>,
+        family: &str,
+    ) -> Result<()> {
+        sql_query(
+            db_conn,
+            format!(
+                "DELETE FROM {} WHERE {} = {}",
+                dialect.quote(&refresh.table_name),
+                dialect.quote(&refresh.family_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(family.to_string())]),
+        )
+        .await?;
+
+        sql_query(
+            db_conn,
+            format!(
+                "DELETE FROM {} WHERE {} = {}",
+                dialect.quote(&self.table_name),
+                dialect.quote(&refresh.session_family_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(family.to_string())]),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[typetag::serde(name = "SqlSimple")]
+#[async_trait]
+impl AnyAuthenticationMethod for SqlSimpleAuthenticationMethod {
+    fn name(&self) -> &'static str {
+        "sqlsimple"
+    }
+
+    fn db_id(&self) -> Option<CompactString> {
+        self.database_id.to_owned()
+    }
+
+    fn login_account(
+        &self,
+        entries: &HashMap<CompactString, CompactString>,
+    ) -> Option<CompactString> {
+        entries.get(&self.name_field).cloned()
+    }
+
+    async fn prepare(&self) -> Result<()> {
+        self.hashing.prepare_decoy().await?;
+
+        Ok(())
+    }
+
+    async fn check(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        entries: HashMap<CompactString, CompactString>,
+    ) -> Result<Option<UserId>> {
+        let dialect = sql_dialect("SqlSimple", &db_conn)?;
+
+        let name_field = entries
+            .get(&self.name_field)
+            .ok_or(anyhow!("'{}' field not found.", self.name_field))?;
+        let password_field = entries
+            .get(&self.password_field)
+            .ok_or(anyhow!("'{}' field not found.", self.password_field))?;
+
+        // The password is verified in the server, as the hash's salt and parameters are stored within it.
+        let res = sql_query(
+            &db_conn,
+            format!(
+                "SELECT {}, {}{} FROM {} WHERE {} = {}",
+                dialect.quote(&self.user_id_field),
+                dialect.quote(&self.password_field),
+                self.disabled_field
+                    .as_ref()
+                    .map(|disabled_field| format!(", {}", dialect.quote(disabled_field)))
+                    .unwrap_or_default(),
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.name_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(name_field.to_string())]),
+        )
+        .await?;
+
+        // Missing users take as long as wrong passwords, so their absence isn't disclosed.
+        let Some(entry) = res.first() else {
+            self.hashing.verify_decoy(password_field).await?;
+            return Ok(None);
+        };
+
+        let Ok(hash) = entry.try_get::<String>("", &self.password_field) else {
+            bail!(
+                "Field '{}' expected but not returned in '{}' table. Maybe it exists but the associated data type is not a string.",
+                self.password_field,
+                self.table_name
+            )
+        };
+
+        if !self.hashing.verify(password_field, &hash).await? {
+            return Ok(None);
+        }
+
+        // Disabled users fail as if the credentials were wrong, so their accounts aren't disclosed.
+        if let Some(disabled_field) = &self.disabled_field {
+            if sql_flag(entry, disabled_field) {
+                return Ok(None);
+            }
+        }
+
+        let Some(user_id) = sql_user_id(entry, &self.user_id_field) else {
+            bail!(
+                "Field '{}' expected but not returned in '{}' table. Maybe it exists but the associated data type is not an integer.",
+                self.user_id_field,
+                self.table_name
+            )
+        };
+
+        // Upgrades the stored hash if the hashing parameters have changed.
+        if self.hashing.needs_rehash(&hash) {
+            let hash = self.hashing.hash(password_field).await?;
+
+            if let Err(err) = self.store_password(dialect, &db_conn, user_id, hash).await {
+                warn!("Cannot rehash the password of user {}. {}", user_id, err);
+            }
+        }
+
+        Ok(Some(user_id))
+    }
+
+    async fn new(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        entries: HashMap<CompactString, CompactString>,
+    ) -> Result<UserId> {
+        let dialect = sql_dialect("SqlSimple", &db_conn)?;
+
+        let name_field = entries
+            .get(&self.name_field)
+            .ok_or(anyhow!("'{}' field not found.", self.name_field))?;
+        let password_field = entries
+            .get(&self.password_field)
+            .ok_or(anyhow!("'{}' field not found.", self.password_field))?;
+
+        let mut query_input = CheapVec::<_, 8>::from_vec(vec![
+            sea_orm::Value::from(name_field.to_string()),
+            sea_orm::Value::from(self.hashing.hash(password_field).await?.to_string()),
+        ]);
+
+        for extra_field in &self.extra_fields {
+            query_input.push(sea_orm::Value::from(
+                entries
+                    .get(extra_field)
+                    .cloned()
+                    .ok_or(anyhow!("'{}' field not found.", extra_field))
+                    .map(|val| val.to_string())?,
+            ));
+        }
+
+        let fields = [
+            vec![self.name_field.to_owned(), self.password_field.to_owned()],
+            self.extra_fields.to_vec(),
+        ]
+        .concat()
+        .iter()
+        .map(|field| dialect.quote(field))
+        .collect::<CheapVec<_>>()
+        .join(", ");
+
+        let returning = if dialect.returning() {
+            format!(" RETURNING {}", dialect.quote(&self.user_id_field))
+        } else {
+            String::new()
+        };
+
+        let res = match sql_query(
+            &db_conn,
+            format!(
+                "INSERT INTO {} ({}) VALUES ({}){}",
+                dialect.quote(&self.table_name),
+                fields,
+                dialect.placeholders(1, self.extra_fields.len() + 2), // +2 as we have count the name and password field.
+                returning
+            ),
+            query_input,
+        )
+        .await
+        {
+            Ok(res) => res,
+            Err(err) => {
+                if dialect.is_unique_violation(&err) {
+                    return Err(anyhow!(
+                        "Signup failed, an account with the same unique fields already exists."
+                    ));
+                } else {
+                    return Err(err);
+                }
+            }
+        };
+
+        // Backends without `RETURNING` require querying the new user.
+        let res = if dialect.returning() {
+            res
+        } else {
+            sql_query(
+                &db_conn,
+                format!(
+                    "SELECT {} FROM {} WHERE {} = {}",
+                    dialect.quote(&self.user_id_field),
+                    dialect.quote(&self.table_name),
+                    dialect.quote(&self.name_field),
+                    dialect.placeholder(1)
+                ),
+                CheapVec::from_vec(vec![sea_orm::Value::from(name_field.to_string())]),
+            )
+            .await?
+        };
+
+        let Some(entry) = res.first() else {
+            bail!("Unexpected database's executor's output.");
+        };
+
+        let Some(user_id) = sql_user_id(entry, &self.user_id_field) else {
+            bail!(
+                "Field '{}' expected but not returned in '{}' table. Maybe it exists but the associated data type is not an integer.",
+                self.user_id_field,
+                self.table_name
+            )
+        };
+
+        Ok(user_id)
+    }
+
+    async fn active(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+    ) -> Result<Option<bool>> {
+        sql_user_active(
+            "SqlSimple",
+            &db_conn,
+            &self.table_name,
+            &self.user_id_field,
+            self.disabled_field.as_ref(),
+            user_id,
+        )
+        .await
+    }
+
+    async fn delete(&self, db_conn: Arc<dyn AnyDatabaseConnection>, user_id: UserId) -> Result<()> {
+        let dialect = sql_dialect("SqlSimple", &db_conn)?;
+
+        sql_query(
+            &db_conn,
+            format!(
+                "DELETE FROM {} WHERE {} = {}",
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(user_id as i64)]),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn hash_plaintext_passwords(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+    ) -> Result<usize> {
+        let dialect = sql_dialect("SqlSimple", &db_conn)?;
+
+        let res = sql_query(
+            &db_conn,
+            format!(
+                "SELECT {}, {} FROM {}",
+                dialect.quote(&self.user_id_field),
+                dialect.quote(&self.password_field),
+                dialect.quote(&self.table_name)
+            ),
+            CheapVec::new(),
+        )
+        .await?;
+
+        let mut hashed = 0;
+
+        for entry in &res {
+            let (Some(user_id), Ok(password)) = (
+                sql_user_id(entry, &self.user_id_field),
+                entry.try_get::<String>("", &self.password_field),
+            ) else {
+                bail!(
+                    "Fields '{}' and '{}' expected but not returned in '{}' table.",
+                    self.user_id_field,
+                    self.password_field,
+                    self.table_name
+                )
+            };
+
+            if PasswordHashing::is_hash(&password) {
+                continue;
+            }
+
+            let hash = self.hashing.hash(&password).await?;
+
+            self.store_password(dialect, &db_conn, user_id, hash)
+                .await?;
+
+            hashed += 1;
+        }
+
+        Ok(hashed)
+    }
+
+    async fn totp(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+    ) -> Result<Option<TotpState>> {
+        let Some(totp_field) = &self.totp_field else {
+            return Ok(None);
+        };
+
+        let dialect = sql_dialect("SqlSimple", &db_conn)?;
+
+        let res = sql_query(
+            &db_conn,
+            format!(
+                "SELECT {} FROM {} WHERE {} = {}",
+                dialect.quote(totp_field),
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(user_id as i64)]),
+        )
+        .await?;
+
+        let Some(entry) = res.first() else {
+            return Ok(None);
+        };
+
+        let Ok(totp) = entry.try_get::<Option<String>>("", totp_field) else {
+            bail!(
+                "Field '{}' expected but not returned in '{}' table. Maybe it exists but the associated data type is not a string.",
+                totp_field,
+                self.table_name
+            )
+        };
+
+        totp.filter(|totp| !totp.is_empty())
+            .map(|totp| {
+                serde_json::from_str::<TotpState>(&totp)
+                    .map_err(|err| anyhow!("Cannot deserialize the user's TOTP state. {}", err))
+            })
+            .transpose()
+    }
+
+    async fn set_totp(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        totp: Option<TotpState>,
+    ) -> Result<()> {
+        let Some(totp_field) = &self.totp_field else {
+            bail!(
+                "TOTP is not supported by '{}' as `totp_field` is not set.",
+                self.table_name
+            )
+        };
+
+        let dialect = sql_dialect("SqlSimple", &db_conn)?;
+
+        let totp = totp.map(|totp| serde_json::to_string(&totp)).transpose()?;
+
+        sql_query(
+            &db_conn,
+            format!(
+                "UPDATE {} SET {} = {} WHERE {} = {}",
+                dialect.quote(&self.table_name),
+                dialect.quote(totp_field),
+                dialect.placeholder(1),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(2)
+            ),
+            CheapVec::from_vec(vec![
+                sea_orm::Value::from(totp),
+                sea_orm::Value::from(user_id as i64),
+            ]),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn replace_totp(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        previous: &TotpState,
+        totp: TotpState,
+    ) -> Result<bool> {
+        let Some(totp_field) = &self.totp_field else {
+            bail!(
+                "TOTP is not supported by '{}' as `totp_field` is not set.",
+                self.table_name
+            )
+        };
+
+        let dialect = sql_dialect("SqlSimple", &db_conn)?;
+
+        // The states are always stored as serialized here, so they can be compared as text.
+        let replaced = sql_execute(
+            &db_conn,
+            format!(
+                "UPDATE {} SET {} = {} WHERE {} = {} AND {} = {}",
+                dialect.quote(&self.table_name),
+                dialect.quote(totp_field),
+                dialect.placeholder(1),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(2),
+                dialect.quote(totp_field),
+                dialect.placeholder(3)
+            ),
+            CheapVec::from_vec(vec![
+                sea_orm::Value::from(serde_json::to_string(&totp)?),
+                sea_orm::Value::from(user_id as i64),
+                sea_orm::Value::from(serde_json::to_string(previous)?),
+            ]),
+        )
+        .await?;
+
+        Ok(replaced > 0)
+    }
+
+    async fn users(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        search: Option<CompactString>,
+        offset: usize,
+        limit: usize,
+    ) -> Result<CheapVec<UserInfo, 0>> {
+        let dialect = sql_dialect("SqlSimple", &db_conn)?;
+
+        let mut fields = CheapVec::<_, 3>::from_vec(vec![
+            dialect.quote(&self.user_id_field),
+            dialect.quote(&self.name_field),
+        ]);
+
+        if let Some(disabled_field) = &self.disabled_field {
+            fields.push(dialect.quote(disabled_field));
+        }
+
+        let mut values = CheapVec::<_, 8>::new();
+
+        let filter = match search {
+            Some(search) => {
+                values.push(sea_orm::Value::from(sql_like_contains(&search)));
+
+                format!(
+                    " WHERE {} LIKE {} ESCAPE '!'",
+                    dialect.quote(&self.name_field),
+                    dialect.placeholder(1)
+                )
+            }
+            None => String::new(),
+        };
+
+        let res = sql_query(
+            &db_conn,
+            format!(
+                "SELECT {} FROM {}{} ORDER BY {} LIMIT {} OFFSET {}",
+                fields.join(", "),
+                dialect.quote(&self.table_name),
+                filter,
+                dialect.quote(&self.user_id_field),
+                limit,
+                offset
+            ),
+            values,
+        )
+        .await?;
+
+        res.iter()
+            .map(|entry| {
+                let (Some(user_id), Ok(name)) = (
+                    sql_user_id(entry, &self.user_id_field),
+                    entry.try_get::<String>("", &self.name_field),
+                ) else {
+                    bail!(
+                        "Fields '{}' and '{}' expected but not returned in '{}' table.",
+                        self.user_id_field,
+                        self.name_field,
+                        self.table_name
+                    )
+                };
+
+                let disabled = self
+                    .disabled_field
+                    .as_ref()
+                    .is_some_and(|disabled_field| sql_flag(entry, disabled_field));
+
+                Ok(UserInfo::new(user_id, name.to_compact_string(), disabled))
+            })
+            .collect()
+    }
+
+    async fn set_disabled(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        disabled: bool,
+    ) -> Result<()> {
+        let Some(disabled_field) = &self.disabled_field else {
+            bail!(
+                "Users cannot be disabled on '{}' as `disabled_field` is not set.",
+                self.table_name
+            )
+        };
+
+        let dialect = sql_dialect("SqlSimple", &db_conn)?;
+
+        sql_query(
+            &db_conn,
+            format!(
+                "UPDATE {} SET {} = {} WHERE {} = {}",
+                dialect.quote(&self.table_name),
+                dialect.quote(disabled_field),
+                dialect.placeholder(1),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(2)
+            ),
+            CheapVec::from_vec(vec![
+                sea_orm::Value::from(disabled),
+                sea_orm::Value::from(user_id as i64),
+            ]),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn force_password_reset(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+    ) -> Result<()> {
+        let dialect = sql_dialect("SqlSimple", &db_conn)?;
+
+        // A valid hash of a discarded password, so plaintext comparisons cannot match it either.
+        let hash = self
+            .hashing
+            .hash(&Alphanumeric.sample_string(&mut rand::rng(), 48))
+            .await?;
+
+        self.store_password(dialect, &db_conn, user_id, hash).await
+    }
+
+    async fn find(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        entries: HashMap<CompactString, CompactString>,
+    ) -> Result<Option<(UserId, CompactString)>> {
+        let dialect = sql_dialect("SqlSimple", &db_conn)?;
+
+        let name_field = entries
+            .get(&self.name_field)
+            .ok_or(anyhow!("'{}' field not found.", self.name_field))?;
+
+        let res = sql_query(
+            &db_conn,
+            format!(
+                "SELECT {}, {} FROM {} WHERE {} = {}",
+                dialect.quote(&self.user_id_field),
+                dialect.quote(self.recipient_field()),
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.name_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(name_field.to_string())]),
+        )
+        .await?;
+
+        let Some(entry) = res.first() else {
+            return Ok(None);
+        };
+
+        let (Some(user_id), Ok(email)) = (
+            sql_user_id(entry, &self.user_id_field),
+            entry.try_get::<Option<String>>("", self.recipient_field()),
+        ) else {
+            bail!(
+                "Fields '{}' and '{}' expected but not returned in '{}' table.",
+                self.user_id_field,
+                self.recipient_field(),
+                self.table_name
+            )
+        };
+
+        Ok(email.map(|email| (user_id, email.to_compact_string())))
+    }
+
+    async fn email(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+    ) -> Result<Option<CompactString>> {
+        let dialect = sql_dialect("SqlSimple", &db_conn)?;
+
+        let res = sql_query(
+            &db_conn,
+            format!(
+                "SELECT {} FROM {} WHERE {} = {}",
+                dialect.quote(self.recipient_field()),
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(user_id as i64)]),
+        )
+        .await?;
+
+        let Some(entry) = res.first() else {
+            return Ok(None);
+        };
+
+        let Ok(email) = entry.try_get::<Option<String>>("", self.recipient_field()) else {
+            bail!(
+                "Field '{}' expected but not returned in '{}' table. Maybe it exists but the associated data type is not a string.",
+                self.recipient_field(),
+                self.table_name
+            )
+        };
+
+        Ok(email.map(|email| email.to_compact_string()))
+    }
+
+    async fn set_password(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        password: CompactString,
+    ) -> Result<()> {
+        let dialect = sql_dialect("SqlSimple", &db_conn)?;
+
+        let hash = self.hashing.hash(&password).await?;
+
+        self.store_password(dialect, &db_conn, user_id, hash).await
+    }
+
+    async fn email_verified(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+    ) -> Result<bool> {
+        let verified_field = self.checked_verified_field()?;
+
+        let dialect = sql_dialect("SqlSimple", &db_conn)?;
+
+        let res = sql_query(
+            &db_conn,
+            format!(
+                "SELECT {} FROM {} WHERE {} = {}",
+                dialect.quote(verified_field),
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(user_id as i64)]),
+        )
+        .await?;
+
+        Ok(res
+            .first()
+            .is_some_and(|entry| sql_flag(entry, verified_field)))
+    }
+
+    async fn set_email_verified(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+    ) -> Result<()> {
+        let verified_field = self.checked_verified_field()?;
+
+        let dialect = sql_dialect("SqlSimple", &db_conn)?;
+
+        sql_query(
+            &db_conn,
+            format!(
+                "UPDATE {} SET {} = {} WHERE {} = {}",
+                dialect.quote(&self.table_name),
+                dialect.quote(verified_field),
+                dialect.placeholder(1),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(2)
+            ),
+            CheapVec::from_vec(vec![
+                sea_orm::Value::from(true),
+                sea_orm::Value::from(user_id as i64),
+            ]),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[typetag::serde(name = "SqlToken")]
+#[async_trait]
+impl AnySessionMethod for SqlToken {
+    fn name(&self) -> &'static str {
+        "sqltoken"
+    }
+
+    fn db_id(&self) -> Option<CompactString> {
+        self.database_id.to_owned()
+    }
+
+    fn max_age(&self) -> Option<usize> {
+        Some(self.max_age)
+    }
+
+    fn refresh_max_age(&self) -> Option<usize> {
+        self.refresh_tokens.as_ref().map(|refresh| refresh.max_age)
+    }
+
+    async fn check(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        token: CompactString,
+    ) -> Result<Option<UserId>> {
+        let dialect = sql_dialect("SqlToken", &db_conn)?;
+
+        let token_hash = sql_token_hash(&token);
+
+        let res = sql_query(
+            &db_conn,
+            format!(
+                "SELECT {}, {}{} FROM {} WHERE {} = {}",
+                dialect.quote(&self.user_id_field),
+                dialect.quote(&self.created_field),
+                self.activity
+                    .as_ref()
+                    .map(|activity| format!(", {}", dialect.quote(&activity.last_seen_field)))
+                    .unwrap_or_default(),
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.token_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(token_hash.to_string())]),
+        )
+        .await?;
+
+        let Some(entry) = res.first() else {
+            return Ok(None);
+        };
+
+        let Some(user_id) = sql_user_id(entry, &self.user_id_field) else {
+            bail!(
+                "Field '{}' expected but not returned in '{}' table. Maybe it exists but the associated data type is not an integer.",
+                self.user_id_field,
+                self.table_name
+            )
+        };
+
+        let Some(created_at) = sql_datetime(entry, &self.created_field) else {
+            bail!(
+                "Cannot find field '{}' in '{}' table. Maybe it exists but the associated data type is not a datetime.",
+                self.created_field,
+                self.table_name
+            )
+        };
+
+        let now = Utc::now().naive_utc();
+
+        // Checks whether the token has expired.
+        if created_at + Duration::from_secs(self.max_age as u64) <= now || created_at > now {
+            return Ok(None);
+        }
+
+        // Renews the session once past half its max age, so it's not updated on every request.
+        if self.sliding_expiration
+            && created_at + Duration::from_secs(self.max_age as u64 / 2) <= now
+        {
+            sql_query(
+                &db_conn,
+                format!(
+                    "UPDATE {} SET {} = {} WHERE {} = {}",
+                    dialect.quote(&self.table_name),
+                    dialect.quote(&self.created_field),
+                    dialect.placeholder(1),
+                    dialect.quote(&self.token_field),
+                    dialect.placeholder(2)
+                ),
+                CheapVec::from_vec(vec![
+                    dialect.datetime(now),
+                    sea_orm::Value::from(token_hash.to_string()),
+                ]),
+            )
+            .await?;
+        }
+
+        // Records the activity at most once per minute.
+        if let Some(activity) = &self.activity {
+            if sql_datetime(entry, &activity.last_seen_field)
+                .is_none_or(|last_seen| last_seen + Duration::from_secs(60) <= now)
+            {
+                sql_query(
+                    &db_conn,
+                    format!(
+                        "UPDATE {} SET {} = {} WHERE {} = {}",
+                        dialect.quote(&self.table_name),
+                        dialect.quote(&activity.last_seen_field),
+                        dialect.placeholder(1),
+                        dialect.quote(&self.token_field),
+                        dialect.placeholder(2)
+                    ),
+                    CheapVec::from_vec(vec![
+                        dialect.datetime(now),
+                        sea_orm::Value::from(token_hash.to_string()),
+                    ]),
+                )
+                .await?;
+            }
+        }
+
+        Ok(Some(user_id))
+    }
+
+    async fn new(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+    ) -> Result<CompactString> {
+        let dialect = sql_dialect("SqlToken", &db_conn)?;
+
+        self.insert_session(dialect, &db_conn, user_id, None, &SessionClient::default())
+            .await
+    }
+
+    async fn new_with_refresh(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        _roles: CheapVec<CompactString, 0>,
+        client: SessionClient,
+    ) -> Result<(CompactString, Option<CompactString>)> {
+        let dialect = sql_dialect("SqlToken", &db_conn)?;
+
+        let Some(refresh) = &self.refresh_tokens else {
+            return Ok((
+                self.insert_session(dialect, &db_conn, user_id, None, &client)
+                    .await?,
+                None,
+            ));
+        };
+
+        // Each login starts a new family.
+        let family = Alphanumeric.sample_string(&mut rand::rng(), 16);
+
+        let session_token = self
+            .insert_session(dialect, &db_conn, user_id, Some(&family), &client)
+            .await?;
+
+        let refresh_token = self
+            .insert_refresh(refresh, dialect, &db_conn, user_id, &family)
+            .await?;
+
+        Ok((session_token, Some(refresh_token)))
+    }
+
+    async fn refresh(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        refresh_token: CompactString,
+        client: SessionClient,
+    ) -> Result<Option<(CompactString, CompactString)>> {
+        let Some(refresh) = &self.refresh_tokens else {
+            bail!("Refresh tokens are not enabled for '{}'.", self.name())
+        };
+
+        let dialect = sql_dialect("SqlToken", &db_conn)?;
+
+        let token_hash = sql_token_hash(&refresh_token);
+
+        let res = sql_query(
+            &db_conn,
+            format!(
+                "SELECT {}, {}, {}, {} FROM {} WHERE {} = {}",
+                dialect.quote(&refresh.user_id_field),
+                dialect.quote(&refresh.family_field),
+                dialect.quote(&refresh.created_field),
+                dialect.quote(&refresh.rotated_field),
+                dialect.quote(&refresh.table_name),
+                dialect.quote(&refresh.token_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(token_hash.to_string())]),
+        )
+        .await?;
+
+        let Some(entry) = res.first() else {
+            return Ok(None);
+        };
+
+        let (Some(user_id), Ok(family), Some(created_at)) = (
+            sql_user_id(entry, &refresh.user_id_field),
+            entry.try_get::<String>("", &refresh.family_field),
+            sql_datetime(entry, &refresh.created_field),
+        ) else {
+            bail!(
+                "Unexpected fields' data types in '{}' table.",
+                refresh.table_name
+            )
+        };
+
+        // The token is marked as rotated only if it wasn't yet, so concurrent refreshes cannot
+        // both rotate it.
+        let reused = if sql_datetime(entry, &refresh.rotated_field).is_some() {
+            true
+        } else {
+            if created_at + Duration::from_secs(refresh.max_age as u64) <= Utc::now().naive_utc() {
+                return Ok(None);
+            }
+
+            sql_execute(
+                &db_conn,
+                format!(
+                    "UPDATE {} SET {} = {} WHERE {} = {} AND {} IS NULL",
+                    dialect.quote(&refresh.table_name),
+                    dialect.quote(&refresh.rotated_field),
+                    dialect.placeholder(1),
+                    dialect.quote(&refresh.token_field),
+                    dialect.placeholder(2),
+                    dialect.quote(&refresh.rotated_field),
+                ),
+                CheapVec::from_vec(vec![
+                    dialect.datetime(Utc::now().naive_utc()),
+                    sea_orm::Value::from(token_hash.to_string()),
+                ]),
+            )
+            .await?
+                == 0
+        };
+
+        // A rotated token can only be used again if it has been stolen.
+        if reused {
+            warn!(
+                "A rotated refresh token of user {} has been reused, revoking its family.",
+                user_id
+            );
+
+            self.revoke_family(refresh, dialect, &db_conn, &family)
+                .await?;
+
+            return Ok(None);
+        }
+
+        // The new session replaces the family's previous one, so each device is listed once and
+        // the old sessions don't count against `max_sessions`, revoking the family.
+        sql_query(
+            &db_conn,
+            format!(
+                "DELETE FROM {} WHERE {} = {}",
+                dialect.quote(&self.table_name),
+                dialect.quote(&refresh.session_family_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(family.to_string())]),
+        )
+        .await?;
+
+        let session_token = self
+            .insert_session(dialect, &db_conn, user_id, Some(&family), &client)
+            .await?;
+
+        let refresh_token = self
+            .insert_refresh(refresh, dialect, &db_conn, user_id, &family)
+            .await?;
+
+        Ok(Some((session_token, refresh_token)))
+    }
+
+    async fn refresh_user(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        refresh_token: CompactString,
+    ) -> Result<Option<UserId>> {
+        let Some(refresh) = &self.refresh_tokens else {
+            bail!("Refresh tokens are not enabled for '{}'.", self.name())
+        };
+
+        let dialect = sql_dialect("SqlToken", &db_conn)?;
+
+        let res = sql_query(
+            &db_conn,
+            format!(
+                "SELECT {} FROM {} WHERE {} = {}",
+                dialect.quote(&refresh.user_id_field),
+                dialect.quote(&refresh.table_name),
+                dialect.quote(&refresh.token_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(
+                sql_token_hash(&refresh_token).to_string(),
+            )]),
+        )
+        .await?;
+
+        Ok(res
+            .first()
+            .and_then(|entry| sql_user_id(entry, &refresh.user_id_field)))
+    }
+
+    async fn invalidate(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        token: Option<CompactString>,
+    ) -> Result<()> {
+        let dialect = sql_dialect("SqlToken", &db_conn)?;
+
+        match token {
+            Some(token) => {
+                // Invalidate a given token id.
+                self.delete_session(dialect, &db_conn, user_id, &sql_token_hash(&token))
+                    .await?;
+            }
+            None => {
+                // Invalidate all tokens from a given user.
+                sql_query(
+                    &db_conn,
+                    format!(
+                        "DELETE FROM {} WHERE {} = {}",
+                        dialect.quote(&self.table_name),
+                        dialect.quote(&self.user_id_field),
+                        dialect.placeholder(1)
+                    ),
+                    CheapVec::from_vec(vec![sea_orm::Value::from(user_id as i64)]),
+                )
+                .await?;
+
+                if let Some(refresh) = &self.refresh_tokens {
+                    sql_query(
+                        &db_conn,
+                        format!(
+                            "DELETE FROM {} WHERE {} = {}",
+                            dialect.quote(&refresh.table_name),
+                            dialect.quote(&refresh.user_id_field),
+                            dialect.placeholder(1)
+                        ),
+                        CheapVec::from_vec(vec![sea_orm::Value::from(user_id as i64)]),
+                    )
+                    .await?;
+                }
+            }
+        };
+
+        Ok(())
+    }
+
+    async fn remove_expired(&self, db_conn: Arc<dyn AnyDatabaseConnection>) -> Result<()> {
+        let dialect = sql_dialect("SqlToken", &db_conn)?;
+
+        sql_query(
+            &db_conn,
+            format!(
+                "DELETE FROM {} WHERE {} <= {}",
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.created_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![dialect.datetime(
+                Utc::now().naive_utc() - Duration::from_secs(self.max_age as u64),
+            )]),
+        )
+        .await?;
+
+        // Rotated refresh tokens are kept until they expire, so their reuse can be detected.
+        if let Some(refresh) = &self.refresh_tokens {
+            sql_query(
+                &db_conn,
+                format!(
+                    "DELETE FROM {} WHERE {} <= {}",
+                    dialect.quote(&refresh.table_name),
+                    dialect.quote(&refresh.created_field),
+                    dialect.placeholder(1)
+                ),
+                CheapVec::from_vec(vec![dialect.datetime(
+                    Utc::now().naive_utc() - Duration::from_secs(refresh.max_age as u64),
+                )]),
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn sessions(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        current_token: Option<CompactString>,
+    ) -> Result<CheapVec<SessionInfo, 0>> {
+        let dialect = sql_dialect("SqlToken", &db_conn)?;
+
+        let mut fields = CheapVec::<_, 6>::from_vec(vec![
+            dialect.quote(&self.token_field),
+            dialect.quote(&self.created_field),
+        ]);
+
+        if let Some(activity) = &self.activity {
+            fields.extend([
+                dialect.quote(&activity.user_agent_field),
+                dialect.quote(&activity.ip_field),
+                dialect.quote(&activity.last_seen_field),
+            ]);
+        }
+
+        let res = sql_query(
+            &db_conn,
+            format!(
+                "SELECT {} FROM {} WHERE {} = {} AND {} > {} ORDER BY {} DESC",
+                fields.join(", "),
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(1),
+                dialect.quote(&self.created_field),
+                dialect.placeholder(2),
+                dialect.quote(&self.created_field)
+            ),
+            CheapVec::from_vec(vec![
+                sea_orm::Value::from(user_id as i64),
+                dialect.datetime(Utc::now().naive_utc() - Duration::from_secs(self.max_age as u64)),
+            ]),
+        )
+        .await?;
+
+        let current_hash = current_token.map(|token| sql_token_hash(&token));
+
+        let mut sessions = CheapVec::new();
+
+        for entry in res.iter() {
+            let (Ok(id), Some(created_at)) = (
+                entry.try_get::<String>("", &self.token_field),
+                sql_datetime(entry, &self.created_field),
+            ) else {
+                bail!(
+                    "Unexpected fields' data types in '{}' table.",
+                    self.table_name
+                )
+            };
+
+            let (client, last_seen) = match &self.activity {
+                Some(activity) => (
+                    SessionClient::new(
+                        entry
+                            .try_get::<Option<String>>("", &activity.user_agent_field)
+                            .ok()
+                            .flatten()
+                            .map(|user_agent| user_agent.to_compact_string()),
+                        entry
+                            .try_get::<Option<String>>("", &activity.ip_field)
+                            .ok()
+                            .flatten()
+                            .map(|ip| ip.to_compact_string()),
+                    ),
+                    sql_datetime(entry, &activity.last_seen_field),
+                ),
+                None => (SessionClient::default(), None),
+            };
+
+            let current = current_hash.as_deref() == Some(id.as_str());
+
+            sessions.push(SessionInfo::new(
+                id.to_compact_string(),
+                created_at,
+                last_seen,
+                client,
+                current,
+            ));
+        }
+
+        Ok(sessions)
+    }
+
+    async fn revoke_session(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        session_id: CompactString,
+    ) -> Result<bool> {
+        let dialect = sql_dialect("SqlToken", &db_conn)?;
+
+        // Sessions are identified by their token's hash.
+        self.delete_session(dialect, &db_conn, user_id, &session_id)
+            .await
+    }
+}
+
+#[typetag::serde(name = "SqlRole")]
+#[async_trait]
+impl AnyRoleMethod for SqlRole {
+    fn name(&self) -> &'static str {
+        "sqlrole"
+    }
+
+    fn db_id(&self) -> Option<CompactString> {
+        self.database_id.to_owned()
+    }
+
+    async fn get(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+    ) -> Result<Option<CompactString>> {
+        Ok(self.get_all(db_conn, user_id).await?.into_iter().next())
+    }
+
+    async fn get_all(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+    ) -> Result<CheapVec<CompactString, 0>> {
+        let dialect = sql_dialect("SqlRole", &db_conn)?;
+
+        let res = sql_query(
+            &db_conn,
+            format!(
+                "SELECT {} FROM {} WHERE {} = {} ORDER BY {}",
+                dialect.quote(&self.role_field),
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(1),
+                dialect.quote(&self.role_field)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(user_id as i64)]),
+        )
+        .await?;
+
+        res.iter()
+            .map(|entry| {
+                let Ok(role) = entry.try_get::<String>("", &self.role_field) else {
+                    bail!(
+                        "Field '{}' expected but not returned in '{}' table. Maybe it exists but the associated data type is not a string.",
+                        self.role_field,
+                        self.table_name
+                    )
+                };
+
+                Ok(role.to_compact_string())
+            })
+            .collect()
+    }
+
+    async fn get_all_for(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_ids: &[UserId],
+    ) -> Result<HashMap<UserId, CheapVec<CompactString, 0>>> {
+        let mut roles = user_ids
+            .iter()
+            .map(|user_id| (*user_id, CheapVec::new()))
+            .collect::<HashMap<_, _>>();
+
+        if user_ids.is_empty() {
+            return Ok(roles);
+        }
+
+        let dialect = sql_dialect("SqlRole", &db_conn)?;
+
+        let res = sql_query(
+            &db_conn,
+            format!(
+                "SELECT {}, {} FROM {} WHERE {} IN ({}) ORDER BY {}",
+                dialect.quote(&self.user_id_field),
+                dialect.quote(&self.role_field),
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholders(1, user_ids.len()),
+                dialect.quote(&self.role_field)
+            ),
+            user_ids
+                .iter()
+                .map(|user_id| sea_orm::Value::from(*user_id as i64))
+                .collect(),
+        )
+        .await?;
+
+        for entry in &res {
+            let (Some(user_id), Ok(role)) = (
+                sql_user_id(entry, &self.user_id_field),
+                entry.try_get::<String>("", &self.role_field),
+            ) else {
+                bail!(
+                    "Unexpected fields' data types in '{}' table.",
+                    self.table_name
+                )
+            };
+
+            roles
+                .entry(user_id)
+                .or_default()
+                .push(role.to_compact_string());
+        }
+
+        Ok(roles)
+    }
+
+    async fn set(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        role: CompactString,
+    ) -> Result<()> {
+        let role = role.to_lowercase().to_compact_string();
+
+        if self.multiple_roles {
+            self.remove(db_conn.to_owned(), user_id).await?;
+
+            return self.grant(db_conn, user_id, role).await;
+        }
+
+        let dialect = sql_dialect("SqlRole", &db_conn)?;
+
+        sql_query(
+            &db_conn,
+            format!(
+                "INSERT INTO {} ({}, {}) VALUES ({}) {}",
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.user_id_field),
+                dialect.quote(&self.role_field),
+                dialect.placeholders(1, 2),
+                dialect.upsert(&self.user_id_field, &[self.role_field.as_str()])
+            ),
+            CheapVec::from_vec(vec![
+                sea_orm::Value::from(user_id as i64),
+                sea_orm::Value::from(role.to_string()),
+            ]),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn grant(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        role: CompactString,
+    ) -> Result<()> {
+        // Users with a single role get it replaced.
+        if !self.multiple_roles {
+            return self.set(db_conn, user_id, role).await;
+        }
+
+        let dialect = sql_dialect("SqlRole", &db_conn)?;
+
+        let role = role.to_lowercase().to_compact_string();
+
+        // Granting a role the user already has is a no-op, as told by the unique constraint.
+        sql_query(
+            &db_conn,
+            format!(
+                "INSERT INTO {} ({}, {}) VALUES ({}) {}",
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.user_id_field),
+                dialect.quote(&self.role_field),
+                dialect.placeholders(1, 2),
+                dialect.ignore_conflicts(&self.role_field)
+            ),
+            CheapVec::from_vec(vec![
+                sea_orm::Value::from(user_id as i64),
+                sea_orm::Value::from(role.to_string()),
+            ]),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn revoke(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        role: CompactString,
+    ) -> Result<()> {
+        let dialect = sql_dialect("SqlRole", &db_conn)?;
+
+        // Also matches the roles stored before they were lowercased.
+        sql_query(
+            &db_conn,
+            format!(
+                "DELETE FROM {} WHERE {} = {} AND LOWER({}) = {}",
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(1),
+                dialect.quote(&self.role_field),
+                dialect.placeholder(2)
+            ),
+            CheapVec::from_vec(vec![
+                sea_orm::Value::from(user_id as i64),
+                sea_orm::Value::from(role.to_lowercase()),
+            ]),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn remove(&self, db_conn: Arc<dyn AnyDatabaseConnection>, user_id: UserId) -> Result<()> {
+        let dialect = sql_dialect("SqlRole", &db_conn)?;
+
+        sql_query(
+            &db_conn,
+            format!(
+                "DELETE FROM {} WHERE {} = {}",
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(user_id as i64)]),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl SqlApiKey {
+    /// Columns selected to build the keys' info.
+    fn fields(&self, dialect: SqlDialect) -> String {
+        [
+            &self.key_field,
+            &self.user_id_field,
+            &self.name_field,
+            &self.scopes_field,
+            &self.created_field,
+            &self.expires_field,
+            &self.last_used_field,
+        ]
+        .iter()
+        .map(|field| dialect.quote(field))
+        .collect::<CheapVec<_>>()
+        .join(", ")
+    }
+
+    /// Reads the key's info from the row.
+    fn info(&self, entry: &QueryResult) -> Result<ApiKeyInfo> {
+        let (Ok(id), Some(user_id), Ok(name), Ok(scopes), Some(created_at)) = (
+            entry.try_get::<String>("", &self.key_field),
+            sql_user_id(entry, &self.user_id_field),
+            entry.try_get::<String>("", &self.name_field),
+            entry.try_get::<Option<String>>("", &self.scopes_field),
+            sql_datetime(entry, &self.created_field),
+        ) else {
+            bail!(
+                "Unexpected fields' data types in '{}' table.",
+                self.table_name
+            )
+        };
+
+        Ok(ApiKeyInfo::new(
+            id.to_compact_string(),
+            user_id,
+            name.to_compact_string(),
+            scopes
+                .unwrap_or_default()
+                .split_whitespace()
+                .map(|scope| scope.to_compact_string())
+                .collect(),
+            created_at,
+            sql_datetime(entry, &self.expires_field),
+            sql_datetime(entry, &self.last_used_field),
+        ))
+    }
+}
+
+#[typetag::serde(name = "SqlApiKey")]
+#[async_trait]
+impl AnyApiKeyMethod for SqlApiKey {
+    fn name(&self) -> &'static str {
+        "sqlapikey"
+    }
+
+    fn db_id(&self) -> Option<CompactString> {
+        self.database_id.to_owned()
+    }
+
+    async fn check(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        key: CompactString,
+    ) -> Result<Option<ApiKeyInfo>> {
+        let dialect = sql_dialect("SqlApiKey", &db_conn)?;
+
+        let key_hash = sql_token_hash(&key);
+
+        let res = sql_query(
+            &db_conn,
+            format!(
+                "SELECT {} FROM {} WHERE {} = {}",
+                self.fields(dialect),
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.key_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(key_hash.to_string())]),
+        )
+        .await?;
+
+        let Some(entry) = res.first() else {
+            return Ok(None);
+        };
+
+        let info = self.info(entry)?;
+
+        let now = Utc::now().naive_utc();
+
+        // Checks whether the key has expired.
+        if info
+            .expires_at()
+            .is_some_and(|expires_at| expires_at <= now)
+        {
+            return Ok(None);
+        }
+
+        // Records the key's use at most once per minute.
+        if info
+            .last_used()
+            .is_none_or(|last_used| last_used + Duration::from_secs(60) <= now)
+        {
+            sql_query(
+                &db_conn,
+                format!(
+                    "UPDATE {} SET {} = {} WHERE {} = {}",
+                    dialect.quote(&self.table_name),
+                    dialect.quote(&self.last_used_field),
+                    dialect.placeholder(1),
+                    dialect.quote(&self.key_field),
+                    dialect.placeholder(2)
+                ),
+                CheapVec::from_vec(vec![
+                    dialect.datetime(now),
+                    sea_orm::Value::from(key_hash.to_string()),
+                ]),
+            )
+            .await?;
+        }
+
+        Ok(Some(info))
+    }
+
+    async fn new(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: UserId,
+        name: CompactString,
+        scopes: CheapVec<CompactString, 0>,
+        expires_at: Option<NaiveDateTime>,
+    ) -> Result<(CompactString, ApiKeyInfo)> {
+        let dialect = sql_dialect("SqlApiKey", &db_conn)?;
+
+        if let Some(scope) = scopes
+            .iter()
+            .find(|scope| scope.contains(char::is_whitespace))
+        {
+            bail!("Scopes can't contain whitespaces, found '{}'.", scope)
+        }
+
+        // Prefixed so leaked keys are easy to spot.
+        let key =
+            format!("wl_{}", Alphanumeric.sample_string(&mut rand::rng(), 40)).to_compact_string();
+
+        let key_hash = sql_token_hash(&key);
+
+        let created_at = Utc::now().naive_utc();
+
+        let mut fields = CheapVec::<_, 6>::from_vec(vec![
+            dialect.quote(&self.key_field),
+            dialect.quote(&self.user_id_field),
+            dialect.quote(&self.name_field),
+            dialect.quote(&self.scopes_field),
+            dialect.quote(&self.created_field),
+        ]);
+
+        let mut values = CheapVec::<_, 8>::from_vec(vec![
+            sea_orm::Value::from(key_hash.to_string()),
+            sea_orm::Value::from(user_id as i64),
+            sea_orm::Value::from(name.to_string()),
+            sea_orm::Value::from(scopes.join(" ")),
+            dialect.datetime(created_at),
+        ]);
+
+        if let Some(expires_at) = expires_at {
+            fields.push(dialect.quote(&self.expires_field));
+            values.push(dialect.datetime(expires_at));
+        }
+
+        sql_query(
+            &db_conn,
+            format!(
+                "INSERT INTO {} ({}) VALUES ({})",
+                dialect.quote(&self.table_name),
+                fields.join(", "),
+                dialect.placeholders(1, values.len())
+            ),
+            values,
+        )
+        .await?;
+
+        Ok((
+            key,
+            ApiKeyInfo::new(
+                key_hash, user_id, name, scopes, created_at, expires_at, None,
+            ),
+        ))
+    }
+
+    async fn list(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        user_id: Option<UserId>,
+    ) -> Result<CheapVec<ApiKeyInfo, 0>> {
+        let dialect = sql_dialect("SqlApiKey", &db_conn)?;
+
+        let res = sql_query(
+            &db_conn,
+            format!(
+                "SELECT {} FROM {}{} ORDER BY {} DESC",
+                self.fields(dialect),
+                dialect.quote(&self.table_name),
+                user_id
+                    .map(|_| format!(
+                        " WHERE {} = {}",
+                        dialect.quote(&self.user_id_field),
+                        dialect.placeholder(1)
+                    ))
+                    .unwrap_or_default(),
+                dialect.quote(&self.created_field)
+            ),
+            user_id
+                .map(|user_id| sea_orm::Value::from(user_id as i64))
+                .into_iter()
+                .collect(),
+        )
+        .await?;
+
+        res.iter().map(|entry| self.info(entry)).collect()
+    }
+
+    async fn revoke(
+        &self,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        key_id: CompactString,
+    ) -> Result<bool> {
+        let dialect = sql_dialect("SqlApiKey", &db_conn)?;
+
+        let res = sql_query(
+            &db_conn,
+            format!(
+                "SELECT {} FROM {} WHERE {} = {}",
+                dialect.quote(&self.key_field),
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.key_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(key_id.to_string())]),
+        )
+        .await?;
+
+        if res.is_empty() {
+            return Ok(false);
+        }
+
+        sql_query(
+            &db_conn,
+            format!(
+                "DELETE FROM {} WHERE {} = {}",
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.key_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(key_id.to_string())]),
+        )
+        .await?;
+
+        Ok(true)
+    }
+
+    async fn remove(&self, db_conn: Arc<dyn AnyDatabaseConnection>, user_id: UserId) -> Result<()> {
+        let dialect = sql_dialect("SqlApiKey", &db_conn)?;
+
+        sql_query(
+            &db_conn,
+            format!(
+                "DELETE FROM {} WHERE {} = {}",
+                dialect.quote(&self.table_name),
+                dialect.quote(&self.user_id_field),
+                dialect.placeholder(1)
+            ),
+            CheapVec::from_vec(vec![sea_orm::Value::from(user_id as i64)]),
+        )
+        .await?;
+
+        Ok(())
+    }
+}
+
+/// Declares the former, database specific names of the SQL methods (e.g. `SQLiteSimple`) as
+/// transparent newtypes delegating to them, so the project files naming them still work.
+macro_rules! sql_method_aliases {
+    (
+        $simple:ident($simple_tag:tt, $simple_name:tt),
+        $token:ident($token_tag:tt, $token_name:tt),
+        $role:ident($role_tag:tt, $role_name:tt)
+    ) => {
+        /// Former name of `SqlSimpleAuthenticationMethod`.
+        #[derive(
+            Clone, PartialEq, Default, Constructor, Serialize, Deserialize, Display, Debug,
+        )]
+        #[serde(transparent)]
+        pub struct $simple(SqlSimpleAuthenticationMethod);
+
+        boxed_any!($simple);
+
+        /// Former name of `SqlToken`.
+        #[derive(
+            Clone, PartialEq, Default, Constructor, Serialize, Deserialize, Display, Debug,
+        )]
+        #[serde(transparent)]
+        pub struct $token(SqlToken);
+
+        boxed_any!($token);
+
+        /// Former name of `SqlRole`.
+        #[derive(
+            Clone, PartialEq, Default, Constructor, Serialize, Deserialize, Display, Debug,
+        )]
+        #[serde(transparent)]
+        pub struct $role(SqlRole);
+
+        boxed_any!($role);
+
+        #[typetag::serde(name = $simple_tag)]
+        #[async_trait]
+        impl AnyAuthenticationMethod for $simple {
+            fn name(&self) -> &'static str {
+                $simple_name
+            }
+
+            fn db_id(&self) -> Option<CompactString> {
+                AnyAuthenticationMethod::db_id(&self.0)
+            }
+
+            async fn prepare(&self) -> Result<()> {
+                AnyAuthenticationMethod::prepare(&self.0).await
+            }
+
+            async fn check(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                entries: HashMap<CompactString, CompactString>,
+            ) -> Result<Option<UserId>> {
+                AnyAuthenticationMethod::check(&self.0, db_conn, entries).await
+            }
+
+            fn login_account(
+                &self,
+                entries: &HashMap<CompactString, CompactString>,
+            ) -> Option<CompactString> {
+                AnyAuthenticationMethod::login_account(&self.0, entries)
+            }
+
+            async fn new(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                entries: HashMap<CompactString, CompactString>,
+            ) -> Result<UserId> {
+                AnyAuthenticationMethod::new(&self.0, db_conn, entries).await
+            }
+
+            async fn delete(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_id: UserId,
+            ) -> Result<()> {
+                AnyAuthenticationMethod::delete(&self.0, db_conn, user_id).await
+            }
+
+            async fn hash_plaintext_passwords(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+            ) -> Result<usize> {
+                AnyAuthenticationMethod::hash_plaintext_passwords(&self.0, db_conn).await
+            }
+
+            async fn totp(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_id: UserId,
+            ) -> Result<Option<TotpState>> {
+                AnyAuthenticationMethod::totp(&self.0, db_conn, user_id).await
+            }
+
+            async fn set_totp(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_id: UserId,
+                totp: Option<TotpState>,
+            ) -> Result<()> {
+                AnyAuthenticationMethod::set_totp(&self.0, db_conn, user_id, totp).await
+            }
+
+            async fn replace_totp(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_id: UserId,
+                previous: &TotpState,
+                totp: TotpState,
+            ) -> Result<bool> {
+                AnyAuthenticationMethod::replace_totp(&self.0, db_conn, user_id, previous, totp)
+                    .await
+            }
+
+            async fn active(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_id: UserId,
+            ) -> Result<Option<bool>> {
+                AnyAuthenticationMethod::active(&self.0, db_conn, user_id).await
+            }
+
+            async fn users(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                search: Option<CompactString>,
+                offset: usize,
+                limit: usize,
+            ) -> Result<CheapVec<UserInfo, 0>> {
+                AnyAuthenticationMethod::users(&self.0, db_conn, search, offset, limit).await
+            }
+
+            async fn set_disabled(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_id: UserId,
+                disabled: bool,
+            ) -> Result<()> {
+                AnyAuthenticationMethod::set_disabled(&self.0, db_conn, user_id, disabled).await
+            }
+
+            async fn force_password_reset(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_id: UserId,
+            ) -> Result<()> {
+                AnyAuthenticationMethod::force_password_reset(&self.0, db_conn, user_id).await
+            }
+
+            async fn find(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                entries: HashMap<CompactString, CompactString>,
+            ) -> Result<Option<(UserId, CompactString)>> {
+                AnyAuthenticationMethod::find(&self.0, db_conn, entries).await
+            }
+
+            async fn email(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_id: UserId,
+            ) -> Result<Option<CompactString>> {
+                AnyAuthenticationMethod::email(&self.0, db_conn, user_id).await
+            }
+
+            async fn set_password(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_id: UserId,
+                password: CompactString,
+            ) -> Result<()> {
+                AnyAuthenticationMethod::set_password(&self.0, db_conn, user_id, password).await
+            }
+
+            async fn email_verified(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_id: UserId,
+            ) -> Result<bool> {
+                AnyAuthenticationMethod::email_verified(&self.0, db_conn, user_id).await
+            }
+
+            async fn set_email_verified(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_id: UserId,
+            ) -> Result<()> {
+                AnyAuthenticationMethod::set_email_verified(&self.0, db_conn, user_id).await
+            }
+        }
+
+        #[typetag::serde(name = $token_tag)]
+        #[async_trait]
+        impl AnySessionMethod for $token {
+            fn name(&self) -> &'static str {
+                $token_name
+            }
+
+            fn db_id(&self) -> Option<CompactString> {
+                AnySessionMethod::db_id(&self.0)
+            }
+
+            fn max_age(&self) -> Option<usize> {
+                AnySessionMethod::max_age(&self.0)
+            }
+
+            fn refresh_max_age(&self) -> Option<usize> {
+                AnySessionMethod::refresh_max_age(&self.0)
+            }
+
+            async fn check(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                token: CompactString,
+            ) -> Result<Option<UserId>> {
+                AnySessionMethod::check(&self.0, db_conn, token).await
+            }
+
+            async fn new(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_id: UserId,
+            ) -> Result<CompactString> {
+                AnySessionMethod::new(&self.0, db_conn, user_id).await
+            }
+
+            fn embeds_roles(&self) -> bool {
+                AnySessionMethod::embeds_roles(&self.0)
+            }
+
+            async fn new_with_roles(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_id: UserId,
+                roles: CheapVec<CompactString, 0>,
+            ) -> Result<CompactString> {
+                AnySessionMethod::new_with_roles(&self.0, db_conn, user_id, roles).await
+            }
+
+            async fn new_with_refresh(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_id: UserId,
+                roles: CheapVec<CompactString, 0>,
+                client: SessionClient,
+            ) -> Result<(CompactString, Option<CompactString>)> {
+                AnySessionMethod::new_with_refresh(&self.0, db_conn, user_id, roles, client).await
+            }
+
+            async fn refresh(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                refresh_token: CompactString,
+                client: SessionClient,
+            ) -> Result<Option<(CompactString, CompactString)>> {
+                AnySessionMethod::refresh(&self.0, db_conn, refresh_token, client).await
+            }
+
+            async fn refresh_user(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                refresh_token: CompactString,
+            ) -> Result<Option<UserId>> {
+                AnySessionMethod::refresh_user(&self.0, db_conn, refresh_token).await
+            }
+
+            async fn check_with_roles(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                token: CompactString,
+            ) -> Result<Option<(UserId, Option<CheapVec<CompactString, 0>>)>> {
+                AnySessionMethod::check_with_roles(&self.0, db_conn, token).await
+            }
+
+            async fn invalidate(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_id: UserId,
+                token: Option<CompactString>,
+            ) -> Result<()> {
+                AnySessionMethod::invalidate(&self.0, db_conn, user_id, token).await
+            }
+
+            async fn remove_expired(&self, db_conn: Arc<dyn AnyDatabaseConnection>) -> Result<()> {
+                AnySessionMethod::remove_expired(&self.0, db_conn).await
+            }
+
+            async fn sessions(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_id: UserId,
+                current_token: Option<CompactString>,
+            ) -> Result<CheapVec<SessionInfo, 0>> {
+                AnySessionMethod::sessions(&self.0, db_conn, user_id, current_token).await
+            }
+
+            async fn revoke_session(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_id: UserId,
+                session_id: CompactString,
+            ) -> Result<bool> {
+                AnySessionMethod::revoke_session(&self.0, db_conn, user_id, session_id).await
+            }
+        }
+
+        #[typetag::serde(name = $role_tag)]
+        #[async_trait]
+        impl AnyRoleMethod for $role {
+            fn name(&self) -> &'static str {
+                $role_name
+            }
+
+            fn db_id(&self) -> Option<CompactString> {
+                AnyRoleMethod::db_id(&self.0)
+            }
+
+            async fn get(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_id: UserId,
+            ) -> Result<Option<CompactString>> {
+                AnyRoleMethod::get(&self.0, db_conn, user_id).await
+            }
+
+            async fn get_all(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_id: UserId,
+            ) -> Result<CheapVec<CompactString, 0>> {
+                AnyRoleMethod::get_all(&self.0, db_conn, user_id).await
+            }
+
+            async fn get_all_for(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_ids: &[UserId],
+            ) -> Result<HashMap<UserId, CheapVec<CompactString, 0>>> {
+                AnyRoleMethod::get_all_for(&self.0, db_conn, user_ids).await
+            }
+
+            async fn set(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_id: UserId,
+                role: CompactString,
+            ) -> Result<()> {
+                AnyRoleMethod::set(&self.0, db_conn, user_id, role).await
+            }
+
+            async fn grant(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_id: UserId,
+                role: CompactString,
+            ) -> Result<()> {
+                AnyRoleMethod::grant(&self.0, db_conn, user_id, role).await
+            }
+
+            async fn revoke(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_id: UserId,
+                role: CompactString,
+            ) -> Result<()> {
+                AnyRoleMethod::revoke(&self.0, db_conn, user_id, role).await
+            }
+
+            async fn remove(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_id: UserId,
+            ) -> Result<()> {
+                AnyRoleMethod::remove(&self.0, db_conn, user_id).await
+            }
+
+            async fn sync(
+                &self,
+                db_conn: Arc<dyn AnyDatabaseConnection>,
+                user_id: UserId,
+                roles: &[CompactString],
+                managed: &[CompactString],
+            ) -> Result<()> {
+                AnyRoleMethod::sync(&self.0, db_conn, user_id, roles, managed).await
+            }
+        }
+    };
+}
+
+pub(crate) use sql_method_aliases;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use databases::sqlite::*;
+
+    #[tokio::test]
+    async fn refresh_rotation_and_reuse() -> Result<()> {
+        let (db_conn, _) = SQLiteDBConnectionConfig::new(":memory:".to_compact_string(), false)
+            .new_conn("sql_token_test".to_compact_string(), None, None)
+            .await?;
+
+        for query in [
+            "CREATE TABLE sessions_auth (session_id TEXT PRIMARY KEY, user_id INTEGER, created_at DATETIME, family_id TEXT)",
+            "CREATE TABLE refresh_tokens_auth (token TEXT PRIMARY KEY, family_id TEXT, user_id INTEGER, created_at DATETIME, rotated_at DATETIME)",
+        ] {
+            db_conn
+                .execute(DatabaseInput::Query(query.to_compact_string()))
+                .await?;
+        }
+
+        let mut session = SqlToken::default();
+        session.refresh_tokens = Some(SqlRefreshTokens::default());
+
+        let (token, Some(refresh_token)) = session
+            .new_with_refresh(
+                db_conn.to_owned(),
+                3,
+                CheapVec::new(),
+                SessionClient::default(),
+            )
+            .await?
+        else {
+            bail!("A refresh token should have been issued.")
+        };
+
+        // Only the hashes are stored.
+        assert_eq!(
+            session.check(db_conn.to_owned(), token.to_owned()).await?,
+            Some(3)
+        );
+        assert!(
+            session
+                .check(db_conn.to_owned(), sql_token_hash(&token))
+                .await?
+                .is_none()
+        );
+
+        let Some((rotated_token, rotated_refresh_token)) = session
+            .refresh(
+                db_conn.to_owned(),
+                refresh_token.to_owned(),
+                SessionClient::default(),
+            )
+            .await?
+        else {
+            bail!("The refresh token should have been rotated.")
+        };
+
+        assert_eq!(
+            session
+                .check(db_conn.to_owned(), rotated_token.to_owned())
+                .await?,
+            Some(3)
+        );
+
+        // The rotated session replaces the previous one, so the device is listed once.
+        assert!(session.check(db_conn.to_owned(), token).await?.is_none());
+        assert_eq!(
+            session.sessions(db_conn.to_owned(), 3, None).await?.len(),
+            1
+        );
+
+        // Reusing the rotated token revokes the whole family.
+        assert!(
+            session
+                .refresh(db_conn.to_owned(), refresh_token, SessionClient::default())
+                .await?
+                .is_none()
+        );
+        assert!(
+            session
+                .refresh(
+                    db_conn.to_owned(),
+                    rotated_refresh_token,
+                    SessionClient::default(),
+                )
+                .await?
+                .is_none()
+        );
+        assert!(
+            session
+                .check(db_conn.to_owned(), rotated_token)
+                .await?
+                .is_none()
+        );
+
+        // Refreshing the same token twice at once can only rotate it once.
+        let (_, Some(refresh_token)) = session
+            .new_with_refresh(
+                db_conn.to_owned(),
+                3,
+                CheapVec::new(),
+                SessionClient::default(),
+            )
+            .await?
+        else {
+            bail!("A refresh token should have been issued.")
+        };
+
+        let (first, second) = tokio::join!(
+            session.refresh(
+                db_conn.to_owned(),
+                refresh_token.to_owned(),
+                SessionClient::default()
+            ),
+            session.refresh(
+                db_conn.to_owned(),
+                refresh_token.to_owned(),
+                SessionClient::default()
+            ),
+        );
+        assert!(first?.is_none() || second?.is_none());
+
+        assert!(
+            session
+                .refresh(db_conn, refresh_token, SessionClient::default())
+                .await?
+                .is_none()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn sessions_listing_and_limit() -> Result<()> {
+        let (db_conn, _) = SQLiteDBConnectionConfig::new(":memory:".to_compact_string(), false)
+            .new_conn("sql_sessions_test".to_compact_string(), None, None)
+            .await?;
+
+        db_conn
+            .execute(DatabaseInput::Query(
+                "CREATE TABLE sessions_auth (session_id TEXT PRIMARY KEY, user_id INTEGER, created_at DATETIME, user_agent TEXT, ip TEXT, last_seen DATETIME)".to_compact_string(),
+            ))
+            .await?;
+
+        let mut session = SqlToken::default();
+        session.activity = Some(SqlSessionActivity::default());
+        session.max_sessions = Some(2);
+
+        let mut tokens = CheapVec::<_, 3>::new();
+
+        for ip in ["10.0.0.1", "10.0.0.2", "10.0.0.3"] {
+            let (token, _) = session
+                .new_with_refresh(
+                    db_conn.to_owned(),
+                    5,
+                    CheapVec::new(),
+                    SessionClient::new(
+                        Some("test-agent".to_compact_string()),
+                        Some(ip.to_compact_string()),
+                    ),
+                )
+                .await?;
+
+            tokens.push(token);
+
+            // Sessions are ordered by their creation, which is stored with seconds' precision.
+            tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+        }
+
+        // The oldest session is revoked once the limit is exceeded.
+        assert!(
+            session
+                .check(db_conn.to_owned(), tokens[0].to_owned())
+                .await?
+                .is_none()
+        );
+
+        let sessions = session
+            .sessions(db_conn.to_owned(), 5, Some(tokens[2].to_owned()))
+            .await?;
+
+        assert_eq!(sessions.len(), 2);
+        assert!(*sessions[0].current());
+        assert_eq!(sessions[0].client().ip().as_deref(), Some("10.0.0.3"));
+        assert!(sessions[1].last_seen().is_some());
+
+        // Sessions can only be revoked by their own user.
+        let id = sessions[1].id().to_owned();
+
+        assert!(
+            !session
+                .revoke_session(db_conn.to_owned(), 6, id.to_owned())
+                .await?
+        );
+        assert!(session.revoke_session(db_conn.to_owned(), 5, id).await?);
+        assert!(
+            session
+                .check(db_conn, tokens[1].to_owned())
+                .await?
+                .is_none()
+        );
+
+        Ok(())
+    }
+    #[tokio::test]
+    async fn roles_synced() -> Result<()> {
+        let (db_conn, _) = SQLiteDBConnectionConfig::new(":memory:".to_compact_string(), false)
+            .new_conn("sql_roles_test".to_compact_string(), None, None)
+            .await?;
+
+        db_conn
+            .execute(DatabaseInput::Query(
+                "CREATE TABLE roles_auth (user_id INTEGER, role TEXT, UNIQUE (user_id, role))"
+                    .to_compact_string(),
+            ))
+            .await?;
+
+        let mut roles = SqlRole::default();
+        roles.multiple_roles = true;
+
+        for role in ["admin", "legacy"] {
+            roles
+                .grant(db_conn.to_owned(), 5, role.to_compact_string())
+                .await?;
+        }
+
+        // Only the managed roles which are no longer given are revoked.
+        roles
+            .sync(
+                db_conn.to_owned(),
+                5,
+                &["editor".to_compact_string()],
+                &["admin".to_compact_string(), "editor".to_compact_string()],
+            )
+            .await?;
+
+        assert_eq!(
+            roles.get_all(db_conn, 5).await?,
+            CheapVec::from_vec(vec![
+                "editor".to_compact_string(),
+                "legacy".to_compact_string()
+            ])
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn api_keys_lifecycle() -> Result<()> {
+        let (db_conn, _) = SQLiteDBConnectionConfig::new(":memory:".to_compact_string(), false)
+            .new_conn("sql_api_keys_test".to_compact_string(), None, None)
+            .await?;
+
+        db_conn
+            .execute(DatabaseInput::Query(
+                "CREATE TABLE api_keys_auth (key_hash TEXT PRIMARY KEY, user_id INTEGER, name TEXT, scopes TEXT, created_at DATETIME, expires_at DATETIME, last_used DATETIME)".to_compact_string(),
+            ))
+            .await?;
+
+        let api_keys = SqlApiKey::default();
+
+        let (key, created) = api_keys
+            .new(
+                db_conn.to_owned(),
+                4,
+                "cron".to_compact_string(),
+                CheapVec::from_vec(vec![
+                    "reporter".to_compact_string(),
+                    "orders:read".to_compact_string(),
+                ]),
+                None,
+            )
+            .await?;
+
+        let Some(checked) = api_keys.check(db_conn.to_owned(), key.to_owned()).await? else {
+            bail!("The API key should be valid.")
+        };
+
+        assert_eq!(*checked.user_id(), 4);
+        assert_eq!(checked.scopes().len(), 2);
+
+        // The use is recorded, and only the hash is stored.
+        let listed = api_keys.list(db_conn.to_owned(), Some(4)).await?;
+
+        assert_eq!(listed.len(), 1);
+        assert!(listed[0].last_used().is_some());
+        assert_ne!(listed[0].id(), &key);
+
+        // Expired keys are rejected.
+        let (expired, _) = api_keys
+            .new(
+                db_conn.to_owned(),
+                4,
+                "old".to_compact_string(),
+                CheapVec::new(),
+                Some(Utc::now().naive_utc() - Duration::from_secs(60)),
+            )
+            .await?;
+
+        assert!(api_keys.check(db_conn.to_owned(), expired).await?.is_none());
+
+        assert!(
+            api_keys
+                .revoke(db_conn.to_owned(), created.id().to_owned())
+                .await?
+        );
+        assert!(api_keys.check(db_conn, key).await?.is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn disabled_and_deleted_users_are_inactive() -> Result<()> {
+        let (db_conn, _) = SQLiteDBConnectionConfig::new(":memory:".to_compact_string(), false)
+            .new_conn("sql_active_test".to_compact_string(), None, None)
+            .await?;
+
+        db_conn
+            .execute(DatabaseInput::Query(
+                "CREATE TABLE users_auth (user_id INTEGER PRIMARY KEY, email TEXT UNIQUE, password TEXT, disabled BOOLEAN)".to_compact_string(),
+            ))
+            .await?;
+
+        let mut auth = SqlSimpleAuthenticationMethod::default();
+        auth.disabled_field = Some("disabled".to_compact_string());
+
+        let user_id = auth
+            .new(
+                db_conn.to_owned(),
+                HashMap::from([
+                    (
+                        "email".to_compact_string(),
+                        "ada@example.com".to_compact_string(),
+                    ),
+                    (
+                        "password".to_compact_string(),
+                        "password".to_compact_string(),
+                    ),
+                ]),
+            )
+            .await?;
+
+        assert_eq!(auth.active(db_conn.to_owned(), user_id).await?, Some(true));
+
+        auth.set_disabled(db_conn.to_owned(), user_id, true).await?;
+        assert_eq!(auth.active(db_conn.to_owned(), user_id).await?, Some(false));
+
+        auth.delete(db_conn.to_owned(), user_id).await?;
+        assert_eq!(auth.active(db_conn, user_id).await?, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn users_administration() -> Result<()> {
+        let (db_conn, _) = SQLiteDBConnectionConfig::new(":memory:".to_compact_string(), false)
+            .new_conn("sql_admin_test".to_compact_string(), None, None)
+            .await?;
+
+        for query in [
+            "CREATE TABLE users_auth (user_id INTEGER PRIMARY KEY, email TEXT UNIQUE, password TEXT, disabled BOOLEAN)",
+            "CREATE TABLE roles_auth (user_id INTEGER, role TEXT, UNIQUE (user_id, role))",
+            "CREATE TABLE api_keys_auth (key_hash TEXT PRIMARY KEY, user_id INTEGER, name TEXT, scopes TEXT, created_at DATETIME, expires_at DATETIME, last_used DATETIME)",
+        ] {
+            db_conn
+                .execute(DatabaseInput::Query(query.to_compact_string()))
+                .await?;
+        }
+
+        let mut auth = SqlSimpleAuthenticationMethod::default();
+        auth.disabled_field = Some("disabled".to_compact_string());
+
+        let mut roles = SqlRole::default();
+        roles.multiple_roles = true;
+
+        let api_keys = SqlApiKey::default();
+
+        let credentials = |email: &str| {
+            HashMap::from([
+                ("email".to_compact_string(), email.to_compact_string()),
+                (
+                    "password".to_compact_string(),
+                    "password".to_compact_string(),
+                ),
+            ])
+        };
+
+        let ada = auth
+            .new(db_conn.to_owned(), credentials("ada@example.com"))
+            .await?;
+        let grace = auth
+            .new(db_conn.to_owned(), credentials("grace@example.com"))
+            .await?;
+
+        // Role names are compared in lowercase, so granting one twice keeps a single row.
+        for role in ["admin", "editor", "Editor", "legacy"] {
+            roles
+                .grant(db_conn.to_owned(), ada, role.to_compact_string())
+                .await?;
+        }
+
+        roles
+            .revoke(db_conn.to_owned(), ada, "LEGACY".to_compact_string())
+            .await?;
+
+        let users = auth
+            .users(
+                db_conn.to_owned(),
+                Some("example".to_compact_string()),
+                0,
+                10,
+            )
+            .await?;
+        assert_eq!(
+            users.iter().map(|user| *user.id()).collect::<Vec<_>>(),
+            vec![ada, grace]
+        );
+
+        // Wildcards in the search are matched literally.
+        assert!(
+            auth.users(db_conn.to_owned(), Some("a_a".to_compact_string()), 0, 10)
+                .await?
+                .is_empty()
+        );
+
+        // The roles of the listed users are read at once.
+        let listed_roles = roles.get_all_for(db_conn.to_owned(), &[ada, grace]).await?;
+        assert_eq!(listed_roles[&ada].len(), 2);
+        assert!(listed_roles[&grace].is_empty());
+
+        // Disabled users cannot log in, and their keys are revoked.
+        let (key, _) = api_keys
+            .new(
+                db_conn.to_owned(),
+                grace,
+                "cron".to_compact_string(),
+                CheapVec::new(),
+                None,
+            )
+            .await?;
+
+        auth.set_disabled(db_conn.to_owned(), grace, true).await?;
+        api_keys.remove(db_conn.to_owned(), grace).await?;
+
+        assert!(
+            auth.check(db_conn.to_owned(), credentials("grace@example.com"))
+                .await?
+                .is_none()
+        );
+        assert!(api_keys.check(db_conn.to_owned(), key).await?.is_none());
+
+        auth.delete(db_conn.to_owned(), ada).await?;
+        roles.remove(db_conn.to_owned(), ada).await?;
+
+        let users = auth.users(db_conn.to_owned(), None, 0, 10).await?;
+        assert_eq!(users.len(), 1);
+        assert!(*users[0].disabled());
+        assert!(roles.get_all(db_conn, ada).await?.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn email_verification_and_password_reset() -> Result<()> {
+        let (db_conn, _) = SQLiteDBConnectionConfig::new(":memory:".to_compact_string(), false)
+            .new_conn("sql_simple_email_test".to_compact_string(), None, None)
+            .await?;
+
+        db_conn
+            .execute(DatabaseInput::Query(
+                "CREATE TABLE users_auth (user_id INTEGER PRIMARY KEY, email TEXT UNIQUE, password TEXT, verified BOOLEAN)".to_compact_string(),
+            ))
+            .await?;
+
+        let mut auth = SqlSimpleAuthenticationMethod::default();
+        auth.verified_field = Some("verified".to_compact_string());
+
+        let credentials = |password: &str| {
+            HashMap::from([
+                (
+                    "email".to_compact_string(),
+                    "user@example.com".to_compact_string(),
+                ),
+                ("password".to_compact_string(), password.to_compact_string()),
+            ])
+        };
+
+        let user_id = auth.new(db_conn.to_owned(), credentials("first")).await?;
+
+        // New users are unverified until they follow the emailed link.
+        assert!(!auth.email_verified(db_conn.to_owned(), user_id).await?);
+
+        auth.set_email_verified(db_conn.to_owned(), user_id).await?;
+
+        assert!(auth.email_verified(db_conn.to_owned(), user_id).await?);
+
+        assert_eq!(
+            auth.find(db_conn.to_owned(), credentials("")).await?,
+            Some((user_id, "user@example.com".to_compact_string()))
+        );
+
+        // The old password stops working once it's reset.
+        auth.set_password(db_conn.to_owned(), user_id, "second".to_compact_string())
+            .await?;
+
+        assert!(
+            auth.check(db_conn.to_owned(), credentials("first"))
+                .await?
+                .is_none()
+        );
+        assert_eq!(
+            auth.check(db_conn, credentials("second")).await?,
+            Some(user_id)
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+//!
+//! Former SQLite authentication, session and role methods, which are now the generic SQL ones.
+//!
+
+use crate::*;
+
+use super::*;
+
+use sql::*;
+
+sql_method_aliases!(
+    SQLiteSimpleAuthenticationMethod("SQLiteSimple", "sqlitesimple"),
+    SQLiteToken("SQLiteToken", "sqlitetoken"),
+    SQLiteRole("SQLiteRole", "sqliterole")
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn former_names_deserialize() -> Result<()> {
+        let simple = serde_json::from_value::<Box<dyn AnyAuthenticationMethod>>(json!({
+            "SQLiteSimple": serde_json::to_value(SqlSimpleAuthenticationMethod::default())?
+        }))?;
+        let token = serde_json::from_value::<Box<dyn AnySessionMethod>>(json!({
+            "SQLiteToken": serde_json::to_value(SqlToken::default())?
+        }))?;
+        let role = serde_json::from_value::<Box<dyn AnyRoleMethod>>(json!({
+            "SQLiteRole": serde_json::to_value(SqlRole::default())?
+        }))?;
+
+        assert_eq!(simple.name(), "sqlitesimple");
+        assert_eq!(token.name(), "sqlitetoken");
+        assert_eq!(role.name(), "sqliterole");
+
+        Ok(())
+    }
+}
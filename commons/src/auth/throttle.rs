@@ -0,0 +1,260 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+//!
+//! Throttling of failed logins, tracked both per account and per client address so neither
+//! guessing an account's password nor trying leaked credentials across accounts scales.
+//! Past a number of failures each new one doubles the delay before the next attempt, up to
+//! the temporary lockout, and failures are forgotten after a while without new ones.
+//!
+
+use crate::*;
+
+use std::time::Instant;
+
+/// Login throttling settings of the project.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display(
+    "Login throttling (after {} failures per account, {} per address)",
+    account_max_failures,
+    address_max_failures
+)]
+#[getset(get = "pub")]
+pub struct LoginThrottlingSettings {
+    /// Failed logins of an account before its logins are delayed.
+    account_max_failures: usize,
+
+    /// Failed logins from a client address before its logins are delayed, higher than the
+    /// accounts' as many users may share an address.
+    address_max_failures: usize,
+
+    /// Delay after the first failure past the max (in seconds), doubled by each further failure.
+    base_delay: usize,
+
+    /// Max delay (in seconds), which amounts to a temporary lockout.
+    lockout: usize,
+
+    /// Failures are forgotten after this time without new ones (in seconds).
+    window: usize,
+}
+
+impl Default for LoginThrottlingSettings {
+    fn default() -> Self {
+        Self {
+            account_max_failures: 5,
+            address_max_failures: 20,
+            base_delay: 1,
+            lockout: 900,
+            window: 3600,
+        }
+    }
+}
+
+/// The failed logins tracked for an account or an address.
+#[derive(Clone, Copy, Debug)]
+struct Failures {
+    count: usize,
+    last_at: Instant,
+}
+
+/// Tracks the failed logins of each key (e.g. `account/{name}` or `address/{ip}`).
+/// NOTE: it's kept in memory, so each instance throttles the logins it receives.
+#[derive(Default, Debug)]
+pub struct LoginThrottle {
+    failures: DashMap<CompactString, Failures>,
+}
+
+impl LoginThrottle {
+    /// Delay of the key's failures, from its last one.
+    fn delay(
+        failures: &Failures,
+        max_failures: usize,
+        settings: &LoginThrottlingSettings,
+    ) -> Duration {
+        if failures.count < max_failures {
+            return Duration::ZERO;
+        }
+
+        let doublings = (failures.count - max_failures).min(32) as u32;
+
+        Duration::from_secs(
+            (settings.base_delay as u64)
+                .saturating_mul(1 << doublings)
+                .min(settings.lockout as u64),
+        )
+    }
+
+    /// Time left until the key may attempt a login again, `None` if it may already.
+    pub fn retry_after(
+        &self,
+        key: &str,
+        max_failures: usize,
+        settings: &LoginThrottlingSettings,
+        now: Instant,
+    ) -> Option<Duration> {
+        let failures = *self.failures.get(key)?;
+
+        (failures.last_at + Self::delay(&failures, max_failures, settings))
+            .checked_duration_since(now)
+            .filter(|left| !left.is_zero())
+    }
+
+    /// Counts a login attempt of the key as failed before it's checked, so concurrent attempts
+    /// cannot exceed the max failures. Returns its delay if it's throttled from now on, or the
+    /// time left as an error if the key may not attempt a login yet.
+    pub fn attempt(
+        &self,
+        key: &str,
+        max_failures: usize,
+        settings: &LoginThrottlingSettings,
+        now: Instant,
+    ) -> Result<Option<Duration>, Duration> {
+        let window = Duration::from_secs(settings.window as u64);
+
+        // The entry is locked meanwhile, so the check and the count are atomic.
+        let mut failures = self
+            .failures
+            .entry(key.to_compact_string())
+            .or_insert(Failures {
+                count: 0,
+                last_at: now,
+            });
+
+        // Forgotten failures start over, while the sweep removes those which aren't tried again.
+        if now.saturating_duration_since(failures.last_at) >= window {
+            failures.count = 0;
+        }
+
+        if let Some(left) = (failures.last_at + Self::delay(&failures, max_failures, settings))
+            .checked_duration_since(now)
+            .filter(|left| !left.is_zero())
+        {
+            return Err(left);
+        }
+
+        failures.count += 1;
+        failures.last_at = now;
+
+        Ok(Some(Self::delay(&failures, max_failures, settings)).filter(|delay| !delay.is_zero()))
+    }
+
+    /// Gives back an attempt of the key which didn't fail.
+    pub fn release(&self, key: &str) {
+        self.failures.remove_if_mut(key, |_, failures| {
+            failures.count = failures.count.saturating_sub(1);
+            failures.count == 0
+        });
+    }
+
+    /// Forgets the key's failures after a successful login.
+    pub fn succeed(&self, key: &str) {
+        self.failures.remove(key);
+    }
+
+    /// Removes the failures forgotten after the window, returning how many keys are left.
+    pub fn sweep(&self, settings: &LoginThrottlingSettings, now: Instant) -> usize {
+        let window = Duration::from_secs(settings.window as u64);
+
+        self.failures
+            .retain(|_, failures| now.saturating_duration_since(failures.last_at) < window);
+
+        self.failures.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_until_the_lockout() {
+        let settings = LoginThrottlingSettings::new(3, 10, 2, 10, 60);
+
+        let throttle = LoginThrottle::default();
+
+        let now = Instant::now();
+
+        let at = |secs: u64| now + Duration::from_secs(secs);
+
+        // The first failures aren't delayed.
+        assert_eq!(throttle.attempt("account/ada", 3, &settings, now), Ok(None));
+        assert_eq!(throttle.attempt("account/ada", 3, &settings, now), Ok(None));
+        assert_eq!(throttle.retry_after("account/ada", 3, &settings, now), None);
+
+        // Then each failure doubles the delay, up to the lockout.
+        assert_eq!(
+            throttle.attempt("account/ada", 3, &settings, now),
+            Ok(Some(Duration::from_secs(2)))
+        );
+
+        // Attempts are rejected during the delay, as they're counted before being checked.
+        assert_eq!(
+            throttle.attempt("account/ada", 3, &settings, now),
+            Err(Duration::from_secs(2))
+        );
+
+        assert_eq!(
+            throttle.attempt("account/ada", 3, &settings, at(2)),
+            Ok(Some(Duration::from_secs(4)))
+        );
+        assert_eq!(
+            throttle.attempt("account/ada", 3, &settings, at(6)),
+            Ok(Some(Duration::from_secs(8)))
+        );
+        assert_eq!(
+            throttle.attempt("account/ada", 3, &settings, at(14)),
+            Ok(Some(Duration::from_secs(10)))
+        );
+
+        assert_eq!(
+            throttle.retry_after("account/ada", 3, &settings, at(18)),
+            Some(Duration::from_secs(6))
+        );
+        assert_eq!(
+            throttle.retry_after("account/ada", 3, &settings, at(24)),
+            None
+        );
+
+        // Other keys aren't affected, and failures are forgotten after the window.
+        assert_eq!(
+            throttle.retry_after("account/grace", 3, &settings, at(14)),
+            None
+        );
+
+        assert_eq!(throttle.sweep(&settings, at(60)), 1);
+        assert_eq!(throttle.sweep(&settings, at(75)), 0);
+
+        assert_eq!(
+            throttle.retry_after("account/ada", 3, &settings, at(20)),
+            None
+        );
+
+        // A successful login forgets the failures too.
+        for _ in 0..3 {
+            throttle
+                .attempt("account/linus", 3, &settings, at(100))
+                .unwrap();
+        }
+
+        throttle.succeed("account/linus");
+
+        assert_eq!(
+            throttle.retry_after("account/linus", 3, &settings, at(100)),
+            None
+        );
+
+        // While attempts which didn't fail are given back.
+        for _ in 0..3 {
+            throttle
+                .attempt("address/::1", 3, &settings, at(100))
+                .unwrap();
+        }
+
+        throttle.release("address/::1");
+
+        assert_eq!(
+            throttle.retry_after("address/::1", 3, &settings, at(100)),
+            None
+        );
+    }
+}
@@ -0,0 +1,229 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+//!
+//! Time-based one-time passwords (RFC 6238) used as the second authentication factor.
+//! The user's TOTP state (the shared secret and the hashes of the unused recovery codes)
+//! is stored by the authentication method, e.g. as JSON in the `totp_field` column.
+//!
+
+use crate::*;
+
+use totp_rs::{Secret, TOTP};
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Seconds each code is valid for.
+const TOTP_STEP: u64 = 30;
+
+/// TOTP settings of the project.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display("TOTP (challenges expire after {}s)", challenge_max_age)]
+#[getset(get = "pub")]
+pub struct TotpSettings {
+    /// Shown by the authenticator apps, the project's name by default.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    issuer: Option<CompactString>,
+
+    /// Max age of the login challenges and of the pending enrollments (in seconds).
+    challenge_max_age: usize,
+
+    /// Max failed attempts before a login challenge is discarded.
+    challenge_max_attempts: usize,
+
+    /// Number of recovery codes generated when TOTP is enabled.
+    recovery_codes: usize,
+}
+
+impl Default for TotpSettings {
+    fn default() -> Self {
+        Self {
+            issuer: None,
+            challenge_max_age: 300,
+            challenge_max_attempts: 5,
+            recovery_codes: 10,
+        }
+    }
+}
+
+/// The TOTP state of a user which has enabled it.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct TotpState {
+    /// Base32 encoded shared secret.
+    secret: CompactString,
+
+    /// Hashes of the recovery codes that haven't been used yet.
+    #[serde(default)]
+    recovery_codes: CheapVec<CompactString, 0>,
+
+    /// Time step of the last code used, the codes of this step or earlier ones are rejected.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    last_step: Option<u64>,
+}
+
+impl TotpState {
+    /// Generates a new random base32 encoded secret.
+    pub fn generate_secret() -> CompactString {
+        Secret::generate_secret().to_encoded().to_compact_string()
+    }
+
+    fn totp(secret: &str, issuer: Option<&str>, account: &str, skew: u8) -> Result<TOTP> {
+        let secret = Secret::Encoded(secret.to_string())
+            .to_bytes()
+            .map_err(|err| anyhow!("Invalid TOTP secret. {:?}", err))?;
+
+        TOTP::new(
+            totp_rs::Algorithm::SHA1,
+            6,
+            skew,
+            TOTP_STEP,
+            secret,
+            issuer.map(|issuer| issuer.to_string()),
+            account.replace(':', ""),
+        )
+        .map_err(|err| anyhow!("Cannot build the TOTP generator. {:?}", err))
+    }
+
+    /// Returns the `otpauth://` URI to be shown as a QR code by the client.
+    pub fn uri(secret: &str, issuer: &str, account: &str) -> Result<CompactString> {
+        Ok(
+            Self::totp(secret, Some(&issuer.replace(':', "")), account, 1)?
+                .get_url()
+                .to_compact_string(),
+        )
+    }
+
+    /// Checks the code against the given secret, allowing one step of clock skew.
+    pub fn verify_secret(secret: &str, code: &str) -> Result<bool> {
+        Ok(Self::totp(secret, None, "waveless", 1)?.check_current(code.trim())?)
+    }
+
+    /// Checks the code against the user's secret at the given time (in seconds), allowing one step
+    /// of clock skew. The step of a valid code is recorded, so the code cannot be used again.
+    /// NOTE: the updated state must be stored for the record to take effect.
+    pub fn verify_at(&mut self, code: &str, time: u64) -> Result<bool> {
+        let totp = Self::totp(&self.secret, None, "waveless", 0)?;
+
+        let current = time / TOTP_STEP;
+
+        for step in [current.saturating_sub(1), current, current + 1] {
+            if self.last_step.is_some_and(|last_step| step <= last_step) {
+                continue;
+            }
+
+            if totp.check(code.trim(), step * TOTP_STEP) {
+                self.last_step = Some(step);
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Checks the code against the user's secret, see `verify_at`.
+    pub fn verify(&mut self, code: &str) -> Result<bool> {
+        self.verify_at(
+            code,
+            SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+        )
+    }
+
+    /// Generates a new set of recovery codes, returning them in plaintext to be shown once
+    /// while only their hashes are kept.
+    pub fn with_recovery_codes(
+        secret: CompactString,
+        count: usize,
+    ) -> (Self, CheapVec<CompactString, 0>) {
+        let codes = (0..count)
+            .map(|_| {
+                Alphanumeric
+                    .sample_string(&mut rand::rng(), 10)
+                    .to_lowercase()
+                    .to_compact_string()
+            })
+            .collect::<CheapVec<CompactString, 0>>();
+
+        let hashes = codes
+            .iter()
+            .map(|code| Self::hash_recovery_code(code))
+            .collect();
+
+        (Self::new(secret, hashes, None), codes)
+    }
+
+    /// Removes the recovery code if it's valid, returning whether it has been used.
+    pub fn use_recovery_code(&mut self, code: &str) -> bool {
+        let hash = Self::hash_recovery_code(code);
+
+        let Some(position) = self
+            .recovery_codes
+            .iter()
+            .position(|stored| *stored == hash)
+        else {
+            return false;
+        };
+
+        self.recovery_codes.remove(position);
+
+        true
+    }
+
+    /// Recovery codes are random, so a fast hash is enough.
+    fn hash_recovery_code(code: &str) -> CompactString {
+        blake3::hash(code.trim().to_lowercase().as_bytes())
+            .to_hex()
+            .to_compact_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_and_recovery_codes() -> Result<()> {
+        let secret = TotpState::generate_secret();
+
+        let (mut state, codes) = TotpState::with_recovery_codes(secret.to_owned(), 3);
+
+        let code = TotpState::totp(&secret, None, "waveless", 1)?.generate_current()?;
+
+        assert!(state.verify(&code)?);
+
+        // Codes cannot be reused.
+        assert!(!state.verify(&code)?);
+        assert!(
+            TotpState::uri(&secret, "Waveless", "user@example.com")?.starts_with("otpauth://totp/")
+        );
+
+        assert!(state.use_recovery_code(&codes[1].to_uppercase()));
+        assert!(!state.use_recovery_code(&codes[1]));
+        assert_eq!(state.recovery_codes().len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn codes_of_earlier_steps_are_rejected() -> Result<()> {
+        let secret = TotpState::generate_secret();
+
+        let (mut state, _) = TotpState::with_recovery_codes(secret.to_owned(), 0);
+
+        let totp = TotpState::totp(&secret, None, "waveless", 0)?;
+
+        let now = 1_800_000_000;
+        let previous = totp.generate(now - TOTP_STEP);
+        let current = totp.generate(now);
+
+        // The previous step is still accepted because of the clock skew, but not after the current one.
+        assert!(state.verify_at(&current, now)?);
+        assert_eq!(*state.last_step(), Some(now / TOTP_STEP));
+        assert!(!state.verify_at(&previous, now)?);
+        assert!(!state.verify_at(&current, now + 1)?);
+
+        assert!(state.verify_at(&totp.generate(now + TOTP_STEP), now + TOTP_STEP)?);
+
+        Ok(())
+    }
+}
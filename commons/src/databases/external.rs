@@ -0,0 +1,679 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+//!
+//! The external database drivers' protocol.
+//! An external driver is a child process that speaks JSON-RPC 2.0 over its stdio, one message per line.
+//! The executor writes requests to the driver's stdin and reads the responses from its stdout (stderr
+//! is inherited, so drivers can log there). Requests are multiplexed by their id, so drivers are free
+//! to answer them out of order.
+//!
+//! Methods:
+//! - `initialize` `{ protocol_version, connection }` → `{}`: always the first request.
+//! - `execute` `{ query, values }` → `{ rows }`: `?` placeholders are bound in order with `values`,
+//!   `rows` must be an array of JSON objects.
+//! - `schema` `{ connection, config }` → `{ schema }`: `schema` must follow the `DataSchema` model.
+//!
+//! Drivers are referenced by their `ExternalDriverId`, if no command is set the executable
+//! `waveless_driver_{id}` will be searched in the `PATH`. Drivers written in Rust can use
+//! `serve_driver` to implement the protocol.
+//!
+use crate::*;
+
+use super::*;
+
+use schema::*;
+
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use serde::de::DeserializeOwned;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::{Mutex, oneshot};
+
+/// Version of the external drivers' protocol sent on `initialize`.
+pub const EXTERNAL_DRIVER_PROTOCOL_VERSION: u32 = 1;
+
+/// Time a driver has to answer a request by default.
+pub const EXTERNAL_DRIVER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// JSON-RPC request sent to external drivers.
+#[derive(Clone, Constructor, Serialize, Deserialize, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct DriverRequest {
+    jsonrpc: CompactString,
+    id: u64,
+    method: CompactString,
+    #[serde(default)]
+    params: serde_json::Value,
+}
+
+/// JSON-RPC response sent back by external drivers.
+#[derive(Clone, Constructor, Serialize, Deserialize, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct DriverResponse {
+    jsonrpc: CompactString,
+    id: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    result: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<DriverError>,
+}
+
+/// JSON-RPC error object.
+#[derive(Clone, Constructor, Serialize, Deserialize, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct DriverError {
+    code: i64,
+    message: CompactString,
+}
+
+#[derive(Clone, Constructor, Serialize, Deserialize, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct InitializeParams {
+    protocol_version: u32,
+    connection: CompactString,
+}
+
+#[derive(Clone, Constructor, Serialize, Deserialize, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct ExecuteParams {
+    query: CompactString,
+    #[serde(default)]
+    values: Vec<serde_json::Value>,
+}
+
+#[derive(Clone, Constructor, Serialize, Deserialize, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct ExecuteResult {
+    #[serde(default)]
+    rows: Vec<serde_json::Value>,
+}
+
+#[derive(Clone, Constructor, Serialize, Deserialize, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct SchemaParams {
+    connection: CompactString,
+    #[serde(default)]
+    config: HashMap<CompactString, Bytes>,
+}
+
+#[derive(Clone, Constructor, Serialize, Deserialize, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct SchemaResult {
+    schema: DataSchema,
+}
+
+/// A running external driver. Requests are written to the driver while a background
+/// task dispatches the responses to the pending requests by their id.
+/// Once the driver closes its output it's marked as failed, and every request fails right away
+/// (it isn't respawned, as the queries it was running may have had side effects).
+pub struct ExternalDriver {
+    id: ExternalDriverId,
+    writer: Mutex<Box<dyn AsyncWrite + Send + Unpin>>,
+    pending: Arc<DashMap<u64, oneshot::Sender<DriverResponse>>>,
+    closed: Arc<AtomicBool>,
+    next_id: AtomicU64,
+    timeout: Duration,
+    _child: Option<Child>,
+}
+
+impl Debug for ExternalDriver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExternalDriver")
+            .field("id", &self.id)
+            .field("pending", &self.pending.len())
+            .field("closed", &self.closed.load(Ordering::Acquire))
+            .finish()
+    }
+}
+
+impl ExternalDriver {
+    /// Launches the driver's process, the child will be killed when the driver is dropped.
+    pub fn spawn(
+        id: ExternalDriverId,
+        command: Option<&CompactString>,
+        args: &[CompactString],
+    ) -> Result<Self> {
+        let command = command
+            .map(|command| command.to_string())
+            .unwrap_or(format!("waveless_driver_{}", id));
+
+        let mut child = Command::new(&command)
+            .args(args.iter().map(|arg| arg.as_str()))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|err| {
+                anyhow!(
+                    "Cannot launch the external driver '{}' using `{}`. {}",
+                    id,
+                    command,
+                    err
+                )
+            })?;
+
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or(anyhow!("Cannot capture the stdin of '{}'.", id))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or(anyhow!("Cannot capture the stdout of '{}'.", id))?;
+
+        Ok(Self::from_io(id, stdout, stdin, Some(child)))
+    }
+
+    /// Creates a driver from any transport, useful to run drivers in-process.
+    pub fn from_io<R, W>(id: ExternalDriverId, reader: R, writer: W, child: Option<Child>) -> Self
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        let pending = Arc::new(DashMap::<u64, oneshot::Sender<DriverResponse>>::new());
+        let closed = Arc::new(AtomicBool::new(false));
+
+        let dispatcher_pending = pending.to_owned();
+        let dispatcher_closed = closed.to_owned();
+        let driver_id = id.to_owned();
+
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(reader).lines();
+
+            loop {
+                match lines.next_line().await {
+                    Ok(Some(line)) if line.trim().is_empty() => continue,
+                    Ok(Some(line)) => match serde_json::from_str::<DriverResponse>(&line) {
+                        Ok(response) => {
+                            if let Some((_, sender)) = dispatcher_pending.remove(&response.id) {
+                                let _ = sender.send(response);
+                            } else {
+                                warn!(
+                                    "External driver '{}' answered an unknown request ({}).",
+                                    driver_id, response.id
+                                );
+                            }
+                        }
+                        Err(err) => warn!(
+                            "External driver '{}' sent a malformed response. {}",
+                            driver_id, err
+                        ),
+                    },
+                    Ok(None) => break,
+                    Err(err) => {
+                        error!("Cannot read from external driver '{}'. {}", driver_id, err);
+                        break;
+                    }
+                }
+            }
+
+            // New requests fail right away, and dropping the senders makes the in-flight ones fail.
+            dispatcher_closed.store(true, Ordering::Release);
+            dispatcher_pending.clear();
+
+            error!("External driver '{}' has been closed.", driver_id);
+        });
+
+        Self {
+            id,
+            writer: Mutex::new(Box::new(writer)),
+            pending,
+            closed,
+            next_id: AtomicU64::new(1),
+            timeout: EXTERNAL_DRIVER_TIMEOUT,
+            _child: child,
+        }
+    }
+
+    /// Sets the time the driver has to answer each request.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn id(&self) -> &ExternalDriverId {
+        &self.id
+    }
+
+    /// Whether the driver has closed its output, so it cannot answer anymore.
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+
+    /// Sends a request to the driver and waits for its response.
+    pub async fn call<P: Serialize, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: P,
+    ) -> Result<R> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+
+        let mut buffer = serde_json::to_vec(&DriverRequest::new(
+            "2.0".to_compact_string(),
+            id,
+            method.to_compact_string(),
+            serde_json::to_value(params)?,
+        ))?;
+        buffer.push(b'\n');
+
+        let (sender, receiver) = oneshot::channel();
+
+        self.pending.insert(id, sender);
+
+        // Checked once the request is pending, so the dispatcher cannot close in between unnoticed.
+        if self.is_closed() {
+            self.pending.remove(&id);
+            bail!("External driver '{}' has been closed.", self.id);
+        }
+
+        let response = self.send_and_wait(&buffer, method, receiver).await;
+
+        // The request isn't pending anymore whatever the outcome, e.g. after a timeout.
+        self.pending.remove(&id);
+
+        let response = response?;
+
+        match (response.result, response.error) {
+            (_, Some(err)) => bail!(
+                "External driver '{}' failed on `{}`: {} (code {}).",
+                self.id,
+                method,
+                err.message,
+                err.code
+            ),
+            (result, None) => Ok(serde_json::from_value(
+                result.unwrap_or(serde_json::Value::Null),
+            )?),
+        }
+    }
+
+    /// Writes the request and waits for its response, up to the driver's timeout.
+    async fn send_and_wait(
+        &self,
+        buffer: &[u8],
+        method: &str,
+        receiver: oneshot::Receiver<DriverResponse>,
+    ) -> Result<DriverResponse> {
+        {
+            let mut writer = self.writer.lock().await;
+
+            writer
+                .write_all(buffer)
+                .await
+                .map_err(|err| anyhow!("Cannot write to external driver '{}'. {}", self.id, err))?;
+
+            writer
+                .flush()
+                .await
+                .map_err(|err| anyhow!("Cannot write to external driver '{}'. {}", self.id, err))?;
+        }
+
+        match tokio::time::timeout(self.timeout, receiver).await {
+            Ok(Ok(response)) => Ok(response),
+            Ok(Err(_)) => bail!(
+                "External driver '{}' was closed before answering `{}`.",
+                self.id,
+                method
+            ),
+            Err(_) => bail!(
+                "External driver '{}' didn't answer `{}` within {}s.",
+                self.id,
+                method,
+                self.timeout.as_secs_f64()
+            ),
+        }
+    }
+
+    /// Performs the protocol's handshake.
+    pub async fn initialize(&self, connection: CompactString) -> Result<()> {
+        let _: serde_json::Value = self
+            .call(
+                "initialize",
+                InitializeParams::new(EXTERNAL_DRIVER_PROTOCOL_VERSION, connection),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Discovers the data schema through the driver.
+    pub async fn schema(
+        &self,
+        connection: CompactString,
+        config: HashMap<CompactString, Bytes>,
+    ) -> Result<DataSchema> {
+        let res: SchemaResult = self
+            .call("schema", SchemaParams::new(connection, config))
+            .await?;
+
+        Ok(res.schema)
+    }
+}
+
+/// Database connection backed by an external driver.
+#[derive(Clone, Constructor, Debug)]
+pub struct ExternalConnection(Arc<ExternalDriver>);
+
+boxed_any!(ExternalConnection);
+
+#[async_trait]
+impl AnyDatabaseConnection for ExternalConnection {
+    fn name(&self) -> &str {
+        self.0.id()
+    }
+
+    /// The output will be the driver's rows as a `Vec<serde_json::Value>`.
+    async fn execute(&self, input: DatabaseInput) -> Result<DatabaseOutput> {
+        let params = match input {
+            DatabaseInput::Query(query) => ExecuteParams::new(query, Vec::new()),
+            DatabaseInput::QueryValues(query, values) => ExecuteParams::new(
+                query,
+                values
+                    .iter()
+                    .map(sea_orm::sea_query::sea_value_to_json_value)
+                    .collect(),
+            ),
+            _ => bail!("Unsupported input for external driver '{}'.", self.0.id()),
+        };
+
+        let res: ExecuteResult = self.0.call("execute", params).await?;
+
+        Ok(DatabaseOutput::Any(Box::new(res.rows)))
+    }
+}
+
+/// Implemented by external drivers written in Rust, see `serve_driver`.
+#[async_trait]
+pub trait ExternalDriverHandler: Send + Sync {
+    async fn initialize(&self, connection: CompactString) -> Result<()>;
+
+    async fn execute(
+        &self,
+        query: CompactString,
+        values: Vec<serde_json::Value>,
+    ) -> Result<Vec<serde_json::Value>>;
+
+    async fn schema(
+        &self,
+        connection: CompactString,
+        config: HashMap<CompactString, Bytes>,
+    ) -> Result<DataSchema>;
+}
+
+/// Serves the external drivers' protocol, usually over the process' stdio,
+/// until the reader is closed. Requests are handled one after the other.
+pub async fn serve_driver<H, R, W>(handler: H, reader: R, mut writer: W) -> Result<()>
+where
+    H: ExternalDriverHandler,
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let DriverRequest {
+            id, method, params, ..
+        } = match serde_json::from_str::<DriverRequest>(&line) {
+            Ok(request) => request,
+            Err(err) => {
+                warn!("Malformed request. {}", err);
+                continue;
+            }
+        };
+
+        let res: Result<serde_json::Value> = async {
+            match method.as_str() {
+                "initialize" => {
+                    let params: InitializeParams = serde_json::from_value(params)?;
+
+                    if params.protocol_version != EXTERNAL_DRIVER_PROTOCOL_VERSION {
+                        bail!("Unsupported protocol version {}.", params.protocol_version);
+                    }
+
+                    handler.initialize(params.connection).await?;
+
+                    Ok(json!({}))
+                }
+                "execute" => {
+                    let params: ExecuteParams = serde_json::from_value(params)?;
+
+                    let rows = handler.execute(params.query, params.values).await?;
+
+                    Ok(serde_json::to_value(ExecuteResult::new(rows))?)
+                }
+                "schema" => {
+                    let params: SchemaParams = serde_json::from_value(params)?;
+
+                    let schema = handler.schema(params.connection, params.config).await?;
+
+                    Ok(serde_json::to_value(SchemaResult::new(schema))?)
+                }
+                method => bail!("Unknown method `{}`.", method),
+            }
+        }
+        .await;
+
+        let response = match res {
+            Ok(result) => DriverResponse::new("2.0".to_compact_string(), id, Some(result), None),
+            Err(err) => DriverResponse::new(
+                "2.0".to_compact_string(),
+                id,
+                None,
+                Some(DriverError::new(-32000, err.to_compact_string())),
+            ),
+        };
+
+        let mut buffer = serde_json::to_vec(&response)?;
+        buffer.push(b'\n');
+
+        writer.write_all(&buffer).await?;
+        writer.flush().await?;
+    }
+
+    Ok(())
+}
+
+/// The in-repo test driver: it doesn't store anything, it echoes the queries and returns a fixed schema.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct EchoDriver;
+
+    #[async_trait]
+    impl ExternalDriverHandler for EchoDriver {
+        async fn initialize(&self, connection: CompactString) -> Result<()> {
+            if connection != "echo://" {
+                bail!("Unknown connection.")
+            }
+            Ok(())
+        }
+
+        async fn execute(
+            &self,
+            query: CompactString,
+            values: Vec<serde_json::Value>,
+        ) -> Result<Vec<serde_json::Value>> {
+            if query.starts_with("FAIL") {
+                bail!("Requested failure.")
+            }
+            Ok(vec![json!({ "query": query, "values": values })])
+        }
+
+        async fn schema(
+            &self,
+            _connection: CompactString,
+            _config: HashMap<CompactString, Bytes>,
+        ) -> Result<DataSchema> {
+            Ok(DataSchema::new(CheapVec::from_vec(vec![TableSchema::new(
+                "posts".to_compact_string(),
+                false,
+                CheapVec::from_vec(vec![
+                    ColumnSchema::new(
+                        "id".to_compact_string(),
+                        "int".to_compact_string(),
+                        false,
+                        true,
+                        true,
+                    ),
+                    ColumnSchema::new(
+                        "title".to_compact_string(),
+                        "text".to_compact_string(),
+                        false,
+                        false,
+                        false,
+                    ),
+                ]),
+                CheapVec::new(),
+            )])))
+        }
+    }
+
+    fn echo_driver() -> ExternalDriver {
+        let (client, server) = tokio::io::duplex(4096);
+        let (client_reader, client_writer) = tokio::io::split(client);
+        let (server_reader, server_writer) = tokio::io::split(server);
+
+        tokio::spawn(serve_driver(EchoDriver, server_reader, server_writer));
+
+        ExternalDriver::from_io(
+            "echo".to_compact_string(),
+            client_reader,
+            client_writer,
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn execute_through_driver() -> Result<()> {
+        let driver = echo_driver();
+
+        driver.initialize("echo://".to_compact_string()).await?;
+
+        let conn = ExternalConnection::new(Arc::new(driver));
+
+        let DatabaseOutput::Any(rows) = conn
+            .execute(DatabaseInput::QueryValues(
+                "SELECT * FROM posts WHERE id = ?".to_compact_string(),
+                CheapVec::from_vec(vec![sea_orm::Value::from("1".to_string())]),
+            ))
+            .await?
+        else {
+            bail!("Unexpected output.")
+        };
+
+        let rows = rows.downcast::<Vec<serde_json::Value>>().unwrap();
+
+        assert_eq!(
+            *rows,
+            vec![json!({ "query": "SELECT * FROM posts WHERE id = ?", "values": ["1"] })]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn driver_errors_are_propagated() -> Result<()> {
+        let driver = echo_driver();
+
+        assert!(
+            driver
+                .initialize("unknown://".to_compact_string())
+                .await
+                .is_err()
+        );
+
+        let conn = ExternalConnection::new(Arc::new(driver));
+
+        assert!(
+            conn.execute(DatabaseInput::Query("FAIL".to_compact_string()))
+                .await
+                .is_err()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn unanswered_requests_time_out() -> Result<()> {
+        // Nobody answers on the other side.
+        let (client, _server) = tokio::io::duplex(4096);
+        let (client_reader, client_writer) = tokio::io::split(client);
+
+        let driver = ExternalDriver::from_io(
+            "silent".to_compact_string(),
+            client_reader,
+            client_writer,
+            None,
+        )
+        .with_timeout(Duration::from_millis(50));
+
+        assert!(
+            driver
+                .initialize("echo://".to_compact_string())
+                .await
+                .is_err()
+        );
+        assert_eq!(driver.pending.len(), 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn closed_drivers_fail_right_away() -> Result<()> {
+        let (client, server) = tokio::io::duplex(4096);
+        let (client_reader, client_writer) = tokio::io::split(client);
+
+        let driver = ExternalDriver::from_io(
+            "closed".to_compact_string(),
+            client_reader,
+            client_writer,
+            None,
+        );
+
+        drop(server);
+
+        // The dispatcher notices the closed output.
+        while !driver.is_closed() {
+            tokio::task::yield_now().await;
+        }
+
+        let res = tokio::time::timeout(
+            Duration::from_secs(1),
+            driver.initialize("echo://".to_compact_string()),
+        )
+        .await?;
+
+        assert!(res.is_err());
+        assert_eq!(driver.pending.len(), 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn schema_through_driver() -> Result<()> {
+        let driver = echo_driver();
+
+        let schema = driver
+            .schema("echo://".to_compact_string(), HashMap::new())
+            .await?;
+
+        assert_eq!(schema.tables().len(), 1);
+        assert_eq!(
+            schema.tables()[0]
+                .primary_key()
+                .map(|column| column.name().as_str()),
+            Some("id")
+        );
+
+        Ok(())
+    }
+}
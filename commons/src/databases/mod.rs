@@ -1,7 +1,10 @@
 // Waveless
 // Copyright (C) 2026 Oscar Alvarez Gonzalez
 
+pub mod external;
 pub mod mysql;
+pub mod postgres;
+pub mod sqlite;
 
 use crate::*;
 
@@ -12,9 +15,10 @@ use sqlx::{mysql::*, pool::*};
 
 /// The database's connections' pools manager.
 /// The primary database won't be in the `ArrayVec` for efficiency.
-#[derive(Constructor, Debug)]
+#[derive(Constructor, Getters, Debug)]
 pub struct DatabasesConnections {
     inner: DashMap<DatabaseId, Arc<dyn AnyDatabaseConnection>>,
+    #[getset(get = "pub")]
     primary_name: CompactString,
 }
 
@@ -22,13 +26,146 @@ pub struct DatabasesConnections {
 pub trait AnyDatabaseConnection: Any + BoxedAny + DynClone + Send + Sync + Debug {
     fn name(&self) -> &str;
 
+    /// The SQL dialect spoken by the connection, if any. SQL connections are expected to accept
+    /// `DatabaseInput::QueryValues` and to return the rows as `Vec<sea_orm::QueryResult>`, and
+    /// `DatabaseInput::ExecuteValues` returning the number of affected rows as `u64`.
+    fn dialect(&self) -> Option<SqlDialect> {
+        None
+    }
+
     async fn execute(&self, input: DatabaseInput) -> Result<DatabaseOutput>;
 }
 
+/// The differences between the supported SQL backends needed to build queries at runtime.
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display, Debug)]
+pub enum SqlDialect {
+    MySQL,
+    Postgres,
+    SQLite,
+}
+
+impl SqlDialect {
+    /// Returns the placeholder of the value at the given (1-based) position.
+    pub fn placeholder(&self, i: usize) -> CompactString {
+        match self {
+            SqlDialect::Postgres => format!("${}", i).to_compact_string(),
+            _ => "?".to_compact_string(),
+        }
+    }
+
+    /// Returns the comma separated placeholders of `count` values, starting at the given position.
+    pub fn placeholders(&self, start: usize, count: usize) -> CompactString {
+        (start..start + count)
+            .map(|i| self.placeholder(i))
+            .collect::<CheapVec<_>>()
+            .join(", ")
+            .to_compact_string()
+    }
+
+    /// Quotes the given identifier, qualified identifiers (`schema.table`) are quoted by parts.
+    pub fn quote(&self, ident: &str) -> CompactString {
+        let quote = match self {
+            SqlDialect::MySQL => '`',
+            _ => '"',
+        };
+
+        ident
+            .split('.')
+            .map(|part| {
+                format!(
+                    "{}{}{}",
+                    quote,
+                    part.replace(quote, &format!("{}{}", quote, quote)),
+                    quote
+                )
+            })
+            .collect::<CheapVec<_>>()
+            .join(".")
+            .to_compact_string()
+    }
+
+    /// Converts the datetime into a value that can be stored and compared by the backend.
+    /// NOTE: SQLite doesn't have a datetime type, so it's stored as text in the same format as `CURRENT_TIMESTAMP`.
+    pub fn datetime(&self, datetime: NaiveDateTime) -> Value {
+        match self {
+            SqlDialect::SQLite => Value::from(datetime.format("%Y-%m-%d %H:%M:%S").to_string()),
+            _ => Value::from(datetime),
+        }
+    }
+
+    /// Like `datetime`, but keeping the microseconds, which still compare right against `CURRENT_TIMESTAMP`.
+    pub fn datetime_micros(&self, datetime: NaiveDateTime) -> Value {
+        match self {
+            SqlDialect::SQLite => Value::from(datetime.format("%Y-%m-%d %H:%M:%S%.6f").to_string()),
+            _ => Value::from(datetime),
+        }
+    }
+
+    /// Whether `INSERT` statements support the `RETURNING` clause.
+    pub fn returning(&self) -> bool {
+        !matches!(self, SqlDialect::MySQL)
+    }
+
+    /// Returns the clause that turns an `INSERT` into an upsert, replacing the `update` columns
+    /// when a row with the same `conflict` column already exists.
+    pub fn upsert(&self, conflict: &str, update: &[&str]) -> CompactString {
+        match self {
+            SqlDialect::MySQL => format!(
+                "ON DUPLICATE KEY UPDATE {}",
+                update
+                    .iter()
+                    .map(|column| format!(
+                        "{} = VALUES({})",
+                        self.quote(column),
+                        self.quote(column)
+                    ))
+                    .collect::<CheapVec<_>>()
+                    .join(", ")
+            ),
+            _ => format!(
+                "ON CONFLICT ({}) DO UPDATE SET {}",
+                self.quote(conflict),
+                update
+                    .iter()
+                    .map(|column| format!(
+                        "{} = excluded.{}",
+                        self.quote(column),
+                        self.quote(column)
+                    ))
+                    .collect::<CheapVec<_>>()
+                    .join(", ")
+            ),
+        }
+        .to_compact_string()
+    }
+
+    /// Returns the clause that makes an `INSERT` do nothing when the row already exists, as told
+    /// by a unique constraint. MySQL assigns the `column` to itself, as it has no such clause.
+    pub fn ignore_conflicts(&self, column: &str) -> CompactString {
+        match self {
+            SqlDialect::MySQL => format!(
+                "ON DUPLICATE KEY UPDATE {} = {}",
+                self.quote(column),
+                self.quote(column)
+            ),
+            _ => "ON CONFLICT DO NOTHING".to_string(),
+        }
+        .to_compact_string()
+    }
+
+    /// Whether the error has been caused by a unique constraint violation.
+    pub fn is_unique_violation(&self, err: &anyhow::Error) -> bool {
+        let err = err.to_string().to_lowercase();
+        err.contains("duplicate") || err.contains("unique")
+    }
+}
+
 #[derive(Debug)]
 pub enum DatabaseInput {
     Query(CompactString),
     QueryValues(CompactString, CheapVec<Value, 8>),
+    /// A statement whose affected rows are counted, e.g. a conditional `UPDATE`.
+    ExecuteValues(CompactString, CheapVec<Value, 8>),
     Bytes(Bytes),
     Any(Box<dyn Any + Send + Sync>),
 }
@@ -78,6 +215,14 @@ impl DatabasesConnections {
         Ok(())
     }
 
+    /// The id of the given connection, if it's one of these.
+    pub fn id_of(&self, db_conn: &Arc<dyn AnyDatabaseConnection>) -> Option<DatabaseId> {
+        self.inner
+            .iter()
+            .find(|entry| Arc::ptr_eq(entry.value(), db_conn))
+            .map(|entry| entry.key().to_owned())
+    }
+
     /// Search for the database given it's id.
     pub fn search(&self, id: Option<DatabaseId>) -> Result<Arc<dyn AnyDatabaseConnection>> {
         if let Some(id) = id {
@@ -129,3 +274,39 @@ pub async fn check_checksums_in_build(build: &ExecutorBuild) -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dialects_placeholders_and_quoting() {
+        assert_eq!(SqlDialect::MySQL.placeholders(1, 3), "?, ?, ?");
+        assert_eq!(SqlDialect::SQLite.placeholder(2), "?");
+        assert_eq!(SqlDialect::Postgres.placeholders(2, 3), "$2, $3, $4");
+
+        assert_eq!(SqlDialect::MySQL.quote("users"), "`users`");
+        assert_eq!(SqlDialect::MySQL.quote("app.users"), "`app`.`users`");
+        assert_eq!(SqlDialect::MySQL.quote("odd`name"), "`odd``name`");
+        assert_eq!(
+            SqlDialect::Postgres.quote("public.users"),
+            r#""public"."users""#
+        );
+        assert_eq!(SqlDialect::SQLite.quote(r#"odd"name"#), r#""odd""name""#);
+    }
+
+    #[test]
+    fn dialects_upserts() {
+        assert_eq!(
+            SqlDialect::MySQL.upsert("user_id", &["role"]),
+            "ON DUPLICATE KEY UPDATE `role` = VALUES(`role`)"
+        );
+        assert_eq!(
+            SqlDialect::Postgres.upsert("user_id", &["role", "updated_at"]),
+            r#"ON CONFLICT ("user_id") DO UPDATE SET "role" = excluded."role", "updated_at" = excluded."updated_at""#
+        );
+
+        assert!(!SqlDialect::MySQL.returning());
+        assert!(SqlDialect::SQLite.returning());
+    }
+}
@@ -20,6 +20,10 @@ impl AnyDatabaseConnection for MySQLConnection {
         "mysql"
     }
 
+    fn dialect(&self) -> Option<SqlDialect> {
+        Some(SqlDialect::MySQL)
+    }
+
     async fn execute(&self, input: DatabaseInput) -> Result<DatabaseOutput> {
         match input {
             DatabaseInput::Query(query) => {
@@ -42,6 +46,18 @@ impl AnyDatabaseConnection for MySQLConnection {
 
                 Ok(DatabaseOutput::Any(Box::new(res)))
             }
+            DatabaseInput::ExecuteValues(query, params) => {
+                let res = self
+                    .0
+                    .execute(Statement::from_sql_and_values(
+                        DbBackend::MySql,
+                        query.to_string(),
+                        params,
+                    ))
+                    .await?;
+
+                Ok(DatabaseOutput::Any(Box::new(res.rows_affected())))
+            }
             _ => Err(anyhow!("Unsupported input for MySQL query.")),
         }
     }
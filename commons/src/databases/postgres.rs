@@ -0,0 +1,181 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+use crate::*;
+
+use super::*;
+
+use project::*;
+
+use sea_orm::{DbBackend, SqlxPostgresPoolConnection, Statement};
+use sqlx::{AssertSqlSafe, Either, Executor, SqlSafeStr, TypeInfo, postgres::*};
+
+/// What PostgreSQL reports about a query once it's prepared.
+#[derive(Clone, PartialEq, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct PostgresStatement {
+    /// Whether it returns rows, i.e. it has any column (`SELECT`, `... RETURNING`...).
+    returns_rows: bool,
+
+    /// Types of the parameters in order, e.g. `INT4` or `TIMESTAMPTZ`.
+    params: CheapVec<CompactString, 0>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PostgresConnection {
+    conn: SqlxPostgresPoolConnection,
+    pool: Pool<Postgres>,
+
+    /// Queries already described, as each endpoint runs the same few queries.
+    statements: Arc<DashMap<CompactString, PostgresStatement>>,
+}
+
+boxed_any!(PostgresConnection);
+
+impl PostgresConnection {
+    /// Describes the query through the driver, without executing it.
+    pub async fn describe(&self, query: &str) -> Result<PostgresStatement> {
+        if let Some(statement) = self.statements.get(query) {
+            return Ok(statement.to_owned());
+        }
+
+        let describe = (&self.pool)
+            .describe(AssertSqlSafe(query.to_string()).into_sql_str())
+            .await?;
+
+        let params = match describe.parameters() {
+            Some(Either::Left(params)) => params
+                .iter()
+                .map(|param| param.name().to_compact_string())
+                .collect(),
+            _ => CheapVec::new(),
+        };
+
+        let statement = PostgresStatement {
+            returns_rows: !describe.columns().is_empty(),
+            params,
+        };
+
+        self.statements
+            .insert(query.to_compact_string(), statement.to_owned());
+
+        Ok(statement)
+    }
+}
+
+#[async_trait]
+impl AnyDatabaseConnection for PostgresConnection {
+    fn name(&self) -> &str {
+        "postgres"
+    }
+
+    fn dialect(&self) -> Option<SqlDialect> {
+        Some(SqlDialect::Postgres)
+    }
+
+    async fn execute(&self, input: DatabaseInput) -> Result<DatabaseOutput> {
+        match input {
+            DatabaseInput::Query(query) => {
+                let res = self
+                    .conn
+                    .query_all(Statement::from_string(
+                        DbBackend::Postgres,
+                        query.to_string(),
+                    ))
+                    .await?;
+
+                Ok(DatabaseOutput::Any(Box::new(res)))
+            }
+            DatabaseInput::QueryValues(query, params) => {
+                let res = self
+                    .conn
+                    .query_all(Statement::from_sql_and_values(
+                        DbBackend::Postgres,
+                        query.to_string(),
+                        params,
+                    ))
+                    .await?;
+
+                Ok(DatabaseOutput::Any(Box::new(res)))
+            }
+            DatabaseInput::ExecuteValues(query, params) => {
+                let res = self
+                    .conn
+                    .execute(Statement::from_sql_and_values(
+                        DbBackend::Postgres,
+                        query.to_string(),
+                        params,
+                    ))
+                    .await?;
+
+                Ok(DatabaseOutput::Any(Box::new(res.rows_affected())))
+            }
+            _ => Err(anyhow!("Unsupported input for PostgreSQL query.")),
+        }
+    }
+}
+
+/// PostgreSQL database
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display("PostgreSQL: {}@{} on {}", username, host, db)]
+#[getset(get = "pub")]
+pub struct PostgresDBConnectionConfig {
+    host: SocketAddr,
+    username: CompactString,
+    password: CompactString,
+    db: CompactString,
+
+    /// Schema (namespace) set as the connections' `search_path`, `public` by default.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    schema: Option<CompactString>,
+}
+
+boxed_any!(PostgresDBConnectionConfig);
+
+#[typetag::serde(name = "Postgres")]
+#[async_trait]
+impl AnyDatabaseConnectionConfig for PostgresDBConnectionConfig {
+    async fn new_conn(
+        &self,
+        id: CompactString,
+        pool_min_size: Option<usize>,
+        pool_max_size: Option<usize>,
+    ) -> Result<(Arc<dyn AnyDatabaseConnection>, Box<dyn Any>)> {
+        info!(
+            "Creating new PostgreSQL database connection ({}) on {}",
+            self.host, self.db
+        );
+
+        let num_cpus = std::thread::available_parallelism()?.get();
+
+        let conn_options = PgConnectOptions::new()
+            .host(&self.host.ip().to_string())
+            .port(self.host.port())
+            .username(&self.username)
+            .password(&self.password)
+            .database(&self.db)
+            .options([("search_path", self.schema.as_deref().unwrap_or("public"))]);
+
+        let pool = PoolOptions::<Postgres>::new()
+            .min_connections(pool_min_size.unwrap_or(num_cpus) as u32)
+            .max_connections(pool_max_size.unwrap_or(num_cpus * 2) as u32)
+            .connect_with(conn_options)
+            .await
+            .map_err(|err| anyhow!("Failed creating {}'s PostgreSQL pool. {}", id, err))?;
+
+        let pool_wrapper = SqlxPostgresPoolConnection::from(pool.to_owned());
+
+        Ok((
+            Arc::new(PostgresConnection {
+                conn: pool_wrapper,
+                pool: pool.to_owned(),
+                statements: Arc::new(DashMap::new()),
+            }),
+            Box::new(pool),
+        ))
+    }
+
+    fn returning(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,203 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+use crate::*;
+
+use super::*;
+
+use project::*;
+
+use sea_orm::{DbBackend, SqlxSqlitePoolConnection, Statement};
+use sqlx::sqlite::*;
+
+#[derive(Clone, Debug)]
+pub struct SQLiteConnection(SqlxSqlitePoolConnection);
+
+boxed_any!(SQLiteConnection);
+
+#[async_trait]
+impl AnyDatabaseConnection for SQLiteConnection {
+    fn name(&self) -> &str {
+        "sqlite"
+    }
+
+    fn dialect(&self) -> Option<SqlDialect> {
+        Some(SqlDialect::SQLite)
+    }
+
+    async fn execute(&self, input: DatabaseInput) -> Result<DatabaseOutput> {
+        match input {
+            DatabaseInput::Query(query) => {
+                let res = self
+                    .0
+                    .query_all(Statement::from_string(DbBackend::Sqlite, query.to_string()))
+                    .await?;
+
+                Ok(DatabaseOutput::Any(Box::new(res)))
+            }
+            DatabaseInput::QueryValues(query, params) => {
+                let res = self
+                    .0
+                    .query_all(Statement::from_sql_and_values(
+                        DbBackend::Sqlite,
+                        query.to_string(),
+                        params,
+                    ))
+                    .await?;
+
+                Ok(DatabaseOutput::Any(Box::new(res)))
+            }
+            DatabaseInput::ExecuteValues(query, params) => {
+                let res = self
+                    .0
+                    .execute(Statement::from_sql_and_values(
+                        DbBackend::Sqlite,
+                        query.to_string(),
+                        params,
+                    ))
+                    .await?;
+
+                Ok(DatabaseOutput::Any(Box::new(res.rows_affected())))
+            }
+            _ => Err(anyhow!("Unsupported input for SQLite query.")),
+        }
+    }
+}
+
+/// SQLite database
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display("SQLite: {}", path)]
+#[getset(get = "pub")]
+pub struct SQLiteDBConnectionConfig {
+    /// Database file, relative to the project's workspace. Use `:memory:` for an in-memory database,
+    /// note that every connection config will open a different in-memory database.
+    path: CompactString,
+
+    /// Whether to create the database file if it doesn't exist.
+    #[serde(default, skip_serializing_if = "should_skip")]
+    create_if_missing: bool,
+}
+
+boxed_any!(SQLiteDBConnectionConfig);
+
+#[typetag::serde(name = "SQLite")]
+#[async_trait]
+impl AnyDatabaseConnectionConfig for SQLiteDBConnectionConfig {
+    /// In-memory databases use a single connection that is never recycled, as the data lives within it.
+    async fn new_conn(
+        &self,
+        id: CompactString,
+        pool_min_size: Option<usize>,
+        pool_max_size: Option<usize>,
+    ) -> Result<(Arc<dyn AnyDatabaseConnection>, Box<dyn Any>)> {
+        info!("Creating new SQLite database connection on {}", self.path);
+
+        let num_cpus = std::thread::available_parallelism()?.get();
+
+        let pool = if self.path == ":memory:" {
+            PoolOptions::<Sqlite>::new()
+                .min_connections(1)
+                .max_connections(1)
+                .idle_timeout(None)
+                .max_lifetime(None)
+                .connect_with(SqliteConnectOptions::new().in_memory(true))
+                .await
+        } else {
+            let workspace_root = get_workspace_root("project.toml").unwrap_or(current_dir()?);
+
+            let conn_options = SqliteConnectOptions::new()
+                .filename(workspace_root.join(self.path.as_str()))
+                .create_if_missing(self.create_if_missing)
+                .foreign_keys(true);
+
+            PoolOptions::<Sqlite>::new()
+                .min_connections(pool_min_size.unwrap_or(1) as u32)
+                .max_connections(pool_max_size.unwrap_or(num_cpus) as u32)
+                .connect_with(conn_options)
+                .await
+        }
+        .map_err(|err| anyhow!("Failed creating {}'s SQLite pool. {}", id, err))?;
+
+        let pool_wrapper = SqlxSqlitePoolConnection::from(pool.to_owned());
+
+        Ok((Arc::new(SQLiteConnection(pool_wrapper)), Box::new(pool)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use endpoint::HttpMethod;
+    use execute::*;
+    use schema::sqlite::SQLiteSchemaDiscoveryMethod;
+
+    #[tokio::test]
+    async fn in_memory_pipeline() -> Result<()> {
+        let config = SQLiteDBConnectionConfig::new(":memory:".to_compact_string(), false);
+
+        let (db_conn, raw_conn) = config
+            .new_conn("sqlite_test".to_compact_string(), None, None)
+            .await?;
+
+        db_conn
+            .execute(DatabaseInput::Query(
+                "CREATE TABLE products (id INTEGER PRIMARY KEY, name TEXT NOT NULL, price REAL)"
+                    .to_compact_string(),
+            ))
+            .await?;
+
+        let discovery = SQLiteSchemaDiscoveryMethod::new(CheapVec::new());
+
+        let pool = raw_conn.downcast::<Pool<Sqlite>>().unwrap();
+
+        let schema = schema::sqlite::discover(&pool).await?;
+        let products = &schema.tables()[0];
+
+        assert_eq!(products.name(), "products");
+        assert_eq!(products.primary_key().unwrap().name(), "id");
+        assert!(*products.primary_key().unwrap().auto_generated());
+        assert!(!*products.columns()[1].nullable());
+
+        let params = HashMap::from([
+            (
+                "name".to_compact_string(),
+                ExecuteParamValue::Client(Some("Keyboard".to_compact_string())),
+            ),
+            (
+                "price".to_compact_string(),
+                ExecuteParamValue::Client(Some("19.5".to_compact_string())),
+            ),
+        ]);
+
+        discovery
+            .executor(
+                "INSERT INTO products (name, price) VALUES ({name}, {price})".to_compact_string(),
+            )
+            .execute(
+                HttpMethod::Post,
+                db_conn.to_owned(),
+                ExecuteInput::new(params, Bytes::new()),
+            )
+            .await
+            .map_err(|err| anyhow!("{:?}", err))?;
+
+        let ExecuteOutput::Json(_, rows) = discovery
+            .executor("SELECT * FROM products".to_compact_string())
+            .execute(
+                HttpMethod::Get,
+                db_conn,
+                ExecuteInput::new(HashMap::new(), Bytes::new()),
+            )
+            .await
+            .map_err(|err| anyhow!("{:?}", err))?
+        else {
+            bail!("Unexpected executor's output.")
+        };
+
+        assert_eq!(rows[0]["id"], json!(1));
+        assert_eq!(rows[0]["name"], json!("Keyboard"));
+
+        Ok(())
+    }
+}
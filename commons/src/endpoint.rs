@@ -83,7 +83,9 @@ impl Default for Endpoints {
 
 /// The main endpoint definition that will be either created by the user or discovered by the compiler.
 /// This will be then included in the Waveless project's binary.
-#[derive(Clone, Serialize, Deserialize, Constructor, Builder, Getters, Display, Debug)]
+#[derive(
+    Clone, Serialize, Deserialize, Constructor, Builder, Getters, MutGetters, Display, Debug,
+)]
 #[display("({}) {} -> ({}, {:?}, {:?})", id, route, method, version, description)]
 #[builder(default, pattern = "mutable", setter(strip_option))]
 #[getset(get = "pub")]
@@ -116,6 +118,15 @@ pub struct Endpoint {
     #[serde(default, skip_serializing_if = "should_skip_cheapvec")]
     tags: CheapVec<CompactString, 0>,
 
+    /// Caches the endpoint's responses, which aren't cached otherwise.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    cache: Option<EndpointCache>,
+
+    /// Time the results of the endpoint's query are cached for (in seconds), when the project's
+    /// query cache is set. NOTE: only the MySQL executor is cached.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    query_cache_ttl: Option<u64>,
+
     /// DEPRECATED: Path parameters are indicated in the route.
     /// Sets the accepted path parameters.
     // #[serde(default, skip_serializing_if = "should_skip_cheapvec")]
@@ -137,9 +148,21 @@ pub struct Endpoint {
     inject_user_id: bool,
 
     /// All allowed roles to query the endpoint.
+    /// NOTE: internal endpoints may get them from the project's settings (e.g. admin endpoints).
     #[serde(default, skip_serializing_if = "should_skip_cheapvec")]
+    #[getset(get = "pub", get_mut = "pub")]
     allowed_roles: CheapVec<CompactString, 0>,
 
+    /// All the permissions required to query the endpoint (e.g. `orders:write`), granted by the user's roles.
+    /// NOTE: like roles, they're only checked when the endpoint requires auth.
+    #[serde(default, skip_serializing_if = "should_skip_cheapvec")]
+    required_permissions: CheapVec<CompactString, 0>,
+
+    /// Roles that bypass the row ownership of the endpoint's query, they're
+    /// notified to it by injecting `|owner_bypass|` (`1` or `0`).
+    #[serde(default, skip_serializing_if = "should_skip_cheapvec")]
+    owner_bypass_roles: CheapVec<CompactString, 0>,
+
     /// Whether to capture all the request's params.
     /// Useful for internal executors and generic trait implementations.
     #[serde(default, skip_serializing_if = "should_skip")]
@@ -163,6 +186,25 @@ impl PartialEq for Endpoint {
     }
 }
 
+/// Caching of an endpoint's responses, invalidated whenever a write endpoint (i.e. not `GET`)
+/// with the same route or any of the same tags succeeds.
+/// NOTE: the responses of endpoints requiring auth are never shared across consumers, so they
+/// already vary by user and role.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct EndpointCache {
+    /// Time the responses are fresh for (in seconds).
+    ttl: u64,
+
+    /// Time the responses are still served for once stale, while they're refreshed in the background (in seconds).
+    #[serde(default)]
+    stale_while_revalidate: u64,
+
+    /// Request headers the responses vary by (e.g. `Accept-Language`).
+    #[serde(default, skip_serializing_if = "should_skip_cheapvec")]
+    vary_headers: CheapVec<CompactString, 0>,
+}
+
 /// Available HTTP methods
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Display, Debug)]
 #[serde(rename_all = "snake_case")]
@@ -201,11 +243,15 @@ impl Default for Endpoint {
             execute: None,
             description: None,
             tags: CheapVec::new_const(),
+            cache: None,
+            query_cache_ttl: None,
             query_params: Default::default(),
             body_params: Default::default(),
             require_auth: false,
             inject_user_id: false,
             allowed_roles: Default::default(),
+            required_permissions: Default::default(),
+            owner_bypass_roles: Default::default(),
             capture_all_params: false,
             deprecated: false,
             auto_generated: false,
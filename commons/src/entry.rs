@@ -36,6 +36,16 @@ impl Key for u64 {
     }
 }
 
+impl Key for CompactString {
+    fn collect(&self) -> impl AsRef<[u8]> {
+        self.as_bytes().to_owned()
+    }
+
+    fn into_key(value: &[u8]) -> Result<Self> {
+        Ok(CompactString::from_utf8(value)?)
+    }
+}
+
 impl<const N: usize> Key for [u8; N] {
     fn collect(&self) -> impl AsRef<[u8]> {
         self.as_slice()
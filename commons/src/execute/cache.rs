@@ -0,0 +1,618 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+//!
+//! Cache of the queries' results, keyed by the database, the normalised query and its bound
+//! values. Results are kept in memory and, optionally, on the embedded store too. Each result
+//! records the versions of the tables its query reads, which the writes going through Waveless
+//! increase (the endpoints' and the authentication methods'), so results are outdated once any
+//! of their tables is written.
+//! NOTE: writes made elsewhere (e.g. by other applications) aren't noticed until the results expire.
+//!
+
+use crate::*;
+
+use databases::AnyDatabaseConnection;
+use store::*;
+
+use regex::Regex;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, OnceLock};
+
+/// Keywords followed by the tables a query references, e.g. `FROM users` or `INSERT INTO orders`.
+static TABLES_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\b(?:from|join|into|update|table)\s+").unwrap());
+
+/// Statements which write, even inside of a `WITH` query.
+static WRITES_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:insert|update|delete|replace|merge|truncate|alter|drop|create)\b")
+        .unwrap()
+});
+
+/// The query cache of this instance once it's set up, so the writes made outside of the
+/// endpoints (e.g. by the authentication methods) outdate its results too.
+static SHARED_QUERY_CACHE: OnceLock<&'static QueryCache> = OnceLock::new();
+
+/// Keywords ending a list of tables.
+const CLAUSE_KEYWORDS: [&str; 28] = [
+    "where",
+    "join",
+    "inner",
+    "left",
+    "right",
+    "full",
+    "cross",
+    "natural",
+    "straight_join",
+    "on",
+    "using",
+    "group",
+    "order",
+    "limit",
+    "offset",
+    "having",
+    "union",
+    "except",
+    "intersect",
+    "set",
+    "values",
+    "value",
+    "select",
+    "window",
+    "for",
+    "lock",
+    "returning",
+    "default",
+];
+
+/// A cached result, stored as JSON as its value cannot be encoded otherwise.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+pub struct CachedQuery {
+    result: CompactString,
+    /// The tables read, along with their versions when the query was run.
+    tables: CheapVec<(CompactString, u64), 0>,
+    /// Unix timestamp (in seconds).
+    expires_at: i64,
+}
+
+/// Hits and misses of the cache since the executor started.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct QueryCacheStats {
+    hits: u64,
+    misses: u64,
+    entries: usize,
+}
+
+/// Collapses the query's whitespace, so the same query formatted differently shares the results.
+pub fn normalize_query(query: &str) -> CompactString {
+    query
+        .split_whitespace()
+        .collect::<CheapVec<&str, 0>>()
+        .join(" ")
+        .to_compact_string()
+}
+
+/// Whether the query only reads, so its results may be cached.
+pub fn is_read_query(query: &str) -> bool {
+    let first_keyword = query
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+
+    ["select", "with"].contains(&first_keyword.as_str()) && !WRITES_RE.is_match(query)
+}
+
+/// Skips the parenthesized group `rest` starts with, returning what follows it.
+fn skip_group(rest: &str) -> &str {
+    let mut depth = 0;
+    let mut quote = None;
+
+    for (i, c) in rest.char_indices() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '\'' | '"' | '`') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => {
+                depth -= 1;
+
+                if depth == 0 {
+                    return &rest[i + 1..];
+                }
+            }
+            _ => {}
+        }
+    }
+
+    ""
+}
+
+/// The tables listed at the start of `rest`, e.g. `users u, orders AS o WHERE ...`.
+/// NOTE: subqueries are skipped, as their own tables follow their own keywords.
+fn listed_tables(mut rest: &str) -> CheapVec<&str, 0> {
+    let mut tables = CheapVec::new();
+    let mut expect_table = true;
+
+    loop {
+        rest = rest.trim_start();
+
+        let Some(c) = rest.chars().next() else {
+            break;
+        };
+
+        match c {
+            '(' => {
+                rest = skip_group(rest);
+                expect_table = false;
+            }
+            ',' => {
+                rest = &rest[1..];
+                expect_table = true;
+            }
+            '`' | '"' | '[' => {
+                let close = if c == '[' { ']' } else { c };
+
+                let Some(end) = rest[1..].find(close) else {
+                    break;
+                };
+
+                if expect_table {
+                    tables.push(&rest[1..end + 1]);
+                    expect_table = false;
+                }
+
+                rest = &rest[end + 2..];
+
+                // Quoted names may still be qualified, e.g. `public`.`users`.
+                if let Some(qualified) = rest.strip_prefix('.') {
+                    tables.pop();
+                    expect_table = true;
+                    rest = qualified;
+                }
+            }
+            _ => {
+                let end = rest
+                    .find(|c: char| !(c.is_alphanumeric() || ['_', '.', '$'].contains(&c)))
+                    .unwrap_or(rest.len());
+
+                // Anything else (e.g. a closing parenthesis) ends the list.
+                if end == 0 {
+                    break;
+                }
+
+                let word = &rest[..end];
+
+                if CLAUSE_KEYWORDS.contains(&word.to_lowercase().as_str()) {
+                    break;
+                }
+
+                if expect_table && !["lateral", "only"].contains(&word.to_lowercase().as_str()) {
+                    tables.push(word);
+                    expect_table = false;
+                }
+
+                rest = &rest[end..];
+            }
+        }
+    }
+
+    tables
+}
+
+/// The tables the query references on the database, as `{database}/{table}`.
+pub fn query_tables(database: &str, query: &str) -> CheapVec<CompactString, 0> {
+    let mut tables = CheapVec::new();
+
+    for keyword in TABLES_RE.find_iter(query) {
+        for table in listed_tables(&query[keyword.end()..]) {
+            // The schema is omitted, e.g. `public.users`.
+            let table = table.rsplit('.').next().unwrap_or(table).to_lowercase();
+
+            let table = format!("{}/{}", database, table).to_compact_string();
+
+            if !tables.contains(&table) {
+                tables.push(table);
+            }
+        }
+    }
+
+    tables
+}
+
+/// Outdates the shared query cache's results of the tables written by the query on the connection.
+/// NOTE: failures are only logged, as the write has already been made.
+pub async fn invalidate_written(db_conn: &Arc<dyn AnyDatabaseConnection>, query: &str) {
+    let Some(query_cache) = SHARED_QUERY_CACHE.get() else {
+        return;
+    };
+
+    if is_read_query(query) {
+        return;
+    }
+
+    let Some(database) = DATABASES_CONNS
+        .get()
+        .and_then(|databases| databases.id_of(db_conn))
+    else {
+        return;
+    };
+
+    if let Err(err) = query_cache.invalidate(query_tables(&database, query)).await {
+        error!(
+            "Cannot invalidate the cached results of the tables written on '{}'. {}",
+            database, err
+        );
+    }
+}
+
+/// Query results' cache with an optional disk tier.
+pub struct QueryCache {
+    memory: DashMap<CompactString, CachedQuery>,
+    store: Option<EmbeddedStore<CompactString, CachedQuery>>,
+    /// Versions of the written tables, on disk too when there's a disk tier.
+    versions: DashMap<CompactString, u64>,
+    versions_store: Option<EmbeddedStore<CompactString, u64>>,
+    max_entries: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl QueryCache {
+    pub fn new(
+        max_entries: usize,
+        store: Option<(
+            EmbeddedStore<CompactString, CachedQuery>,
+            EmbeddedStore<CompactString, u64>,
+        )>,
+    ) -> Self {
+        let (store, versions_store) = store.unzip();
+
+        Self {
+            memory: DashMap::new(),
+            store,
+            versions: DashMap::new(),
+            versions_store,
+            max_entries,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Identifies the results of the bound query on the database.
+    pub fn key(database: &str, query: &str, values: &[sea_orm::Value]) -> CompactString {
+        format!("{}/{}/{:?}", database, normalize_query(query), values).to_compact_string()
+    }
+
+    /// The current version of the table, read from disk the first time.
+    async fn version(&self, table: &CompactString) -> Result<u64> {
+        if let Some(version) = self.versions.get(table) {
+            return Ok(*version);
+        }
+
+        let stored = match &self.versions_store {
+            Some(versions_store) => versions_store.get(table.to_owned()).await?,
+            None => None,
+        };
+
+        // Unless an invalidation has set it meanwhile.
+        Ok(*self
+            .versions
+            .entry(table.to_owned())
+            .or_insert(stored.unwrap_or_default()))
+    }
+
+    /// The current versions of the tables, taken before running the query so the writes made
+    /// meanwhile outdate its result.
+    pub async fn versions(
+        &self,
+        tables: CheapVec<CompactString, 0>,
+    ) -> Result<CheapVec<(CompactString, u64), 0>> {
+        let mut versions = CheapVec::new();
+
+        for table in tables {
+            let version = self.version(&table).await?;
+
+            versions.push((table, version));
+        }
+
+        Ok(versions)
+    }
+
+    /// Whether none of the result's tables has been written since it was cached.
+    async fn is_current(&self, cached: &CachedQuery) -> Result<bool> {
+        for (table, version) in &cached.tables {
+            if self.version(table).await? != *version {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Gets the unexpired and current result, looking it up on the disk tier if it isn't in memory.
+    pub async fn get(&self, key: &CompactString) -> Result<Option<serde_json::Value>> {
+        let now = Utc::now().timestamp();
+
+        let mut cached = self
+            .memory
+            .get(key)
+            .map(|cached| cached.to_owned())
+            .filter(|cached| cached.expires_at > now);
+
+        if let (None, Some(store)) = (&cached, &self.store) {
+            cached = store
+                .get(key.to_owned())
+                .await?
+                .filter(|cached| cached.expires_at > now);
+
+            // Results found on disk are kept in memory from now on.
+            if let Some(cached) = &cached {
+                self.remember(key.to_owned(), cached.to_owned());
+            }
+        }
+
+        // Results whose tables were written since are removed.
+        if let Some(outdated) = &cached {
+            if !self.is_current(outdated).await? {
+                self.memory.remove(key);
+
+                if let Some(store) = &self.store {
+                    store.remove(key.to_owned()).await?;
+                }
+
+                cached = None;
+            }
+        }
+
+        match cached {
+            Some(cached) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+
+                Ok(Some(serde_json::from_str(&cached.result)?))
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+
+                Ok(None)
+            }
+        }
+    }
+
+    /// Caches the result of the query given the versions of the tables it reads.
+    pub async fn insert(
+        &self,
+        key: CompactString,
+        versions: CheapVec<(CompactString, u64), 0>,
+        result: &serde_json::Value,
+        ttl: Duration,
+    ) -> Result<()> {
+        let cached = CachedQuery {
+            result: serde_json::to_string(result)?.to_compact_string(),
+            tables: versions,
+            expires_at: Utc::now().timestamp() + ttl.as_secs() as i64,
+        };
+
+        self.remember(key.to_owned(), cached.to_owned());
+
+        if let Some(store) = &self.store {
+            store.insert(key, cached).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Keeps the result in memory, unless it's full of unexpired and current ones.
+    fn remember(&self, key: CompactString, cached: CachedQuery) {
+        if self.memory.len() >= self.max_entries && !self.memory.contains_key(&key) {
+            let now = Utc::now().timestamp();
+
+            self.memory
+                .retain(|_, cached| cached.expires_at > now && self.is_known_current(cached));
+
+            if self.memory.len() >= self.max_entries {
+                return;
+            }
+        }
+
+        self.memory.insert(key, cached);
+    }
+
+    /// Whether the result is current as far as the versions in memory tell.
+    fn is_known_current(&self, cached: &CachedQuery) -> bool {
+        cached.tables.iter().all(|(table, version)| {
+            self.versions
+                .get(table)
+                .is_none_or(|current| *current == *version)
+        })
+    }
+
+    /// Shares the cache with the writes made outside of the endpoints, see `invalidate_written`.
+    pub fn share(&'static self) {
+        let _ = SHARED_QUERY_CACHE.set(self);
+    }
+
+    /// Outdates the results which read any of the written tables.
+    pub async fn invalidate(&self, tables: CheapVec<CompactString, 0>) -> Result<()> {
+        for table in &tables {
+            // Loaded first, so the new version follows the one on disk.
+            self.version(table).await?;
+
+            *self.versions.entry(table.to_owned()).or_default() += 1;
+        }
+
+        if let Some(versions_store) = &self.versions_store {
+            let versions = tables
+                .iter()
+                .filter_map(|table| {
+                    self.versions
+                        .get(table)
+                        .map(|version| (table.to_owned(), *version))
+                })
+                .collect::<HashMap<_, _>>();
+
+            versions_store
+                .update(tables.to_vec(), move |table, stored| {
+                    versions
+                        .get(table)
+                        .copied()
+                        .unwrap_or_default()
+                        .max(stored.unwrap_or_default())
+                })
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the expired and outdated results, returning how many were removed.
+    pub async fn sweep(&self) -> Result<usize> {
+        let now = Utc::now().timestamp();
+
+        let before = self.memory.len();
+
+        self.memory
+            .retain(|_, cached| cached.expires_at > now && self.is_known_current(cached));
+
+        let mut removed = before.saturating_sub(self.memory.len());
+
+        if let Some(store) = &self.store {
+            let versions = self
+                .versions
+                .iter()
+                .map(|entry| (entry.key().to_owned(), *entry.value()))
+                .collect::<HashMap<_, _>>();
+
+            removed += store
+                .retain(move |_, cached| {
+                    cached.expires_at > now
+                        && cached.tables.iter().all(|(table, version)| {
+                            versions.get(table).is_none_or(|current| current == version)
+                        })
+                })
+                .await?;
+        }
+
+        Ok(removed)
+    }
+
+    pub fn stats(&self) -> QueryCacheStats {
+        QueryCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.memory.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_queries_and_their_tables() {
+        assert!(is_read_query(
+            "SELECT * FROM users JOIN orders ON orders.user_id = users.id"
+        ));
+        assert!(!is_read_query("UPDATE users SET name = ? WHERE id = ?"));
+        assert!(!is_read_query(
+            "WITH old AS (SELECT id FROM users) DELETE FROM sessions WHERE user_id IN (SELECT id FROM old)"
+        ));
+
+        assert_eq!(
+            query_tables(
+                "main",
+                "SELECT * FROM `Users` JOIN public.orders ON orders.user_id = users.id"
+            )
+            .as_slice(),
+            ["main/users", "main/orders"]
+        );
+
+        assert_eq!(
+            query_tables(
+                "main",
+                "SELECT * FROM users u, (SELECT * FROM sessions) s, `shop`.`orders` AS o WHERE u.id = o.user_id"
+            )
+            .as_slice(),
+            ["main/users", "main/orders", "main/sessions"]
+        );
+
+        assert_eq!(
+            QueryCache::key("main", "SELECT *\n  FROM users", &[]),
+            QueryCache::key("main", "SELECT * FROM users", &[])
+        );
+    }
+
+    #[tokio::test]
+    async fn invalidated_by_the_written_tables() -> Result<()> {
+        let cache = QueryCache::new(16, None);
+
+        let users = QueryCache::key("main", "SELECT * FROM users", &[]);
+        let orders = QueryCache::key("main", "SELECT * FROM orders", &[]);
+
+        assert_eq!(cache.get(&users).await?, None);
+
+        cache
+            .insert(
+                users.to_owned(),
+                cache
+                    .versions(query_tables("main", "SELECT * FROM users"))
+                    .await?,
+                &json!([{ "id": 1 }]),
+                Duration::from_secs(60),
+            )
+            .await?;
+        cache
+            .insert(
+                orders.to_owned(),
+                cache
+                    .versions(query_tables("main", "SELECT * FROM orders"))
+                    .await?,
+                &json!([]),
+                Duration::from_secs(60),
+            )
+            .await?;
+
+        assert_eq!(cache.get(&users).await?, Some(json!([{ "id": 1 }])));
+
+        cache
+            .invalidate(query_tables("main", "DELETE FROM users WHERE id = ?"))
+            .await?;
+
+        assert_eq!(cache.get(&users).await?, None);
+        assert_eq!(cache.get(&orders).await?, Some(json!([])));
+
+        assert_eq!(
+            cache.stats(),
+            QueryCacheStats {
+                hits: 2,
+                misses: 2,
+                entries: 1
+            }
+        );
+
+        // Results of queries run while their tables were written are outdated already.
+        let versions = cache
+            .versions(query_tables("main", "SELECT * FROM orders"))
+            .await?;
+
+        cache
+            .invalidate(query_tables("main", "INSERT INTO orders VALUES (?)"))
+            .await?;
+        cache
+            .insert(
+                orders.to_owned(),
+                versions,
+                &json!([]),
+                Duration::from_secs(60),
+            )
+            .await?;
+
+        assert_eq!(cache.sweep().await?, 1);
+        assert_eq!(cache.get(&orders).await?, None);
+
+        Ok(())
+    }
+}
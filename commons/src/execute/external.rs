@@ -0,0 +1,58 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+use crate::*;
+
+use super::*;
+
+/// Executes a query through an external database driver. Client parameters are
+/// sent to the driver as `?` placeholders along with their values in order.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display("External query: {:?}", query)]
+#[getset(get = "pub")]
+pub struct ExternalExecute {
+    query: CompactString,
+}
+
+boxed_any!(ExternalExecute);
+
+#[typetag::serde(name = "External")]
+#[async_trait]
+impl AnyExecute for ExternalExecute {
+    /// The driver's rows are expected to be JSON values and will be
+    /// returned as a JSON array.
+    async fn execute(
+        &self,
+        method: HttpMethod,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        input: ExecuteInput,
+    ) -> Result<ExecuteOutput, RequestError> {
+        let (query, ordered_values) =
+            bind_query(self.query(), method, &input, |_, _| "?".to_compact_string())?;
+
+        let res = db_conn
+            .execute(DatabaseInput::QueryValues(query, ordered_values))
+            .await
+            .map_err(|err| {
+                RequestError::Expected(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Query execution error: {}", err).to_compact_string(),
+                )
+            })?;
+
+        let DatabaseOutput::Any(res) = res else {
+            return Err(RequestError::Other(anyhow!(
+                "Unexpected database's executor's output."
+            )));
+        };
+
+        let rows = res.downcast::<Vec<serde_json::Value>>().map_err(|err| {
+            RequestError::Other(anyhow!(
+                "Cannot downcast to the external driver's rows. {:?}",
+                err
+            ))
+        })?;
+
+        Ok(ExecuteOutput::Json(None, json!(*rows)))
+    }
+}
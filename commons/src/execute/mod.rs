@@ -1,13 +1,19 @@
 // Waveless
 // Copyright (C) 2026 Oscar Alvarez Gonzalez
 
+pub mod cache;
+pub mod external;
 pub mod mysql;
+pub mod postgres;
+pub mod sqlite;
 
 use crate::*;
 
 use databases::*;
 use endpoint::*;
 
+use sea_orm::{FromQueryResult, QueryResult};
+
 /// Generic methods trait to handle requests to the endpoints.
 #[typetag::serde]
 #[async_trait]
@@ -45,3 +51,244 @@ pub enum ExecuteOutput {
     ),
     Any(Box<dyn Encode<Output = Bytes> + Send + Sync>),
 }
+
+/// Translates a Waveless query into the backend's syntax and gathers the bound values in order.
+/// Client parameters (`{param}`) are replaced by the placeholder returned by `placeholder`, which
+/// receives the 1-based position of the value and its type hint (`{param::type}`), if any. On PUT
+/// requests, the assignments of the parameters that weren't provided are stripped from the query.
+pub fn bind_query(
+    query: &str,
+    method: HttpMethod,
+    input: &ExecuteInput,
+    placeholder: impl Fn(usize, Option<&str>) -> CompactString,
+) -> Result<(CompactString, CheapVec<sea_orm::Value, 8>), RequestError> {
+    let client_value = |param_id: &str| match input.params.get(param_id) {
+        Some(ExecuteParamValue::Client(value)) => value.to_owned(),
+        _ => None,
+    };
+
+    let params_order = query
+        .trim_start_matches(|c| c != '{')
+        .split('{')
+        .map(|sub| sub.split_once('}').unwrap_or_default().0)
+        .map(|sub| {
+            sub.split_once("::")
+                .map_or(sub, |(param_id, _)| param_id)
+                .trim()
+        })
+        .filter(|sub| !sub.is_empty())
+        .collect::<CheapVec<&str>>();
+
+    let mut query = query.to_compact_string();
+
+    // As it is a PUT query we have to strip the column's name and the parameter placeholder.
+    if method == HttpMethod::Put {
+        for param_id in params_order.iter() {
+            if client_value(param_id).is_some() {
+                continue;
+            }
+
+            let param_id = regex::escape(param_id);
+
+            let re = regex::Regex::new(
+                format!(
+                    r#",\s*{}\s*=\s*\{{\s*{}\s*(::[^}}]*)?\}}|{}\s*=\s*\{{\s*{}\s*(::[^}}]*)?\}}\s*,?\s*"#,
+                    param_id, param_id, param_id, param_id
+                )
+                .as_str(),
+            )
+            .map_err(|err| {
+                RequestError::Expected(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!(
+                        "Cannot create the regex to extract '{}' from the query: {}",
+                        param_id, err
+                    )
+                    .to_compact_string(),
+                )
+            })?;
+
+            query = re.replace_all(&query, "").to_compact_string();
+        }
+    }
+
+    // Replaces Waveless' client's query's parameters placeholders with the backend's ones.
+    let mut bound_query = CompactString::default();
+    let mut ordered_values = CheapVec::<_, 8>::new();
+
+    let mut rest = query.as_str();
+
+    while let Some(start) = rest.find('{') {
+        let Some(length) = rest[start..].find('}') else {
+            break;
+        };
+
+        bound_query.push_str(&rest[..start]);
+
+        let (param_id, param_type) = match rest[start + 1..start + length].split_once("::") {
+            Some((param_id, param_type)) => (param_id.trim(), Some(param_type.trim())),
+            None => (rest[start + 1..start + length].trim(), None),
+        };
+
+        match client_value(param_id) {
+            Some(value) => {
+                ordered_values.push(sea_orm::Value::from(value.to_string()));
+                bound_query.push_str(&placeholder(ordered_values.len(), param_type));
+            }
+            None => {
+                return Err(RequestError::Expected(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!(
+                        "The endpoint requires '{}', but it wasn't provided in the request.",
+                        param_id
+                    )
+                    .to_compact_string(),
+                ));
+            }
+        }
+
+        rest = &rest[start + length + 1..];
+    }
+
+    bound_query.push_str(rest);
+
+    //  Replaces Waveless' runtime injected query's parameters placeholders with the value.
+    // NOTE: the value will be replaced directly in the query,
+    // be aware that a malformed runtime parameter might cause a SQL
+    // injection attack (the attack vector could be in malicious
+    // authentications, sessions, roles methods implementations).
+    let bound_query = bound_query
+        .split('|')
+        .enumerate()
+        .map(|(i, sub)| {
+            if i % 2 != 0 {
+                if let Some(ExecuteParamValue::Internal(value)) = input.params.get(sub.trim()) {
+                    Ok(value.to_compact_string())
+                } else {
+                    Err(RequestError::Expected(
+                        StatusCode::INTERNAL_SERVER_ERROR,
+                        format!(
+                            "Expected the runtime parameter `{}`, but it was not injected.",
+                            sub
+                        )
+                        .to_compact_string(),
+                    ))
+                }
+            } else {
+                Ok(sub.to_compact_string())
+            }
+        })
+        .collect::<Result<CompactString, RequestError>>()?;
+
+    Ok((bound_query, ordered_values))
+}
+
+/// Serializes the rows returned by a SQL backend into a JSON array.
+pub fn rows_to_json(rows: Vec<QueryResult>) -> Result<serde_json::Value, RequestError> {
+    let mut json_rows = CheapVec::<_, 0>::new();
+
+    for row in rows {
+        json_rows.push(
+            sea_orm::JsonValue::from_query_result(&row, "").map_err(|err| {
+                RequestError::Expected(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Internal error: cannot serialize row into JSON. {}", err)
+                        .to_compact_string(),
+                )
+            })?,
+        );
+    }
+
+    Ok(json!(&json_rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bind_query_with_type_hints() -> Result<()> {
+        let input = ExecuteInput::new(
+            HashMap::from([
+                (
+                    "id".to_compact_string(),
+                    ExecuteParamValue::Client(Some("7".to_compact_string())),
+                ),
+                (
+                    "price".to_compact_string(),
+                    ExecuteParamValue::Client(Some("19.5".to_compact_string())),
+                ),
+                ("name".to_compact_string(), ExecuteParamValue::Client(None)),
+            ]),
+            Bytes::new(),
+        );
+
+        let (query, values) = bind_query(
+            "UPDATE products SET name = {name::text}, price = { price::numeric(10, 2) } WHERE id = {id::integer}",
+            HttpMethod::Put,
+            &input,
+            |i, param_type| match param_type {
+                Some(param_type) => format!("${}::{}", i, param_type).to_compact_string(),
+                None => format!("${}", i).to_compact_string(),
+            },
+        )
+        .map_err(|err| anyhow!("{:?}", err))?;
+
+        assert_eq!(
+            query,
+            "UPDATE products SET price = $1::numeric(10, 2) WHERE id = $2::integer"
+        );
+        assert_eq!(values.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn bind_owned_query() -> Result<()> {
+        let query =
+            "DELETE FROM notes WHERE id = {id} AND (owner_id = '|user_id|' OR |owner_bypass| = 1)";
+
+        let mut input = ExecuteInput::new(
+            HashMap::from([
+                (
+                    "id".to_compact_string(),
+                    ExecuteParamValue::Client(Some("7".to_compact_string())),
+                ),
+                (
+                    "owner_bypass".to_compact_string(),
+                    ExecuteParamValue::Internal("0".to_compact_string()),
+                ),
+                (
+                    "user_id".to_compact_string(),
+                    ExecuteParamValue::Client(Some("1".to_compact_string())),
+                ),
+            ]),
+            Bytes::new(),
+        );
+
+        // The owner can't be provided by the client.
+        assert!(
+            bind_query(query, HttpMethod::Delete, &input, |_, _| "?"
+                .to_compact_string())
+            .is_err()
+        );
+
+        input.params_mut().insert(
+            "user_id".to_compact_string(),
+            ExecuteParamValue::Internal("3".to_compact_string()),
+        );
+
+        let (query, values) = bind_query(query, HttpMethod::Delete, &input, |_, _| {
+            "?".to_compact_string()
+        })
+        .map_err(|err| anyhow!("{:?}", err))?;
+
+        assert_eq!(
+            query,
+            "DELETE FROM notes WHERE id = ? AND (owner_id = '3' OR 0 = 1)"
+        );
+        assert_eq!(values.len(), 1);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,238 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+use crate::*;
+
+use super::*;
+
+use databases::postgres::*;
+
+use sea_orm::QueryResult;
+use sea_orm::prelude::{Date, DateTime, DateTimeWithTimeZone, Decimal, Json, Time, Uuid};
+
+use std::str::FromStr;
+
+/// Executes a query on a PostgreSQL database. Client parameters are bound as `$n` placeholders,
+/// their values are converted into the types PostgreSQL infers for them. Values of other types
+/// (enums, arrays...) are sent as text and casted by PostgreSQL, as are the ones with a type
+/// hint (`{param::type}`), which is translated into a cast (`$n::type`).
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display("PostgreSQL query: {:?}", query)]
+#[getset(get = "pub")]
+pub struct PostgresExecute {
+    query: CompactString,
+}
+
+boxed_any!(PostgresExecute);
+
+/// Converts the client's value into the given parameter's type, `None` if it's sent as text.
+fn typed_value(param_type: &str, value: &str) -> Option<Result<sea_orm::Value, ()>> {
+    fn parse<T: FromStr>(value: &str) -> Result<T, ()> {
+        value.trim().parse::<T>().map_err(|_| ())
+    }
+
+    let value = match param_type {
+        "TEXT" | "VARCHAR" | "BPCHAR" | "NAME" => Ok(sea_orm::Value::from(value.to_string())),
+        "BOOL" => match value.trim().to_lowercase().as_str() {
+            "true" | "t" | "1" => Ok(true.into()),
+            "false" | "f" | "0" => Ok(false.into()),
+            _ => Err(()),
+        },
+        "INT2" => parse::<i16>(value).map(Into::into),
+        "INT4" => parse::<i32>(value).map(Into::into),
+        "INT8" => parse::<i64>(value).map(Into::into),
+        "FLOAT4" => parse::<f32>(value).map(Into::into),
+        "FLOAT8" => parse::<f64>(value).map(Into::into),
+        "NUMERIC" => parse::<Decimal>(value).map(Into::into),
+        "UUID" => parse::<Uuid>(value).map(Into::into),
+        "JSON" | "JSONB" => serde_json::from_str::<Json>(value)
+            .map(Into::into)
+            .map_err(|_| ()),
+        "DATE" => parse::<Date>(value).map(Into::into),
+        "TIME" => parse::<Time>(value).map(Into::into),
+        "TIMESTAMP" => parse::<DateTime>(value)
+            .or_else(|_| {
+                DateTime::parse_from_str(value.trim(), "%Y-%m-%d %H:%M:%S%.f").map_err(|_| ())
+            })
+            .map(Into::into),
+        "TIMESTAMPTZ" => DateTimeWithTimeZone::parse_from_rfc3339(value.trim())
+            .map(Into::into)
+            .map_err(|_| ()),
+        _ => return None,
+    };
+
+    Some(value)
+}
+
+#[typetag::serde(name = "Postgres")]
+#[async_trait]
+impl AnyExecute for PostgresExecute {
+    /// The rows are serialized into JSON by PostgreSQL itself, so every type (uuid, jsonb, arrays,
+    /// timestamptz, enums...) keeps its native JSON representation.
+    async fn execute(
+        &self,
+        method: HttpMethod,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        input: ExecuteInput,
+    ) -> Result<ExecuteOutput, RequestError> {
+        let Ok(postgres_conn) = db_conn
+            .to_owned()
+            .into_arc_any()
+            .downcast::<PostgresConnection>()
+        else {
+            return Err(RequestError::Other(anyhow!(
+                "Database connection should be of type {:?} but it's of type {:?}.",
+                TypeId::of::<PostgresConnection>(),
+                db_conn.inner_type_id()
+            )));
+        };
+
+        let hinted = |i: usize, param_type: Option<&str>| match param_type {
+            Some(param_type) => format!("${}::{}", i, param_type).to_compact_string(),
+            None => format!("${}", i).to_compact_string(),
+        };
+
+        let (postgres_query, text_values) = bind_query(self.query(), method, &input, hinted)?;
+
+        let statement = postgres_conn
+            .describe(&postgres_query)
+            .await
+            .map_err(|err| {
+                RequestError::Expected(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Query execution error: {}", err).to_compact_string(),
+                )
+            })?;
+
+        let mut ordered_values = CheapVec::<sea_orm::Value, 8>::new();
+        let mut untyped = CheapVec::<usize, 0>::new();
+
+        for (i, value) in text_values.into_iter().enumerate() {
+            let text = match &value {
+                sea_orm::Value::String(Some(text)) => text.to_string(),
+                _ => {
+                    ordered_values.push(value);
+                    continue;
+                }
+            };
+
+            match statement.params().get(i).and_then(|param_type| {
+                typed_value(param_type, &text).map(|typed| (param_type, typed))
+            }) {
+                Some((_, Ok(typed))) => ordered_values.push(typed),
+                Some((param_type, Err(()))) => {
+                    return Err(RequestError::Expected(
+                        StatusCode::BAD_REQUEST,
+                        format!("Parameter ${} must be a valid {} value.", i + 1, param_type)
+                            .to_compact_string(),
+                    ));
+                }
+                None => {
+                    untyped.push(i + 1);
+                    ordered_values.push(value);
+                }
+            }
+        }
+
+        // The values sent as text are casted into the type PostgreSQL inferred for them.
+        let postgres_query = if untyped.is_empty() {
+            postgres_query
+        } else {
+            bind_query(self.query(), method, &input, |i, param_type| {
+                match (param_type, statement.params().get(i - 1)) {
+                    (None, Some(inferred)) if untyped.contains(&i) => {
+                        format!("${}::{}", i, inferred.to_lowercase()).to_compact_string()
+                    }
+                    _ => hinted(i, param_type),
+                }
+            })?
+            .0
+        };
+
+        let returns_rows = *statement.returns_rows();
+
+        let postgres_query = if returns_rows {
+            format!(
+                "WITH waveless_rows AS ({}) SELECT COALESCE(jsonb_agg(waveless_rows), '[]'::jsonb) AS waveless_rows FROM waveless_rows",
+                postgres_query.trim().trim_end_matches(';')
+            )
+            .to_compact_string()
+        } else {
+            postgres_query
+        };
+
+        let res = db_conn
+            .execute(DatabaseInput::QueryValues(postgres_query, ordered_values))
+            .await
+            .map_err(|err| {
+                RequestError::Expected(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Query execution error: {}", err).to_compact_string(),
+                )
+            })?;
+
+        let DatabaseOutput::Any(res) = res else {
+            return Err(RequestError::Other(anyhow!(
+                "Unexpected database's executor's output."
+            )));
+        };
+
+        let res = res.downcast::<Vec<QueryResult>>().map_err(|err| {
+            RequestError::Other(anyhow!(
+                "Cannot downcast to PostgreSQL query result. {:?}",
+                err
+            ))
+        })?;
+
+        if !returns_rows {
+            return Ok(ExecuteOutput::Json(None, json!([])));
+        }
+
+        let rows = res
+            .first()
+            .map(|row| row.try_get::<serde_json::Value>("", "waveless_rows"))
+            .transpose()
+            .map_err(|err| {
+                RequestError::Expected(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Internal error: cannot deserialize the rows' JSON. {}", err)
+                        .to_compact_string(),
+                )
+            })?
+            .unwrap_or(json!([]));
+
+        Ok(ExecuteOutput::Json(None, rows))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_typed_as_the_parameters() {
+        assert_eq!(
+            typed_value("INT4", " 42 "),
+            Some(Ok(sea_orm::Value::from(42i32)))
+        );
+        assert_eq!(typed_value("INT8", "4.2"), Some(Err(())));
+        assert_eq!(
+            typed_value("BOOL", "t"),
+            Some(Ok(sea_orm::Value::from(true)))
+        );
+        assert_eq!(
+            typed_value("TIMESTAMP", "2026-01-02 03:04:05"),
+            Some(Ok(sea_orm::Value::from(
+                DateTime::parse_from_str("2026-01-02T03:04:05", "%Y-%m-%dT%H:%M:%S").unwrap()
+            )))
+        );
+        assert_eq!(
+            typed_value("JSONB", r#"{"tags": ["a"]}"#),
+            Some(Ok(sea_orm::Value::from(json!({ "tags": ["a"] }))))
+        );
+
+        // Sent as text and casted by PostgreSQL.
+        assert_eq!(typed_value("mood", "happy"), None);
+        assert_eq!(typed_value("INT4[]", "{1,2}"), None);
+    }
+}
@@ -0,0 +1,55 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+use crate::*;
+
+use super::*;
+
+use sea_orm::QueryResult;
+
+/// Executes a query on a SQLite database, client parameters are bound as `?` placeholders.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display("SQLite query: {:?}", query)]
+#[getset(get = "pub")]
+pub struct SQLiteExecute {
+    query: CompactString,
+}
+
+boxed_any!(SQLiteExecute);
+
+#[typetag::serde(name = "SQLite")]
+#[async_trait]
+impl AnyExecute for SQLiteExecute {
+    /// The rows will be returned as a JSON array.
+    async fn execute(
+        &self,
+        method: HttpMethod,
+        db_conn: Arc<dyn AnyDatabaseConnection>,
+        input: ExecuteInput,
+    ) -> Result<ExecuteOutput, RequestError> {
+        let (sqlite_query, ordered_values) =
+            bind_query(self.query(), method, &input, |_, _| "?".to_compact_string())?;
+
+        let res = db_conn
+            .execute(DatabaseInput::QueryValues(sqlite_query, ordered_values))
+            .await
+            .map_err(|err| {
+                RequestError::Expected(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Query execution error: {}", err).to_compact_string(),
+                )
+            })?;
+
+        let DatabaseOutput::Any(res) = res else {
+            return Err(RequestError::Other(anyhow!(
+                "Unexpected database's executor's output."
+            )));
+        };
+
+        let res = res.downcast::<Vec<QueryResult>>().map_err(|err| {
+            RequestError::Other(anyhow!("Cannot downcast to SQLite query result. {:?}", err))
+        })?;
+
+        Ok(ExecuteOutput::Json(None, rows_to_json(*res)?))
+    }
+}
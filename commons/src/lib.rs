@@ -8,9 +8,11 @@ pub mod endpoint;
 pub mod entry;
 pub mod execute;
 pub mod logging;
+pub mod mail;
 pub mod project;
 pub mod runtime;
 pub mod schema;
+pub mod store;
 
 mod serialize_utils;
 
@@ -107,6 +109,13 @@ macro_rules! boxed_any {
 pub enum RequestError {
     #[error("Request error.")]
     Expected(StatusCode, CompactString),
+    /// Rejected until the given time has passed, sent as `Retry-After`.
+    #[error("Request throttled.")]
+    Throttled(Duration, CompactString),
+    /// Rejected by a rate limit of the given requests until its window resets, sent as the
+    /// `RateLimit-*` headers and `Retry-After`.
+    #[error("Rate limit exceeded.")]
+    RateLimited(u64, Duration, CompactString),
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 }
@@ -0,0 +1,373 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+//!
+//! Outbound mail, either sent through SMTP or written to a maildir for development and tests.
+//! Mails are rendered from the plain text templates on the project's directory, whose first line
+//! is the subject (`Subject: ...`) and the rest the body. Placeholders like `{{link}}` are replaced
+//! when rendering, and the built-in templates are used when the project doesn't provide them.
+//!
+
+use crate::*;
+
+use lettre::message::{Mailbox, header::ContentType};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+/// Mailing settings of the project.
+#[derive(Clone, Constructor, Serialize, Deserialize, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct Mailing {
+    mailer: Arc<dyn AnyMailer>,
+
+    /// Sender's mailbox, e.g. `Example <no-reply@example.com>`.
+    from: CompactString,
+
+    /// Directory with the mails' templates, relative to the project's workspace.
+    templates_dir: CompactString,
+
+    /// Store file keeping the tokens sent by mail, relative to the project's workspace.
+    path: CompactString,
+
+    table_name: CompactString,
+}
+
+impl PartialEq for Mailing {
+    fn eq(&self, other: &Self) -> bool {
+        self.from == other.from && self.templates_dir == other.templates_dir
+    }
+}
+
+impl Default for Mailing {
+    fn default() -> Self {
+        Self {
+            mailer: Arc::new(MaildirMailer::default()),
+            from: "Example <no-reply@example.com>".to_compact_string(),
+            templates_dir: "mail".to_compact_string(),
+            path: "waveless.redb".to_compact_string(),
+            table_name: "mail_tokens".to_compact_string(),
+        }
+    }
+}
+
+impl Mailing {
+    /// Renders the template for the given recipient, replacing the `{{name}}` placeholders.
+    pub async fn render(
+        &self,
+        template: MailTemplate,
+        to: &str,
+        placeholders: &[(&str, &str)],
+    ) -> Result<Mail> {
+        let path = get_workspace_root("project.toml")
+            .unwrap_or(current_dir()?)
+            .join(self.templates_dir.as_str())
+            .join(template.file_name());
+
+        let source = match tokio::fs::read_to_string(&path).await {
+            Ok(source) => source,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                template.fallback().to_string()
+            }
+            Err(err) => bail!("Cannot read the mail template at {:?}. {}", path, err),
+        };
+
+        let source = placeholders.iter().fold(source, |source, (name, value)| {
+            source.replace(&format!("{{{{{}}}}}", name), value)
+        });
+
+        let Some((subject, body)) = source
+            .split_once('\n')
+            .and_then(|(subject, body)| Some((subject.strip_prefix("Subject:")?, body)))
+        else {
+            bail!(
+                "The mail template at {:?} must start with the `Subject:` line.",
+                path
+            )
+        };
+
+        Ok(Mail::new(
+            to.to_compact_string(),
+            subject.trim().to_compact_string(),
+            body.trim_start_matches(['\r', '\n']).to_compact_string(),
+        ))
+    }
+
+    /// Sends the mail from the project's sender.
+    pub async fn send(&self, mail: Mail) -> Result<()> {
+        self.mailer.send(&self.from, mail).await
+    }
+}
+
+/// The mails sent by the server, each one rendered from its own template.
+#[derive(Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Display, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum MailTemplate {
+    PasswordReset,
+    EmailVerification,
+    MagicLink,
+}
+
+impl MailTemplate {
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            MailTemplate::PasswordReset => "password_reset.txt",
+            MailTemplate::EmailVerification => "email_verification.txt",
+            MailTemplate::MagicLink => "magic_link.txt",
+        }
+    }
+
+    /// The built-in template, used when the project doesn't provide it.
+    pub fn fallback(&self) -> &'static str {
+        match self {
+            MailTemplate::PasswordReset => {
+                "Subject: Reset your {{project}} password\n\nA password reset was requested for your {{project}} account.\nFollow the link below to set a new password, it expires in {{minutes}} minutes:\n\n{{link}}\n\nIf you didn't request it, you can safely ignore this mail.\n"
+            }
+            MailTemplate::EmailVerification => {
+                "Subject: Verify your {{project}} email\n\nWelcome to {{project}}!\nFollow the link below to verify your email, it expires in {{minutes}} minutes:\n\n{{link}}\n"
+            }
+            MailTemplate::MagicLink => {
+                "Subject: Log in to {{project}}\n\nFollow the link below to log in to {{project}}, or enter the code {{code}}.\nBoth expire in {{minutes}} minutes and can only be used once:\n\n{{link}}\n\nIf you didn't request it, you can safely ignore this mail.\n"
+            }
+        }
+    }
+}
+
+/// A rendered plain text mail.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct Mail {
+    to: CompactString,
+    subject: CompactString,
+    body: CompactString,
+}
+
+impl Mail {
+    fn message(&self, from: &str) -> Result<Message> {
+        let from = from
+            .parse::<Mailbox>()
+            .map_err(|err| anyhow!("Invalid sender's mailbox '{}'. {}", from, err))?;
+
+        let to = self
+            .to
+            .parse::<Mailbox>()
+            .map_err(|err| anyhow!("Invalid recipient's mailbox '{}'. {}", self.to, err))?;
+
+        Ok(Message::builder()
+            .from(from)
+            .to(to)
+            .subject(self.subject.as_str())
+            .header(ContentType::TEXT_PLAIN)
+            .body(self.body.to_string())?)
+    }
+}
+
+/// Trait implemented for every way of delivering mails.
+#[typetag::serde]
+#[async_trait]
+pub trait AnyMailer: Any + BoxedAny + DynClone + Send + Sync + Debug {
+    fn name(&self) -> &str;
+
+    /// Delivers the mail from the given sender.
+    async fn send(&self, from: &str, mail: Mail) -> Result<()>;
+}
+
+/// Sends the mails through a SMTP relay.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display("SMTP relay at {}", host)]
+#[getset(get = "pub")]
+pub struct SmtpMailer {
+    host: CompactString,
+
+    /// The security's default port by default.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    port: Option<u16>,
+
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    username: Option<CompactString>,
+
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    password: Option<CompactString>,
+
+    security: SmtpSecurity,
+}
+
+boxed_any!(SmtpMailer);
+
+impl Default for SmtpMailer {
+    fn default() -> Self {
+        Self {
+            host: "localhost".to_compact_string(),
+            port: None,
+            username: None,
+            password: None,
+            security: SmtpSecurity::StartTls,
+        }
+    }
+}
+
+/// How the connection to the SMTP relay is secured.
+#[derive(Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Display, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    /// Implicit TLS, on port 465 by default.
+    Tls,
+    /// Upgrades the connection to TLS, on port 587 by default.
+    StartTls,
+    /// Plain connection, on port 25 by default. NOTE: only meant for local relays.
+    None,
+}
+
+#[typetag::serde(name = "Smtp")]
+#[async_trait]
+impl AnyMailer for SmtpMailer {
+    fn name(&self) -> &str {
+        "smtp"
+    }
+
+    async fn send(&self, from: &str, mail: Mail) -> Result<()> {
+        let message = mail.message(from)?;
+
+        let mut transport = match self.security {
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&self.host)?,
+            SmtpSecurity::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&self.host)?
+            }
+            SmtpSecurity::None => {
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(self.host.as_str())
+            }
+        };
+
+        if let Some(port) = self.port {
+            transport = transport.port(port);
+        }
+
+        if let (Some(username), Some(password)) = (&self.username, &self.password) {
+            transport =
+                transport.credentials(Credentials::new(username.to_string(), password.to_string()));
+        }
+
+        transport
+            .build()
+            .send(message)
+            .await
+            .map_err(|err| anyhow!("Cannot send the mail to '{}'. {}", mail.to, err))?;
+
+        Ok(())
+    }
+}
+
+/// Writes the mails to a maildir instead of sending them, meant for development and tests.
+/// NOTE: the mails are delivered to the `new` subdirectory, which mail clients can open.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display("Maildir at {}", path)]
+#[getset(get = "pub")]
+pub struct MaildirMailer {
+    /// Relative to the project's workspace.
+    path: CompactString,
+}
+
+boxed_any!(MaildirMailer);
+
+impl Default for MaildirMailer {
+    fn default() -> Self {
+        Self {
+            path: "target/mail".to_compact_string(),
+        }
+    }
+}
+
+#[typetag::serde(name = "Maildir")]
+#[async_trait]
+impl AnyMailer for MaildirMailer {
+    fn name(&self) -> &str {
+        "maildir"
+    }
+
+    async fn send(&self, from: &str, mail: Mail) -> Result<()> {
+        let message = mail.message(from)?;
+
+        let maildir = get_workspace_root("project.toml")
+            .unwrap_or(current_dir()?)
+            .join(self.path.as_str());
+
+        for subdir in ["tmp", "new", "cur"] {
+            tokio::fs::create_dir_all(maildir.join(subdir)).await?;
+        }
+
+        let file_name = format!(
+            "{}.{}.waveless",
+            Utc::now().timestamp_micros(),
+            Alphanumeric.sample_string(&mut rand::rng(), 16)
+        );
+
+        // Mails are moved into `new` once written, so readers never see them partially.
+        tokio::fs::write(maildir.join("tmp").join(&file_name), message.formatted()).await?;
+
+        tokio::fs::rename(
+            maildir.join("tmp").join(&file_name),
+            maildir.join("new").join(&file_name),
+        )
+        .await?;
+
+        info!(
+            "Mail '{}' to '{}' written to {:?}.",
+            mail.subject,
+            mail.to,
+            maildir.join("new").join(&file_name)
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn render_and_deliver_to_maildir() -> Result<()> {
+        let maildir = std::env::temp_dir().join(format!(
+            "waveless_mail_{}",
+            Alphanumeric.sample_string(&mut rand::rng(), 8)
+        ));
+
+        let mailing = Mailing {
+            mailer: Arc::new(MaildirMailer::new(
+                maildir.to_string_lossy().to_compact_string(),
+            )),
+            templates_dir: "missing_templates".to_compact_string(),
+            ..Default::default()
+        };
+
+        // The built-in template is used as the project doesn't provide it.
+        let mail = mailing
+            .render(
+                MailTemplate::PasswordReset,
+                "user@example.com",
+                &[
+                    ("project", "Example"),
+                    ("link", "https://example.com/reset?token=abc"),
+                    ("minutes", "60"),
+                ],
+            )
+            .await?;
+
+        assert_eq!(mail.subject(), "Reset your Example password");
+        assert!(mail.body().contains("https://example.com/reset?token=abc"));
+        assert!(!mail.body().contains("{{"));
+
+        mailing.send(mail).await?;
+
+        let mut delivered = std::fs::read_dir(maildir.join("new"))?;
+
+        let message = std::fs::read_to_string(delivered.next().unwrap()?.path())?;
+
+        assert!(message.contains("To: user@example.com"));
+        assert!(message.contains("Subject: Reset your Example password"));
+        assert!(delivered.next().is_none());
+
+        std::fs::remove_dir_all(maildir)?;
+
+        Ok(())
+    }
+}
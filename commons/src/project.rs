@@ -10,9 +10,13 @@
 
 use crate::*;
 
-use auth::{mysql::*, *};
+use auth::{email::*, roles::*, sql::*, throttle::*, totp::*, *};
 use build::*;
 use databases::*;
+use execute::*;
+use mail::*;
+
+use std::net::IpAddr;
 
 /// Includes all the project's config
 #[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, MutGetters, Debug)]
@@ -51,6 +55,14 @@ pub struct Config {
 
     /// Contains admin settings.
     admin: Admin,
+
+    /// Usage quotas of the authenticated consumers.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    quotas: Option<Quotas>,
+
+    /// Outbound mail, required by the password reset and the email verification.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    mailing: Option<Mailing>,
 }
 
 impl Default for Config {
@@ -65,6 +77,8 @@ impl Default for Config {
                     connection: Arc::new(ExternalDBConnectionConfig {
                         id: "custom_database_driver".to_compact_string(),
                         connection: "...".to_compact_string(),
+                        command: None,
+                        args: CheapVec::new_const(),
                     }),
                     schema_discovery: None,
                     pool_min_size: None,
@@ -73,6 +87,8 @@ impl Default for Config {
             ]),
             authentication: Default::default(),
             admin: Default::default(),
+            quotas: None,
+            mailing: None,
         }
     }
 }
@@ -90,6 +106,10 @@ pub struct Compiler {
     /// this is the directory where scripts that may be used to create the db, make migrations... are located
     #[serde(default, skip_serializing_if = "should_skip_option")]
     bootstrap_scripts_dir: Option<CompactString>,
+
+    /// maximum age (in seconds) of the schema snapshots used by offline builds before warning that they may be outdated
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    schema_snapshot_max_age: Option<usize>,
 }
 
 impl Default for Compiler {
@@ -98,6 +118,7 @@ impl Default for Compiler {
             endpoints_dir: "./endpoints/".to_compact_string(),
             hooks_dir: Some("./hooks/".to_compact_string()),
             bootstrap_scripts_dir: Some("./bootstrap/".to_compact_string()),
+            schema_snapshot_max_age: Some(604800),
         }
     }
 }
@@ -120,8 +141,19 @@ pub struct Executor {
     /// the compiler will generate a checksum of the schema of each database, if this option is marked, the server executor will check whether the checksum on each start
     check_databases_cheksums: bool,
 
-    /// set the http cache time header
-    http_cache_time: usize,
+    /// DEPRECATED: set the endpoints' `cache` instead.
+    /// Caches the responses of the `GET` endpoints which don't set their own `cache` for the given
+    /// time (in seconds).
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    http_cache_time: Option<u64>,
+
+    /// Limits the requests of each client.
+    #[serde(default)]
+    rate_limiting: RateLimiting,
+
+    /// Caches the results of the queries of the endpoints setting a `query_cache_ttl`.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    query_cache: Option<QueryCacheSettings>,
 }
 
 impl Default for Executor {
@@ -131,11 +163,99 @@ impl Default for Executor {
             static_files: Some("./static/".to_compact_string()),
             api_prefix: "/api".to_compact_string(),
             check_databases_cheksums: true,
-            http_cache_time: 0,
+            http_cache_time: None,
+            rate_limiting: RateLimiting::default(),
+            query_cache: None,
+        }
+    }
+}
+
+/// Query result cache settings, results are kept in memory and optionally on the embedded store
+/// too, so they survive restarts. NOTE: they're read once, when the cache is first used.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct QueryCacheSettings {
+    /// Max results kept in memory.
+    max_entries: usize,
+
+    /// Store file of the disk tier, relative to the project's workspace. Only in memory if not set.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    path: Option<CompactString>,
+
+    table_name: CompactString,
+}
+
+impl Default for QueryCacheSettings {
+    fn default() -> Self {
+        Self {
+            max_entries: 4096,
+            path: None,
+            table_name: "query_cache".to_compact_string(),
+        }
+    }
+}
+
+/// Rate limiting of the clients' requests. Every client address is limited across the whole
+/// server (static files included), while the rules limit the endpoints' requests on top of it.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Debug)]
+#[serde(default)]
+#[getset(get = "pub")]
+pub struct RateLimiting {
+    /// Milliseconds to replenish one request of each client address' allowance.
+    replenish_interval: u64,
+
+    /// Requests a client address may make at once before being limited.
+    burst_size: u32,
+
+    /// Reverse proxies trusted to forward the client's address through `X-Forwarded-For`.
+    #[serde(default, skip_serializing_if = "should_skip_cheapvec")]
+    trusted_proxies: CheapVec<IpAddr, 0>,
+
+    #[serde(default, skip_serializing_if = "should_skip_cheapvec")]
+    rules: CheapVec<RateLimitRule, 0>,
+}
+
+impl Default for RateLimiting {
+    fn default() -> Self {
+        Self {
+            replenish_interval: 1000,
+            burst_size: 1000,
+            trusted_proxies: CheapVec::new_const(),
+            rules: CheapVec::new_const(),
         }
     }
 }
 
+/// Caps the requests of each client of the given kind during every period.
+/// NOTE: users and API keys are only known on the endpoints requiring auth, elsewhere the rules
+/// keyed by them don't apply.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct RateLimitRule {
+    key: RateLimitKey,
+
+    requests: u64,
+
+    /// Period's length (in seconds).
+    period: u64,
+
+    /// Endpoints sharing the limit, along with those having any of the `tags`. All of them if both are empty.
+    #[serde(default, skip_serializing_if = "should_skip_cheapvec")]
+    endpoints: CheapVec<CompactString, 0>,
+
+    #[serde(default, skip_serializing_if = "should_skip_cheapvec")]
+    tags: CheapVec<CompactString, 0>,
+}
+
+/// What identifies the client a rate limit applies to.
+#[derive(Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Display, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitKey {
+    Ip,
+    User,
+    ApiKey,
+}
+
 /// Defines a database to be used by Waveless
 #[derive(Clone, Constructor, Serialize, Deserialize, Getters, MutGetters, Debug)]
 #[getset(get = "pub", get_mut = "pub")]
@@ -202,14 +322,36 @@ pub trait AnyDatabaseConnectionConfig: Any + BoxedAny + DynClone + Send + Sync +
         pool_min_size: Option<usize>,
         pool_max_size: Option<usize>,
     ) -> Result<(Arc<dyn AnyDatabaseConnection>, Box<dyn Any>)>;
+
+    /// Renders the placeholder of the given column's value in the generated queries,
+    /// backends with strictly typed parameters may add a type hint (`{param::type}`).
+    fn query_param(&self, param_id: &str, _column: &schema::ColumnSchema) -> CompactString {
+        format!("{{{}}}", param_id).to_compact_string()
+    }
+
+    /// Whether the generated `INSERT` and `UPDATE` queries return the affected rows (`RETURNING *`).
+    fn returning(&self) -> bool {
+        false
+    }
 }
 
-/// TODO: load custom database drivers.
-#[derive(Clone, Serialize, Deserialize, Display, Debug)]
+/// Database served by an external driver, see `databases::external` for the driver's protocol.
+#[derive(Clone, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
 #[display("{:?}: {}", id, connection)]
+#[getset(get = "pub")]
 pub struct ExternalDBConnectionConfig {
     id: ExternalDriverId,
+
+    /// Connection string forwarded to the driver on initialization.
     connection: CompactString,
+
+    /// Driver's executable, by default `waveless_driver_{id}` will be searched in the `PATH`.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    command: Option<CompactString>,
+
+    /// Arguments passed to the driver's executable.
+    #[serde(default, skip_serializing_if = "should_skip_cheapvec")]
+    args: CheapVec<CompactString, 0>,
 }
 
 boxed_any!(ExternalDBConnectionConfig);
@@ -223,13 +365,36 @@ impl PartialEq for ExternalDBConnectionConfig {
 #[typetag::serde]
 #[async_trait]
 impl AnyDatabaseConnectionConfig for ExternalDBConnectionConfig {
+    /// Every connection spawns a single driver process, requests are multiplexed
+    /// over it so the pool sizes are left to the driver.
     async fn new_conn(
         &self,
-        _id: CompactString,
+        id: CompactString,
         _pool_min_size: Option<usize>,
         _pool_max_size: Option<usize>,
     ) -> Result<(Arc<dyn AnyDatabaseConnection>, Box<dyn Any>)> {
-        todo!("Not implemented yet.");
+        info!(
+            "Creating new external database connection ({}) for {}",
+            self.id, id
+        );
+
+        let driver = Arc::new(databases::external::ExternalDriver::spawn(
+            self.id.to_owned(),
+            self.command.as_ref(),
+            &self.args,
+        )?);
+
+        driver
+            .initialize(self.connection.to_owned())
+            .await
+            .map_err(|err| anyhow!("Failed initializing {}'s external driver. {}", id, err))?;
+
+        Ok((
+            Arc::new(databases::external::ExternalConnection::new(
+                driver.to_owned(),
+            )),
+            Box::new(driver),
+        ))
     }
 }
 
@@ -245,6 +410,23 @@ pub struct DataSchemaDiscoveryConfig {
 
     // Whether to checksum the database's schema.
     checksum: bool,
+
+    /// Row ownership policies of the generated endpoints, per table.
+    #[serde(default, skip_serializing_if = "should_skip_cheapvec")]
+    ownership: CheapVec<OwnershipPolicy, 0>,
+}
+
+/// Scopes the generated endpoints of a table to the rows owned by the requesting user.
+/// Those endpoints will require auth, reads, updates and deletes are filtered by the owner's
+/// column and inserts force it to the user's id. Admin roles bypass the filter, not the insert.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct OwnershipPolicy {
+    /// Table owned, case insensitive.
+    table: CompactString,
+
+    /// Column holding the owner's user id.
+    owner_column: CompactString,
 }
 
 impl Default for DataSchemaDiscoveryConfig {
@@ -255,6 +437,7 @@ impl Default for DataSchemaDiscoveryConfig {
             )),
             generate_endpoints: true,
             checksum: true,
+            ownership: CheapVec::new_const(),
         }
     }
 }
@@ -263,20 +446,36 @@ impl Default for DataSchemaDiscoveryConfig {
 #[typetag::serde]
 #[async_trait]
 pub trait AnyDataSchemaDiscoveryMethod: Any + BoxedAny + DynClone + Send + Sync + Debug {
+    /// Discovers the database's schema. If endpoints are to be generated from it,
+    /// the schema is expected to be a `schema::DataSchema`.
     async fn schema(
         &self,
         db_id: CompactString,
         db_config: Arc<dyn AnyDatabaseConnectionConfig>,
     ) -> Result<(Box<dyn Any>, DatabaseChecksum)>;
+
+    /// Creates the executor of the given generated query.
+    fn executor(&self, query: CompactString) -> Arc<dyn AnyExecute>;
 }
 
-/// The external module will use the project's hooks tp establish a database connection.
-/// TODO: load custom schema discovery drivers.
-#[derive(Clone, Serialize, Deserialize, Display, Debug)]
+/// Discovers the schema through an external driver, see `databases::external` for the driver's protocol.
+/// The database connection must be an `ExternalDBConnectionConfig`, its connection string will be forwarded to the driver.
+#[derive(Clone, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
 #[display("{:?}: {:?}", id, config)]
+#[getset(get = "pub")]
 pub struct ExternalSchemaDiscoveryMethod {
     id: DataSchemaDiscoveryMethodId,
+
+    /// Driver specific settings.
     config: HashMap<CompactString, Bytes>,
+
+    /// Driver's executable, by default `waveless_driver_{id}` will be searched in the `PATH`.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    command: Option<CompactString>,
+
+    /// Arguments passed to the driver's executable.
+    #[serde(default, skip_serializing_if = "should_skip_cheapvec")]
+    args: CheapVec<CompactString, 0>,
 }
 
 boxed_any!(ExternalSchemaDiscoveryMethod);
@@ -292,10 +491,50 @@ impl PartialEq for ExternalSchemaDiscoveryMethod {
 impl AnyDataSchemaDiscoveryMethod for ExternalSchemaDiscoveryMethod {
     async fn schema(
         &self,
-        _db_id: CompactString,
-        _db_conn_config: Arc<dyn AnyDatabaseConnectionConfig>,
+        db_id: CompactString,
+        db_conn_config: Arc<dyn AnyDatabaseConnectionConfig>,
     ) -> Result<(Box<dyn Any>, DatabaseChecksum)> {
-        todo!("Not implemented yet.")
+        let Ok(db_conn_config) = db_conn_config
+            .to_owned()
+            .into_arc_any()
+            .downcast::<ExternalDBConnectionConfig>()
+        else {
+            bail!(
+                "Database connection config should be of type {:?} but it's of type {:?}.",
+                TypeId::of::<ExternalDBConnectionConfig>(),
+                db_conn_config.inner_type_id()
+            )
+        };
+
+        let driver = databases::external::ExternalDriver::spawn(
+            self.id.to_owned(),
+            self.command.as_ref(),
+            &self.args,
+        )?;
+
+        driver
+            .initialize(db_conn_config.connection().to_owned())
+            .await?;
+
+        let schema = driver
+            .schema(
+                db_conn_config.connection().to_owned(),
+                self.config.to_owned(),
+            )
+            .await?;
+
+        let checksum = DatabaseChecksum::new(
+            db_id,
+            CheapVec::from_slice(
+                &crc32fast::hash(format!("{:?}", schema).as_str().as_bytes()).to_ne_bytes(),
+            ),
+        );
+
+        Ok((Box::new(schema), checksum))
+    }
+
+    fn executor(&self, query: CompactString) -> Arc<dyn AnyExecute> {
+        Arc::new(execute::external::ExternalExecute::new(query))
     }
 }
 
@@ -317,6 +556,14 @@ pub struct Authentication {
     #[serde(default, skip_serializing_if = "should_skip_option")]
     default_role: Option<CompactString>,
 
+    /// The method for manage API keys, accepted through the `X-Api-Key` header.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    api_keys: Option<Arc<dyn AnyApiKeyMethod>>,
+
+    /// Roles' hierarchy and their permissions, which endpoints may require instead of roles.
+    #[serde(default, skip_serializing_if = "should_skip_cheapvec")]
+    roles: CheapVec<RoleDefinition, 0>,
+
     /// Whether to read the session token from the cookie header.
     /// NOTE: if set, the session token will be read from the Authorization header
     /// and will fallback to the cookie header.
@@ -324,6 +571,29 @@ pub struct Authentication {
 
     /// Whether to allow user signup.
     allow_signup: bool,
+
+    /// Enables TOTP as the second authentication factor for the users who enroll.
+    /// NOTE: the authentication method must be able to store the TOTP state (e.g. `totp_field`).
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    totp: Option<TotpSettings>,
+
+    /// Lets users reset their password through an emailed link, which requires `mailing`.
+    /// NOTE: the authentication method must be able to find the users' emails (e.g. `email_field`).
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    password_reset: Option<PasswordResetSettings>,
+
+    /// Sends an email verification link on signup, which requires `mailing`.
+    /// NOTE: the authentication method must be able to store the flag (e.g. `verified_field`).
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    email_verification: Option<EmailVerificationSettings>,
+
+    /// Seconds between the removals of expired sessions, hourly by default.
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    sweep_interval: Option<usize>,
+
+    /// Delays the logins of accounts and client addresses after repeated failures.
+    #[serde(default)]
+    login_throttling: LoginThrottlingSettings,
 }
 
 impl PartialEq for Authentication {
@@ -335,14 +605,19 @@ impl PartialEq for Authentication {
 impl Default for Authentication {
     fn default() -> Self {
         Self {
-            backends: CheapVec::from_vec(vec![
-                Arc::new(MySQLSimpleAuthenticationMethod::default()),
-            ]),
-            session: Arc::new(MySQLToken::default()),
-            role: Some(Arc::new(MySQLRole::default())),
+            backends: CheapVec::from_vec(vec![Arc::new(SqlSimpleAuthenticationMethod::default())]),
+            session: Arc::new(SqlToken::default()),
+            role: Some(Arc::new(SqlRole::default())),
             default_role: None,
+            api_keys: None,
+            roles: CheapVec::new_const(),
             session_cookie: true,
             allow_signup: true,
+            totp: None,
+            password_reset: None,
+            email_verification: None,
+            sweep_interval: None,
+            login_throttling: LoginThrottlingSettings::default(),
         }
     }
 }
@@ -358,8 +633,8 @@ pub struct Admin {
     #[serde(default, skip_serializing_if = "should_skip_cheapvec")]
     allowed_roles: CheapVec<CompactString, 0>,
 
-    /// Whether to gather statistics or not.
-    statistics: bool, // TODO
+    /// Whether to report the statistics of the instance (e.g. the query cache's hits) on `admin/statistics`.
+    statistics: bool,
 }
 
 impl Default for Admin {
@@ -372,6 +647,77 @@ impl Default for Admin {
     }
 }
 
+/// Usage quotas, the usage of every consumer (API key or user) is accounted on the embedded store
+/// whether or not it's limited. NOTE: only endpoints requiring auth have a consumer.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct Quotas {
+    /// Store file, relative to the project's workspace.
+    path: CompactString,
+
+    table_name: CompactString,
+
+    #[serde(default, skip_serializing_if = "should_skip_cheapvec")]
+    limits: CheapVec<QuotaLimit, 0>,
+}
+
+impl Default for Quotas {
+    fn default() -> Self {
+        Self {
+            path: "waveless.redb".to_compact_string(),
+            table_name: "usage".to_compact_string(),
+            limits: CheapVec::new_const(),
+        }
+    }
+}
+
+/// Caps the requests and returned rows of each consumer of the given kind.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct QuotaLimit {
+    consumer: QuotaConsumer,
+
+    period: QuotaPeriod,
+
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    requests: Option<u64>,
+
+    #[serde(default, skip_serializing_if = "should_skip_option")]
+    rows: Option<u64>,
+
+    /// Endpoints sharing the quota, all of them if empty.
+    #[serde(default, skip_serializing_if = "should_skip_cheapvec")]
+    endpoints: CheapVec<CompactString, 0>,
+}
+
+/// Who a quota applies to, requests made with an API key are only accounted to the key.
+#[derive(Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Display, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaConsumer {
+    ApiKey,
+    User,
+}
+
+/// The period after which quotas are reset, in UTC.
+#[derive(Copy, Clone, Eq, PartialEq, Serialize, Deserialize, Display, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaPeriod {
+    Daily,
+    Monthly,
+}
+
+impl QuotaPeriod {
+    /// Identifies the current period, e.g. `2026-10-18` or `2026-10`.
+    pub fn current(&self) -> CompactString {
+        let format = match self {
+            QuotaPeriod::Daily => "%Y-%m-%d",
+            QuotaPeriod::Monthly => "%Y-%m",
+        };
+
+        Utc::now().format(format).to_compact_string()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
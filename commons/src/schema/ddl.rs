@@ -0,0 +1,888 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+use crate::*;
+
+use super::*;
+
+use databases::SqlDialect;
+use execute::*;
+use project::*;
+
+use std::fs::{read_dir, read_to_string};
+
+/// The SQL DDL discovery strategy parses the `CREATE TABLE`, `CREATE VIEW` and `ALTER TABLE ... ADD`
+/// statements of the given files to build the data model, so no database connection is needed.
+/// Any other statement is ignored.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display(
+    "{} DDL schema discovery from {:?} (skipping: {:?})",
+    dialect,
+    paths,
+    skip_tables
+)]
+#[getset(get = "pub")]
+pub struct DdlSchemaDiscoveryMethod {
+    /// Files or directories (all their `.sql` files are read in alphabetical order), relative to the project's workspace.
+    paths: CheapVec<CompactString, 0>,
+
+    /// Dialect of the scripts, the generated queries are executed with the same backend.
+    dialect: SqlDialect,
+
+    #[serde(default, skip_serializing_if = "should_skip_cheapvec")]
+    skip_tables: CheapVec<CompactString, 0>,
+}
+
+boxed_any!(DdlSchemaDiscoveryMethod);
+
+impl Default for DdlSchemaDiscoveryMethod {
+    fn default() -> Self {
+        Self {
+            paths: CheapVec::from_vec(vec!["./bootstrap/".to_compact_string()]),
+            dialect: SqlDialect::MySQL,
+            skip_tables: CheapVec::new_const(),
+        }
+    }
+}
+
+impl DdlSchemaDiscoveryMethod {
+    /// Reads all the DDL scripts in order.
+    pub fn read_scripts(&self) -> Result<CompactString> {
+        let workspace_root = get_workspace_root("project.toml").unwrap_or(current_dir()?);
+
+        let mut scripts = CompactString::default();
+
+        for path in &self.paths {
+            let path = workspace_root.join(path.as_str());
+
+            let mut files = if path.is_dir() {
+                read_dir(&path)
+                    .map_err(|err| anyhow!("Cannot list '{}'. {}", path.display(), err))?
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|file| file.extension().is_some_and(|ext| ext == "sql"))
+                    .collect::<Vec<_>>()
+            } else {
+                vec![path]
+            };
+
+            files.sort();
+
+            for file in files {
+                let script = read_to_string(&file)
+                    .map_err(|err| anyhow!("Cannot read '{}'. {}", file.display(), err))?;
+
+                scripts.push_str(&script);
+
+                // Prevents a missing trailing `;` from merging two statements.
+                scripts.push_str(";\n");
+            }
+        }
+
+        Ok(scripts)
+    }
+}
+
+#[typetag::serde(name = "SqlDdl")]
+#[async_trait]
+impl AnyDataSchemaDiscoveryMethod for DdlSchemaDiscoveryMethod {
+    async fn schema(
+        &self,
+        db_id: CompactString,
+        _db_conn_config: Arc<dyn AnyDatabaseConnectionConfig>,
+    ) -> Result<(Box<dyn Any>, DatabaseChecksum)> {
+        let mut schema = parse_ddl(&self.read_scripts()?, self.dialect);
+
+        schema
+            .tables_mut()
+            .retain(|table| !self.skip_tables.contains(table.name()));
+
+        let checksum = DatabaseChecksum::new(
+            db_id,
+            CheapVec::from_slice(
+                &crc32fast::hash(format!("{:?}", schema).as_str().as_bytes()).to_ne_bytes(),
+            ),
+        );
+
+        Ok((Box::new(schema), checksum))
+    }
+
+    fn executor(&self, query: CompactString) -> Arc<dyn AnyExecute> {
+        match self.dialect {
+            SqlDialect::MySQL => Arc::new(execute::mysql::MySQLExecute::new(query)),
+            SqlDialect::Postgres => Arc::new(execute::postgres::PostgresExecute::new(query)),
+            SqlDialect::SQLite => Arc::new(execute::sqlite::SQLiteExecute::new(query)),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Token {
+    Word(CompactString),
+    Quoted(CompactString),
+    Literal,
+    Symbol(char),
+}
+
+impl Token {
+    fn is(&self, keyword: &str) -> bool {
+        matches!(self, Token::Word(word) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    fn ident(&self) -> Option<&CompactString> {
+        match self {
+            Token::Word(ident) | Token::Quoted(ident) => Some(ident),
+            _ => None,
+        }
+    }
+}
+
+/// Splits the SQL script into tokens, comments are dropped and literals' contents are discarded.
+fn tokenize(sql: &str, dialect: SqlDialect) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = sql.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => (),
+            '-' if chars.peek() == Some(&'-') => {
+                chars.find(|c| *c == '\n');
+            }
+            // It's an operator elsewhere, e.g. PostgreSQL's bitwise XOR.
+            '#' if dialect == SqlDialect::MySQL => {
+                chars.find(|c| *c == '\n');
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = '\0';
+                for c in chars.by_ref() {
+                    if last == '*' && c == '/' {
+                        break;
+                    }
+                    last = c;
+                }
+            }
+            '\'' => {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '\'' if chars.peek() == Some(&'\'') => {
+                            chars.next();
+                        }
+                        '\'' => break,
+                        _ => (),
+                    }
+                }
+                tokens.push(Token::Literal);
+            }
+            // Dollar quoted bodies (`$$ ... $$`, `$tag$ ... $tag$`) of PostgreSQL's functions.
+            '$' if dialect == SqlDialect::Postgres
+                && chars
+                    .clone()
+                    .find(|next| !(next.is_alphanumeric() || *next == '_'))
+                    == Some('$')
+                && !chars.peek().is_some_and(|next| next.is_ascii_digit()) =>
+            {
+                let tag = chars
+                    .by_ref()
+                    .take_while(|next| *next != '$')
+                    .collect::<CompactString>();
+                let delimiter = format!("${}$", tag);
+
+                let mut body = CompactString::default();
+                for c in chars.by_ref() {
+                    body.push(c);
+                    if body.ends_with(&delimiter) {
+                        break;
+                    }
+                }
+                tokens.push(Token::Literal);
+            }
+            '`' | '"' => {
+                let ident = chars.by_ref().take_while(|next| *next != c).collect();
+                tokens.push(Token::Quoted(ident));
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '$' => {
+                let mut word = CompactString::default();
+                word.push(c);
+                while let Some(next) = chars.peek() {
+                    if next.is_alphanumeric() || *next == '_' || *next == '$' {
+                        word.push(*next);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Word(word));
+            }
+            c => tokens.push(Token::Symbol(c)),
+        }
+    }
+
+    tokens
+}
+
+/// Splits the tokens by the given symbol, ignoring the ones nested in parentheses.
+fn split_top_level(tokens: &[Token], separator: char) -> Vec<&[Token]> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Symbol('(') => depth += 1,
+            Token::Symbol(')') => depth = depth.saturating_sub(1),
+            Token::Symbol(c) if *c == separator && depth == 0 => {
+                parts.push(&tokens[start..i]);
+                start = i + 1;
+            }
+            _ => (),
+        }
+    }
+
+    parts.push(&tokens[start..]);
+
+    parts.into_iter().filter(|part| !part.is_empty()).collect()
+}
+
+/// Returns the tokens inside the parentheses starting at `start` and the index after the closing one.
+fn parenthesized(tokens: &[Token], start: usize) -> Option<(&[Token], usize)> {
+    if tokens.get(start) != Some(&Token::Symbol('(')) {
+        return None;
+    }
+
+    let mut depth = 0usize;
+
+    for (i, token) in tokens.iter().enumerate().skip(start) {
+        match token {
+            Token::Symbol('(') => depth += 1,
+            Token::Symbol(')') => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((&tokens[start + 1..i], i + 1));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    None
+}
+
+/// Reads a (possibly schema qualified) name, returning its last part and the index after it.
+fn qualified_name(tokens: &[Token], mut i: usize) -> Option<(CompactString, usize)> {
+    let mut name = tokens.get(i)?.ident()?.to_owned();
+    i += 1;
+
+    while tokens.get(i) == Some(&Token::Symbol('.')) {
+        let Some(part) = tokens.get(i + 1).and_then(|token| token.ident()) else {
+            break;
+        };
+        name = part.to_owned();
+        i += 2;
+    }
+
+    Some((name, i))
+}
+
+/// Reads a parenthesized list of column names.
+fn column_list(tokens: &[Token], start: usize) -> Option<(CheapVec<CompactString, 0>, usize)> {
+    let (inner, next) = parenthesized(tokens, start)?;
+
+    let columns = split_top_level(inner, ',')
+        .into_iter()
+        .filter_map(|column| column.first().and_then(|token| token.ident()).cloned())
+        .collect();
+
+    Some((columns, next))
+}
+
+/// Skips `IF NOT EXISTS` / `IF EXISTS`.
+fn skip_if_exists(tokens: &[Token], mut i: usize) -> usize {
+    if tokens.get(i).is_some_and(|token| token.is("if")) {
+        i += 1;
+        if tokens.get(i).is_some_and(|token| token.is("not")) {
+            i += 1;
+        }
+        if tokens.get(i).is_some_and(|token| token.is("exists")) {
+            i += 1;
+        }
+    }
+    i
+}
+
+/// Parses `REFERENCES table (columns)` at the given position.
+fn references(
+    tokens: &[Token],
+    i: usize,
+    columns: CheapVec<CompactString, 0>,
+) -> Option<ForeignKeySchema> {
+    if !tokens.get(i)?.is("references") {
+        return None;
+    }
+
+    let (referenced_table, next) = qualified_name(tokens, i + 1)?;
+
+    let referenced_columns = column_list(tokens, next)
+        .map(|(columns, _)| columns)
+        .unwrap_or_default();
+
+    Some(ForeignKeySchema::new(
+        columns,
+        referenced_table,
+        referenced_columns,
+    ))
+}
+
+/// Whether the definition starting with a keyword is a table constraint rather than a column named
+/// as it (e.g. `key TEXT`), which depends on the following tokens and on whether the dialect reserves it.
+fn is_constraint(definition: &[Token], i: usize, dialect: SqlDialect) -> bool {
+    let Some(first) = definition.get(i) else {
+        return false;
+    };
+
+    let next = definition.get(i + 1);
+
+    let followed_by = |keyword: &str| next.is_some_and(|next| next.is(keyword));
+    let opens = next == Some(&Token::Symbol('('));
+
+    if first.is("primary") || first.is("foreign") {
+        followed_by("key")
+    } else if first.is("unique") {
+        opens || followed_by("key") || followed_by("index") || dialect == SqlDialect::MySQL
+    } else if first.is("check") {
+        opens
+    } else if ["key", "index", "fulltext", "spatial"]
+        .iter()
+        .any(|keyword| first.is(keyword))
+    {
+        // MySQL's indexes, the keywords are only reserved there.
+        dialect == SqlDialect::MySQL
+    } else if first.is("exclude") {
+        dialect == SqlDialect::Postgres && (opens || followed_by("using"))
+    } else if first.is("like") {
+        // `LIKE table [INCLUDING ...]`, SQLite doesn't support it.
+        dialect != SqlDialect::SQLite
+            && qualified_name(definition, i + 1).is_some_and(|(_, next)| {
+                definition
+                    .get(next)
+                    .is_none_or(|token| token.is("including") || token.is("excluding"))
+            })
+    } else {
+        false
+    }
+}
+
+/// Parses a table constraint (`PRIMARY KEY (...)`, `FOREIGN KEY (...) REFERENCES ...`), any other constraint is ignored.
+/// Returns `None` if the definition is not a constraint.
+fn table_constraint(
+    table: &mut TableSchema,
+    definition: &[Token],
+    dialect: SqlDialect,
+) -> Option<()> {
+    let mut i = 0;
+
+    if definition.first()?.is("constraint") {
+        i = 2;
+    } else if !is_constraint(definition, 0, dialect) {
+        return None;
+    }
+
+    let first = definition.get(i)?;
+
+    if first.is("primary") {
+        let (columns, _) = column_list(definition, i + 2)?;
+
+        for column in table.columns_mut() {
+            if columns.contains(column.name()) {
+                *column.primary_key_mut() = true;
+                *column.nullable_mut() = false;
+            }
+        }
+    } else if first.is("foreign") {
+        // Skips the optional index name of MySQL.
+        let start = (i + 2..definition.len()).find(|j| definition[*j] == Token::Symbol('('))?;
+
+        let (columns, next) = column_list(definition, start)?;
+
+        table
+            .foreign_keys_mut()
+            .push(references(definition, next, columns)?);
+    }
+
+    Some(())
+}
+
+/// Parses a column definition.
+fn column_definition(table: &mut TableSchema, definition: &[Token]) -> Option<()> {
+    const CONSTRAINTS: [&str; 16] = [
+        "not",
+        "null",
+        "primary",
+        "auto_increment",
+        "autoincrement",
+        "default",
+        "unique",
+        "references",
+        "generated",
+        "check",
+        "collate",
+        "comment",
+        "constraint",
+        "identity",
+        "charset",
+        "on",
+    ];
+
+    let name = definition.first()?.ident()?.to_owned();
+
+    // Collects the data type until the first constraint.
+    let mut data_type = CompactString::default();
+    let mut i = 1;
+
+    while let Some(token) = definition.get(i) {
+        if CONSTRAINTS.iter().any(|keyword| token.is(keyword))
+            || (token.is("character") && definition.get(i + 1).is_some_and(|next| next.is("set")))
+        {
+            break;
+        }
+
+        match token {
+            Token::Symbol('(') => {
+                let (_, next) = parenthesized(definition, i)?;
+                for token in &definition[i..next] {
+                    match token {
+                        Token::Word(word) | Token::Quoted(word) => {
+                            data_type.push_str(&word.to_lowercase())
+                        }
+                        Token::Symbol(c) => data_type.push(*c),
+                        Token::Literal => data_type.push_str("''"),
+                    }
+                }
+                i = next;
+                continue;
+            }
+            Token::Word(word) => {
+                if !data_type.is_empty() {
+                    data_type.push(' ');
+                }
+                data_type.push_str(&word.to_lowercase());
+            }
+            Token::Symbol(c) => data_type.push(*c),
+            _ => (),
+        }
+
+        i += 1;
+    }
+
+    let mut column = ColumnSchema::new(
+        name.to_owned(),
+        data_type.to_owned(),
+        true,
+        false,
+        data_type.ends_with("serial"),
+    );
+
+    // Checks the column constraints.
+    while let Some(token) = definition.get(i) {
+        if token.is("not") && definition.get(i + 1).is_some_and(|next| next.is("null")) {
+            *column.nullable_mut() = false;
+            i += 1;
+        } else if token.is("primary") {
+            *column.primary_key_mut() = true;
+            *column.nullable_mut() = false;
+        } else if token.is("auto_increment")
+            || token.is("autoincrement")
+            || token.is("identity")
+            || token.is("generated")
+        {
+            *column.auto_generated_mut() = true;
+        } else if token.is("references") {
+            if let Some(foreign_key) =
+                references(definition, i, CheapVec::from_vec(vec![name.to_owned()]))
+            {
+                table.foreign_keys_mut().push(foreign_key);
+            }
+        }
+
+        i += 1;
+    }
+
+    table.columns_mut().push(column);
+
+    Some(())
+}
+
+/// Parses the body of `CREATE TABLE` statements.
+fn create_table(
+    schema: &mut DataSchema,
+    tokens: &[Token],
+    i: usize,
+    dialect: SqlDialect,
+) -> Option<()> {
+    let i = skip_if_exists(tokens, i);
+
+    let (name, next) = qualified_name(tokens, i)?;
+
+    // `CREATE TABLE ... AS SELECT` and `CREATE TABLE ... LIKE` are not supported.
+    let (body, _) = parenthesized(tokens, next)?;
+
+    let mut table = TableSchema::new(name, false, CheapVec::new(), CheapVec::new());
+
+    for definition in split_top_level(body, ',') {
+        if table_constraint(&mut table, definition, dialect).is_none() {
+            column_definition(&mut table, definition);
+        }
+    }
+
+    schema
+        .tables_mut()
+        .retain(|existing| existing.name() != table.name());
+    schema.tables_mut().push(table);
+
+    Some(())
+}
+
+/// Parses `CREATE VIEW` statements, the view's columns are taken from the explicit column
+/// list or from the aliases of the select list (`*` is expanded when the source table is known).
+fn create_view(schema: &mut DataSchema, tokens: &[Token], i: usize) -> Option<()> {
+    let i = skip_if_exists(tokens, i);
+
+    let (name, mut next) = qualified_name(tokens, i)?;
+
+    let view_column = |name: CompactString| {
+        ColumnSchema::new(name, "unknown".to_compact_string(), true, false, false)
+    };
+
+    let mut columns = CheapVec::<ColumnSchema, 0>::new();
+
+    if let Some((names, after)) = column_list(tokens, next) {
+        columns.extend(names.into_iter().map(view_column));
+        next = after;
+    }
+
+    if columns.is_empty() {
+        let select = (next..tokens.len()).find(|j| tokens[*j].is("select"))?;
+
+        let mut depth = 0usize;
+        let from = (select..tokens.len()).find(|j| {
+            match tokens[*j] {
+                Token::Symbol('(') => depth += 1,
+                Token::Symbol(')') => depth = depth.saturating_sub(1),
+                _ => (),
+            }
+            depth == 0 && tokens[*j].is("from")
+        });
+
+        let select_list = &tokens[select + 1..from.unwrap_or(tokens.len())];
+        let select_list = match select_list.first() {
+            Some(token) if token.is("distinct") || token.is("all") => &select_list[1..],
+            _ => select_list,
+        };
+
+        for item in split_top_level(select_list, ',') {
+            if item.last() == Some(&Token::Symbol('*')) {
+                // Expands the columns of the source table.
+                let source = from
+                    .and_then(|from| qualified_name(tokens, from + 1))
+                    .and_then(|(source, _)| {
+                        schema
+                            .tables()
+                            .iter()
+                            .find(|table| *table.name() == source)
+                            .cloned()
+                    });
+
+                if let Some(source) = source {
+                    columns.extend(
+                        source
+                            .columns()
+                            .iter()
+                            .map(|column| view_column(column.name().to_owned())),
+                    );
+                }
+                continue;
+            }
+
+            let Some(alias) = item.last().and_then(|token| token.ident()) else {
+                continue;
+            };
+
+            columns.push(view_column(alias.to_owned()));
+        }
+    }
+
+    let view = TableSchema::new(name, true, columns, CheapVec::new());
+
+    schema
+        .tables_mut()
+        .retain(|existing| existing.name() != view.name());
+    schema.tables_mut().push(view);
+
+    Some(())
+}
+
+/// Parses the `ADD` clauses of `ALTER TABLE` statements.
+fn alter_table(
+    schema: &mut DataSchema,
+    tokens: &[Token],
+    i: usize,
+    dialect: SqlDialect,
+) -> Option<()> {
+    let i = if tokens.get(i)?.is("only") { i + 1 } else { i };
+    let i = skip_if_exists(tokens, i);
+
+    let (name, next) = qualified_name(tokens, i)?;
+
+    let table = schema
+        .tables_mut()
+        .iter_mut()
+        .find(|table| *table.name() == name)?;
+
+    for clause in split_top_level(&tokens[next..], ',') {
+        if clause.first().is_some_and(|token| token.is("add")) {
+            let definition = match clause.get(1) {
+                Some(token) if token.is("column") => &clause[2..],
+                _ => &clause[1..],
+            };
+
+            if table_constraint(table, definition, dialect).is_none() {
+                column_definition(table, definition);
+            }
+        }
+    }
+
+    Some(())
+}
+
+/// Parses the tables and views defined by the given SQL script.
+pub fn parse_ddl(sql: &str, dialect: SqlDialect) -> DataSchema {
+    let tokens = tokenize(sql, dialect);
+
+    let mut schema = DataSchema::default();
+
+    for statement in split_top_level(&tokens, ';') {
+        let Some(first) = statement.first() else {
+            continue;
+        };
+
+        if first.is("create") {
+            // Finds the kind of object being created, skipping modifiers as `OR REPLACE`, `TEMPORARY`, `DEFINER = ...`...
+            let kind = statement.iter().position(|token| {
+                [
+                    "table",
+                    "view",
+                    "index",
+                    "trigger",
+                    "function",
+                    "procedure",
+                    "sequence",
+                    "type",
+                    "schema",
+                    "database",
+                    "extension",
+                    "event",
+                ]
+                .iter()
+                .any(|keyword| token.is(keyword))
+            });
+
+            match kind {
+                Some(i) if statement[i].is("table") => {
+                    if create_table(&mut schema, statement, i + 1, dialect).is_none() {
+                        warn!("Cannot parse a `CREATE TABLE` statement, it will be skipped.");
+                    }
+                }
+                Some(i) if statement[i].is("view") => {
+                    if create_view(&mut schema, statement, i + 1).is_none() {
+                        warn!("Cannot parse a `CREATE VIEW` statement, it will be skipped.");
+                    }
+                }
+                _ => (),
+            }
+        } else if first.is("alter") && statement.get(1).is_some_and(|token| token.is("table")) {
+            alter_table(&mut schema, statement, 2, dialect);
+        }
+    }
+
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DDL: &str = r#"
+        -- Users of the application.
+        CREATE TABLE IF NOT EXISTS `users` (
+            `user_id` INT UNSIGNED NOT NULL AUTO_INCREMENT,
+            `email` VARCHAR(255) NOT NULL,
+            `name` VARCHAR(64) DEFAULT 'anonymous; user',
+            PRIMARY KEY (`user_id`),
+            UNIQUE KEY `email_idx` (`email`)
+        ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4;
+
+        /* Posts written by the users. */
+        CREATE TABLE public.posts (
+            id BIGSERIAL PRIMARY KEY,
+            author INT NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+            title TEXT,
+            created_at TIMESTAMP WITH TIME ZONE DEFAULT now()
+        );
+
+        CREATE TABLE tags (post_id BIGINT, tag VARCHAR(32));
+
+        ALTER TABLE ONLY tags ADD CONSTRAINT tags_post_fk FOREIGN KEY (post_id) REFERENCES posts(id);
+
+        CREATE OR REPLACE ALGORITHM=UNDEFINED DEFINER=`root`@`localhost` SQL SECURITY DEFINER VIEW `recent_posts` AS
+            SELECT p.id, p.title AS headline, u.email
+            FROM posts p JOIN users u ON u.user_id = p.author;
+
+        CREATE VIEW all_tags AS SELECT * FROM tags;
+
+        CREATE INDEX posts_title_idx ON posts (title);
+
+        INSERT INTO users (email) VALUES ('admin@example.com');
+    "#;
+
+    #[test]
+    fn parse_tables_and_views() {
+        let schema = parse_ddl(DDL, SqlDialect::MySQL);
+
+        let names = schema
+            .tables()
+            .iter()
+            .map(|table| table.name().as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            names,
+            vec!["users", "posts", "tags", "recent_posts", "all_tags"]
+        );
+
+        let users = &schema.tables()[0];
+        assert_eq!(users.primary_key().unwrap().name(), "user_id");
+        assert!(*users.primary_key().unwrap().auto_generated());
+        assert_eq!(users.columns().len(), 3);
+        assert_eq!(users.columns()[1].data_type(), "varchar(255)");
+        assert!(!*users.columns()[1].nullable());
+        assert!(*users.columns()[2].nullable());
+
+        let posts = &schema.tables()[1];
+        assert_eq!(posts.primary_key().unwrap().name(), "id");
+        assert!(*posts.primary_key().unwrap().auto_generated());
+        assert_eq!(posts.columns()[3].data_type(), "timestamp with time zone");
+        assert_eq!(
+            posts.foreign_keys()[0],
+            ForeignKeySchema::new(
+                CheapVec::from_vec(vec!["author".to_compact_string()]),
+                "users".to_compact_string(),
+                CheapVec::from_vec(vec!["user_id".to_compact_string()]),
+            )
+        );
+
+        let tags = &schema.tables()[2];
+        assert!(tags.primary_key().is_none());
+        assert_eq!(tags.foreign_keys()[0].referenced_table(), "posts");
+
+        let recent_posts = &schema.tables()[3];
+        assert!(*recent_posts.is_view());
+        assert_eq!(
+            recent_posts
+                .columns()
+                .iter()
+                .map(|column| column.name().as_str())
+                .collect::<Vec<_>>(),
+            vec!["id", "headline", "email"]
+        );
+
+        let all_tags = &schema.tables()[4];
+        assert_eq!(all_tags.columns().len(), 2);
+    }
+
+    fn column_names(table: &TableSchema) -> Vec<&str> {
+        table
+            .columns()
+            .iter()
+            .map(|column| column.name().as_str())
+            .collect()
+    }
+
+    #[test]
+    fn keywords_as_column_names() {
+        let schema = parse_ddl(
+            r#"
+            CREATE TABLE settings (
+                key TEXT PRIMARY KEY,
+                index INT NOT NULL,
+                check BOOLEAN,
+                unique INT,
+                CHECK (index >= 0),
+                UNIQUE (index)
+            );
+            "#,
+            SqlDialect::Postgres,
+        );
+
+        let settings = &schema.tables()[0];
+        assert_eq!(
+            column_names(settings),
+            vec!["key", "index", "check", "unique"]
+        );
+        assert_eq!(settings.primary_key().unwrap().name(), "key");
+
+        let schema = parse_ddl(
+            "CREATE TABLE words (like TEXT, key TEXT);",
+            SqlDialect::SQLite,
+        );
+        assert_eq!(column_names(&schema.tables()[0]), vec!["like", "key"]);
+
+        let schema = parse_ddl(
+            r#"
+            CREATE TABLE counters (
+                post_id INT NOT NULL,
+                likes INT,
+                KEY post_idx (post_id),
+                UNIQUE post_uq (post_id)
+            );
+            "#,
+            SqlDialect::MySQL,
+        );
+        assert_eq!(column_names(&schema.tables()[0]), vec!["post_id", "likes"]);
+    }
+
+    #[test]
+    fn dialect_specific_comments_and_quoting() {
+        let schema = parse_ddl(
+            r#"
+            # Counters of the posts; they're never deleted.
+            CREATE TABLE counters (post_id INT, likes INT);
+            "#,
+            SqlDialect::MySQL,
+        );
+        assert_eq!(column_names(&schema.tables()[0]), vec!["post_id", "likes"]);
+
+        let schema = parse_ddl(
+            r#"
+            CREATE TABLE flags (id INT, bits INT);
+
+            CREATE FUNCTION reset_flags() RETURNS void AS $body$
+                DELETE FROM flags; CREATE TABLE ghost (id INT); SELECT '$$';
+            $body$ LANGUAGE sql;
+
+            CREATE VIEW masks AS SELECT bits # 1 AS mask
+            FROM flags;
+            "#,
+            SqlDialect::Postgres,
+        );
+
+        let names = schema
+            .tables()
+            .iter()
+            .map(|table| table.name().as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["flags", "masks"]);
+        assert_eq!(column_names(&schema.tables()[1]), vec!["mask"]);
+    }
+}
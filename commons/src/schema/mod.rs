@@ -1,10 +1,78 @@
 // Waveless
 // Copyright (C) 2026 Oscar Alvarez Gonzalez
 
+pub mod ddl;
 pub mod mysql;
+pub mod postgres;
+pub mod sqlite;
 
 use crate::*;
 
 use build::*;
 
 use sqlx::{mysql::*, pool::*};
+
+/// Backend agnostic representation of a database's data model.
+/// Every `AnyDataSchemaDiscoveryMethod` translates its backend's schema into
+/// this model, which is the one consumed by the compiler's endpoint generator.
+#[derive(
+    Clone, PartialEq, Default, Constructor, Serialize, Deserialize, Getters, MutGetters, Debug,
+)]
+#[getset(get = "pub", get_mut = "pub")]
+pub struct DataSchema {
+    #[serde(default)]
+    tables: CheapVec<TableSchema, 0>,
+}
+
+/// A table (or view) of the data model.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, MutGetters, Debug)]
+#[getset(get = "pub", get_mut = "pub")]
+pub struct TableSchema {
+    name: CompactString,
+
+    /// Only the GET many endpoint will be generated for views.
+    #[serde(default)]
+    is_view: bool,
+
+    #[serde(default)]
+    columns: CheapVec<ColumnSchema, 0>,
+
+    #[serde(default)]
+    foreign_keys: CheapVec<ForeignKeySchema, 0>,
+}
+
+impl TableSchema {
+    /// Returns the table's primary key column (only the first one is returned on composite keys).
+    pub fn primary_key(&self) -> Option<&ColumnSchema> {
+        self.columns.iter().find(|column| column.primary_key)
+    }
+}
+
+/// A column of a table of the data model.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, MutGetters, Debug)]
+#[getset(get = "pub", get_mut = "pub")]
+pub struct ColumnSchema {
+    name: CompactString,
+
+    /// The column's data type as reported by the backend (lowercased).
+    data_type: CompactString,
+
+    #[serde(default)]
+    nullable: bool,
+
+    #[serde(default)]
+    primary_key: bool,
+
+    /// Whether the value is generated by the database (auto increment, generated columns...).
+    #[serde(default)]
+    auto_generated: bool,
+}
+
+/// A foreign key constraint of a table of the data model.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, MutGetters, Debug)]
+#[getset(get = "pub", get_mut = "pub")]
+pub struct ForeignKeySchema {
+    columns: CheapVec<CompactString, 0>,
+    referenced_table: CompactString,
+    referenced_columns: CheapVec<CompactString, 0>,
+}
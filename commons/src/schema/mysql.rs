@@ -6,8 +6,11 @@ use crate::*;
 use super::*;
 
 use databases::mysql::*;
+use execute::{mysql::*, *};
 use project::*;
 
+use sea_schema::mysql::def::ColumnKey;
+
 /// The MySQL discovery strategy will analyze a MySQL database in order to generate a representation of the data model that will be analyzed by the endpoint generator backend.
 #[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
 #[display("MySQL schema discovery (skipping: {:?})", skip_tables)]
@@ -62,8 +65,61 @@ impl AnyDataSchemaDiscoveryMethod for MySQLSchemaDiscoveryMethod {
         .discover()
         .await?;
 
+        // Translates MySQL's schema into the backend agnostic data model.
+        let data_schema = DataSchema::new(
+            schema
+                .tables
+                .iter()
+                .filter(|table| {
+                    !self
+                        .skip_tables
+                        .contains(&table.info.name.to_compact_string())
+                })
+                .map(|table| {
+                    TableSchema::new(
+                        table.info.name.to_compact_string(),
+                        table.info.comment.to_lowercase().eq("view"),
+                        table
+                            .columns
+                            .iter()
+                            .map(|column| {
+                                ColumnSchema::new(
+                                    column.name.to_compact_string(),
+                                    format!("{:?}", column.col_type)
+                                        .to_lowercase()
+                                        .to_compact_string(),
+                                    column.null,
+                                    column.key == ColumnKey::Primary,
+                                    column.extra.auto_increment || column.extra.generated,
+                                )
+                            })
+                            .collect(),
+                        table
+                            .foreign_keys
+                            .iter()
+                            .map(|foreign_key| {
+                                ForeignKeySchema::new(
+                                    foreign_key
+                                        .columns
+                                        .iter()
+                                        .map(|column| column.to_compact_string())
+                                        .collect(),
+                                    foreign_key.referenced_table.to_compact_string(),
+                                    foreign_key
+                                        .referenced_columns
+                                        .iter()
+                                        .map(|column| column.to_compact_string())
+                                        .collect(),
+                                )
+                            })
+                            .collect(),
+                    )
+                })
+                .collect(),
+        );
+
         Ok((
-            Box::new(schema.to_owned()),
+            Box::new(data_schema),
             DatabaseChecksum::new(
                 db_id,
                 CheapVec::from_slice(
@@ -72,4 +128,8 @@ impl AnyDataSchemaDiscoveryMethod for MySQLSchemaDiscoveryMethod {
             ),
         ))
     }
+
+    fn executor(&self, query: CompactString) -> Arc<dyn AnyExecute> {
+        Arc::new(MySQLExecute::new(query))
+    }
 }
@@ -0,0 +1,212 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+use crate::*;
+
+use super::*;
+
+use databases::postgres::*;
+use execute::{postgres::*, *};
+use project::*;
+
+use sqlx::{Row, postgres::*};
+
+/// The PostgreSQL discovery strategy analyzes the connection's schema (namespace) through sea-schema
+/// (tables, columns, primary and foreign keys, enums) and completes it with the views and the columns'
+/// SQL types read from the catalog.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display("PostgreSQL schema discovery (skipping: {:?})", skip_tables)]
+#[getset(get = "pub")]
+pub struct PostgresSchemaDiscoveryMethod {
+    #[serde(default, skip_serializing_if = "should_skip_cheapvec")]
+    skip_tables: CheapVec<CompactString, 0>,
+}
+
+boxed_any!(PostgresSchemaDiscoveryMethod);
+
+#[typetag::serde(name = "Postgres")]
+#[async_trait]
+impl AnyDataSchemaDiscoveryMethod for PostgresSchemaDiscoveryMethod {
+    async fn schema(
+        &self,
+        db_id: CompactString,
+        db_conn_config: Arc<dyn AnyDatabaseConnectionConfig>,
+    ) -> Result<(Box<dyn Any>, DatabaseChecksum)> {
+        let Ok(db_conn_config) = db_conn_config
+            .to_owned()
+            .into_arc_any()
+            .downcast::<PostgresDBConnectionConfig>()
+        else {
+            bail!(
+                "Database connection config should be of type {:?} but it's of type {:?}.",
+                TypeId::of::<PostgresDBConnectionConfig>(),
+                db_conn_config.inner_type_id()
+            )
+        };
+
+        let (_, raw_conn) = db_conn_config
+            .new_conn(
+                "postgres_discovery_connection".to_compact_string(),
+                Some(1),
+                Some(1),
+            )
+            .await?;
+
+        let Ok(postgres_raw_pool) = raw_conn.downcast::<Pool<Postgres>>() else {
+            bail!(
+                "Database connection raw pool should be of type {:?}.",
+                TypeId::of::<Pool<Postgres>>(),
+            )
+        };
+
+        let namespace = db_conn_config.schema().as_deref().unwrap_or("public");
+
+        let discovery = sea_schema::postgres::discovery::SchemaDiscovery::new(
+            (*postgres_raw_pool).to_owned(),
+            namespace,
+        );
+
+        let schema = discovery.discover().await?;
+        let enums = discovery.discover_enums().await?;
+
+        // Columns of all the tables and views with their SQL types (as they would be written in a cast).
+        let relations = sqlx::query(
+            r#"
+            SELECT c.relname AS relation, c.relkind::text AS kind, a.attname AS column_name,
+                format_type(a.atttypid, a.atttypmod) AS data_type, a.attnotnull AS not_null
+            FROM pg_catalog.pg_attribute a
+            JOIN pg_catalog.pg_class c ON c.oid = a.attrelid
+            JOIN pg_catalog.pg_namespace n ON n.oid = c.relnamespace
+            WHERE n.nspname = $1 AND c.relkind IN ('r', 'p', 'v', 'm') AND a.attnum > 0 AND NOT a.attisdropped
+            ORDER BY c.relname, a.attnum
+            "#,
+        )
+        .bind(namespace)
+        .fetch_all(&*postgres_raw_pool)
+        .await?;
+
+        let column_type = |relation: &str, column_name: &str| {
+            relations
+                .iter()
+                .find(|row| {
+                    row.try_get::<String, _>("relation")
+                        .is_ok_and(|name| name == relation)
+                        && row
+                            .try_get::<String, _>("column_name")
+                            .is_ok_and(|name| name == column_name)
+                })
+                .and_then(|row| row.try_get::<String, _>("data_type").ok())
+                .unwrap_or("text".to_string())
+                .to_compact_string()
+        };
+
+        // Translates PostgreSQL's schema into the backend agnostic data model.
+        let mut tables = schema
+            .tables
+            .iter()
+            .filter(|table| {
+                !self
+                    .skip_tables
+                    .contains(&table.info.name.to_compact_string())
+            })
+            .map(|table| {
+                let primary_key = table
+                    .primary_key_constraints
+                    .iter()
+                    .flat_map(|primary_key| primary_key.columns.iter())
+                    .collect::<CheapVec<&String>>();
+
+                TableSchema::new(
+                    table.info.name.to_compact_string(),
+                    false,
+                    table
+                        .columns
+                        .iter()
+                        .map(|column| {
+                            let is_primary_key = primary_key.contains(&&column.name);
+
+                            ColumnSchema::new(
+                                column.name.to_compact_string(),
+                                column_type(&table.info.name, &column.name),
+                                column.not_null.is_none() && !is_primary_key,
+                                is_primary_key,
+                                column.is_identity
+                                    || column.generated.is_some()
+                                    || format!("{:?}", column.default).contains("nextval("),
+                            )
+                        })
+                        .collect(),
+                    table
+                        .reference_constraints
+                        .iter()
+                        .map(|foreign_key| {
+                            ForeignKeySchema::new(
+                                foreign_key
+                                    .columns
+                                    .iter()
+                                    .map(|column| column.to_compact_string())
+                                    .collect(),
+                                foreign_key.table.to_compact_string(),
+                                foreign_key
+                                    .foreign_columns
+                                    .iter()
+                                    .map(|column| column.to_compact_string())
+                                    .collect(),
+                            )
+                        })
+                        .collect(),
+                )
+            })
+            .collect::<CheapVec<TableSchema, 0>>();
+
+        // Views (and materialized views) aren't analyzed by sea-schema.
+        for row in &relations {
+            let kind = row.try_get::<String, _>("kind")?;
+
+            if kind != "v" && kind != "m" {
+                continue;
+            }
+
+            let relation = row.try_get::<String, _>("relation")?.to_compact_string();
+
+            if self.skip_tables.contains(&relation) {
+                continue;
+            }
+
+            let column = ColumnSchema::new(
+                row.try_get::<String, _>("column_name")?.to_compact_string(),
+                row.try_get::<String, _>("data_type")?.to_compact_string(),
+                !row.try_get::<bool, _>("not_null")?,
+                false,
+                false,
+            );
+
+            match tables.iter_mut().find(|table| *table.name() == relation) {
+                Some(view) => view.columns_mut().push(column),
+                None => tables.push(TableSchema::new(
+                    relation,
+                    true,
+                    CheapVec::from_vec(vec![column]),
+                    CheapVec::new(),
+                )),
+            }
+        }
+
+        let data_schema = DataSchema::new(tables);
+
+        // The data schema is hashed instead of sea-schema's one, as the latter doesn't include the views.
+        let checksum = DatabaseChecksum::new(
+            db_id,
+            CheapVec::from_slice(
+                &crc32fast::hash(format!("{:?}{:?}", data_schema, enums).as_str().as_bytes())
+                    .to_ne_bytes(),
+            ),
+        );
+
+        Ok((Box::new(data_schema), checksum))
+    }
+
+    fn executor(&self, query: CompactString) -> Arc<dyn AnyExecute> {
+        Arc::new(PostgresExecute::new(query))
+    }
+}
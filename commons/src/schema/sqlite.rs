@@ -0,0 +1,186 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+use crate::*;
+
+use super::*;
+
+use databases::sqlite::*;
+use execute::{sqlite::*, *};
+use project::*;
+
+use sqlx::{Row, sqlite::*};
+
+/// The SQLite discovery strategy reads the database's catalog (`sqlite_master` and the `table_xinfo`
+/// and `foreign_key_list` pragmas) in order to generate the representation of the data model.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Display, Debug)]
+#[display("SQLite schema discovery (skipping: {:?})", skip_tables)]
+#[getset(get = "pub")]
+pub struct SQLiteSchemaDiscoveryMethod {
+    #[serde(default, skip_serializing_if = "should_skip_cheapvec")]
+    skip_tables: CheapVec<CompactString, 0>,
+}
+
+boxed_any!(SQLiteSchemaDiscoveryMethod);
+
+#[typetag::serde(name = "SQLite")]
+#[async_trait]
+impl AnyDataSchemaDiscoveryMethod for SQLiteSchemaDiscoveryMethod {
+    async fn schema(
+        &self,
+        db_id: CompactString,
+        db_conn_config: Arc<dyn AnyDatabaseConnectionConfig>,
+    ) -> Result<(Box<dyn Any>, DatabaseChecksum)> {
+        let Ok(db_conn_config) = db_conn_config
+            .to_owned()
+            .into_arc_any()
+            .downcast::<SQLiteDBConnectionConfig>()
+        else {
+            bail!(
+                "Database connection config should be of type {:?} but it's of type {:?}.",
+                TypeId::of::<SQLiteDBConnectionConfig>(),
+                db_conn_config.inner_type_id()
+            )
+        };
+
+        let (_, raw_conn) = db_conn_config
+            .new_conn(
+                "sqlite_discovery_connection".to_compact_string(),
+                Some(1),
+                Some(1),
+            )
+            .await?;
+
+        let Ok(sqlite_raw_pool) = raw_conn.downcast::<Pool<Sqlite>>() else {
+            bail!(
+                "Database connection raw pool should be of type {:?}.",
+                TypeId::of::<Pool<Sqlite>>(),
+            )
+        };
+
+        let schema = discover(&sqlite_raw_pool).await?;
+
+        let data_schema = DataSchema::new(
+            schema
+                .tables()
+                .iter()
+                .filter(|table| !self.skip_tables.contains(table.name()))
+                .cloned()
+                .collect(),
+        );
+
+        Ok((
+            Box::new(data_schema),
+            DatabaseChecksum::new(
+                db_id,
+                CheapVec::from_slice(
+                    &crc32fast::hash(format!("{:?}", schema).as_str().as_bytes()).to_ne_bytes(),
+                ),
+            ),
+        ))
+    }
+
+    fn executor(&self, query: CompactString) -> Arc<dyn AnyExecute> {
+        Arc::new(SQLiteExecute::new(query))
+    }
+}
+
+/// Reads all the tables and views of the given SQLite database.
+pub async fn discover(pool: &Pool<Sqlite>) -> Result<DataSchema> {
+    let mut tables = CheapVec::<TableSchema, 0>::new();
+
+    let objects = sqlx::query(
+        "SELECT name, type FROM sqlite_master WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for object in objects {
+        let name = object.try_get::<String, _>("name")?;
+        let is_view = object.try_get::<String, _>("type")? == "view";
+
+        let columns_info = sqlx::query("SELECT * FROM pragma_table_xinfo(?)")
+            .bind(&name)
+            .fetch_all(pool)
+            .await?;
+
+        // A single `INTEGER PRIMARY KEY` column is an alias of the `rowid`, so its value is generated.
+        let pk_count = columns_info
+            .iter()
+            .filter(|column| column.try_get::<i64, _>("pk").unwrap_or(0) > 0)
+            .count();
+
+        let mut columns = CheapVec::<ColumnSchema, 0>::new();
+
+        for column in columns_info {
+            let data_type = column.try_get::<String, _>("type")?.to_lowercase();
+            let primary_key = column.try_get::<i64, _>("pk")? > 0;
+
+            // Hidden columns: 1 → virtual table's hidden column, 2 and 3 → generated columns.
+            let hidden = column.try_get::<i64, _>("hidden")?;
+
+            if hidden == 1 {
+                continue;
+            }
+
+            columns.push(ColumnSchema::new(
+                column.try_get::<String, _>("name")?.to_compact_string(),
+                data_type.to_compact_string(),
+                column.try_get::<i64, _>("notnull")? == 0 && !primary_key,
+                primary_key,
+                hidden > 1 || (primary_key && pk_count == 1 && data_type == "integer"),
+            ));
+        }
+
+        let mut foreign_keys = CheapVec::<ForeignKeySchema, 0>::new();
+
+        if !is_view {
+            let foreign_keys_info =
+                sqlx::query("SELECT * FROM pragma_foreign_key_list(?) ORDER BY id, seq")
+                    .bind(&name)
+                    .fetch_all(pool)
+                    .await?;
+
+            // Composite foreign keys are returned as one row per column sharing the same id.
+            let mut last_id = None;
+
+            for foreign_key in foreign_keys_info {
+                let id = foreign_key.try_get::<i64, _>("id")?;
+                let from = foreign_key
+                    .try_get::<String, _>("from")?
+                    .to_compact_string();
+                let to = foreign_key
+                    .try_get::<Option<String>, _>("to")?
+                    .map(|to| to.to_compact_string());
+
+                if last_id != Some(id) {
+                    foreign_keys.push(ForeignKeySchema::new(
+                        CheapVec::new(),
+                        foreign_key
+                            .try_get::<String, _>("table")?
+                            .to_compact_string(),
+                        CheapVec::new(),
+                    ));
+                    last_id = Some(id);
+                }
+
+                let current = foreign_keys.last_mut().unwrap();
+                current.columns_mut().push(from);
+
+                // A missing referenced column means that the referenced table's primary key is used.
+                if let Some(to) = to {
+                    current.referenced_columns_mut().push(to);
+                }
+            }
+        }
+
+        tables.push(TableSchema::new(
+            name.to_compact_string(),
+            is_view,
+            columns,
+            foreign_keys,
+        ));
+    }
+
+    Ok(DataSchema::new(tables))
+}
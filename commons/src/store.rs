@@ -0,0 +1,297 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+//!
+//! Embedded key-value store backed by redb, meant for single-node deployments which don't want
+//! to keep short-lived data (like sessions or cached query results) on their databases.
+//! Keys and values are encoded through the `entry::Key` and `entry::Value` traits, so each table
+//! is typed while being stored as raw bytes.
+//!
+
+use crate::*;
+
+use entry::*;
+
+use dashmap::mapref::entry::Entry;
+use redb::{Database, ReadableDatabase, ReadableTable, TableDefinition, TableError};
+
+use std::marker::PhantomData;
+use std::sync::LazyLock;
+
+/// Opened store files, as each one can only be opened once by the process.
+static STORE_FILES: LazyLock<DashMap<PathBuf, Arc<Database>>> = LazyLock::new(DashMap::new);
+
+/// A typed table on an embedded store file.
+#[derive(Clone)]
+pub struct EmbeddedStore<K: Key, V: Value> {
+    db: Arc<Database>,
+    table: CompactString,
+    _entry: PhantomData<(K, V)>,
+}
+
+impl<K: Key, V: Value> EmbeddedStore<K, V> {
+    /// Opens (or creates) the table on the store file, relative to the project's workspace.
+    pub fn open(path: &str, table: &str) -> Result<Self> {
+        let path = get_workspace_root("project.toml")
+            .unwrap_or(current_dir()?)
+            .join(path);
+
+        // The entry is locked while the file is created, so it cannot be opened twice.
+        let db = match STORE_FILES.entry(path) {
+            Entry::Occupied(entry) => entry.get().to_owned(),
+            Entry::Vacant(entry) => {
+                let db = Arc::new(Database::create(entry.key()).map_err(|err| {
+                    anyhow!("Cannot open the store at {:?}. {}", entry.key(), err)
+                })?);
+
+                entry.insert(db).to_owned()
+            }
+        };
+
+        Ok(Self {
+            db,
+            table: table.to_compact_string(),
+            _entry: PhantomData,
+        })
+    }
+
+    fn definition(table: &str) -> TableDefinition<'_, &'static [u8], &'static [u8]> {
+        TableDefinition::new(table)
+    }
+
+    /// Gets the entry's value.
+    pub async fn get(&self, key: K) -> Result<Option<V>> {
+        let (db, table_name) = (self.db.to_owned(), self.table.to_owned());
+
+        tokio::task::spawn_blocking(move || -> Result<Option<V>> {
+            let txn = db.begin_read()?;
+
+            // Tables are created on their first write.
+            let table = match txn.open_table(Self::definition(&table_name)) {
+                Ok(table) => table,
+                Err(TableError::TableDoesNotExist(_)) => return Ok(None),
+                Err(err) => return Err(err.into()),
+            };
+
+            match table.get(key.collect().as_ref())? {
+                Some(value) => Ok(Some(V::decode(value.value())?)),
+                None => Ok(None),
+            }
+        })
+        .await?
+    }
+
+    /// Inserts (or replaces) the entry.
+    pub async fn insert(&self, key: K, value: V) -> Result<()> {
+        let (db, table_name) = (self.db.to_owned(), self.table.to_owned());
+
+        tokio::task::spawn_blocking(move || -> Result<()> {
+            let value = value.encode()?;
+
+            let txn = db.begin_write()?;
+            txn.open_table(Self::definition(&table_name))?
+                .insert(key.collect().as_ref(), value.as_slice())?;
+            txn.commit()?;
+
+            Ok(())
+        })
+        .await?
+    }
+
+    /// Removes the entry, returning whether it existed.
+    pub async fn remove(&self, key: K) -> Result<bool> {
+        let (db, table_name) = (self.db.to_owned(), self.table.to_owned());
+
+        tokio::task::spawn_blocking(move || -> Result<bool> {
+            let txn = db.begin_write()?;
+            let removed = txn
+                .open_table(Self::definition(&table_name))?
+                .remove(key.collect().as_ref())?
+                .is_some();
+            txn.commit()?;
+
+            Ok(removed)
+        })
+        .await?
+    }
+
+    /// Updates the entries within a single transaction, returning their new values.
+    /// NOTE: the current value is given to `update`, or `None` if the entry doesn't exist.
+    pub async fn update(
+        &self,
+        keys: Vec<K>,
+        update: impl Fn(&K, Option<V>) -> V + Send + 'static,
+    ) -> Result<Vec<V>> {
+        let (db, table_name) = (self.db.to_owned(), self.table.to_owned());
+
+        tokio::task::spawn_blocking(move || -> Result<Vec<V>> {
+            let txn = db.begin_write()?;
+            let mut values = Vec::with_capacity(keys.len());
+
+            {
+                let mut table = txn.open_table(Self::definition(&table_name))?;
+
+                for key in keys {
+                    let current = match table.get(key.collect().as_ref())? {
+                        Some(value) => Some(V::decode(value.value())?),
+                        None => None,
+                    };
+
+                    let value = update(&key, current);
+
+                    table.insert(key.collect().as_ref(), value.encode()?.as_slice())?;
+
+                    values.push(value);
+                }
+            }
+
+            txn.commit()?;
+
+            Ok(values)
+        })
+        .await?
+    }
+
+    /// Gets the entries which match the predicate.
+    /// NOTE: it scans the whole table, entries which cannot be decoded are skipped.
+    pub async fn entries(
+        &self,
+        predicate: impl Fn(&K, &V) -> bool + Send + 'static,
+    ) -> Result<Vec<(K, V)>> {
+        let (db, table_name) = (self.db.to_owned(), self.table.to_owned());
+
+        tokio::task::spawn_blocking(move || -> Result<Vec<(K, V)>> {
+            let txn = db.begin_read()?;
+
+            // Tables are created on their first write.
+            let table = match txn.open_table(Self::definition(&table_name)) {
+                Ok(table) => table,
+                Err(TableError::TableDoesNotExist(_)) => return Ok(Vec::new()),
+                Err(err) => return Err(err.into()),
+            };
+
+            let mut entries = Vec::new();
+
+            for entry in table.iter()? {
+                let (key, value) = entry?;
+
+                if let (Ok(key), Ok(value)) = (K::into_key(key.value()), V::decode(value.value())) {
+                    if predicate(&key, &value) {
+                        entries.push((key, value));
+                    }
+                }
+            }
+
+            Ok(entries)
+        })
+        .await?
+    }
+
+    /// Removes the entries which don't match the predicate, returning how many were removed.
+    /// NOTE: it scans the whole table, entries which cannot be decoded are removed too.
+    pub async fn retain(&self, predicate: impl Fn(K, V) -> bool + Send + 'static) -> Result<usize> {
+        let (db, table_name) = (self.db.to_owned(), self.table.to_owned());
+
+        tokio::task::spawn_blocking(move || -> Result<usize> {
+            let txn = db.begin_write()?;
+            let mut removed = 0;
+
+            txn.open_table(Self::definition(&table_name))?
+                .retain(|key, value| {
+                    let keep = match (K::into_key(key), V::decode(value)) {
+                        (Ok(key), Ok(value)) => predicate(key, value),
+                        _ => false,
+                    };
+
+                    if !keep {
+                        removed += 1;
+                    }
+
+                    keep
+                })?;
+
+            txn.commit()?;
+
+            Ok(removed)
+        })
+        .await?
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn typed_entries() -> Result<()> {
+        let path = std::env::temp_dir().join(format!(
+            "waveless_store_{}.redb",
+            Alphanumeric.sample_string(&mut rand::rng(), 8)
+        ));
+
+        let store =
+            EmbeddedStore::<u64, CompactString>::open(path.to_str().unwrap(), "store_test")?;
+
+        assert_eq!(store.get(1).await?, None);
+
+        for key in 1..=4 {
+            store.insert(key, key.to_compact_string()).await?;
+        }
+
+        assert_eq!(store.get(2).await?, Some("2".to_compact_string()));
+        assert!(store.remove(2).await?);
+        assert!(!store.remove(2).await?);
+
+        // The same file is shared by the tables opened on it.
+        let other =
+            EmbeddedStore::<u64, CompactString>::open(path.to_str().unwrap(), "store_test")?;
+
+        assert_eq!(other.retain(|key, _| key % 2 == 0).await?, 2);
+        assert_eq!(store.get(4).await?, Some("4".to_compact_string()));
+        assert_eq!(store.get(3).await?, None);
+
+        std::fs::remove_file(path)?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn batched_updates() -> Result<()> {
+        #[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+        struct Counter(u64);
+
+        let path = std::env::temp_dir().join(format!(
+            "waveless_store_{}.redb",
+            Alphanumeric.sample_string(&mut rand::rng(), 8)
+        ));
+
+        let store =
+            EmbeddedStore::<CompactString, Counter>::open(path.to_str().unwrap(), "counters_test")?;
+
+        let keys = || vec!["a".to_compact_string(), "a/b".to_compact_string()];
+
+        assert!(store.entries(|_, _| true).await?.is_empty());
+
+        store
+            .update(keys(), |_, counter| {
+                Counter(counter.map_or(0, |counter| counter.0) + 1)
+            })
+            .await?;
+
+        let counters = store
+            .update(keys(), |key, counter| {
+                Counter(counter.map_or(0, |counter| counter.0) + key.len() as u64)
+            })
+            .await?;
+
+        assert_eq!(counters, vec![Counter(2), Counter(4)]);
+
+        let entries = store.entries(|key, _| key.starts_with("a/")).await?;
+
+        assert_eq!(entries, vec![("a/b".to_compact_string(), Counter(4))]);
+
+        std::fs::remove_file(path)?;
+
+        Ok(())
+    }
+}
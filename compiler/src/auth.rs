@@ -0,0 +1,49 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+//!
+//! One-off maintenance tasks on the project's authentication data.
+//!
+
+use crate::*;
+
+use databases::*;
+
+/// Hashes the passwords still stored in plaintext by all the project's authentication methods.
+#[instrument(skip_all)]
+pub async fn hash_passwords() -> Result<ResultContext> {
+    let cx = CompilerCx::acquire();
+
+    let config = cx.project().config();
+
+    let Some(authentication) = config.authentication() else {
+        bail!("Authentication is not set for the current project.")
+    };
+
+    DatabasesConnections::load(config.databases().to_owned()).await?;
+
+    let databases = DATABASES_CONNS.get().unwrap();
+
+    let mut hashed = 0;
+
+    for auth_method in authentication.backends() {
+        let auth_db = databases.search(auth_method.db_id()).map_err(|_| {
+            anyhow!(
+                "Cannot get the database connection for '{}'.",
+                auth_method.db_id().unwrap_or("main".to_compact_string())
+            )
+        })?;
+
+        let count = auth_method.hash_plaintext_passwords(auth_db).await?;
+
+        info!(
+            "{} plaintext passwords have been hashed by '{}'.",
+            count,
+            auth_method.name()
+        );
+
+        hashed += count;
+    }
+
+    Ok(format!("{} plaintext passwords have been hashed.", hashed).to_compact_string())
+}
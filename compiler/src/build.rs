@@ -63,7 +63,13 @@ pub async fn build<T: 'static>() -> Result<Either<ExecutorBuild, Bytes>> {
     }
 
     // Discovers the endpoints and checksums the database's schema.
-    let (db_endpoints, db_checksums) = discovery::discover().await?;
+    let (db_endpoints, db_checksums) = match cx.discovery_mode() {
+        DiscoveryMode::Skip => {
+            info!("Skipping endpoint discovery, only user-defined endpoints will be included.");
+            (CheapVec::new(), CheapVec::new())
+        }
+        _ => discovery::discover().await?,
+    };
 
     if create_dir(workspace_root.join(".discovered_endpoints")).is_ok() {
         debug!("'.discovered_endpoints' directory does't exist, a new one will be created.")
@@ -3,11 +3,26 @@
 
 use crate::*;
 
+/// Defines how the databases' schema is obtained during the build.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub enum DiscoveryMode {
+    /// Connects to the databases to discover their schema, refreshing the workspace's snapshots.
+    #[default]
+    Online,
+
+    /// Reuses the last schema snapshots stored in the workspace, no database connection is made.
+    Offline,
+
+    /// Skips the discovery, only user-defined endpoints will be included.
+    Skip,
+}
+
 #[derive(Constructor, Getters, Debug)]
 #[getset(get = "pub")]
 pub struct CompilerCx {
     project: project::Project,
     workspace_root: PathBuf,
+    discovery_mode: DiscoveryMode,
 }
 
 impl CompilerCx {
@@ -30,12 +45,12 @@ impl CompilerCx {
 
     /// Builds the compiler's context by loading the project
     /// from the workspace's root.
-    pub async fn from_workspace() -> Result<Self> {
+    pub async fn from_workspace(discovery_mode: DiscoveryMode) -> Result<Self> {
         let workspace_root = get_workspace_root("project.toml")?;
 
         match read(workspace_root.join("project.toml")) {
             Ok(file_buffer) => match toml::from_slice::<project::Project>(&file_buffer) {
-                Ok(project) => Ok(Self::new(project, workspace_root)),
+                Ok(project) => Ok(Self::new(project, workspace_root, discovery_mode)),
                 Err(err) => Err(anyhow!(
                     "Cannot deserialize the `project.toml` file.%{}",
                     err.to_string()
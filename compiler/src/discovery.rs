@@ -12,9 +12,73 @@
 //!
 use crate::*;
 
-use schema::mysql::MySQLSchemaDiscoveryMethod;
+use project::*;
+use schema::{ColumnSchema, DataSchema};
+use snapshot::*;
 
-use sea_schema::mysql::def::Schema;
+use std::any::Any;
+
+/// Discovers the database's schema and, if it's a data schema, stores its snapshot in the workspace.
+pub async fn pull_schema(
+    db_config: &DatabaseConfig,
+    schema_discovery: &DataSchemaDiscoveryConfig,
+) -> Result<(Box<dyn Any>, DatabaseChecksum)> {
+    let (schema, checksum) = schema_discovery
+        .method()
+        .schema(db_config.id().to_owned(), db_config.connection().to_owned())
+        .await?;
+
+    if let Some(data_schema) = schema.downcast_ref::<DataSchema>() {
+        let target_file =
+            SchemaSnapshot::new(Utc::now(), checksum.to_owned(), data_schema.to_owned()).store()?;
+
+        debug!(
+            "The schema snapshot of '{}' was stored into '{}'.",
+            db_config.id(),
+            target_file.display()
+        );
+    }
+
+    Ok((schema, checksum))
+}
+
+/// Refreshes the schema snapshots of all the project's databases with a schema discovery method.
+#[instrument(skip_all)]
+pub async fn pull_schemas() -> Result<ResultContext> {
+    let cx = CompilerCx::acquire();
+
+    let mut pulled = CheapVec::<CompactString, 0>::new();
+
+    for db_config in cx.project().config().databases() {
+        let Some(schema_discovery) = db_config.schema_discovery() else {
+            continue;
+        };
+
+        pull_schema(db_config, schema_discovery).await?;
+
+        pulled.push(db_config.id().to_owned());
+    }
+
+    Ok(format!("Schema snapshots of {:?} have been refreshed.", pulled).to_compact_string())
+}
+
+/// Whether the table's endpoints require auth: owned rows are filtered by their user, while the
+/// permissions are checked against the session's roles.
+fn table_requires_auth(is_owned: bool, roles_defined: bool) -> bool {
+    is_owned || roles_defined
+}
+
+/// Default permission of the table's operation, e.g. `orders:write`.
+/// NOTE: permissions are only granted by the roles' definitions, so none is required without them.
+fn table_permissions(table: &str, action: &str, roles_defined: bool) -> CheapVec<CompactString, 0> {
+    if roles_defined {
+        CheapVec::from_vec(vec![
+            format!("{}:{}", table.to_lowercase(), action).to_compact_string(),
+        ])
+    } else {
+        CheapVec::new_const()
+    }
+}
 
 /// Discovers all endpoints from the project's database and calculate the checksum per database.
 /// TODO: Maybe the endpoint generation logic should be delegated to the `AnyDataSchemaDiscoveryMethod` trait.
@@ -38,10 +102,25 @@ pub async fn discover() -> Result<(
         };
 
         // Load the schema.
-        let (schema, checksum) = schema_discovery
-            .method()
-            .schema(db_config.id().to_owned(), db_config.connection().to_owned())
-            .await?;
+        let (schema, checksum) = match cx.discovery_mode() {
+            DiscoveryMode::Offline => {
+                let snapshot = SchemaSnapshot::load(db_config.id())?;
+
+                snapshot.check_age();
+
+                info!(
+                    "Using the schema snapshot of '{}' taken at {}.",
+                    db_config.id(),
+                    snapshot.taken_at()
+                );
+
+                (
+                    Box::new(snapshot.schema().to_owned()) as Box<dyn Any>,
+                    snapshot.checksum().to_owned(),
+                )
+            }
+            _ => pull_schema(db_config, schema_discovery).await?,
+        };
 
         // Check if checksum for the current db has to be computed.
         if *schema_discovery.checksum() {
@@ -50,157 +129,234 @@ pub async fn discover() -> Result<(
 
         // Discover endpoints from the schema.
         if *schema_discovery.generate_endpoints() {
-            // TODO: each discovery method should have it's generic endpoint generation.
-            if let Some(mysql_discovery) = schema_discovery
-                .method()
-                .to_owned()
-                .into_arc_any()
-                .downcast_ref::<MySQLSchemaDiscoveryMethod>()
-            {
-                let Ok(mysql_schema) = schema.downcast::<Schema>() else {
-                    bail!("Cannot downcast to MySQL schema.")
-                };
+            let Ok(data_schema) = schema.downcast::<DataSchema>() else {
+                bail!(
+                    "Cannot generate endpoints for '{}', its discovery method doesn't produce a data schema.",
+                    db_config.id()
+                )
+            };
+
+            let mut discovered_endpoints = Endpoints::new_unchecked(CheapVec::new_const());
 
-                let mut discovered_endpoints = Endpoints::new_unchecked(CheapVec::new_const());
+            // For each table generate a GET one, GET many, POST, UPDATE and DELETE endpoints.
+            for table in data_schema.tables() {
+                // Check whether the table is a view, only the GET many endpoint will be generated.
+                let is_view = *table.is_view();
+
+                // Get the table primary key. If it is not present only the GET one and POST endpoints will generated.
+                let pk_id = table.primary_key().map(|column| column.name().to_owned());
+
+                if pk_id.is_none() {
+                    debug!(
+                        "Table {} doesn't have a primary key. Only GET many and POST endpoints will be generated.",
+                        table.name()
+                    )
+                }
 
-                // For each table generate a GET one, GET many, POST, UPDATE and DELETE endpoints.
-                for table in mysql_schema.tables {
-                    if mysql_discovery
-                        .skip_tables()
-                        .contains(&table.info.name.to_compact_string())
+                // The table's owner column, if it has an ownership policy.
+                let owner_column = schema_discovery
+                    .ownership()
+                    .iter()
+                    .find(|policy| policy.table().eq_ignore_ascii_case(table.name()))
+                    .map(|policy| policy.owner_column().to_owned());
+
+                if let Some(owner_column) = &owner_column {
+                    if !table
+                        .columns()
+                        .iter()
+                        .any(|column| column.name() == owner_column)
                     {
-                        continue;
+                        bail!(
+                            "Cannot apply the ownership policy of '{}', column '{}' doesn't exist.",
+                            table.name(),
+                            owner_column
+                        )
                     }
+                }
 
-                    // Check whether the table is a view, only the GET many endpoint will be generated.
-                    let is_view = table.info.comment.to_lowercase().eq("view");
+                let is_owned = owner_column.is_some();
 
-                    // Get the table primary key. If it is not present only the GET one and POST endpoints will generated.
-                    let pk_id = table
-                        .columns
-                        .iter()
-                        .find(|column| column.key == sea_schema::mysql::def::ColumnKey::Primary)
-                        .map(|table| table.name.to_owned());
+                // The owner column is never set by the client.
+                let columns = table
+                    .columns()
+                    .iter()
+                    .filter(|column| !column.primary_key())
+                    .filter(|column| owner_column.as_deref() != Some(column.name().as_str()))
+                    .collect::<CheapVec<&ColumnSchema>>();
 
-                    if pk_id.is_none() {
-                        debug!(
-                            "Table {} doesn't have a primary key. Only GET many and POST endpoints will be generated.",
-                            table.info.name
-                        )
+                let columns_names = columns
+                    .iter()
+                    .map(|column| column.name().to_owned())
+                    .collect::<CheapVec<CompactString>>();
+
+                // The primary key's placeholder, backends may add a type hint to it.
+                let id_param = table
+                    .primary_key()
+                    .map(|column| db_config.connection().query_param("id", column))
+                    .unwrap_or_default();
+
+                let returning = if db_config.connection().returning() {
+                    " RETURNING *"
+                } else {
+                    ""
+                };
+
+                // Admins bypass the ownership's filter.
+                let owner_bypass_roles = if is_owned {
+                    project.config().admin().allowed_roles().to_owned()
+                } else {
+                    CheapVec::new_const()
+                };
+
+                // Filters the rows by their owner, `|user_id|` is quoted to be compared with any column's type.
+                let owner_filter = owner_column.as_ref().map(|owner_column| {
+                    if owner_bypass_roles.is_empty() {
+                        format!("{} = '|user_id|'", owner_column)
+                    } else {
+                        format!("({} = '|user_id|' OR |owner_bypass| = 1)", owner_column)
                     }
+                });
 
-                    let columns_names = table
-                        .columns
-                        .iter()
-                        .filter(|column| column.key != sea_schema::mysql::def::ColumnKey::Primary)
-                        .map(|column| column.name.to_compact_string())
-                        .collect::<CheapVec<CompactString>>();
-
-                    let route_one = format!("{}/{}", table.info.name.to_lowercase(), "{id}")
-                        .to_compact_string();
-                    let route_many = table.info.name.to_lowercase().to_compact_string();
-
-                    for method in &[
-                        HttpMethod::Get,
-                        HttpMethod::Post,
-                        HttpMethod::Put,
-                        HttpMethod::Delete,
-                    ] {
-                        match (method, &pk_id) {
-                            (HttpMethod::Get, _) => {
-                                match &pk_id {
-                                    Some(pk_id) if !is_view => {
-                                        let mut endpoint_one = EndpointBuilder::default();
-
-                                        endpoint_one
-                                            .id(format!("{}_GetOne", table.info.name)
-                                                .to_compact_string())
-                                            .method(*method)
-                                            .version("v1".to_compact_string())
-                                            .route(route_one.to_owned())
-                                            .description(
-                                                format!(
-                                                    "Get row from {} by it's primary key.",
-                                                    table.info.name
-                                                )
-                                                .to_compact_string(),
+                let and_owner = owner_filter
+                    .as_ref()
+                    .map(|filter| format!(" AND {}", filter))
+                    .unwrap_or_default();
+
+                let roles_defined = project
+                    .config()
+                    .authentication()
+                    .as_ref()
+                    .is_some_and(|auth_config| !auth_config.roles().is_empty());
+
+                let require_auth = table_requires_auth(is_owned, roles_defined);
+
+                let permission =
+                    |action: &str| table_permissions(table.name(), action, roles_defined);
+
+                let route_one =
+                    format!("{}/{}", table.name().to_lowercase(), "{id}").to_compact_string();
+                let route_many = table.name().to_lowercase().to_compact_string();
+
+                for method in &[
+                    HttpMethod::Get,
+                    HttpMethod::Post,
+                    HttpMethod::Put,
+                    HttpMethod::Delete,
+                ] {
+                    match (method, &pk_id) {
+                        (HttpMethod::Get, _) => {
+                            match &pk_id {
+                                Some(pk_id) if !is_view => {
+                                    let mut endpoint_one = EndpointBuilder::default();
+
+                                    endpoint_one
+                                        .id(format!("{}_GetOne", table.name()).to_compact_string())
+                                        .method(*method)
+                                        .version("v1".to_compact_string())
+                                        .route(route_one.to_owned())
+                                        .description(
+                                            format!(
+                                                "Get row from {} by it's primary key.",
+                                                table.name()
                                             )
-                                            .target_database(db_config.id().to_owned())
-                                            .execute(Arc::new(MySQLExecute::new(
+                                            .to_compact_string(),
+                                        )
+                                        .target_database(db_config.id().to_owned())
+                                        .execute(
+                                            schema_discovery.method().executor(
                                                 format!(
-                                                    "SELECT * FROM {} WHERE {} = {}",
-                                                    table.info.name, pk_id, "{id}"
+                                                    "SELECT * FROM {} WHERE {} = {}{}",
+                                                    table.name(),
+                                                    pk_id,
+                                                    id_param,
+                                                    and_owner
                                                 )
                                                 .to_compact_string(),
-                                            )))
-                                            .tags(CheapVec::from_vec(vec![
-                                                table.info.name.to_compact_string(),
-                                                "get_one".to_compact_string(),
-                                            ]))
-                                            .query_params(CheapVec::new_const())
-                                            .body_params(CheapVec::new_const())
-                                            .require_auth(false)
-                                            .inject_user_id(false)
-                                            .allowed_roles(CheapVec::new_const())
-                                            .capture_all_params(false)
-                                            .deprecated(false)
-                                            .auto_generated(true);
-
-                                        discovered_endpoints.add(endpoint_one.build()?)?;
-                                    }
-                                    _ => (),
+                                            ),
+                                        )
+                                        .tags(CheapVec::from_vec(vec![
+                                            table.name().to_compact_string(),
+                                            "get_one".to_compact_string(),
+                                        ]))
+                                        .query_params(CheapVec::new_const())
+                                        .body_params(CheapVec::new_const())
+                                        .require_auth(require_auth)
+                                        .inject_user_id(is_owned)
+                                        .allowed_roles(CheapVec::new_const())
+                                        .owner_bypass_roles(owner_bypass_roles.to_owned())
+                                        .required_permissions(permission("read"))
+                                        .capture_all_params(false)
+                                        .deprecated(false)
+                                        .auto_generated(true);
+
+                                    discovered_endpoints.add(endpoint_one.build()?)?;
                                 }
+                                _ => (),
+                            }
 
-                                let mut endpoint_many = EndpointBuilder::default();
+                            let mut endpoint_many = EndpointBuilder::default();
 
-                                endpoint_many
-                                    .id(format!("{}_GetMany", table.info.name).to_compact_string())
-                                    .method(*method)
-                                    .version("v1".to_compact_string())
-                                    .route(route_many.to_owned())
-                                    .description(
-                                        format!("Get all rows from {}.", table.info.name)
-                                            .to_compact_string(),
-                                    )
-                                    .target_database(db_config.id().to_owned())
-                                    .execute(Arc::new(MySQLExecute::new(
-                                        format!("SELECT * FROM {}", table.info.name,)
-                                            .to_compact_string(),
-                                    )))
-                                    .tags(CheapVec::from_vec(vec![
-                                        table.info.name.to_compact_string(),
-                                        "get_all".to_compact_string(),
-                                    ]))
-                                    .query_params(CheapVec::new_const())
-                                    .body_params(CheapVec::new_const())
-                                    .require_auth(false)
-                                    .inject_user_id(false)
-                                    .allowed_roles(CheapVec::new_const())
-                                    .capture_all_params(false)
-                                    .deprecated(false)
-                                    .auto_generated(true);
-
-                                discovered_endpoints.add(endpoint_many.build()?)?;
-                            }
-                            (HttpMethod::Post, _) if !is_view => {
-                                let mut endpoint = EndpointBuilder::default();
-
-                                endpoint
-                                    .id(format!("{}_Post", table.info.name).to_compact_string())
-                                    .method(*method)
-                                    .version("v1".to_compact_string())
-                                    .route(route_many.to_owned())
-                                    .description(
-                                        format!("Insert data into {}.", table.info.name)
-                                            .to_compact_string(),
-                                    )
-                                    .target_database(db_config.id().to_owned())
-                                    .execute(Arc::new(MySQLExecute::new(
+                            endpoint_many
+                                .id(format!("{}_GetMany", table.name()).to_compact_string())
+                                .method(*method)
+                                .version("v1".to_compact_string())
+                                .route(route_many.to_owned())
+                                .description(
+                                    format!("Get all rows from {}.", table.name())
+                                        .to_compact_string(),
+                                )
+                                .target_database(db_config.id().to_owned())
+                                .execute(
+                                    schema_discovery.method().executor(
                                         format!(
-                                            "INSERT INTO {} ({}) VALUES ({})",
-                                            table.info.name,
+                                            "SELECT * FROM {}{}",
+                                            table.name(),
+                                            owner_filter
+                                                .as_ref()
+                                                .map(|filter| format!(" WHERE {}", filter))
+                                                .unwrap_or_default()
+                                        )
+                                        .to_compact_string(),
+                                    ),
+                                )
+                                .tags(CheapVec::from_vec(vec![
+                                    table.name().to_compact_string(),
+                                    "get_all".to_compact_string(),
+                                ]))
+                                .query_params(CheapVec::new_const())
+                                .body_params(CheapVec::new_const())
+                                .require_auth(require_auth)
+                                .inject_user_id(is_owned)
+                                .allowed_roles(CheapVec::new_const())
+                                .owner_bypass_roles(owner_bypass_roles.to_owned())
+                                .required_permissions(permission("read"))
+                                .capture_all_params(false)
+                                .deprecated(false)
+                                .auto_generated(true);
+
+                            discovered_endpoints.add(endpoint_many.build()?)?;
+                        }
+                        (HttpMethod::Post, _) if !is_view => {
+                            let mut endpoint = EndpointBuilder::default();
+
+                            endpoint
+                                .id(format!("{}_Post", table.name()).to_compact_string())
+                                .method(*method)
+                                .version("v1".to_compact_string())
+                                .route(route_many.to_owned())
+                                .description(
+                                    format!("Insert data into {}.", table.name())
+                                        .to_compact_string(),
+                                )
+                                .target_database(db_config.id().to_owned())
+                                .execute(
+                                    schema_discovery.method().executor(
+                                        format!(
+                                            "INSERT INTO {} ({}) VALUES ({}){}",
+                                            table.name(),
                                             columns_names
                                                 .iter()
+                                                .chain(owner_column.iter())
                                                 .fold(String::new(), |last, next| format!(
                                                     "{}, {}",
                                                     last, next
@@ -208,57 +364,75 @@ pub async fn discover() -> Result<(
                                                 .trim_matches(
                                                     |c: char| c.is_whitespace() || c == ','
                                                 ),
-                                            columns_names
+                                            columns
                                                 .iter()
+                                                .map(|column| db_config
+                                                    .connection()
+                                                    .query_param(column.name(), column))
+                                                .chain(
+                                                    is_owned
+                                                        .then(|| "'|user_id|'".to_compact_string())
+                                                )
                                                 .fold(String::new(), |last, next| format!(
-                                                    "{}, {{ {} }}",
+                                                    "{}, {}",
                                                     last, next
                                                 ))
                                                 .trim_matches(
                                                     |c: char| c.is_whitespace() || c == ','
                                                 ),
+                                            returning,
                                         )
                                         .to_compact_string(),
-                                    )))
-                                    .body_params(columns_names.to_owned())
-                                    .tags(CheapVec::from_vec(vec![
-                                        table.info.name.to_compact_string(),
-                                        "post".to_compact_string(),
-                                    ]))
-                                    .query_params(CheapVec::new_const())
-                                    .body_params(columns_names.to_owned())
-                                    .require_auth(false)
-                                    .inject_user_id(false)
-                                    .allowed_roles(CheapVec::new_const())
-                                    .capture_all_params(false)
-                                    .deprecated(false)
-                                    .auto_generated(true);
-
-                                discovered_endpoints.add(endpoint.build()?)?;
-                            }
-                            (HttpMethod::Put, Some(pk_id)) if !is_view => {
-                                let mut endpoint = EndpointBuilder::default();
-
-                                endpoint
-                                    .id(format!("{}_Put", table.info.name).to_compact_string())
-                                    .method(*method)
-                                    .version("v1".to_compact_string())
-                                    .route(route_one.to_owned())
-                                    .description(
-                                        format!(
-                                            "Updates {} on row with the given primary key.",
-                                            table.info.name
-                                        )
-                                        .to_compact_string(),
+                                    ),
+                                )
+                                .body_params(columns_names.to_owned())
+                                .tags(CheapVec::from_vec(vec![
+                                    table.name().to_compact_string(),
+                                    "post".to_compact_string(),
+                                ]))
+                                .query_params(CheapVec::new_const())
+                                .body_params(columns_names.to_owned())
+                                .require_auth(require_auth)
+                                .inject_user_id(is_owned)
+                                .allowed_roles(CheapVec::new_const())
+                                .owner_bypass_roles(owner_bypass_roles.to_owned())
+                                .required_permissions(permission("write"))
+                                .capture_all_params(false)
+                                .deprecated(false)
+                                .auto_generated(true);
+
+                            discovered_endpoints.add(endpoint.build()?)?;
+                        }
+                        (HttpMethod::Put, Some(pk_id)) if !is_view => {
+                            let mut endpoint = EndpointBuilder::default();
+
+                            endpoint
+                                .id(format!("{}_Put", table.name()).to_compact_string())
+                                .method(*method)
+                                .version("v1".to_compact_string())
+                                .route(route_one.to_owned())
+                                .description(
+                                    format!(
+                                        "Updates {} on row with the given primary key.",
+                                        table.name()
                                     )
-                                    .target_database(db_config.id().to_owned())
-                                    .execute(Arc::new(MySQLExecute::new(
+                                    .to_compact_string(),
+                                )
+                                .target_database(db_config.id().to_owned())
+                                .execute(
+                                    schema_discovery.method().executor(
                                         format!(
-                                            "UPDATE {} SET {} WHERE {} = {} ",
-                                            table.info.name,
-                                            columns_names
+                                            "UPDATE {} SET {} WHERE {} = {}{}{}",
+                                            table.name(),
+                                            columns
                                                 .iter()
-                                                .map(|name| format!("{} = {{ {} }}", name, name))
+                                                .map(|column| format!(
+                                                    "{} = {}",
+                                                    column.name(),
+                                                    db_config
+                                                        .connection()
+                                                        .query_param(column.name(), column)
+                                                ))
                                                 .fold(String::new(), |last, next| format!(
                                                     "{}, {}",
                                                     last, next
@@ -267,76 +441,105 @@ pub async fn discover() -> Result<(
                                                     |c: char| c.is_whitespace() || c == ','
                                                 ),
                                             pk_id,
-                                            "{id}"
-                                        )
-                                        .to_compact_string(),
-                                    )))
-                                    .tags(CheapVec::from_vec(vec![
-                                        table.info.name.to_compact_string(),
-                                        "put".to_compact_string(),
-                                    ]))
-                                    .query_params(CheapVec::new_const())
-                                    .body_params(columns_names.to_owned())
-                                    .require_auth(false)
-                                    .inject_user_id(false)
-                                    .allowed_roles(CheapVec::new_const())
-                                    .capture_all_params(false)
-                                    .deprecated(false)
-                                    .auto_generated(true);
-
-                                discovered_endpoints.add(endpoint.build()?)?;
-                            }
-                            (HttpMethod::Delete, Some(pk_id)) if !is_view => {
-                                let mut endpoint = EndpointBuilder::default();
-
-                                endpoint
-                                    .id(format!("{}_Delete", table.info.name).to_compact_string())
-                                    .method(*method)
-                                    .version("v1".to_compact_string())
-                                    .route(route_one.to_owned())
-                                    .description(
-                                        format!(
-                                            "Deletes data from {} with the given primary key.",
-                                            table.info.name
+                                            id_param,
+                                            and_owner,
+                                            returning
                                         )
                                         .to_compact_string(),
+                                    ),
+                                )
+                                .tags(CheapVec::from_vec(vec![
+                                    table.name().to_compact_string(),
+                                    "put".to_compact_string(),
+                                ]))
+                                .query_params(CheapVec::new_const())
+                                .body_params(columns_names.to_owned())
+                                .require_auth(require_auth)
+                                .inject_user_id(is_owned)
+                                .allowed_roles(CheapVec::new_const())
+                                .owner_bypass_roles(owner_bypass_roles.to_owned())
+                                .required_permissions(permission("write"))
+                                .capture_all_params(false)
+                                .deprecated(false)
+                                .auto_generated(true);
+
+                            discovered_endpoints.add(endpoint.build()?)?;
+                        }
+                        (HttpMethod::Delete, Some(pk_id)) if !is_view => {
+                            let mut endpoint = EndpointBuilder::default();
+
+                            endpoint
+                                .id(format!("{}_Delete", table.name()).to_compact_string())
+                                .method(*method)
+                                .version("v1".to_compact_string())
+                                .route(route_one.to_owned())
+                                .description(
+                                    format!(
+                                        "Deletes data from {} with the given primary key.",
+                                        table.name()
                                     )
-                                    .target_database(db_config.id().to_owned())
-                                    .execute(Arc::new(MySQLExecute::new(
+                                    .to_compact_string(),
+                                )
+                                .target_database(db_config.id().to_owned())
+                                .execute(
+                                    schema_discovery.method().executor(
                                         format!(
-                                            "DELETE FROM {} WHERE {} = {} ",
-                                            table.info.name, pk_id, "{id}"
+                                            "DELETE FROM {} WHERE {} = {}{}",
+                                            table.name(),
+                                            pk_id,
+                                            id_param,
+                                            and_owner
                                         )
                                         .to_compact_string(),
-                                    )))
-                                    .body_params(columns_names.to_owned())
-                                    .tags(CheapVec::from_vec(vec![
-                                        table.info.name.to_compact_string(),
-                                        "delete".to_compact_string(),
-                                    ]))
-                                    .query_params(CheapVec::new_const())
-                                    .body_params(CheapVec::new_const())
-                                    .require_auth(false)
-                                    .inject_user_id(false)
-                                    .allowed_roles(CheapVec::new_const())
-                                    .capture_all_params(false)
-                                    .deprecated(false)
-                                    .auto_generated(true);
-
-                                discovered_endpoints.add(endpoint.build()?)?;
-                            }
-                            _ => {}
+                                    ),
+                                )
+                                .body_params(columns_names.to_owned())
+                                .tags(CheapVec::from_vec(vec![
+                                    table.name().to_compact_string(),
+                                    "delete".to_compact_string(),
+                                ]))
+                                .query_params(CheapVec::new_const())
+                                .body_params(CheapVec::new_const())
+                                .require_auth(require_auth)
+                                .inject_user_id(is_owned)
+                                .allowed_roles(CheapVec::new_const())
+                                .owner_bypass_roles(owner_bypass_roles.to_owned())
+                                .required_permissions(permission("delete"))
+                                .capture_all_params(false)
+                                .deprecated(false)
+                                .auto_generated(true);
+
+                            discovered_endpoints.add(endpoint.build()?)?;
                         }
+                        _ => {}
                     }
                 }
-
-                db_endpoints.push((db_config.id().to_owned(), discovered_endpoints));
-            } else {
-                return Err(anyhow!(
-                    "Unimplemented discovery method or invalid discovery solver for the given database id."
-                ));
             }
+
+            db_endpoints.push((db_config.id().to_owned(), discovered_endpoints));
         }
     }
     Ok((db_endpoints, checksums))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn access_of_the_discovered_tables() {
+        // Tables which aren't owned stay public unless roles are defined, as their permissions
+        // are only checked once authenticated.
+        assert!(!table_requires_auth(false, false));
+        assert!(table_permissions("Orders", "read", false).is_empty());
+
+        assert!(table_requires_auth(false, true));
+        assert_eq!(
+            table_permissions("Orders", "write", true).as_slice(),
+            ["orders:write"]
+        );
+
+        assert!(table_requires_auth(true, false));
+        assert!(table_permissions("Orders", "delete", false).is_empty());
+    }
+}
@@ -1,11 +1,13 @@
 // Waveless
 // Copyright (C) 2026 Oscar Alvarez Gonzalez
 
+pub mod auth;
 pub mod bootstrap;
 pub mod build;
 pub mod compiler_cx;
 pub mod discovery;
 pub mod new;
+pub mod snapshot;
 
 pub use compiler_cx::*;
 
@@ -26,11 +28,13 @@ use std::process::exit;
 use std::sync::Arc;
 
 use anyhow::{Context, Result, anyhow, bail};
+use chrono::{DateTime, Utc};
 use compact_str::*;
 use derive_more::Constructor;
 use either::*;
 use getset::*;
 use owo_colors::*;
+use serde::{Deserialize, Serialize};
 use tokio::sync::OnceCell;
 use tracing::*;
 
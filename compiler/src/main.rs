@@ -6,7 +6,9 @@
 //!
 
 use waveless_commons::{logging::*, runtime::handle_main, *};
-use waveless_compiler::{build::*, compiler_cx::*, new::*};
+use waveless_compiler::{
+    auth::hash_passwords, build::*, compiler_cx::*, discovery::pull_schemas, new::*,
+};
 use waveless_executor::{frontend_options::*, server::serve, *};
 
 use build::*;
@@ -51,6 +53,10 @@ nest! {
         #[arg(short = 'S', long = "skip_endpoint_discovery", default_value_t = false, help = "Whether to skip endpoint discovery and only include user-defined endpoints (this overrides the `project.toml` file)")]
         skip_endpoint_discovery: bool,
 
+        /// Whether to reuse the last schema snapshots stored in the workspace instead of connecting to the databases.
+        #[arg(short = 'O', long = "offline", default_value_t = false, conflicts_with = "skip_endpoint_discovery", help = "Whether to reuse the last schema snapshots stored in the workspace instead of connecting to the databases.")]
+        offline: bool,
+
         /// All cli subcommands
         #[command(subcommand)]
         subcommand: Option<
@@ -78,6 +84,28 @@ nest! {
                 #[command(about = "Bootstraps the database, running all the scripts under the specified `bootstrap_scripts_dir` folder.")]
                 Bootstrap,
 
+                /// Manages the schema snapshots used by offline builds.
+                #[command(about = "Manages the schema snapshots used by offline builds.", subcommand)]
+                Schema(
+                    #[derive(Subcommand)]
+                    enum SchemaSubcommands {
+                        /// Discovers the databases' schema and refreshes the workspace's snapshots.
+                        #[command(about = "Discovers the databases' schema and refreshes the workspace's snapshots.")]
+                        Pull,
+                    }
+                ),
+
+                /// Maintenance tasks on the authentication data.
+                #[command(about = "Maintenance tasks on the authentication data.", subcommand)]
+                Auth(
+                    #[derive(Subcommand)]
+                    enum AuthSubcommands {
+                        /// Hashes the passwords still stored in plaintext using the configured parameters.
+                        #[command(about = "Hashes the passwords still stored in plaintext using the configured parameters.")]
+                        HashPasswords,
+                    }
+                ),
+
                 /// The Waveless' executor.
                 #[command(about = "The Waveless' executor.", subcommand)]
                 Executor(ExecutorFrontendOptions)
@@ -96,11 +124,19 @@ async fn try_main() -> Result<ResultContext> {
     // Setup logging
     subscribe_logging(cli.debug)?;
 
+    let discovery_mode = if cli.skip_endpoint_discovery {
+        DiscoveryMode::Skip
+    } else if cli.offline {
+        DiscoveryMode::Offline
+    } else {
+        DiscoveryMode::Online
+    };
+
     // Handle frontend subcommands
     match cli.subcommand {
         Some(Subcommands::New { name }) => new_project(name),
         Some(Subcommands::Run { addr }) => {
-            CompilerCx::set_cx(CompilerCx::from_workspace().await?);
+            CompilerCx::set_cx(CompilerCx::from_workspace(discovery_mode).await?);
 
             let build = build::<ExecutorBuild>().await?.left().unwrap();
 
@@ -131,11 +167,19 @@ async fn try_main() -> Result<ResultContext> {
             return Ok("".to_compact_string());
         }
         Some(Subcommands::Build) => {
-            CompilerCx::set_cx(CompilerCx::from_workspace().await?);
+            CompilerCx::set_cx(CompilerCx::from_workspace(discovery_mode).await?);
             let buff = build::<Bytes>().await?.right().unwrap();
             binary_file_from_buff(buff)
         }
         Some(Subcommands::Bootstrap) => todo!(),
+        Some(Subcommands::Schema(SchemaSubcommands::Pull)) => {
+            CompilerCx::set_cx(CompilerCx::from_workspace(DiscoveryMode::Online).await?);
+            pull_schemas().await
+        }
+        Some(Subcommands::Auth(AuthSubcommands::HashPasswords)) => {
+            CompilerCx::set_cx(CompilerCx::from_workspace(DiscoveryMode::Skip).await?);
+            hash_passwords().await
+        }
         Some(Subcommands::Executor(executor_options)) => match executor_options {
             ExecutorFrontendOptions::Run { path, addr } => {
                 RuntimeCx::set_cx(RuntimeCx::from_path(path).await?);
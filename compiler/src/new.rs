@@ -63,6 +63,8 @@ pub fn new_project(name: CompactString) -> Result<ResultContext> {
 
         create_dir(project_path.join(".discovered_endpoints"))?;
 
+        create_dir(project_path.join(".schema_snapshots"))?;
+
         create_dir(project_path.join("target"))?;
 
         debug!("Created project directories.");
@@ -87,6 +89,8 @@ pub fn new_project(name: CompactString) -> Result<ResultContext> {
                 false,
                 false,
                 Default::default(),
+                Default::default(),
+                Default::default(),
                 false,
                 false,
                 false,
@@ -107,6 +111,8 @@ pub fn new_project(name: CompactString) -> Result<ResultContext> {
                 false,
                 false,
                 Default::default(),
+                Default::default(),
+                Default::default(),
                 false,
                 false,
                 false,
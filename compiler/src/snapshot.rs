@@ -0,0 +1,165 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+//!
+//! The databases' schema snapshots.
+//! Every successful discovery stores the database's data schema and checksum in the workspace,
+//! so offline builds (`waveless --offline build`) can reuse them without connecting to the database.
+//!
+use crate::*;
+
+use schema::DataSchema;
+
+/// The last successfully discovered schema of a database.
+#[derive(Clone, PartialEq, Constructor, Serialize, Deserialize, Getters, Debug)]
+#[getset(get = "pub")]
+pub struct SchemaSnapshot {
+    taken_at: DateTime<Utc>,
+    checksum: DatabaseChecksum,
+    schema: DataSchema,
+}
+
+impl SchemaSnapshot {
+    /// Returns the path of the given database's snapshot.
+    pub fn path(db_id: &DatabaseId) -> PathBuf {
+        Self::path_in(CompilerCx::acquire().workspace_root(), db_id)
+    }
+
+    /// Returns the path of the given database's snapshot in the workspace.
+    fn path_in(workspace_root: &Path, db_id: &DatabaseId) -> PathBuf {
+        workspace_root
+            .join(".schema_snapshots")
+            .join(format!("{}.toml", db_id))
+    }
+
+    /// Loads the given database's snapshot from the workspace.
+    pub fn load(db_id: &DatabaseId) -> Result<Self> {
+        Self::load_from(CompilerCx::acquire().workspace_root(), db_id)
+    }
+
+    fn load_from(workspace_root: &Path, db_id: &DatabaseId) -> Result<Self> {
+        let file_buffer = read(Self::path_in(workspace_root, db_id)).map_err(|err| {
+            anyhow!(
+                "Cannot open the schema snapshot of '{}', run `waveless schema pull` with the database available to create it.%{}",
+                db_id,
+                err.to_string()
+            )
+        })?;
+
+        toml::from_slice::<Self>(&file_buffer).map_err(|err| {
+            anyhow!(
+                "Cannot deserialize the schema snapshot of '{}'.%{}",
+                db_id,
+                err.to_string()
+            )
+        })
+    }
+
+    /// Stores the snapshot in the workspace, replacing the previous one.
+    pub fn store(&self) -> Result<PathBuf> {
+        self.store_in(CompilerCx::acquire().workspace_root())
+    }
+
+    fn store_in(&self, workspace_root: &Path) -> Result<PathBuf> {
+        let target_file = Self::path_in(workspace_root, self.checksum.database_id());
+
+        if create_dir(workspace_root.join(".schema_snapshots")).is_ok() {
+            debug!("'.schema_snapshots' directory doesn't exist, a new one will be created.")
+        };
+
+        write(&target_file, toml::to_string_pretty(self)?.as_bytes())?;
+
+        Ok(target_file)
+    }
+
+    /// Warns if the snapshot is older than the project's `schema_snapshot_max_age`.
+    pub fn check_age(&self) {
+        let max_age = CompilerCx::acquire()
+            .project()
+            .compiler()
+            .schema_snapshot_max_age()
+            .to_owned();
+
+        if let Some(warning) = self.age_warning(max_age, Utc::now()) {
+            warn!("{}", warning);
+        }
+    }
+
+    /// The warning of a snapshot older than the max age (in seconds), if any.
+    fn age_warning(&self, max_age: Option<usize>, now: DateTime<Utc>) -> Option<CompactString> {
+        let age = now - self.taken_at;
+
+        (age.num_seconds() > max_age? as i64).then(|| {
+            format!(
+                "The schema snapshot of '{}' was taken {} hours ago, it may be outdated. Run `waveless schema pull` to refresh it.",
+                self.checksum.database_id(),
+                age.num_hours()
+            )
+            .to_compact_string()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use schema::TableSchema;
+
+    fn snapshot(taken_at: DateTime<Utc>) -> SchemaSnapshot {
+        SchemaSnapshot::new(
+            taken_at,
+            DatabaseChecksum::new(
+                "main".to_compact_string(),
+                CheapVec::from_slice(&[1, 2, 3, 4]),
+            ),
+            DataSchema::new(CheapVec::from_vec(vec![TableSchema::new(
+                "users".to_compact_string(),
+                false,
+                CheapVec::new(),
+                CheapVec::new(),
+            )])),
+        )
+    }
+
+    #[test]
+    fn stored_and_loaded() -> Result<()> {
+        let workspace_root = std::env::temp_dir().join(format!(
+            "waveless_snapshots_{}_{}",
+            std::process::id(),
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        create_dir(&workspace_root)?;
+
+        // Timestamps are stored with a second precision.
+        let snapshot = snapshot(DateTime::from_timestamp(1_700_000_000, 0).unwrap());
+
+        let target_file = snapshot.store_in(&workspace_root)?;
+        assert_eq!(
+            target_file,
+            workspace_root.join(".schema_snapshots").join("main.toml")
+        );
+
+        let loaded = SchemaSnapshot::load_from(&workspace_root, &"main".to_compact_string())?;
+        assert_eq!(loaded, snapshot);
+
+        assert!(SchemaSnapshot::load_from(&workspace_root, &"other".to_compact_string()).is_err());
+
+        std::fs::remove_dir_all(workspace_root)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn warns_about_old_snapshots() {
+        let now = Utc::now();
+
+        let snapshot = snapshot(now - chrono::Duration::hours(3));
+
+        assert!(snapshot.age_warning(None, now).is_none());
+        assert!(snapshot.age_warning(Some(4 * 3600), now).is_none());
+
+        let warning = snapshot.age_warning(Some(3600), now).unwrap();
+        assert!(warning.contains("'main' was taken 3 hours ago"));
+    }
+}
@@ -7,9 +7,84 @@ pub const LOGIN_ENDPOINT_ID: &str = "Login";
 pub const SIGNUP_ENDPOINT_ID: &str = "SignUp";
 pub const LOGOUT_ENDPOINT_ID: &str = "Logout";
 pub const LOGOUT_ALL_ENDPOINT_ID: &str = "LogoutAll";
+pub const REFRESH_ENDPOINT_ID: &str = "Refresh";
+pub const SESSIONS_ENDPOINT_ID: &str = "Sessions";
+pub const SESSION_REVOKE_ENDPOINT_ID: &str = "SessionRevoke";
+pub const LOGIN_TOTP_ENDPOINT_ID: &str = "LoginTotp";
+pub const TOTP_ENROLL_ENDPOINT_ID: &str = "TotpEnroll";
+pub const TOTP_CONFIRM_ENDPOINT_ID: &str = "TotpConfirm";
+pub const TOTP_DISABLE_ENDPOINT_ID: &str = "TotpDisable";
+pub const OAUTH_START_ENDPOINT_ID: &str = "OAuthStart";
+pub const OAUTH_CALLBACK_ENDPOINT_ID: &str = "OAuthCallback";
+pub const PASSWORD_RESET_REQUEST_ENDPOINT_ID: &str = "PasswordResetRequest";
+pub const PASSWORD_RESET_ENDPOINT_ID: &str = "PasswordReset";
+pub const EMAIL_VERIFY_ENDPOINT_ID: &str = "EmailVerify";
+pub const EMAIL_VERIFY_RESEND_ENDPOINT_ID: &str = "EmailVerifyResend";
+pub const MAGIC_LINK_REQUEST_ENDPOINT_ID: &str = "MagicLinkRequest";
+pub const MAGIC_LINK_VERIFY_ENDPOINT_ID: &str = "MagicLinkVerify";
+pub const PASSKEY_SIGNUP_START_ENDPOINT_ID: &str = "PasskeySignupStart";
+pub const PASSKEY_REGISTER_START_ENDPOINT_ID: &str = "PasskeyRegisterStart";
+pub const PASSKEY_REGISTER_FINISH_ENDPOINT_ID: &str = "PasskeyRegisterFinish";
+pub const PASSKEY_LOGIN_START_ENDPOINT_ID: &str = "PasskeyLoginStart";
+pub const PASSKEY_LOGIN_FINISH_ENDPOINT_ID: &str = "PasskeyLoginFinish";
+pub const ADMIN_USERS_ENDPOINT_ID: &str = "AdminUsers";
+pub const ADMIN_ROLE_GRANT_ENDPOINT_ID: &str = "AdminRoleGrant";
+pub const ADMIN_ROLE_REVOKE_ENDPOINT_ID: &str = "AdminRoleRevoke";
+pub const ADMIN_USER_DISABLE_ENDPOINT_ID: &str = "AdminUserDisable";
+pub const ADMIN_USER_ENABLE_ENDPOINT_ID: &str = "AdminUserEnable";
+pub const ADMIN_USER_DELETE_ENDPOINT_ID: &str = "AdminUserDelete";
+pub const ADMIN_PASSWORD_RESET_ENDPOINT_ID: &str = "AdminPasswordReset";
+pub const ADMIN_API_KEYS_ENDPOINT_ID: &str = "AdminApiKeys";
+pub const ADMIN_API_KEY_CREATE_ENDPOINT_ID: &str = "AdminApiKeyCreate";
+pub const ADMIN_API_KEY_REVOKE_ENDPOINT_ID: &str = "AdminApiKeyRevoke";
+pub const ADMIN_USAGE_ENDPOINT_ID: &str = "AdminUsage";
+pub const ADMIN_STATISTICS_ENDPOINT_ID: &str = "AdminStatistics";
+
+/// Endpoints only available when TOTP is enabled.
+pub const TOTP_ENDPOINT_IDS: [&str; 4] = [
+    LOGIN_TOTP_ENDPOINT_ID,
+    TOTP_ENROLL_ENDPOINT_ID,
+    TOTP_CONFIRM_ENDPOINT_ID,
+    TOTP_DISABLE_ENDPOINT_ID,
+];
+
+/// Endpoints only available when there is an OpenID Connect authentication method.
+pub const OAUTH_ENDPOINT_IDS: [&str; 2] = [OAUTH_START_ENDPOINT_ID, OAUTH_CALLBACK_ENDPOINT_ID];
+
+/// Endpoints only available when the password reset is enabled.
+pub const PASSWORD_RESET_ENDPOINT_IDS: [&str; 2] = [
+    PASSWORD_RESET_REQUEST_ENDPOINT_ID,
+    PASSWORD_RESET_ENDPOINT_ID,
+];
+
+/// Endpoints only available when the email verification is enabled.
+pub const EMAIL_VERIFICATION_ENDPOINT_IDS: [&str; 2] =
+    [EMAIL_VERIFY_ENDPOINT_ID, EMAIL_VERIFY_RESEND_ENDPOINT_ID];
+
+/// Endpoints only available when there is a magic link authentication method.
+pub const MAGIC_LINK_ENDPOINT_IDS: [&str; 2] = [
+    MAGIC_LINK_REQUEST_ENDPOINT_ID,
+    MAGIC_LINK_VERIFY_ENDPOINT_ID,
+];
+
+/// Endpoints only available when there is a passkey authentication method.
+pub const PASSKEY_ENDPOINT_IDS: [&str; 5] = [
+    PASSKEY_SIGNUP_START_ENDPOINT_ID,
+    PASSKEY_REGISTER_START_ENDPOINT_ID,
+    PASSKEY_REGISTER_FINISH_ENDPOINT_ID,
+    PASSKEY_LOGIN_START_ENDPOINT_ID,
+    PASSKEY_LOGIN_FINISH_ENDPOINT_ID,
+];
+
+/// Admin endpoints only available when there is an API keys method.
+pub const API_KEY_ENDPOINT_IDS: [&str; 3] = [
+    ADMIN_API_KEYS_ENDPOINT_ID,
+    ADMIN_API_KEY_CREATE_ENDPOINT_ID,
+    ADMIN_API_KEY_REVOKE_ENDPOINT_ID,
+];
 
 /// Internal endpoints provided by the executor.
-pub const INTERNAL_ENDPOINTS: LazyCell<[(InternalEndpointKind, Endpoint); 4]> = LazyCell::new(
+pub const INTERNAL_ENDPOINTS: LazyCell<[(InternalEndpointKind, Endpoint); 36]> = LazyCell::new(
     || {
         [
             (
@@ -65,6 +140,438 @@ pub const INTERNAL_ENDPOINTS: LazyCell<[(InternalEndpointKind, Endpoint); 4]> =
                     .auto_generated(true)
                     .build()
                     .unwrap()
+            ),
+            (
+                InternalEndpointKind::Authentication,
+                EndpointBuilder::default()
+                    .id(REFRESH_ENDPOINT_ID.to_compact_string())
+                    .route("refresh".to_compact_string())
+                    .method(HttpMethod::Post)
+                    .version("internal".to_compact_string())
+                    .description("Rotate the given `refresh_token`, returning a new authorization token and refresh token.".to_compact_string())
+                    .capture_all_params(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Authentication,
+                EndpointBuilder::default()
+                    .id(SESSIONS_ENDPOINT_ID.to_compact_string())
+                    .route("sessions".to_compact_string())
+                    .method(HttpMethod::Get)
+                    .version("internal".to_compact_string())
+                    .description("List the active sessions of the current user, along with their clients and last activity.".to_compact_string())
+                    .require_auth(true)
+                    .inject_user_id(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Authentication,
+                EndpointBuilder::default()
+                    .id(SESSION_REVOKE_ENDPOINT_ID.to_compact_string())
+                    .route("sessions/revoke".to_compact_string())
+                    .method(HttpMethod::Post)
+                    .version("internal".to_compact_string())
+                    .description("Revoke one of the sessions of the current user given its `session_id`.".to_compact_string())
+                    .capture_all_params(true)
+                    .require_auth(true)
+                    .inject_user_id(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Authentication,
+                EndpointBuilder::default()
+                    .id(LOGIN_TOTP_ENDPOINT_ID.to_compact_string())
+                    .route("login/totp".to_compact_string())
+                    .method(HttpMethod::Post)
+                    .version("internal".to_compact_string())
+                    .description("Complete a login challenge with a TOTP code (`totp_code`) or a recovery code (`recovery_code`).".to_compact_string())
+                    .capture_all_params(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Authentication,
+                EndpointBuilder::default()
+                    .id(TOTP_ENROLL_ENDPOINT_ID.to_compact_string())
+                    .route("totp/enroll".to_compact_string())
+                    .method(HttpMethod::Post)
+                    .version("internal".to_compact_string())
+                    .description("Generate a new TOTP secret and its `otpauth` URI for the current user, which must be confirmed.".to_compact_string())
+                    .capture_all_params(true)
+                    .require_auth(true)
+                    .inject_user_id(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Authentication,
+                EndpointBuilder::default()
+                    .id(TOTP_CONFIRM_ENDPOINT_ID.to_compact_string())
+                    .route("totp/confirm".to_compact_string())
+                    .method(HttpMethod::Post)
+                    .version("internal".to_compact_string())
+                    .description("Enable TOTP for the current user by confirming a code of the pending secret, returns the recovery codes.".to_compact_string())
+                    .capture_all_params(true)
+                    .require_auth(true)
+                    .inject_user_id(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Authentication,
+                EndpointBuilder::default()
+                    .id(TOTP_DISABLE_ENDPOINT_ID.to_compact_string())
+                    .route("totp/disable".to_compact_string())
+                    .method(HttpMethod::Post)
+                    .version("internal".to_compact_string())
+                    .description("Disable TOTP for the current user, who must provide their credentials again.".to_compact_string())
+                    .capture_all_params(true)
+                    .require_auth(true)
+                    .inject_user_id(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Authentication,
+                EndpointBuilder::default()
+                    .id(OAUTH_START_ENDPOINT_ID.to_compact_string())
+                    .route("oauth/{provider}/start".to_compact_string())
+                    .method(HttpMethod::Get)
+                    .version("internal".to_compact_string())
+                    .description("Start an OpenID Connect login, redirecting to the provider's authorization page.".to_compact_string())
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Authentication,
+                EndpointBuilder::default()
+                    .id(OAUTH_CALLBACK_ENDPOINT_ID.to_compact_string())
+                    .route("oauth/{provider}/callback".to_compact_string())
+                    .method(HttpMethod::Get)
+                    .version("internal".to_compact_string())
+                    .description("Complete an OpenID Connect login given the provider's `code` and `state`, creating a new session.".to_compact_string())
+                    .capture_all_params(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Authentication,
+                EndpointBuilder::default()
+                    .id(PASSWORD_RESET_REQUEST_ENDPOINT_ID.to_compact_string())
+                    .route("password-reset/request".to_compact_string())
+                    .method(HttpMethod::Post)
+                    .version("internal".to_compact_string())
+                    .description("Email a password reset link to the user given its name (e.g. `email`), always succeeds so accounts aren't disclosed.".to_compact_string())
+                    .capture_all_params(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Authentication,
+                EndpointBuilder::default()
+                    .id(PASSWORD_RESET_ENDPOINT_ID.to_compact_string())
+                    .route("password-reset".to_compact_string())
+                    .method(HttpMethod::Post)
+                    .version("internal".to_compact_string())
+                    .description("Set the user's new `password` given the emailed reset `token`, revoking all the user's sessions.".to_compact_string())
+                    .capture_all_params(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Authentication,
+                EndpointBuilder::default()
+                    .id(EMAIL_VERIFY_ENDPOINT_ID.to_compact_string())
+                    .route("email/verify".to_compact_string())
+                    .method(HttpMethod::Get)
+                    .version("internal".to_compact_string())
+                    .description("Verify the user's email given the emailed verification `token`.".to_compact_string())
+                    .capture_all_params(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Authentication,
+                EndpointBuilder::default()
+                    .id(EMAIL_VERIFY_RESEND_ENDPOINT_ID.to_compact_string())
+                    .route("email/verify/resend".to_compact_string())
+                    .method(HttpMethod::Post)
+                    .version("internal".to_compact_string())
+                    .description("Email a new verification link to the user given its name (e.g. `email`), always succeeds so accounts aren't disclosed.".to_compact_string())
+                    .capture_all_params(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Authentication,
+                EndpointBuilder::default()
+                    .id(MAGIC_LINK_REQUEST_ENDPOINT_ID.to_compact_string())
+                    .route("login/magic-link".to_compact_string())
+                    .method(HttpMethod::Post)
+                    .version("internal".to_compact_string())
+                    .description("Email a login link and code to the given address (e.g. `email`), creating the account if signup is allowed. The code is then sent along with the address to the login endpoint.".to_compact_string())
+                    .capture_all_params(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Authentication,
+                EndpointBuilder::default()
+                    .id(MAGIC_LINK_VERIFY_ENDPOINT_ID.to_compact_string())
+                    .route("login/magic-link/verify".to_compact_string())
+                    .method(HttpMethod::Get)
+                    .version("internal".to_compact_string())
+                    .description("Complete a login given the emailed link's `token`, creating a new session.".to_compact_string())
+                    .capture_all_params(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Authentication,
+                EndpointBuilder::default()
+                    .id(PASSKEY_SIGNUP_START_ENDPOINT_ID.to_compact_string())
+                    .route("passkey/signup/start".to_compact_string())
+                    .method(HttpMethod::Post)
+                    .version("internal".to_compact_string())
+                    .description("Start the registration of a new account (e.g. given its `email`) along with its first passkey, returning the `challenge_id` and the WebAuthn creation `options`.".to_compact_string())
+                    .capture_all_params(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Authentication,
+                EndpointBuilder::default()
+                    .id(PASSKEY_REGISTER_START_ENDPOINT_ID.to_compact_string())
+                    .route("passkey/register/start".to_compact_string())
+                    .method(HttpMethod::Post)
+                    .version("internal".to_compact_string())
+                    .description("Start the registration of a new passkey for the current user, returning the `challenge_id` and the WebAuthn creation `options`.".to_compact_string())
+                    .capture_all_params(true)
+                    .require_auth(true)
+                    .inject_user_id(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Authentication,
+                EndpointBuilder::default()
+                    .id(PASSKEY_REGISTER_FINISH_ENDPOINT_ID.to_compact_string())
+                    .route("passkey/register/finish".to_compact_string())
+                    .method(HttpMethod::Post)
+                    .version("internal".to_compact_string())
+                    .description("Finish a passkey registration given its `challenge_id` and the authenticator's `credential` (as JSON), creating a new session for new accounts.".to_compact_string())
+                    .capture_all_params(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Authentication,
+                EndpointBuilder::default()
+                    .id(PASSKEY_LOGIN_START_ENDPOINT_ID.to_compact_string())
+                    .route("passkey/login/start".to_compact_string())
+                    .method(HttpMethod::Post)
+                    .version("internal".to_compact_string())
+                    .description("Start the login of the user given its name (e.g. `email`), returning the `challenge_id` and the WebAuthn request `options`.".to_compact_string())
+                    .capture_all_params(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Authentication,
+                EndpointBuilder::default()
+                    .id(PASSKEY_LOGIN_FINISH_ENDPOINT_ID.to_compact_string())
+                    .route("passkey/login/finish".to_compact_string())
+                    .method(HttpMethod::Post)
+                    .version("internal".to_compact_string())
+                    .description("Finish a passkey login given its `challenge_id` and the authenticator's `credential` (as JSON), creating a new session.".to_compact_string())
+                    .capture_all_params(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Admin,
+                EndpointBuilder::default()
+                    .id(ADMIN_USERS_ENDPOINT_ID.to_compact_string())
+                    .route("admin/users".to_compact_string())
+                    .method(HttpMethod::Get)
+                    .version("internal".to_compact_string())
+                    .description("List the users along with their roles, optionally filtering by name (`search`) and paginating (`offset`, `limit`).".to_compact_string())
+                    .capture_all_params(true)
+                    .require_auth(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Admin,
+                EndpointBuilder::default()
+                    .id(ADMIN_ROLE_GRANT_ENDPOINT_ID.to_compact_string())
+                    .route("admin/users/{id}/roles/grant".to_compact_string())
+                    .method(HttpMethod::Post)
+                    .version("internal".to_compact_string())
+                    .description("Grant the given `role` to the user.".to_compact_string())
+                    .capture_all_params(true)
+                    .require_auth(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Admin,
+                EndpointBuilder::default()
+                    .id(ADMIN_ROLE_REVOKE_ENDPOINT_ID.to_compact_string())
+                    .route("admin/users/{id}/roles/revoke".to_compact_string())
+                    .method(HttpMethod::Post)
+                    .version("internal".to_compact_string())
+                    .description("Revoke the given `role` from the user.".to_compact_string())
+                    .capture_all_params(true)
+                    .require_auth(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Admin,
+                EndpointBuilder::default()
+                    .id(ADMIN_USER_DISABLE_ENDPOINT_ID.to_compact_string())
+                    .route("admin/users/{id}/disable".to_compact_string())
+                    .method(HttpMethod::Post)
+                    .version("internal".to_compact_string())
+                    .description("Disable the user's account, invalidating all its sessions.".to_compact_string())
+                    .require_auth(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Admin,
+                EndpointBuilder::default()
+                    .id(ADMIN_USER_ENABLE_ENDPOINT_ID.to_compact_string())
+                    .route("admin/users/{id}/enable".to_compact_string())
+                    .method(HttpMethod::Post)
+                    .version("internal".to_compact_string())
+                    .description("Enable again the user's account.".to_compact_string())
+                    .require_auth(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Admin,
+                EndpointBuilder::default()
+                    .id(ADMIN_USER_DELETE_ENDPOINT_ID.to_compact_string())
+                    .route("admin/users/{id}".to_compact_string())
+                    .method(HttpMethod::Delete)
+                    .version("internal".to_compact_string())
+                    .description("Delete the user's account along with its roles, invalidating all its sessions.".to_compact_string())
+                    .require_auth(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Admin,
+                EndpointBuilder::default()
+                    .id(ADMIN_PASSWORD_RESET_ENDPOINT_ID.to_compact_string())
+                    .route("admin/users/{id}/password/reset".to_compact_string())
+                    .method(HttpMethod::Post)
+                    .version("internal".to_compact_string())
+                    .description("Force the user to reset its password, invalidating all its sessions.".to_compact_string())
+                    .require_auth(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Admin,
+                EndpointBuilder::default()
+                    .id(ADMIN_API_KEYS_ENDPOINT_ID.to_compact_string())
+                    .route("admin/api-keys".to_compact_string())
+                    .method(HttpMethod::Get)
+                    .version("internal".to_compact_string())
+                    .description("List the API keys, optionally only the ones of the given `user`.".to_compact_string())
+                    .capture_all_params(true)
+                    .require_auth(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Admin,
+                EndpointBuilder::default()
+                    .id(ADMIN_API_KEY_CREATE_ENDPOINT_ID.to_compact_string())
+                    .route("admin/api-keys".to_compact_string())
+                    .method(HttpMethod::Post)
+                    .version("internal".to_compact_string())
+                    .description("Create an API key acting as the given `user`, named `name`, restricted to the comma separated `scopes` and optionally expiring in `expires_in` seconds. The key is only shown once.".to_compact_string())
+                    .capture_all_params(true)
+                    .require_auth(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Admin,
+                EndpointBuilder::default()
+                    .id(ADMIN_API_KEY_REVOKE_ENDPOINT_ID.to_compact_string())
+                    .route("admin/api-keys/{id}".to_compact_string())
+                    .method(HttpMethod::Delete)
+                    .version("internal".to_compact_string())
+                    .description("Revoke the API key given its id.".to_compact_string())
+                    .require_auth(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Admin,
+                EndpointBuilder::default()
+                    .id(ADMIN_USAGE_ENDPOINT_ID.to_compact_string())
+                    .route("admin/usage".to_compact_string())
+                    .method(HttpMethod::Get)
+                    .version("internal".to_compact_string())
+                    .description("Report the usage per consumer and endpoint during the given `period` (e.g. `2026-10-18` or `2026-10`, the current month by default), optionally only the given `consumer`'s one (e.g. `user:3`).".to_compact_string())
+                    .capture_all_params(true)
+                    .require_auth(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
+            ),
+            (
+                InternalEndpointKind::Admin,
+                EndpointBuilder::default()
+                    .id(ADMIN_STATISTICS_ENDPOINT_ID.to_compact_string())
+                    .route("admin/statistics".to_compact_string())
+                    .method(HttpMethod::Get)
+                    .version("internal".to_compact_string())
+                    .description("Report the statistics of this instance since it started, e.g. the query cache's hits and misses.".to_compact_string())
+                    .require_auth(true)
+                    .auto_generated(true)
+                    .build()
+                    .unwrap()
             )
         ]
     },
@@ -74,5 +581,7 @@ pub const INTERNAL_ENDPOINTS: LazyCell<[(InternalEndpointKind, Endpoint); 4]> =
 #[derive(Debug)]
 pub enum InternalEndpointKind {
     Authentication,
+    /// Only added when there are admin roles, which are the only ones allowed.
+    Admin,
     Other,
 }
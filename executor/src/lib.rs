@@ -13,9 +13,15 @@ pub use services::*;
 
 use waveless_commons::*;
 
+use waveless_commons::auth::{
+    AnyAuthenticationMethod, AnySessionMethod, SessionClient, magic::*, oidc::*, passkey::*,
+    roles::*, totp::*,
+};
 use waveless_commons::build::*;
+use waveless_commons::databases::AnyDatabaseConnection;
 use waveless_commons::endpoint::*;
 use waveless_commons::execute::*;
+use waveless_commons::project::Authentication;
 
 use rustyrosetta::*;
 
@@ -23,11 +29,12 @@ use std::cell::LazyCell;
 use std::collections::HashMap;
 use std::convert::Infallible;
 use std::fs::read;
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::path::PathBuf;
 use std::pin::Pin;
+use std::sync::{Arc, LazyLock};
 use std::task::Poll;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anyhow::{Result, anyhow};
 use clap::Subcommand;
@@ -41,12 +48,12 @@ use http_body_util::BodyExt;
 use hyper::{body::Incoming, server::conn::http1, *};
 use hyper_util::{rt::TokioIo, service::TowerToHyperService};
 use matchit::*;
+use rand::distr::{Alphanumeric, SampleString};
 use serde_json::json;
 use tokio::sync::{OnceCell, RwLock};
 use tower::{Layer, Service, ServiceBuilder, util::BoxCloneService};
 use tower_governor::{governor::*, key_extractor::*};
 use tower_http::{compression::*, cors::*, timeout::*};
-use tower_http_cache::prelude::*;
 use tracing::*;
 
 pub type EndpointRouter = DashMap<HttpMethod, Router<Endpoint>>;
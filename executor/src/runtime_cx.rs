@@ -72,6 +72,8 @@ impl RuntimeCx {
 
         // Add authentication endpoints if enabled.
         if let Some(auth_config) = build.read().await.config().authentication().to_owned() {
+            let has_mailing = build.read().await.config().mailing().is_some();
+
             for (kind, endpoint) in INTERNAL_ENDPOINTS.iter() {
                 if let InternalEndpointKind::Authentication = kind {
                     // Check whether we are trying to add the signup endpoint while being disabled.
@@ -79,9 +81,122 @@ impl RuntimeCx {
                         continue;
                     }
 
+                    // TOTP endpoints are only added when TOTP is enabled.
+                    if auth_config.totp().is_none()
+                        && TOTP_ENDPOINT_IDS.contains(&endpoint.id().as_str())
+                    {
+                        continue;
+                    }
+
+                    // The refresh endpoint is only added when refresh tokens are issued.
+                    if auth_config.session().refresh_max_age().is_none()
+                        && endpoint.id() == REFRESH_ENDPOINT_ID
+                    {
+                        continue;
+                    }
+
+                    // OAuth endpoints are only added when there is an OpenID Connect method.
+                    if !auth_config.backends().iter().any(|backend| {
+                        backend
+                            .to_owned()
+                            .into_arc_any()
+                            .downcast::<OidcAuthenticationMethod>()
+                            .is_ok()
+                    }) && OAUTH_ENDPOINT_IDS.contains(&endpoint.id().as_str())
+                    {
+                        continue;
+                    }
+
+                    // Magic link endpoints are only added when there is a magic link method, which mails the links.
+                    if (!has_mailing
+                        || !auth_config.backends().iter().any(|backend| {
+                            backend
+                                .to_owned()
+                                .into_arc_any()
+                                .downcast::<SqlMagicLinkAuthenticationMethod>()
+                                .is_ok()
+                        }))
+                        && MAGIC_LINK_ENDPOINT_IDS.contains(&endpoint.id().as_str())
+                    {
+                        continue;
+                    }
+
+                    // Passkey endpoints are only added when there is a passkey method.
+                    if !auth_config.backends().iter().any(|backend| {
+                        backend
+                            .to_owned()
+                            .into_arc_any()
+                            .downcast::<SqlPasskeyAuthenticationMethod>()
+                            .is_ok()
+                    }) && PASSKEY_ENDPOINT_IDS.contains(&endpoint.id().as_str())
+                    {
+                        continue;
+                    }
+
+                    if !auth_config.allow_signup()
+                        && endpoint.id() == PASSKEY_SIGNUP_START_ENDPOINT_ID
+                    {
+                        continue;
+                    }
+
+                    // Emailed links' endpoints are only added when their flow and the mailing are set.
+                    if (!has_mailing || auth_config.password_reset().is_none())
+                        && PASSWORD_RESET_ENDPOINT_IDS.contains(&endpoint.id().as_str())
+                    {
+                        continue;
+                    }
+
+                    if (!has_mailing || auth_config.email_verification().is_none())
+                        && EMAIL_VERIFICATION_ENDPOINT_IDS.contains(&endpoint.id().as_str())
+                    {
+                        continue;
+                    }
+
                     endpoints.push(endpoint.to_owned());
                 }
             }
+
+            // Admin endpoints are only allowed to the admin roles, so they require roles to be set.
+            let admin_roles = build
+                .read()
+                .await
+                .config()
+                .admin()
+                .allowed_roles()
+                .to_owned();
+
+            let has_quotas = build.read().await.config().quotas().is_some();
+
+            let has_statistics = *build.read().await.config().admin().statistics();
+
+            if auth_config.role().is_some() && !admin_roles.is_empty() {
+                for (kind, endpoint) in INTERNAL_ENDPOINTS.iter() {
+                    if let InternalEndpointKind::Admin = kind {
+                        // API keys' endpoints are only added when there is an API keys method.
+                        if auth_config.api_keys().is_none()
+                            && API_KEY_ENDPOINT_IDS.contains(&endpoint.id().as_str())
+                        {
+                            continue;
+                        }
+
+                        // The usage report is only added when there are quotas.
+                        if !has_quotas && endpoint.id() == ADMIN_USAGE_ENDPOINT_ID {
+                            continue;
+                        }
+
+                        // The statistics are only reported when they're enabled.
+                        if !has_statistics && endpoint.id() == ADMIN_STATISTICS_ENDPOINT_ID {
+                            continue;
+                        }
+
+                        let mut endpoint = endpoint.to_owned();
+
+                        *endpoint.allowed_roles_mut() = admin_roles.to_owned();
+
+                        endpoints.push(endpoint);
+                    }
+                }
+            }
         }
 
         // Add all other internal endpoints.
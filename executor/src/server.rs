@@ -3,6 +3,54 @@
 
 use crate::*;
 
+/// Set by the server on every request with the IP of the client, overwriting the client's one. It's the
+/// connection's peer unless it's a trusted proxy, then it's the one the proxies forwarded the request for.
+pub const PEER_ADDR_HEADER: &str = "x-waveless-peer-addr";
+
+/// The client's IP: the right-most address of `X-Forwarded-For` which isn't a trusted proxy, when the
+/// peer is one, as the addresses on its left may have been set by the client itself.
+fn client_addr(peer_ip: IpAddr, headers: &HeaderMap, trusted_proxies: &[IpAddr]) -> IpAddr {
+    if !trusted_proxies.contains(&peer_ip) {
+        return peer_ip;
+    }
+
+    let forwarded = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|addr| addr.trim().parse::<IpAddr>())
+        .collect::<Vec<_>>();
+
+    let mut client_ip = peer_ip;
+
+    for addr in forwarded.into_iter().rev() {
+        match addr {
+            Ok(addr) if trusted_proxies.contains(&addr) => client_ip = addr,
+            Ok(addr) => return addr,
+            Err(_) => break,
+        }
+    }
+
+    client_ip
+}
+
+/// Rate limits each client by the IP the server resolved for it.
+#[derive(Clone, Debug)]
+struct ClientAddrKeyExtractor;
+
+impl KeyExtractor for ClientAddrKeyExtractor {
+    type Key = IpAddr;
+
+    fn extract<T>(&self, req: &Request<T>) -> Result<Self::Key, tower_governor::GovernorError> {
+        req.headers()
+            .get(PEER_ADDR_HEADER)
+            .and_then(|addr| addr.to_str().ok())
+            .and_then(|addr| addr.parse().ok())
+            .ok_or(tower_governor::GovernorError::UnableToExtractKey)
+    }
+}
+
 #[instrument(skip_all)]
 pub async fn serve(
     addr: Option<SocketAddr>,
@@ -30,30 +78,49 @@ pub async fn serve(
         chrono::Local::now()
     );
 
+    // Logins are served once the authentication methods are ready, e.g. their decoy password hashes are made.
+    if let Some(auth_config) = _build_lock.read().await.config().authentication() {
+        for backend in auth_config.backends() {
+            backend.prepare().await?;
+        }
+    }
+
+    if let Some(http_cache_time) = _build_lock.read().await.executor().http_cache_time() {
+        warn!(
+            "`http_cache_time` is deprecated, the `GET` endpoints without a `cache` are cached for {}s meanwhile. HINT: set the endpoints' `cache` instead.",
+            http_cache_time
+        );
+    }
+
+    let rate_limiting = _build_lock
+        .read()
+        .await
+        .executor()
+        .rate_limiting()
+        .to_owned();
+
     let governor_conf = std::sync::Arc::new(
         GovernorConfigBuilder::default()
-            .per_second(1)
-            .burst_size(1000) // TODO: Make this a parameter in the 'project.toml'.
-            .key_extractor(GlobalKeyExtractor) // TODO: Change this setting to allow IP-based rate limiting.
+            .per_millisecond(*rate_limiting.replenish_interval())
+            .burst_size(*rate_limiting.burst_size())
+            .key_extractor(ClientAddrKeyExtractor)
             .finish()
-            .unwrap(),
+            .ok_or(anyhow!(
+                "The rate limiting's replenish interval and burst size must be greater than zero."
+            ))?,
     );
 
-    // TODO: A POST request to an endpoint invalidates the caches of the GET endpoints with the same route.
-    let cache = CacheLayer::builder(InMemoryBackend::new(4096))
-        .ttl(Duration::from_secs(1)) // TODO: Make this a parameter in the 'project.toml'.
-        .stale_while_revalidate(Duration::from_secs(1))
-        .build();
-
-    // Cleans up the governor key pool.
+    // Cleans up the governor key pool periodically.
     let governor_limiter = governor_conf.limiter().to_owned();
     tokio::spawn(async move {
-        tokio::time::sleep(Duration::from_secs(30)).await;
-        debug!(
-            "Cleaning up rate's limiter storage (size: {})",
-            governor_limiter.len()
-        );
-        governor_limiter.retain_recent();
+        loop {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            debug!(
+                "Cleaning up rate's limiter storage (size: {})",
+                governor_limiter.len()
+            );
+            governor_limiter.retain_recent();
+        }
     });
 
     // This would have worked ad-hoc without modifying the original crate if it has implemented `From<String>` for `GovernorError`...
@@ -70,36 +137,88 @@ pub async fn serve(
             .unwrap()
     });
 
+    // Removes the expired sessions in the background.
+    tokio::spawn(sweep_sessions());
+
+    // Flushes the usage counters and removes those of past periods in the background.
+    tokio::spawn(flush_usage_periodically());
+    tokio::spawn(sweep_usage());
+
+    // Removes the expired mail tokens in the background.
+    tokio::spawn(sweep_mail_tokens());
+
+    // Removes the expired TOTP challenges in the background.
+    tokio::spawn(sweep_totp_challenges());
+
+    // Removes the forgotten failed logins in the background.
+    tokio::spawn(sweep_login_throttle());
+
+    // Removes the expired magic logins in the background.
+    tokio::spawn(sweep_magic_logins());
+
+    // Removes the ended rate limit windows in the background.
+    tokio::spawn(sweep_rate_limits());
+
+    // Sets the query cache up before serving, so the writes of the authentication methods outdate
+    // its results from the start.
+    if let Err(err) = query_cache().await {
+        error!("Cannot set up the query cache. {}", err);
+    }
+
+    // Removes the expired and outdated query results in the background.
+    tokio::spawn(sweep_query_cache());
+
     let compression = CompressionLayer::new().compress_when(predicate::SizeAbove::new(2048));
 
     let endpoint_svc = ServiceBuilder::new()
         .layer(ExecuteWrapperLayer)
         .layer(RequestParamsExtractorLayer)
+        .layer(RateLimitGuardLayer::by_ip())
         .layer(SessionWatchdogLayer)
+        .layer(RateLimitGuardLayer::by_consumer())
+        .layer(QuotaGuardLayer)
+        .layer(ResponseCacheLayer)
         .layer(AuthCaptureLayer)
         .service(ExecuteHandler);
 
     let router = services::RouterService::new(endpoint_svc, Some(frontend));
 
     let svc = ServiceBuilder::new()
-        .layer(cache)
         .layer(compression)
         .layer(CorsLayer::permissive())
         .layer(TimeoutLayer::with_status_code(
             http::StatusCode::REQUEST_TIMEOUT,
             Duration::from_secs(10),
         ))
-        .layer(governor) // Rate limiting does not apply for cached requests.
+        .layer(governor)
         .service(router);
 
-    let svc = TowerToHyperService::new(svc);
-
     loop {
-        let (stream, _) = listener.accept().await?;
+        let (stream, peer_addr) = listener.accept().await?;
 
         let io = TokioIo::new(stream);
 
-        let svc = svc.to_owned();
+        let trusted_proxies = rate_limiting.trusted_proxies().to_owned();
+
+        // The client's IP is recorded on the sessions and limits its requests, so it cannot be spoofed by the client.
+        let svc = TowerToHyperService::new(tower::ServiceExt::map_request(
+            svc.to_owned(),
+            move |mut req: Request<Incoming>| {
+                let client_ip = client_addr(peer_addr.ip(), req.headers(), &trusted_proxies);
+
+                // The client's own header is removed even if the IP couldn't be set, so it's never trusted.
+                match HeaderValue::try_from(client_ip.to_string()) {
+                    Ok(client_ip) => {
+                        req.headers_mut().insert(PEER_ADDR_HEADER, client_ip);
+                    }
+                    Err(_) => {
+                        req.headers_mut().remove(PEER_ADDR_HEADER);
+                    }
+                }
+
+                req
+            },
+        ));
 
         tokio::task::spawn(async move {
             if let Err(err) = http1::Builder::new().serve_connection(io, svc).await {
@@ -111,3 +230,41 @@ pub async fn serve(
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_addr_behind_trusted_proxies() {
+        let proxy: IpAddr = "10.0.0.1".parse().unwrap();
+        let client: IpAddr = "203.0.113.7".parse().unwrap();
+
+        let forwarded = |value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert("x-forwarded-for", HeaderValue::from_str(value).unwrap());
+            headers
+        };
+
+        // Untrusted peers are the client, whatever they forward.
+        assert_eq!(
+            client_addr(client, &forwarded("198.51.100.1"), &[proxy]),
+            client
+        );
+
+        // Trusted proxies forward the right-most address which isn't one of them, as those on
+        // its left may have been set by the client.
+        assert_eq!(
+            client_addr(
+                proxy,
+                &forwarded("198.51.100.1, 203.0.113.7, 10.0.0.1"),
+                &[proxy]
+            ),
+            client
+        );
+
+        // The peer is kept when nothing valid is forwarded.
+        assert_eq!(client_addr(proxy, &HeaderMap::new(), &[proxy]), proxy);
+        assert_eq!(client_addr(proxy, &forwarded("not an ip"), &[proxy]), proxy);
+    }
+}
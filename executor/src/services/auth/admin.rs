@@ -0,0 +1,212 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+use crate::*;
+
+use waveless_commons::databases::DatabasesConnections;
+
+use percent_encoding::percent_decode_str;
+
+/// Max users listed at once.
+const MAX_USERS_LIMIT: usize = 200;
+
+/// Manages the users' accounts and roles, only allowed to the admin roles.
+#[derive(Clone, Constructor, Debug)]
+pub struct AdminCaptured;
+
+impl Service<RequestParamsExtractorRequest> for AdminCaptured {
+    type Response = ExecuteOutput;
+
+    type Error = RequestError;
+
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    #[instrument(skip_all)]
+    fn call(&mut self, cx: RequestParamsExtractorRequest) -> Self::Future {
+        let future: Pin<_> = Box::pin(async move {
+            let (headers, endpoint, request_params, _) = cx;
+
+            let request_params = request_params
+                .iter()
+                .filter_map(|entry| {
+                    if let (key, ExecuteParamValue::Client(Some(value))) = entry {
+                        Some((key.to_owned(), value.to_owned()))
+                    } else {
+                        None
+                    }
+                })
+                .collect::<HashMap<CompactString, CompactString>>();
+
+            let auth_config = RuntimeCx::acquire()
+                .build()
+                .read()
+                .await
+                .config()
+                .authentication()
+                .to_owned()
+                .ok_or(RequestError::Other(anyhow!(
+                    "Authentication is not set for the current build."
+                )))?;
+
+            let databases = DATABASES_CONNS.get().unwrap();
+
+            let auth_method = select_auth_method(&auth_config, &headers)?;
+
+            let Ok(auth_db) = databases.search(auth_method.db_id()) else {
+                return Err(RequestError::Other(anyhow!(
+                    "Cannot get the database connection for '{}'.",
+                    auth_method.db_id().unwrap_or("main".to_compact_string())
+                )));
+            };
+
+            let Some(role_method) = auth_config.role() else {
+                return Err(RequestError::Other(anyhow!(
+                    "Roles are not set for the current build."
+                )));
+            };
+
+            let Ok(role_db) = databases.search(role_method.db_id()) else {
+                return Err(RequestError::Other(anyhow!(
+                    "Cannot get the database connection for '{}'.",
+                    role_method.db_id().unwrap_or("main".to_compact_string())
+                )));
+            };
+
+            if endpoint.id() == ADMIN_USERS_ENDPOINT_ID {
+                let search = request_params
+                    .get("search")
+                    .map(|search| percent_decode_str(search).decode_utf8_lossy())
+                    .filter(|search| !search.is_empty())
+                    .map(|search| search.to_compact_string());
+
+                let parse = |param: &str, default: usize| {
+                    request_params
+                        .get(param)
+                        .map(|value| value.parse::<usize>())
+                        .unwrap_or(Ok(default))
+                        .map_err(|_| {
+                            RequestError::Expected(
+                                StatusCode::BAD_REQUEST,
+                                format!("`{}` must be a positive integer.", param)
+                                    .to_compact_string(),
+                            )
+                        })
+                };
+
+                let offset = parse("offset", 0)?;
+                let limit = parse("limit", 50)?.min(MAX_USERS_LIMIT);
+
+                let users = auth_method.users(auth_db, search, offset, limit).await?;
+
+                let user_ids = users.iter().map(|user| *user.id()).collect::<Vec<_>>();
+
+                let roles = role_method.get_all_for(role_db, &user_ids).await?;
+
+                let listed = users
+                    .iter()
+                    .map(|user| {
+                        let roles = roles.get(user.id()).map(|roles| roles.as_slice());
+
+                        json!({
+                            "id": user.id(),
+                            "name": user.name(),
+                            "disabled": user.disabled(),
+                            "roles": roles.unwrap_or_default()
+                        })
+                    })
+                    .collect::<Vec<_>>();
+
+                return Ok(ExecuteOutput::Json(None, json!({ "users": listed })));
+            }
+
+            let user_id = request_params
+                .get("id")
+                .and_then(|user_id| user_id.parse::<UserId>().ok())
+                .ok_or(RequestError::Expected(
+                    StatusCode::BAD_REQUEST,
+                    "The user's `id` must be an integer.".to_compact_string(),
+                ))?;
+
+            match endpoint.id().as_str() {
+                ADMIN_ROLE_GRANT_ENDPOINT_ID | ADMIN_ROLE_REVOKE_ENDPOINT_ID => {
+                    let Some(role) = request_params.get("role") else {
+                        return Err(RequestError::Expected(
+                            StatusCode::BAD_REQUEST,
+                            "The `role` is required.".to_compact_string(),
+                        ));
+                    };
+
+                    if endpoint.id() == ADMIN_ROLE_GRANT_ENDPOINT_ID {
+                        role_method.grant(role_db, user_id, role.to_owned()).await?;
+                    } else {
+                        role_method
+                            .revoke(role_db, user_id, role.to_owned())
+                            .await?;
+                    }
+                }
+                ADMIN_USER_ENABLE_ENDPOINT_ID => {
+                    auth_method.set_disabled(auth_db, user_id, false).await?;
+                }
+                ADMIN_USER_DISABLE_ENDPOINT_ID => {
+                    auth_method.set_disabled(auth_db, user_id, true).await?;
+                    revoke_access(&auth_config, databases, user_id).await?;
+                }
+                ADMIN_PASSWORD_RESET_ENDPOINT_ID => {
+                    auth_method.force_password_reset(auth_db, user_id).await?;
+                    revoke_access(&auth_config, databases, user_id).await?;
+                }
+                ADMIN_USER_DELETE_ENDPOINT_ID => {
+                    revoke_access(&auth_config, databases, user_id).await?;
+                    auth_method.delete(auth_db, user_id).await?;
+                    role_method.remove(role_db, user_id).await?;
+                }
+                _ => {
+                    return Err(RequestError::Other(anyhow!(
+                        "Unexpected admin endpoint '{}'.",
+                        endpoint.id()
+                    )));
+                }
+            }
+
+            Ok(ExecuteOutput::Json(None, json!({})))
+        })
+        .into();
+
+        future as Self::Future // Actually, this is not an error! https://github.com/rust-lang/rust/issues/92929
+    }
+}
+
+/// Invalidates the user's sessions and revokes its API keys, so they don't outlive an admin's change.
+async fn revoke_access(
+    auth_config: &Authentication,
+    databases: &DatabasesConnections,
+    user_id: UserId,
+) -> Result<(), RequestError> {
+    let session_method = auth_config.session();
+
+    let Ok(session_db) = databases.search(session_method.db_id()) else {
+        return Err(RequestError::Other(anyhow!(
+            "Cannot get the database connection for '{}'.",
+            session_method.db_id().unwrap_or("main".to_compact_string())
+        )));
+    };
+
+    session_method.invalidate(session_db, user_id, None).await?;
+
+    if let Some(api_key_method) = auth_config.api_keys() {
+        let Ok(api_key_db) = databases.search(api_key_method.db_id()) else {
+            return Err(RequestError::Other(anyhow!(
+                "Cannot get the database connection for '{}'.",
+                api_key_method.db_id().unwrap_or("main".to_compact_string())
+            )));
+        };
+
+        api_key_method.remove(api_key_db, user_id).await?;
+    }
+
+    Ok(())
+}
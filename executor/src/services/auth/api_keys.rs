@@ -0,0 +1,160 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+use crate::*;
+
+/// Manages the API keys of machine clients, only allowed to the admin roles.
+#[derive(Clone, Constructor, Debug)]
+pub struct ApiKeysCaptured;
+
+impl Service<RequestParamsExtractorRequest> for ApiKeysCaptured {
+    type Response = ExecuteOutput;
+
+    type Error = RequestError;
+
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    #[instrument(skip_all)]
+    fn call(&mut self, cx: RequestParamsExtractorRequest) -> Self::Future {
+        let future: Pin<_> = Box::pin(async move {
+            let (_, endpoint, request_params, _) = cx;
+
+            let request_params = request_params
+                .iter()
+                .filter_map(|entry| {
+                    if let (key, ExecuteParamValue::Client(Some(value))) = entry {
+                        Some((key.to_owned(), value.to_owned()))
+                    } else {
+                        None
+                    }
+                })
+                .collect::<HashMap<CompactString, CompactString>>();
+
+            let auth_config = RuntimeCx::acquire()
+                .build()
+                .read()
+                .await
+                .config()
+                .authentication()
+                .to_owned()
+                .ok_or(RequestError::Other(anyhow!(
+                    "Authentication is not set for the current build."
+                )))?;
+
+            let Some(api_key_method) = auth_config.api_keys() else {
+                return Err(RequestError::Other(anyhow!(
+                    "API keys are not set for the current build."
+                )));
+            };
+
+            let databases = DATABASES_CONNS.get().unwrap();
+
+            let Ok(api_key_db) = databases.search(api_key_method.db_id()) else {
+                return Err(RequestError::Other(anyhow!(
+                    "Cannot get the database connection for '{}'.",
+                    api_key_method.db_id().unwrap_or("main".to_compact_string())
+                )));
+            };
+
+            let parse_user = |user: Option<&CompactString>| {
+                user.map(|user| user.parse::<UserId>())
+                    .transpose()
+                    .map_err(|_| {
+                        RequestError::Expected(
+                            StatusCode::BAD_REQUEST,
+                            "The `user` must be an integer.".to_compact_string(),
+                        )
+                    })
+            };
+
+            match endpoint.id().as_str() {
+                ADMIN_API_KEYS_ENDPOINT_ID => {
+                    let user_id = parse_user(request_params.get("user"))?;
+
+                    let api_keys = api_key_method.list(api_key_db, user_id).await?;
+
+                    Ok(ExecuteOutput::Json(
+                        None,
+                        json!({ "api_keys": api_keys.as_slice() }),
+                    ))
+                }
+                ADMIN_API_KEY_CREATE_ENDPOINT_ID => {
+                    let (Some(user_id), Some(name)) = (
+                        parse_user(request_params.get("user"))?,
+                        request_params.get("name"),
+                    ) else {
+                        return Err(RequestError::Expected(
+                            StatusCode::BAD_REQUEST,
+                            "Both the `user` and the key's `name` are required."
+                                .to_compact_string(),
+                        ));
+                    };
+
+                    let scopes = request_params
+                        .get("scopes")
+                        .map(|scopes| {
+                            scopes
+                                .split(|c: char| c == ',' || c.is_whitespace())
+                                .filter(|scope| !scope.is_empty())
+                                .map(|scope| scope.to_compact_string())
+                                .collect()
+                        })
+                        .unwrap_or_default();
+
+                    let expires_at = request_params
+                        .get("expires_in")
+                        .map(|expires_in| expires_in.parse::<u64>())
+                        .transpose()
+                        .map_err(|_| {
+                            RequestError::Expected(
+                                StatusCode::BAD_REQUEST,
+                                "`expires_in` must be a positive number of seconds."
+                                    .to_compact_string(),
+                            )
+                        })?
+                        .map(|expires_in| {
+                            chrono::Utc::now().naive_utc() + Duration::from_secs(expires_in)
+                        });
+
+                    let (key, api_key) = api_key_method
+                        .new(api_key_db, user_id, name.to_owned(), scopes, expires_at)
+                        .await?;
+
+                    // The key can't be recovered later, as only its hash is stored.
+                    Ok(ExecuteOutput::Json(
+                        None,
+                        json!({ "key": key, "api_key": api_key }),
+                    ))
+                }
+                ADMIN_API_KEY_REVOKE_ENDPOINT_ID => {
+                    let Some(key_id) = request_params.get("id") else {
+                        return Err(RequestError::Expected(
+                            StatusCode::BAD_REQUEST,
+                            "The key's `id` is required.".to_compact_string(),
+                        ));
+                    };
+
+                    if !api_key_method.revoke(api_key_db, key_id.to_owned()).await? {
+                        return Err(RequestError::Expected(
+                            StatusCode::NOT_FOUND,
+                            "API key not found.".to_compact_string(),
+                        ));
+                    }
+
+                    Ok(ExecuteOutput::Json(None, json!({})))
+                }
+                _ => Err(RequestError::Other(anyhow!(
+                    "Unexpected API keys endpoint '{}'.",
+                    endpoint.id()
+                ))),
+            }
+        })
+        .into();
+
+        future as Self::Future // Actually, this is not an error! https://github.com/rust-lang/rust/issues/92929
+    }
+}
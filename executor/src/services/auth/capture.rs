@@ -74,6 +74,79 @@ where
                         .call((headers, endpoint, request_params, request_body))
                         .await
                 }
+                REFRESH_ENDPOINT_ID => {
+                    RefreshCaptured
+                        .call((headers, endpoint, request_params, request_body))
+                        .await
+                }
+                SESSIONS_ENDPOINT_ID | SESSION_REVOKE_ENDPOINT_ID => {
+                    SessionsCaptured
+                        .call((headers, endpoint, request_params, request_body))
+                        .await
+                }
+                LOGIN_TOTP_ENDPOINT_ID
+                | TOTP_ENROLL_ENDPOINT_ID
+                | TOTP_CONFIRM_ENDPOINT_ID
+                | TOTP_DISABLE_ENDPOINT_ID => {
+                    TotpCaptured
+                        .call((headers, endpoint, request_params, request_body))
+                        .await
+                }
+                OAUTH_START_ENDPOINT_ID | OAUTH_CALLBACK_ENDPOINT_ID => {
+                    OAuthCaptured
+                        .call((headers, endpoint, request_params, request_body))
+                        .await
+                }
+                PASSWORD_RESET_REQUEST_ENDPOINT_ID
+                | PASSWORD_RESET_ENDPOINT_ID
+                | EMAIL_VERIFY_ENDPOINT_ID
+                | EMAIL_VERIFY_RESEND_ENDPOINT_ID => {
+                    EmailCaptured
+                        .call((headers, endpoint, request_params, request_body))
+                        .await
+                }
+                MAGIC_LINK_REQUEST_ENDPOINT_ID | MAGIC_LINK_VERIFY_ENDPOINT_ID => {
+                    MagicLinkCaptured
+                        .call((headers, endpoint, request_params, request_body))
+                        .await
+                }
+                PASSKEY_SIGNUP_START_ENDPOINT_ID
+                | PASSKEY_REGISTER_START_ENDPOINT_ID
+                | PASSKEY_REGISTER_FINISH_ENDPOINT_ID
+                | PASSKEY_LOGIN_START_ENDPOINT_ID
+                | PASSKEY_LOGIN_FINISH_ENDPOINT_ID => {
+                    PasskeyCaptured
+                        .call((headers, endpoint, request_params, request_body))
+                        .await
+                }
+                ADMIN_USERS_ENDPOINT_ID
+                | ADMIN_ROLE_GRANT_ENDPOINT_ID
+                | ADMIN_ROLE_REVOKE_ENDPOINT_ID
+                | ADMIN_USER_DISABLE_ENDPOINT_ID
+                | ADMIN_USER_ENABLE_ENDPOINT_ID
+                | ADMIN_USER_DELETE_ENDPOINT_ID
+                | ADMIN_PASSWORD_RESET_ENDPOINT_ID => {
+                    AdminCaptured
+                        .call((headers, endpoint, request_params, request_body))
+                        .await
+                }
+                ADMIN_API_KEYS_ENDPOINT_ID
+                | ADMIN_API_KEY_CREATE_ENDPOINT_ID
+                | ADMIN_API_KEY_REVOKE_ENDPOINT_ID => {
+                    ApiKeysCaptured
+                        .call((headers, endpoint, request_params, request_body))
+                        .await
+                }
+                ADMIN_USAGE_ENDPOINT_ID => {
+                    UsageCaptured
+                        .call((headers, endpoint, request_params, request_body))
+                        .await
+                }
+                ADMIN_STATISTICS_ENDPOINT_ID => {
+                    StatisticsCaptured
+                        .call((headers, endpoint, request_params, request_body))
+                        .await
+                }
                 _ => {
                     inner
                         .call((headers, endpoint, request_params, request_body))
@@ -0,0 +1,528 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+//!
+//! Password reset and email verification through emailed links. The links' tokens are single-use
+//! and only their hashes are kept on the embedded store, along with the user they were sent to.
+//! Each user's latest token per purpose is indexed on a second table, so issuing a new one revokes
+//! the previous without scanning the tokens, while the expired ones are removed periodically.
+//!
+
+use crate::*;
+
+use waveless_commons::mail::*;
+use waveless_commons::store::*;
+
+use serde::{Deserialize, Serialize};
+
+/// A token sent by mail, which keeps its own expiration.
+#[derive(Clone, PartialEq, Serialize, Deserialize, Debug)]
+struct MailTokenEntry {
+    purpose: MailTemplate,
+    user_id: UserId,
+    /// Name of the authentication method the user belongs to.
+    auth_method: CompactString,
+    expires_at: i64,
+}
+
+/// Mails requested per account within the window.
+const MAIL_ACCOUNT_MAX_REQUESTS: usize = 3;
+
+/// Mails requested per client address within the window, higher than the accounts' as many users
+/// may share an address.
+const MAIL_ADDRESS_MAX_REQUESTS: usize = 10;
+
+const MAIL_REQUESTS_WINDOW: Duration = Duration::from_secs(900);
+
+/// How often the expired tokens are removed.
+const MAIL_TOKENS_SWEEP_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Mails requested by each key (`account/{method}/{name}` or `address/{ip}`) and when their
+/// window started.
+/// NOTE: it's kept in memory, so each instance limits the requests it receives.
+static MAIL_REQUESTS: LazyLock<DashMap<CompactString, (usize, Instant)>> =
+    LazyLock::new(DashMap::new);
+
+fn token_store(mailing: &Mailing) -> Result<EmbeddedStore<[u8; 32], MailTokenEntry>> {
+    EmbeddedStore::open(mailing.path(), mailing.table_name())
+}
+
+/// The latest token's key of each user and purpose.
+fn user_token_store(mailing: &Mailing) -> Result<EmbeddedStore<[u8; 32], [u8; 32]>> {
+    EmbeddedStore::open(mailing.path(), &format!("{}_by_user", mailing.table_name()))
+}
+
+fn token_key(token: &str) -> [u8; 32] {
+    *blake3::hash(token.as_bytes()).as_bytes()
+}
+
+fn user_token_key(purpose: MailTemplate, auth_method: &str, user_id: UserId) -> [u8; 32] {
+    *blake3::hash(format!("{}/{}/{}", purpose, auth_method, user_id).as_bytes()).as_bytes()
+}
+
+/// Counts the mail request of the account and the client address, rejecting it once either has
+/// requested too many within the window.
+/// NOTE: requests are counted whether the account exists or not, so it cannot be told.
+fn check_mail_requests(
+    auth_method: &Arc<dyn AnyAuthenticationMethod>,
+    request_params: &HashMap<CompactString, CompactString>,
+    headers: &HeaderMap,
+) -> Result<(), RequestError> {
+    let mut keys = CheapVec::<_, 2>::new();
+
+    if let Some(account) = auth_method.login_account(request_params) {
+        keys.push((
+            format!(
+                "account/{}/{}",
+                auth_method.name(),
+                account.trim().to_lowercase()
+            )
+            .to_compact_string(),
+            MAIL_ACCOUNT_MAX_REQUESTS,
+        ));
+    }
+
+    if let Some(address) = headers
+        .get(server::PEER_ADDR_HEADER)
+        .and_then(|address| address.to_str().ok())
+    {
+        keys.push((
+            format!("address/{}", address).to_compact_string(),
+            MAIL_ADDRESS_MAX_REQUESTS,
+        ));
+    }
+
+    let now = Instant::now();
+
+    let mut retry_after = None;
+
+    for (key, max_requests) in keys {
+        let mut requests = MAIL_REQUESTS.entry(key).or_insert((0, now));
+
+        if now.saturating_duration_since(requests.1) >= MAIL_REQUESTS_WINDOW {
+            *requests = (0, now);
+        }
+
+        requests.0 += 1;
+
+        if requests.0 > max_requests {
+            retry_after = retry_after.max(Some(
+                (requests.1 + MAIL_REQUESTS_WINDOW).saturating_duration_since(now),
+            ));
+        }
+    }
+
+    match retry_after {
+        Some(retry_after) => Err(RequestError::Throttled(
+            retry_after,
+            "Too many mails requested, try again later.".to_compact_string(),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Looks the user up and mails it the link in the background, so the response is the same
+/// whether the user exists or not. Failures are only logged for the same reason.
+fn send_link_in_background(
+    mailing: Mailing,
+    purpose: MailTemplate,
+    auth_method: Arc<dyn AnyAuthenticationMethod>,
+    auth_db: Arc<dyn AnyDatabaseConnection>,
+    request_params: HashMap<CompactString, CompactString>,
+    link: CompactString,
+    max_age: usize,
+) {
+    tokio::spawn(async move {
+        let user = match auth_method.find(auth_db.to_owned(), request_params).await {
+            Ok(Some(user)) => user,
+            Ok(None) => return,
+            Err(err) => {
+                error!("Cannot find the user to mail the {} link. {}", purpose, err);
+                return;
+            }
+        };
+
+        let (user_id, email) = user;
+
+        // Verified emails aren't verified again.
+        if purpose == MailTemplate::EmailVerification {
+            match auth_method.email_verified(auth_db, user_id).await {
+                Ok(false) => {}
+                Ok(true) => return,
+                Err(err) => {
+                    error!("Cannot check the email of user {}. {}", user_id, err);
+                    return;
+                }
+            }
+        }
+
+        if let Err(err) = send_link(
+            &mailing,
+            purpose,
+            &auth_method,
+            user_id,
+            &email,
+            &link,
+            max_age,
+        )
+        .await
+        {
+            error!(
+                "Cannot send the {} link of user {}. {}",
+                purpose, user_id, err
+            );
+        }
+    });
+}
+
+/// Issues a new token for the user and mails it within the link, where `{token}` is replaced.
+/// NOTE: the user's previous tokens for the same purpose stop being valid.
+pub async fn send_link(
+    mailing: &Mailing,
+    purpose: MailTemplate,
+    auth_method: &Arc<dyn AnyAuthenticationMethod>,
+    user_id: UserId,
+    email: &str,
+    link: &str,
+    max_age: usize,
+) -> Result<()> {
+    let project = RuntimeCx::acquire()
+        .build()
+        .read()
+        .await
+        .config()
+        .name()
+        .to_owned();
+
+    let (store, user_store) = (token_store(mailing)?, user_token_store(mailing)?);
+
+    let auth_method_name = auth_method.name().to_compact_string();
+
+    let now = chrono::Utc::now().timestamp();
+
+    let user_key = user_token_key(purpose, &auth_method_name, user_id);
+
+    if let Some(previous) = user_store.get(user_key).await? {
+        store.remove(previous).await?;
+    }
+
+    let token = Alphanumeric.sample_string(&mut rand::rng(), 40);
+
+    store
+        .insert(
+            token_key(&token),
+            MailTokenEntry {
+                purpose,
+                user_id,
+                auth_method: auth_method_name,
+                expires_at: now + max_age as i64,
+            },
+        )
+        .await?;
+
+    user_store.insert(user_key, token_key(&token)).await?;
+
+    let mail = mailing
+        .render(
+            purpose,
+            email,
+            &[
+                ("project", project.as_str()),
+                ("link", link.replace("{token}", &token).as_str()),
+                ("minutes", (max_age / 60).to_string().as_str()),
+            ],
+        )
+        .await?;
+
+    mailing.send(mail).await
+}
+
+/// Removes the token, returning its entry if it was still valid for the given purpose.
+async fn consume_token(
+    mailing: &Mailing,
+    purpose: MailTemplate,
+    token: &str,
+) -> Result<Option<MailTokenEntry>> {
+    let store = token_store(mailing)?;
+
+    let key = token_key(token);
+
+    let Some(entry) = store.get(key).await? else {
+        return Ok(None);
+    };
+
+    // Concurrent requests with the same token fail but for the one which removes it.
+    if entry.purpose != purpose || !store.remove(key).await? {
+        return Ok(None);
+    }
+
+    if entry.expires_at <= chrono::Utc::now().timestamp() {
+        return Ok(None);
+    }
+
+    Ok(Some(entry))
+}
+
+/// Password reset and email verification endpoints.
+#[derive(Clone, Constructor, Debug)]
+pub struct EmailCaptured;
+
+impl Service<RequestParamsExtractorRequest> for EmailCaptured {
+    type Response = ExecuteOutput;
+
+    type Error = RequestError;
+
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    #[instrument(skip_all)]
+    fn call(&mut self, cx: RequestParamsExtractorRequest) -> Self::Future {
+        let future: Pin<_> = Box::pin(async move {
+            let (headers, endpoint, request_params, _) = cx;
+
+            let request_params = request_params
+                .iter()
+                .filter_map(|entry| {
+                    if let (key, ExecuteParamValue::Client(Some(value))) = entry {
+                        Some((key.to_owned(), value.to_owned()))
+                    } else {
+                        None
+                    }
+                })
+                .collect::<HashMap<CompactString, CompactString>>();
+
+            let (auth_config, mailing) = {
+                let _build_lock = RuntimeCx::acquire().build();
+
+                let build = _build_lock.read().await;
+
+                (
+                    build.config().authentication().to_owned(),
+                    build.config().mailing().to_owned(),
+                )
+            };
+
+            let auth_config = auth_config.ok_or(RequestError::Other(anyhow!(
+                "Authentication is not set for the current build."
+            )))?;
+
+            let mailing = mailing.ok_or(RequestError::Other(anyhow!(
+                "Mailing is not set for the current build."
+            )))?;
+
+            let databases = DATABASES_CONNS.get().unwrap();
+
+            let auth_db = |auth_method: &Arc<dyn AnyAuthenticationMethod>| {
+                databases.search(auth_method.db_id()).map_err(|_| {
+                    RequestError::Other(anyhow!(
+                        "Cannot get the database connection for '{}'.",
+                        auth_method.db_id().unwrap_or("main".to_compact_string())
+                    ))
+                })
+            };
+
+            let token = request_params.get("token");
+
+            match endpoint.id().as_str() {
+                PASSWORD_RESET_REQUEST_ENDPOINT_ID => {
+                    let Some(settings) = auth_config.password_reset() else {
+                        return Err(RequestError::Other(anyhow!(
+                            "Password reset is not set for the current build."
+                        )));
+                    };
+
+                    let auth_method = select_auth_method(&auth_config, &headers)?;
+
+                    check_mail_requests(auth_method, &request_params, &headers)?;
+
+                    send_link_in_background(
+                        mailing,
+                        MailTemplate::PasswordReset,
+                        auth_method.to_owned(),
+                        auth_db(auth_method)?,
+                        request_params,
+                        settings.link().to_owned(),
+                        *settings.max_age(),
+                    );
+
+                    Ok(ExecuteOutput::Json(None, json!({})))
+                }
+                PASSWORD_RESET_ENDPOINT_ID => {
+                    let (Some(token), Some(password)) = (token, request_params.get("password"))
+                    else {
+                        return Err(RequestError::Expected(
+                            StatusCode::BAD_REQUEST,
+                            "Both the reset `token` and the new `password` are required."
+                                .to_compact_string(),
+                        ));
+                    };
+
+                    let Some(entry) =
+                        consume_token(&mailing, MailTemplate::PasswordReset, token).await?
+                    else {
+                        return Err(RequestError::Expected(
+                            StatusCode::BAD_REQUEST,
+                            "Invalid or expired reset token.".to_compact_string(),
+                        ));
+                    };
+
+                    let auth_method = auth_config
+                        .backends()
+                        .iter()
+                        .find(|auth_method| auth_method.name() == entry.auth_method)
+                        .ok_or(RequestError::Other(anyhow!(
+                            "Cannot find the '{}' authentication method.",
+                            entry.auth_method
+                        )))?;
+
+                    let auth_db = auth_db(auth_method)?;
+
+                    auth_method
+                        .set_password(auth_db.to_owned(), entry.user_id, password.to_owned())
+                        .await?;
+
+                    // Following the emailed link proves the email's ownership too.
+                    if auth_config.email_verification().is_some() {
+                        if let Err(err) =
+                            auth_method.set_email_verified(auth_db, entry.user_id).await
+                        {
+                            warn!("Cannot verify the email of user {}. {}", entry.user_id, err);
+                        }
+                    }
+
+                    // The sessions opened with the old password must not outlive it.
+                    let session_method = auth_config.session();
+
+                    let Ok(session_db) = databases.search(session_method.db_id()) else {
+                        return Err(RequestError::Other(anyhow!(
+                            "Cannot get the database connection for '{}'.",
+                            session_method.db_id().unwrap_or("main".to_compact_string())
+                        )));
+                    };
+
+                    session_method
+                        .invalidate(session_db, entry.user_id, None)
+                        .await?;
+
+                    Ok(ExecuteOutput::Json(None, json!({})))
+                }
+                EMAIL_VERIFY_ENDPOINT_ID => {
+                    let Some(token) = token else {
+                        return Err(RequestError::Expected(
+                            StatusCode::BAD_REQUEST,
+                            "The verification `token` is required.".to_compact_string(),
+                        ));
+                    };
+
+                    let Some(entry) =
+                        consume_token(&mailing, MailTemplate::EmailVerification, token).await?
+                    else {
+                        return Err(RequestError::Expected(
+                            StatusCode::BAD_REQUEST,
+                            "Invalid or expired verification token.".to_compact_string(),
+                        ));
+                    };
+
+                    let auth_method = auth_config
+                        .backends()
+                        .iter()
+                        .find(|auth_method| auth_method.name() == entry.auth_method)
+                        .ok_or(RequestError::Other(anyhow!(
+                            "Cannot find the '{}' authentication method.",
+                            entry.auth_method
+                        )))?;
+
+                    auth_method
+                        .set_email_verified(auth_db(auth_method)?, entry.user_id)
+                        .await?;
+
+                    Ok(ExecuteOutput::Json(None, json!({ "verified": true })))
+                }
+                EMAIL_VERIFY_RESEND_ENDPOINT_ID => {
+                    let Some(settings) = auth_config.email_verification() else {
+                        return Err(RequestError::Other(anyhow!(
+                            "Email verification is not set for the current build."
+                        )));
+                    };
+
+                    let auth_method = select_auth_method(&auth_config, &headers)?;
+
+                    check_mail_requests(auth_method, &request_params, &headers)?;
+
+                    send_link_in_background(
+                        mailing,
+                        MailTemplate::EmailVerification,
+                        auth_method.to_owned(),
+                        auth_db(auth_method)?,
+                        request_params,
+                        settings.link().to_owned(),
+                        *settings.max_age(),
+                    );
+
+                    Ok(ExecuteOutput::Json(None, json!({})))
+                }
+                _ => Err(RequestError::Other(anyhow!(
+                    "Unexpected email endpoint '{}'.",
+                    endpoint.id()
+                ))),
+            }
+        })
+        .into();
+
+        future as Self::Future // Actually, this is not an error! https://github.com/rust-lang/rust/issues/92929
+    }
+}
+
+/// Removes the expired tokens, along with the users' index entries pointing to removed tokens,
+/// and the ended windows of the mail requests.
+/// The build is read on every run so it follows reloads.
+#[instrument(skip_all)]
+pub async fn sweep_mail_tokens() {
+    loop {
+        tokio::time::sleep(MAIL_TOKENS_SWEEP_INTERVAL).await;
+
+        let now = Instant::now();
+
+        MAIL_REQUESTS.retain(|_, (_, started_at)| {
+            now.saturating_duration_since(*started_at) < MAIL_REQUESTS_WINDOW
+        });
+
+        let Some(mailing) = RuntimeCx::acquire()
+            .build()
+            .read()
+            .await
+            .config()
+            .mailing()
+            .to_owned()
+        else {
+            continue;
+        };
+
+        let swept = async {
+            let (store, user_store) = (token_store(&mailing)?, user_token_store(&mailing)?);
+
+            let now = chrono::Utc::now().timestamp();
+
+            let removed = store.retain(move |_, entry| entry.expires_at > now).await?;
+
+            let live = store
+                .entries(|_, _| true)
+                .await?
+                .into_iter()
+                .map(|(key, _)| key)
+                .collect::<std::collections::HashSet<_>>();
+
+            user_store.retain(move |_, key| live.contains(&key)).await?;
+
+            Ok::<_, anyhow::Error>(removed)
+        };
+
+        match swept.await {
+            Ok(removed) => debug!("{} expired mail tokens have been removed.", removed),
+            Err(err) => error!("Cannot remove the expired mail tokens. {}", err),
+        }
+    }
+}
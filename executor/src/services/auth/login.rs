@@ -32,7 +32,7 @@ impl Service<RequestParamsExtractorRequest> for LoginCaptured {
                         None
                     }
                 })
-                .collect();
+                .collect::<HashMap<CompactString, CompactString>>();
 
             let auth_config = RuntimeCx::acquire()
                 .build()
@@ -47,36 +47,7 @@ impl Service<RequestParamsExtractorRequest> for LoginCaptured {
 
             let databases = DATABASES_CONNS.get().unwrap();
 
-            let auth_method = {
-                if auth_config.backends().len() == 1 {
-                    auth_config.backends().first().unwrap()
-                } else if let Some(auth_backend_name) = headers.get("AuthenticationType") {
-                    // Checks for authentication type header if set.
-                    if let Ok(auth_backend_name) = auth_backend_name.to_str() {
-                        auth_config
-                            .backends()
-                            .iter()
-                            .find(|auth_method| auth_method.name() == auth_backend_name)
-                            .ok_or(RequestError::Expected(
-                                StatusCode::BAD_REQUEST,
-                                "Cannot find the requested authentication method."
-                                    .to_compact_string(),
-                            ))?
-                    } else {
-                        return Err(RequestError::Expected(
-                            StatusCode::BAD_REQUEST,
-                            "Cannot deserialize the `AuthenticationMethod` header."
-                                .to_compact_string(),
-                        ));
-                    }
-                } else {
-                    return Err(RequestError::Expected(
-                        StatusCode::BAD_REQUEST,
-                        "No authentication method has been set. HINT: set one using the `AuthenticationMethod` header."
-                            .to_compact_string(),
-                    ));
-                }
-            };
+            let auth_method = select_auth_method(&auth_config, &headers)?;
 
             let Ok(auth_db) = databases.search(auth_method.db_id()) else {
                 return Err(RequestError::Other(anyhow!(
@@ -85,60 +56,237 @@ impl Service<RequestParamsExtractorRequest> for LoginCaptured {
                 )));
             };
 
-            match auth_method.check(auth_db, request_params).await {
+            let throttling = auth_config.login_throttling();
+
+            check_login_throttle(auth_method, &request_params, &headers, throttling)?;
+
+            match auth_method
+                .check(auth_db.to_owned(), request_params.to_owned())
+                .await
+            {
                 Ok(Some(user_id)) => {
-                    // Create a new session.
-                    let session_method = auth_config.session();
-
-                    let Ok(session_db) = databases.search(session_method.db_id()) else {
-                        return Err(RequestError::Other(anyhow!(
-                            "Cannot get the database connection for '{}'.",
-                            session_method.db_id().unwrap_or("main".to_compact_string())
-                        )));
-                    };
-
-                    let session_token =
-                        session_method
-                            .new(session_db, user_id)
-                            .await
-                            .map_err(|err| {
-                                RequestError::Other(anyhow!(
-                                    "Cannot check the session token. {}",
-                                    err
-                                ))
-                            })?;
-
-                    let mut headers = HashMap::new();
-
-                    // TODO: should add the secure param to `Set-Cookie`.
-                    headers.insert(
-                        "Set-Cookie".to_compact_string(),
-                        format!(
-                            "Authorization={}; SameSite=Lax; Path=/; {}",
-                            session_token,
-                            session_method
-                                .max_age()
-                                .map(|max_age| format!("Max-Age={}", max_age))
-                                .unwrap_or_default()
-                        )
-                        .to_compact_string(),
-                    );
-
-                    Ok(ExecuteOutput::Json(
-                        Some(headers),
-                        json!({
-                            "token": session_token
-                        }),
-                    ))
+                    complete_login(
+                        &auth_config,
+                        auth_method,
+                        auth_db,
+                        user_id,
+                        &request_params,
+                        &headers,
+                    )
+                    .await
                 }
+                // The failure has already been counted before checking the credentials.
                 Ok(None) => Err(RequestError::Expected(
                     StatusCode::FORBIDDEN,
                     format!("Login failed, invalid credentials.").to_compact_string(),
                 )),
                 Err(err) => Err(RequestError::Other(err)),
             }
-        }).into();
+        })
+        .into();
 
         future as Self::Future // Actually, this is not an error! https://github.com/rust-lang/rust/issues/92929
     }
 }
+
+/// Completes the login of the user once its credentials are checked, requiring its email to be
+/// verified and its second factor when they're enabled. The login's throttling attempt is only
+/// recorded as successful once a session is issued, so a failed second factor counts as a failure.
+pub async fn complete_login(
+    auth_config: &Authentication,
+    auth_method: &Arc<dyn AnyAuthenticationMethod>,
+    auth_db: Arc<dyn AnyDatabaseConnection>,
+    user_id: UserId,
+    request_params: &HashMap<CompactString, CompactString>,
+    headers: &HeaderMap,
+) -> Result<ExecuteOutput, RequestError> {
+    // Users must have verified their email when it's required.
+    if auth_config
+        .email_verification()
+        .as_ref()
+        .is_some_and(|settings| *settings.required())
+        && !auth_method
+            .email_verified(auth_db.to_owned(), user_id)
+            .await?
+    {
+        release_login_attempt(
+            auth_method,
+            request_params,
+            headers,
+            auth_config.login_throttling(),
+        );
+
+        return Err(RequestError::Expected(
+            StatusCode::FORBIDDEN,
+            "Login failed, the email hasn't been verified yet.".to_compact_string(),
+        ));
+    }
+
+    // Users with TOTP enabled must provide a code, either in the same request or
+    // in a second step using the returned challenge.
+    if let Some(totp_settings) = auth_config.totp() {
+        if let Some(totp) = auth_method.totp(auth_db.to_owned(), user_id).await? {
+            if request_params.contains_key("totp_code")
+                || request_params.contains_key("recovery_code")
+            {
+                check_second_factor(auth_method, auth_db, user_id, totp, request_params).await?;
+            } else {
+                release_login_attempt(
+                    auth_method,
+                    request_params,
+                    headers,
+                    auth_config.login_throttling(),
+                );
+
+                let challenge = TotpChallenges::issue(
+                    user_id,
+                    auth_method.name().to_compact_string(),
+                    login_account_key(auth_method, request_params),
+                    totp_settings,
+                );
+
+                return Ok(ExecuteOutput::Json(
+                    None,
+                    json!({
+                        "totp_required": true,
+                        "challenge": challenge
+                    }),
+                ));
+            }
+        }
+    }
+
+    let session = new_session(auth_config, user_id, headers).await?;
+
+    record_successful_login(
+        auth_method,
+        request_params,
+        headers,
+        auth_config.login_throttling(),
+    );
+
+    Ok(session)
+}
+
+/// Selects the authentication method, when there are many it must be set using the `AuthenticationType` header.
+pub fn select_auth_method<'a>(
+    auth_config: &'a Authentication,
+    headers: &HeaderMap,
+) -> Result<&'a Arc<dyn AnyAuthenticationMethod>, RequestError> {
+    if auth_config.backends().len() == 1 {
+        Ok(auth_config.backends().first().unwrap())
+    } else if let Some(auth_backend_name) = headers.get("AuthenticationType") {
+        // Checks for authentication type header if set.
+        if let Ok(auth_backend_name) = auth_backend_name.to_str() {
+            auth_config
+                .backends()
+                .iter()
+                .find(|auth_method| auth_method.name() == auth_backend_name)
+                .ok_or(RequestError::Expected(
+                    StatusCode::BAD_REQUEST,
+                    "Cannot find the requested authentication method.".to_compact_string(),
+                ))
+        } else {
+            Err(RequestError::Expected(
+                StatusCode::BAD_REQUEST,
+                "Cannot deserialize the `AuthenticationMethod` header.".to_compact_string(),
+            ))
+        }
+    } else {
+        Err(RequestError::Expected(
+            StatusCode::BAD_REQUEST,
+            "No authentication method has been set. HINT: set one using the `AuthenticationMethod` header."
+                .to_compact_string(),
+        ))
+    }
+}
+
+/// Creates a new session for the given user, returning its token both in the body and as a cookie.
+pub async fn new_session(
+    auth_config: &Authentication,
+    user_id: UserId,
+    headers: &HeaderMap,
+) -> Result<ExecuteOutput, RequestError> {
+    let session_method = auth_config.session();
+
+    let Ok(session_db) = DATABASES_CONNS
+        .get()
+        .unwrap()
+        .search(session_method.db_id())
+    else {
+        return Err(RequestError::Other(anyhow!(
+            "Cannot get the database connection for '{}'.",
+            session_method.db_id().unwrap_or("main".to_compact_string())
+        )));
+    };
+
+    // The roles are only looked up when they're embedded in the session token.
+    let roles = match auth_config.role() {
+        Some(role_method) if session_method.embeds_roles() => {
+            let Ok(role_db) = DATABASES_CONNS.get().unwrap().search(role_method.db_id()) else {
+                return Err(RequestError::Other(anyhow!(
+                    "Cannot get the database connection for '{}'.",
+                    role_method.db_id().unwrap_or("main".to_compact_string())
+                )));
+            };
+
+            role_method.get_all(role_db, user_id).await?
+        }
+        _ => CheapVec::new(),
+    };
+
+    let (session_token, refresh_token) = session_method
+        .new_with_refresh(session_db, user_id, roles, session_client(headers))
+        .await
+        .map_err(|err| RequestError::Other(anyhow!("Cannot check the session token. {}", err)))?;
+
+    Ok(session_output(session_method, session_token, refresh_token))
+}
+
+/// Identifies the client starting a session, the IP is the one set by the server for the connection.
+pub fn session_client(headers: &HeaderMap) -> SessionClient {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_compact_string())
+    };
+
+    SessionClient::new(header("User-Agent"), header(server::PEER_ADDR_HEADER))
+}
+
+/// Returns the session token both in the body and as a cookie, along with the refresh token if any.
+/// NOTE: the refresh token is only returned in the body, it must be sent to the `refresh` endpoint.
+pub fn session_output(
+    session_method: &Arc<dyn AnySessionMethod>,
+    session_token: CompactString,
+    refresh_token: Option<CompactString>,
+) -> ExecuteOutput {
+    let mut headers = HashMap::new();
+
+    // TODO: should add the secure param to `Set-Cookie`.
+    headers.insert(
+        "Set-Cookie".to_compact_string(),
+        format!(
+            "Authorization={}; SameSite=Lax; Path=/; {}",
+            session_token,
+            session_method
+                .max_age()
+                .map(|max_age| format!("Max-Age={}", max_age))
+                .unwrap_or_default()
+        )
+        .to_compact_string(),
+    );
+
+    let body = match refresh_token {
+        Some(refresh_token) => json!({
+            "token": session_token,
+            "refresh_token": refresh_token
+        }),
+        None => json!({
+            "token": session_token
+        }),
+    };
+
+    ExecuteOutput::Json(Some(headers), body)
+}
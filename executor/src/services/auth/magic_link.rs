@@ -0,0 +1,324 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+use crate::*;
+
+use waveless_commons::mail::{MailTemplate, Mailing};
+
+/// Links requested per address, along with the start of their rate window.
+/// NOTE: it's kept in memory, so each instance limits the requests it receives.
+static LINK_REQUESTS: LazyLock<DashMap<CompactString, (usize, Instant)>> =
+    LazyLock::new(DashMap::new);
+
+/// How often the expired logins and the ended rate windows are removed.
+const MAGIC_LOGINS_SWEEP_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Counts a request for the address, returning whether it's within the rate limit.
+fn within_rate_limit(address: &str, method: &SqlMagicLinkAuthenticationMethod) -> bool {
+    let window = Duration::from_secs(*method.rate_window() as u64);
+
+    let mut requests = LINK_REQUESTS
+        .entry(address.trim().to_lowercase().to_compact_string())
+        .or_insert((0, Instant::now()));
+
+    // Ended windows start over, while the sweep removes those which aren't requested again.
+    if requests.1.elapsed() >= window {
+        *requests = (0, Instant::now());
+    }
+
+    requests.0 += 1;
+
+    requests.0 <= *method.max_requests()
+}
+
+/// Removes the expired magic logins and the ended rate windows periodically.
+pub async fn sweep_magic_logins() {
+    loop {
+        tokio::time::sleep(MAGIC_LOGINS_SWEEP_INTERVAL).await;
+
+        let Some(auth_config) = RuntimeCx::acquire()
+            .build()
+            .read()
+            .await
+            .config()
+            .authentication()
+            .to_owned()
+        else {
+            continue;
+        };
+
+        for backend in auth_config.backends() {
+            let Ok(magic_method) = backend
+                .to_owned()
+                .into_arc_any()
+                .downcast::<SqlMagicLinkAuthenticationMethod>()
+            else {
+                continue;
+            };
+
+            let window = Duration::from_secs(*magic_method.rate_window() as u64);
+
+            LINK_REQUESTS.retain(|_, (_, started_at)| started_at.elapsed() < window);
+
+            match magic_method.sweep().await {
+                Ok(removed) => debug!("{} expired magic logins have been removed.", removed),
+                Err(err) => error!("Cannot remove the expired magic logins. {}", err),
+            }
+        }
+    }
+}
+
+/// Looks the user up, issues its login and mails it the link in the background, so the response
+/// is the same whether the user exists or not. Failures are only logged for the same reason.
+fn send_magic_link_in_background(
+    mailing: Mailing,
+    auth_method: Arc<dyn AnyAuthenticationMethod>,
+    magic_method: Arc<SqlMagicLinkAuthenticationMethod>,
+    auth_db: Arc<dyn AnyDatabaseConnection>,
+    address: CompactString,
+    request_params: HashMap<CompactString, CompactString>,
+    allow_signup: bool,
+) {
+    tokio::spawn(async move {
+        let project = RuntimeCx::acquire()
+            .build()
+            .read()
+            .await
+            .config()
+            .name()
+            .to_owned();
+
+        let user = match auth_method.find(auth_db, request_params.to_owned()).await {
+            Ok(user) => user,
+            Err(err) => {
+                error!("Cannot find the user to mail the login link. {}", err);
+                return;
+            }
+        };
+
+        // Accounts which don't exist yet are only created once their login is completed.
+        let (user_id, email, signup_fields) = match user {
+            Some((user_id, email)) => (Some(user_id), email, HashMap::new()),
+            None if allow_signup => {
+                let signup_fields = request_params
+                    .iter()
+                    .filter(|(key, _)| {
+                        *key == magic_method.name_field()
+                            || magic_method.extra_fields().contains(*key)
+                    })
+                    .map(|(key, value)| (key.to_owned(), value.to_owned()))
+                    .collect();
+
+                (None, address.to_owned(), signup_fields)
+            }
+            None => return,
+        };
+
+        let sent = match magic_method.issue(&address, user_id, signup_fields).await {
+            Ok(login) => {
+                let link = magic_method.link().replace("{token}", login.token());
+
+                let minutes = (magic_method.max_age() / 60).to_string();
+
+                match mailing
+                    .render(
+                        MailTemplate::MagicLink,
+                        &email,
+                        &[
+                            ("project", project.as_str()),
+                            ("link", link.as_str()),
+                            ("code", login.code().as_str()),
+                            ("minutes", minutes.as_str()),
+                        ],
+                    )
+                    .await
+                {
+                    Ok(mail) => mailing.send(mail).await,
+                    Err(err) => Err(err),
+                }
+            }
+            Err(err) => Err(err),
+        };
+
+        if let Err(err) = sent {
+            error!("Cannot send the login link of user {:?}. {}", user_id, err);
+        }
+    });
+}
+
+/// Mails passwordless login links and completes the logins of the mailed links.
+#[derive(Clone, Constructor, Debug)]
+pub struct MagicLinkCaptured;
+
+impl Service<RequestParamsExtractorRequest> for MagicLinkCaptured {
+    type Response = ExecuteOutput;
+
+    type Error = RequestError;
+
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    #[instrument(skip_all)]
+    fn call(&mut self, cx: RequestParamsExtractorRequest) -> Self::Future {
+        let future: Pin<_> = Box::pin(async move {
+            let (headers, endpoint, request_params, _) = cx;
+
+            let (auth_config, mailing) = {
+                let _build_lock = RuntimeCx::acquire().build();
+
+                let build = _build_lock.read().await;
+
+                (
+                    build.config().authentication().to_owned(),
+                    build.config().mailing().to_owned(),
+                )
+            };
+
+            let auth_config = auth_config.ok_or(RequestError::Other(anyhow!(
+                "Authentication is not set for the current build."
+            )))?;
+
+            let Some((auth_method, magic_method)) =
+                auth_config.backends().iter().find_map(|backend| {
+                    backend
+                        .to_owned()
+                        .into_arc_any()
+                        .downcast::<SqlMagicLinkAuthenticationMethod>()
+                        .ok()
+                        .map(|magic_method| (backend, magic_method))
+                })
+            else {
+                return Err(RequestError::Other(anyhow!(
+                    "There is no magic link authentication method for the current build."
+                )));
+            };
+
+            match endpoint.id().as_str() {
+                MAGIC_LINK_REQUEST_ENDPOINT_ID => {
+                    let mailing = mailing.ok_or(RequestError::Other(anyhow!(
+                        "Mailing is not set for the current build."
+                    )))?;
+
+                    let request_params = request_params
+                        .iter()
+                        .filter_map(|entry| {
+                            if let (key, ExecuteParamValue::Client(Some(value))) = entry {
+                                Some((key.to_owned(), value.to_owned()))
+                            } else {
+                                None
+                            }
+                        })
+                        .collect::<HashMap<CompactString, CompactString>>();
+
+                    let Some(address) = request_params.get(magic_method.name_field()) else {
+                        return Err(RequestError::Expected(
+                            StatusCode::BAD_REQUEST,
+                            format!("The `{}` is required.", magic_method.name_field())
+                                .to_compact_string(),
+                        ));
+                    };
+
+                    // Limited by address whether the user exists or not, so accounts aren't disclosed.
+                    if !within_rate_limit(address, &magic_method) {
+                        return Err(RequestError::Expected(
+                            StatusCode::TOO_MANY_REQUESTS,
+                            "Too many login links requested for this address, try again later."
+                                .to_compact_string(),
+                        ));
+                    }
+
+                    let Ok(auth_db) = DATABASES_CONNS.get().unwrap().search(auth_method.db_id())
+                    else {
+                        return Err(RequestError::Other(anyhow!(
+                            "Cannot get the database connection for '{}'.",
+                            auth_method.db_id().unwrap_or("main".to_compact_string())
+                        )));
+                    };
+
+                    send_magic_link_in_background(
+                        mailing,
+                        auth_method.to_owned(),
+                        magic_method,
+                        auth_db,
+                        address.to_owned(),
+                        request_params.to_owned(),
+                        *auth_config.allow_signup(),
+                    );
+
+                    Ok(ExecuteOutput::Json(None, json!({})))
+                }
+                MAGIC_LINK_VERIFY_ENDPOINT_ID => {
+                    let request_params = request_params
+                        .iter()
+                        .filter_map(|entry| {
+                            if let (key, ExecuteParamValue::Client(Some(value))) = entry {
+                                Some((key.to_owned(), value.to_owned()))
+                            } else {
+                                None
+                            }
+                        })
+                        .collect::<HashMap<CompactString, CompactString>>();
+
+                    let Ok(auth_db) = DATABASES_CONNS.get().unwrap().search(auth_method.db_id())
+                    else {
+                        return Err(RequestError::Other(anyhow!(
+                            "Cannot get the database connection for '{}'.",
+                            auth_method.db_id().unwrap_or("main".to_compact_string())
+                        )));
+                    };
+
+                    let throttling = auth_config.login_throttling();
+
+                    check_login_throttle(auth_method, &request_params, &headers, throttling)?;
+
+                    // Completed as a regular login, so TOTP and the email verification apply too.
+                    match magic_method
+                        .redeem(
+                            auth_db.to_owned(),
+                            request_params.to_owned(),
+                            *auth_config.allow_signup(),
+                        )
+                        .await
+                    {
+                        Ok(Some((user_id, created))) => {
+                            if created {
+                                assign_default_role(
+                                    &auth_config,
+                                    auth_method,
+                                    auth_db.to_owned(),
+                                    user_id,
+                                )
+                                .await?;
+                            }
+
+                            complete_login(
+                                &auth_config,
+                                auth_method,
+                                auth_db,
+                                user_id,
+                                &request_params,
+                                &headers,
+                            )
+                            .await
+                        }
+                        Ok(None) => Err(RequestError::Expected(
+                            StatusCode::FORBIDDEN,
+                            "Login failed, invalid credentials.".to_compact_string(),
+                        )),
+                        Err(err) => Err(RequestError::Other(err)),
+                    }
+                }
+                _ => Err(RequestError::Other(anyhow!(
+                    "Unexpected magic link endpoint '{}'.",
+                    endpoint.id()
+                ))),
+            }
+        })
+        .into();
+
+        future as Self::Future // Actually, this is not an error! https://github.com/rust-lang/rust/issues/92929
+    }
+}
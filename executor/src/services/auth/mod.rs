@@ -1,12 +1,28 @@
 // Waveless
 // Copyright (C) 2026 Oscar Alvarez Gonzalez
 
+pub mod admin;
+pub mod api_keys;
 pub mod capture;
+pub mod email;
 pub mod login;
+pub mod magic_link;
+pub mod oauth;
+pub mod passkey;
 pub mod session;
 pub mod signup;
+pub mod throttle;
+pub mod totp;
 
+pub use admin::*;
+pub use api_keys::*;
 pub use capture::*;
+pub use email::*;
 pub use login::*;
+pub use magic_link::*;
+pub use oauth::*;
+pub use passkey::*;
 pub use session::*;
 pub use signup::*;
+pub use throttle::*;
+pub use totp::*;
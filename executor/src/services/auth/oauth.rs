@@ -0,0 +1,154 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+use crate::*;
+
+/// Handles the OpenID Connect login, both redirecting to the provider and receiving its callback.
+#[derive(Clone, Constructor, Debug)]
+pub struct OAuthCaptured;
+
+impl Service<RequestParamsExtractorRequest> for OAuthCaptured {
+    type Response = ExecuteOutput;
+
+    type Error = RequestError;
+
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    #[instrument(skip_all)]
+    fn call(&mut self, cx: RequestParamsExtractorRequest) -> Self::Future {
+        let future: Pin<_> = Box::pin(async move {
+            let (request_headers, endpoint, request_params, _) = cx;
+
+            let request_params = request_params
+                .iter()
+                .filter_map(|entry| {
+                    if let (key, ExecuteParamValue::Client(Some(value))) = entry {
+                        Some((key.to_owned(), value.to_owned()))
+                    } else {
+                        None
+                    }
+                })
+                .collect::<HashMap<CompactString, CompactString>>();
+
+            let auth_config = RuntimeCx::acquire()
+                .build()
+                .read()
+                .await
+                .config()
+                .authentication()
+                .to_owned()
+                .ok_or(RequestError::Other(anyhow!(
+                    "Authentication is not set for the current build."
+                )))?;
+
+            let provider = request_params
+                .get("provider")
+                .ok_or(RequestError::Other(anyhow!("The provider is not set.")))?;
+
+            let Some(oidc) = auth_config.backends().iter().find_map(|backend| {
+                backend
+                    .to_owned()
+                    .into_arc_any()
+                    .downcast::<OidcAuthenticationMethod>()
+                    .ok()
+                    .filter(|oidc| oidc.provider() == provider)
+            }) else {
+                return Err(RequestError::Expected(
+                    StatusCode::NOT_FOUND,
+                    format!("Cannot find the '{}' provider.", provider).to_compact_string(),
+                ));
+            };
+
+            // Neither the authorization URL nor the session may be cached.
+            let mut headers = HashMap::from([(
+                "Cache-Control".to_compact_string(),
+                "no-store".to_compact_string(),
+            )]);
+
+            if endpoint.id() == OAUTH_START_ENDPOINT_ID {
+                let url = oidc.start().await?;
+
+                headers.insert("Location".to_compact_string(), url.to_owned());
+
+                return Ok(ExecuteOutput::Json(Some(headers), json!({ "url": url })));
+            }
+
+            // The provider may return an error instead of the code, e.g. when the user denies the access.
+            if let Some(error) = request_params.get("error") {
+                return Err(RequestError::Expected(
+                    StatusCode::FORBIDDEN,
+                    format!("Login failed, '{}' returned '{}'.", provider, error)
+                        .to_compact_string(),
+                ));
+            }
+
+            let (Some(code), Some(state)) =
+                (request_params.get("code"), request_params.get("state"))
+            else {
+                return Err(RequestError::Expected(
+                    StatusCode::BAD_REQUEST,
+                    "Both `code` and `state` are required.".to_compact_string(),
+                ));
+            };
+
+            let databases = DATABASES_CONNS.get().unwrap();
+
+            let Ok(auth_db) = databases.search(oidc.db_id()) else {
+                return Err(RequestError::Other(anyhow!(
+                    "Cannot get the database connection for '{}'.",
+                    oidc.db_id().unwrap_or("main".to_compact_string())
+                )));
+            };
+
+            let Some((user_id, roles)) = oidc.callback(auth_db, code, state).await? else {
+                return Err(RequestError::Expected(
+                    StatusCode::FORBIDDEN,
+                    "Login failed, invalid or expired authorization.".to_compact_string(),
+                ));
+            };
+
+            // Roles claimed by the provider are kept in sync on every login, only the roles declared
+            // on the project are revoked when they're no longer claimed.
+            if let (Some(roles), Some(role_method)) = (roles, auth_config.role()) {
+                let Ok(role_db) = databases.search(role_method.db_id()) else {
+                    return Err(RequestError::Other(anyhow!(
+                        "Cannot get the database connection for '{}'.",
+                        role_method.db_id().unwrap_or("main".to_compact_string())
+                    )));
+                };
+
+                let managed = auth_config
+                    .roles()
+                    .iter()
+                    .map(|definition| definition.name().to_owned())
+                    .collect::<Vec<_>>();
+
+                role_method.sync(role_db, user_id, &roles, &managed).await?;
+            }
+
+            let ExecuteOutput::Json(session_headers, body) =
+                new_session(&auth_config, user_id, &request_headers).await?
+            else {
+                return Err(RequestError::Other(anyhow!("Unexpected session's output.")));
+            };
+
+            headers.extend(session_headers.unwrap_or_default());
+
+            if let Some(post_login_redirect) = oidc.post_login_redirect() {
+                headers.insert(
+                    "Location".to_compact_string(),
+                    post_login_redirect.to_owned(),
+                );
+            }
+
+            Ok(ExecuteOutput::Json(Some(headers), body))
+        })
+        .into();
+
+        future as Self::Future // Actually, this is not an error! https://github.com/rust-lang/rust/issues/92929
+    }
+}
@@ -0,0 +1,187 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+use crate::*;
+
+/// Runs the passkeys' registration and login ceremonies.
+#[derive(Clone, Constructor, Debug)]
+pub struct PasskeyCaptured;
+
+impl Service<RequestParamsExtractorRequest> for PasskeyCaptured {
+    type Response = ExecuteOutput;
+
+    type Error = RequestError;
+
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    #[instrument(skip_all)]
+    fn call(&mut self, cx: RequestParamsExtractorRequest) -> Self::Future {
+        let future: Pin<_> = Box::pin(async move {
+            let (mut headers, endpoint, request_params, request_body) = cx;
+
+            let auth_config = RuntimeCx::acquire()
+                .build()
+                .read()
+                .await
+                .config()
+                .authentication()
+                .to_owned()
+                .ok_or(RequestError::Other(anyhow!(
+                    "Authentication is not set for the current build."
+                )))?;
+
+            let Some((auth_method, passkey_method)) =
+                auth_config.backends().iter().find_map(|backend| {
+                    backend
+                        .to_owned()
+                        .into_arc_any()
+                        .downcast::<SqlPasskeyAuthenticationMethod>()
+                        .ok()
+                        .map(|passkey_method| (backend, passkey_method))
+                })
+            else {
+                return Err(RequestError::Other(anyhow!(
+                    "There is no passkey authentication method for the current build."
+                )));
+            };
+
+            // The login is completed as a regular one, so TOTP and the email verification apply too.
+            if endpoint.id() == PASSKEY_LOGIN_FINISH_ENDPOINT_ID {
+                headers.insert(
+                    "AuthenticationType",
+                    HeaderValue::from_str(auth_method.name())
+                        .map_err(|err| RequestError::Other(err.into()))?,
+                );
+
+                return LoginCaptured
+                    .call((headers, endpoint, request_params, request_body))
+                    .await;
+            }
+
+            let client_params = request_params
+                .iter()
+                .filter_map(|entry| {
+                    if let (key, ExecuteParamValue::Client(Some(value))) = entry {
+                        Some((key.to_owned(), value.to_owned()))
+                    } else {
+                        None
+                    }
+                })
+                .collect::<HashMap<CompactString, CompactString>>();
+
+            let Ok(auth_db) = DATABASES_CONNS.get().unwrap().search(auth_method.db_id()) else {
+                return Err(RequestError::Other(anyhow!(
+                    "Cannot get the database connection for '{}'.",
+                    auth_method.db_id().unwrap_or("main".to_compact_string())
+                )));
+            };
+
+            let challenge_output = |challenge: PasskeyChallenge| {
+                ExecuteOutput::Json(
+                    None,
+                    json!({
+                        "challenge_id": challenge.challenge_id(),
+                        "options": challenge.options(),
+                    }),
+                )
+            };
+
+            match endpoint.id().as_str() {
+                PASSKEY_SIGNUP_START_ENDPOINT_ID => {
+                    if !auth_config.allow_signup() {
+                        return Err(RequestError::Other(anyhow!(
+                            "Signup is disabled for the current build."
+                        )));
+                    }
+
+                    let challenge = passkey_method
+                        .start_registration(auth_db, PasskeyOwner::Signup(client_params))
+                        .await
+                        .map_err(|err| {
+                            RequestError::Expected(StatusCode::BAD_REQUEST, err.to_compact_string())
+                        })?;
+
+                    Ok(challenge_output(challenge))
+                }
+                PASSKEY_REGISTER_START_ENDPOINT_ID => {
+                    let user_id =
+                        match request_params
+                            .get("user_id")
+                            .ok_or(RequestError::Other(anyhow!(
+                                "Cannot register a passkey as there is no session active.",
+                            )))? {
+                            ExecuteParamValue::Internal(user_id) => Ok(user_id.to_owned()),
+                            _ => Err(RequestError::Expected(
+                                StatusCode::FORBIDDEN,
+                                "User id injection from the client is forbidden."
+                                    .to_compact_string(),
+                            )),
+                        }?
+                        .parse::<UserId>()
+                        .map_err(|_| {
+                            RequestError::Other(anyhow!(
+                                "Cannot convert user id to it's internal representation."
+                            ))
+                        })?;
+
+                    let challenge = passkey_method
+                        .start_registration(auth_db, PasskeyOwner::User(user_id))
+                        .await?;
+
+                    Ok(challenge_output(challenge))
+                }
+                PASSKEY_REGISTER_FINISH_ENDPOINT_ID => {
+                    let (Some(challenge_id), Some(credential)) = (
+                        client_params.get("challenge_id"),
+                        client_params.get("credential"),
+                    ) else {
+                        return Err(RequestError::Expected(
+                            StatusCode::BAD_REQUEST,
+                            "Both the `challenge_id` and the `credential` are required."
+                                .to_compact_string(),
+                        ));
+                    };
+
+                    match passkey_method
+                        .finish_registration(auth_db.to_owned(), challenge_id, credential)
+                        .await?
+                    {
+                        // New accounts go through the email verification like any other signup.
+                        Some((user_id, true)) => {
+                            assign_default_role(
+                                &auth_config,
+                                auth_method,
+                                auth_db.to_owned(),
+                                user_id,
+                            )
+                            .await?;
+
+                            finish_signup(&auth_config, auth_method, auth_db, user_id, &headers)
+                                .await
+                        }
+                        Some((_, false)) => Ok(ExecuteOutput::Json(None, json!({}))),
+                        None => Err(RequestError::Expected(
+                            StatusCode::BAD_REQUEST,
+                            "Invalid or expired passkey registration.".to_compact_string(),
+                        )),
+                    }
+                }
+                // Unknown users get a decoy challenge, so accounts aren't disclosed.
+                PASSKEY_LOGIN_START_ENDPOINT_ID => Ok(challenge_output(
+                    passkey_method.start_login(auth_db, client_params).await?,
+                )),
+                _ => Err(RequestError::Other(anyhow!(
+                    "Unexpected passkey endpoint '{}'.",
+                    endpoint.id()
+                ))),
+            }
+        })
+        .into();
+
+        future as Self::Future // Actually, this is not an error! https://github.com/rust-lang/rust/issues/92929
+    }
+}
@@ -2,7 +2,13 @@
 // Copyright (C) 2026 Oscar Alvarez Gonzalez
 
 pub mod logout;
+pub mod refresh;
+pub mod sessions;
+pub mod sweeper;
 pub mod watchdog;
 
 pub use logout::*;
+pub use refresh::*;
+pub use sessions::*;
+pub use sweeper::*;
 pub use watchdog::*;
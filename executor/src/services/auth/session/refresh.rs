@@ -0,0 +1,100 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+use crate::*;
+
+/// Rotates the given `refresh_token`, returning a new session and refresh token.
+#[derive(Clone, Constructor, Debug)]
+pub struct RefreshCaptured;
+
+impl Service<RequestParamsExtractorRequest> for RefreshCaptured {
+    type Response = ExecuteOutput;
+
+    type Error = RequestError;
+
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    #[instrument(skip_all)]
+    fn call(&mut self, cx: RequestParamsExtractorRequest) -> Self::Future {
+        let future: Pin<_> = Box::pin(async move {
+            let (headers, _, request_params, _) = cx;
+
+            let auth_config = RuntimeCx::acquire()
+                .build()
+                .read()
+                .await
+                .config()
+                .authentication()
+                .to_owned()
+                .ok_or(RequestError::Other(anyhow!(
+                    "Authentication is not set for the current build."
+                )))?;
+
+            let Some(ExecuteParamValue::Client(Some(refresh_token))) =
+                request_params.get("refresh_token")
+            else {
+                return Err(RequestError::Expected(
+                    StatusCode::BAD_REQUEST,
+                    "The `refresh_token` is required.".to_compact_string(),
+                ));
+            };
+
+            let session_method = auth_config.session();
+
+            let Ok(session_db) = DATABASES_CONNS
+                .get()
+                .unwrap()
+                .search(session_method.db_id())
+            else {
+                return Err(RequestError::Other(anyhow!(
+                    "Cannot get the database connection for '{}'.",
+                    session_method.db_id().unwrap_or("main".to_compact_string())
+                )));
+            };
+
+            let invalid = || {
+                RequestError::Expected(
+                    StatusCode::UNAUTHORIZED,
+                    "Invalid or expired refresh token.".to_compact_string(),
+                )
+            };
+
+            let Some(user_id) = session_method
+                .refresh_user(session_db.to_owned(), refresh_token.to_owned())
+                .await?
+            else {
+                return Err(invalid());
+            };
+
+            // Disabled or deleted users lose their sessions, refresh tokens included.
+            if !user_active(&auth_config, DATABASES_CONNS.get().unwrap(), user_id).await? {
+                session_method.invalidate(session_db, user_id, None).await?;
+
+                return Err(invalid());
+            }
+
+            match session_method
+                .refresh(
+                    session_db,
+                    refresh_token.to_owned(),
+                    session_client(&headers),
+                )
+                .await?
+            {
+                Some((session_token, refresh_token)) => Ok(session_output(
+                    session_method,
+                    session_token,
+                    Some(refresh_token),
+                )),
+                None => Err(invalid()),
+            }
+        })
+        .into();
+
+        future as Self::Future // Actually, this is not an error! https://github.com/rust-lang/rust/issues/92929
+    }
+}
@@ -0,0 +1,108 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+use crate::*;
+
+/// Lists the current user's sessions or revokes one of them given its `session_id`.
+#[derive(Clone, Constructor, Debug)]
+pub struct SessionsCaptured;
+
+impl Service<RequestParamsExtractorRequest> for SessionsCaptured {
+    type Response = ExecuteOutput;
+
+    type Error = RequestError;
+
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    #[instrument(skip_all)]
+    fn call(&mut self, cx: RequestParamsExtractorRequest) -> Self::Future {
+        let future: Pin<_> = Box::pin(async move {
+            let (_, endpoint, request_params, _) = cx;
+
+            let auth_config = RuntimeCx::acquire()
+                .build()
+                .read()
+                .await
+                .config()
+                .authentication()
+                .to_owned()
+                .ok_or(RequestError::Other(anyhow!(
+                    "Authentication is not set for the current build."
+                )))?;
+
+            let user_id =
+                match request_params
+                    .get("user_id")
+                    .ok_or(RequestError::Other(anyhow!(
+                        "Cannot manage sessions as there is no session active.",
+                    )))? {
+                    ExecuteParamValue::Internal(user_id) => Ok(user_id.to_owned()),
+                    _ => Err(RequestError::Expected(
+                        StatusCode::FORBIDDEN,
+                        "User id injection from the client is forbidden.".to_compact_string(),
+                    )),
+                }?
+                .parse::<UserId>()
+                .map_err(|_| {
+                    RequestError::Other(anyhow!(
+                        "Cannot convert user id to it's internal representation."
+                    ))
+                })?;
+
+            let session_method = auth_config.session();
+
+            let Ok(session_db) = DATABASES_CONNS
+                .get()
+                .unwrap()
+                .search(session_method.db_id())
+            else {
+                return Err(RequestError::Other(anyhow!(
+                    "Cannot get the database connection for '{}'.",
+                    session_method.db_id().unwrap_or("main".to_compact_string())
+                )));
+            };
+
+            if endpoint.id() == SESSIONS_ENDPOINT_ID {
+                let token = match request_params.get("token") {
+                    Some(ExecuteParamValue::Internal(token)) => Some(token.to_owned()),
+                    _ => None,
+                };
+
+                let sessions = session_method.sessions(session_db, user_id, token).await?;
+
+                return Ok(ExecuteOutput::Json(
+                    None,
+                    json!({ "sessions": sessions.as_slice() }),
+                ));
+            }
+
+            let Some(ExecuteParamValue::Client(Some(session_id))) =
+                request_params.get("session_id")
+            else {
+                return Err(RequestError::Expected(
+                    StatusCode::BAD_REQUEST,
+                    "The `session_id` is required.".to_compact_string(),
+                ));
+            };
+
+            if !session_method
+                .revoke_session(session_db, user_id, session_id.to_owned())
+                .await?
+            {
+                return Err(RequestError::Expected(
+                    StatusCode::NOT_FOUND,
+                    "Cannot find the session.".to_compact_string(),
+                ));
+            }
+
+            Ok(ExecuteOutput::Json(None, json!({})))
+        })
+        .into();
+
+        future as Self::Future // Actually, this is not an error! https://github.com/rust-lang/rust/issues/92929
+    }
+}
@@ -0,0 +1,52 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+use crate::*;
+
+/// Removes the expired sessions periodically, the build is read on every run so it follows reloads.
+#[instrument(skip_all)]
+pub async fn sweep_sessions() {
+    loop {
+        let interval = RuntimeCx::acquire()
+            .build()
+            .read()
+            .await
+            .config()
+            .authentication()
+            .as_ref()
+            .and_then(|auth_config| auth_config.sweep_interval().to_owned())
+            .unwrap_or(3600);
+
+        tokio::time::sleep(Duration::from_secs(interval as u64)).await;
+
+        let Some(auth_config) = RuntimeCx::acquire()
+            .build()
+            .read()
+            .await
+            .config()
+            .authentication()
+            .to_owned()
+        else {
+            continue;
+        };
+
+        let session_method = auth_config.session();
+
+        let Ok(session_db) = DATABASES_CONNS
+            .get()
+            .unwrap()
+            .search(session_method.db_id())
+        else {
+            error!(
+                "Cannot get the database connection for '{}'.",
+                session_method.db_id().unwrap_or("main".to_compact_string())
+            );
+            continue;
+        };
+
+        match session_method.remove_expired(session_db).await {
+            Ok(()) => debug!("Expired sessions have been removed."),
+            Err(err) => error!("Cannot remove the expired sessions. {}", err),
+        }
+    }
+}
@@ -3,6 +3,11 @@
 
 use crate::*;
 
+use waveless_commons::databases::DatabasesConnections;
+
+/// Header carrying the API keys of machine clients.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
 /// TODO: add documentation.
 #[derive(Clone, Constructor, Debug)]
 pub struct SessionWatchdog<S>
@@ -87,8 +92,52 @@ where
                     )));
                 };
 
+                // API keys authenticate machine clients, taking precedence over sessions.
+                let api_key = match (headers.get(API_KEY_HEADER), auth_config.api_keys()) {
+                    (Some(api_key), Some(api_key_method)) => {
+                        let Ok(api_key) = api_key.to_str() else {
+                            return Err(RequestError::Expected(
+                                StatusCode::BAD_REQUEST,
+                                "Malformed API key header.".to_compact_string(),
+                            ));
+                        };
+
+                        let Ok(api_key_db) = databases.search(api_key_method.db_id()) else {
+                            return Err(RequestError::Other(anyhow!(
+                                "Cannot get the database connection for '{}'.",
+                                api_key_method.db_id().unwrap_or("main".to_compact_string())
+                            )));
+                        };
+
+                        let Some(api_key_info) = api_key_method
+                            .check(api_key_db, api_key.to_compact_string())
+                            .await
+                            .map_err(|err| {
+                                RequestError::Other(anyhow!("Cannot check the API key. {}", err))
+                            })?
+                        else {
+                            return Err(RequestError::Expected(
+                                StatusCode::UNAUTHORIZED,
+                                "Invalid API key.".to_compact_string(),
+                            ));
+                        };
+
+                        // Keys of disabled or deleted users are rejected.
+                        if !user_active(&auth_config, databases, *api_key_info.user_id()).await? {
+                            return Err(RequestError::Expected(
+                                StatusCode::UNAUTHORIZED,
+                                "Invalid API key.".to_compact_string(),
+                            ));
+                        }
+
+                        Some((api_key, api_key_info))
+                    }
+                    _ => None,
+                };
+
                 // Check the session.
                 let token = match (headers.get("Authorization"), headers.get("Cookie")) {
+                    _ if api_key.is_some() => api_key.as_ref().map(|(api_key, _)| *api_key),
                     (Some(auth_header), _) => {
                         if let Ok(token) = auth_header.to_str() {
                             Some(token)
@@ -138,15 +187,33 @@ where
                     ));
                 };
 
-                let session_check = session_method
-                    .check(session_db, token.to_compact_string())
-                    .await
-                    .map_err(|err| {
-                        RequestError::Other(anyhow!("Cannot check the session token. {}", err))
-                    })?;
+                let session_check = match &api_key {
+                    // The API key's scopes replace the user's roles.
+                    Some((_, api_key_info)) => Some((
+                        *api_key_info.user_id(),
+                        Some(api_key_info.scopes().to_owned()),
+                    )),
+                    None => session_method
+                        .check_with_roles(session_db, token.to_compact_string())
+                        .await
+                        .map_err(|err| {
+                            RequestError::Other(anyhow!("Cannot check the session token. {}", err))
+                        })?,
+                };
 
                 match session_check {
-                    Some(user_id) => {
+                    Some((user_id, embedded_roles)) => {
+                        // Identifies the consumer whose usage is accounted.
+                        request_params.insert(
+                            CONSUMER_PARAM.to_compact_string(),
+                            ExecuteParamValue::Internal(match &api_key {
+                                Some((_, api_key_info)) => {
+                                    format!("key:{}", api_key_info.id()).to_compact_string()
+                                }
+                                None => format!("user:{}", user_id).to_compact_string(),
+                            }),
+                        );
+
                         // Inject user id if required.
                         if *endpoint.inject_user_id() {
                             request_params.insert(
@@ -159,53 +226,116 @@ where
                                 ExecuteParamValue::Internal(token.to_compact_string()),
                             );
                         }
-                        if endpoint.allowed_roles().is_empty() {
-                            inner
+
+                        let checks_roles = !endpoint.allowed_roles().is_empty()
+                            || !endpoint.required_permissions().is_empty();
+
+                        if !checks_roles && endpoint.owner_bypass_roles().is_empty() {
+                            return inner
                                 .call((headers, endpoint, request_params, request_body))
-                                .await
-                        } else {
-                            let Some(role_method) = role_method else {
-                                // TODO: the compiler should fail when including endpoints
-                                // that require roles while not having
-                                // roles set for the project.
-                                return Err(RequestError::Other(anyhow!(
-                                    "Endpoint '{}' requires roles authentication but they are not set for this build.",
-                                    endpoint.id()
-                                )));
-                            };
-
-                            let Ok(role_db) = databases.search(role_method.db_id()) else {
-                                return Err(RequestError::Other(anyhow!(
-                                    "Cannot get the database connection for '{}'.",
-                                    session_method.db_id().unwrap_or("main".to_compact_string())
-                                )));
-                            };
-
-                            let Ok(role_check) = role_method.get(role_db, user_id).await else {
-                                return Err(RequestError::Other(anyhow!(
-                                    "Cannot check the user's role."
-                                )));
-                            };
-
-                            let Some(role) = role_check else {
-                                return Err(RequestError::Expected(
-                                    StatusCode::UNAUTHORIZED,
-                                    "Current user does not have any role.".to_compact_string(),
-                                ));
-                            };
-
-                            if endpoint.allowed_roles().contains(&role.to_lowercase()) {
-                                inner
-                                    .call((headers, endpoint, request_params, request_body))
-                                    .await
-                            } else {
-                                return Err(RequestError::Expected(
-                                    StatusCode::UNAUTHORIZED,
-                                    "Current user does not have any of the allowed roles."
-                                        .to_compact_string(),
-                                ));
+                                .await;
+                        }
+
+                        // Roles embedded in the session token don't need to be looked up.
+                        let roles = match embedded_roles {
+                            Some(roles) => roles,
+                            None => {
+                                let Some(role_method) = role_method else {
+                                    if !checks_roles {
+                                        // Nobody can bypass the ownership without roles.
+                                        request_params.insert(
+                                            "owner_bypass".to_compact_string(),
+                                            ExecuteParamValue::Internal("0".to_compact_string()),
+                                        );
+
+                                        return inner
+                                            .call((headers, endpoint, request_params, request_body))
+                                            .await;
+                                    }
+
+                                    // TODO: the compiler should fail when including endpoints
+                                    // that require roles while not having
+                                    // roles set for the project.
+                                    return Err(RequestError::Other(anyhow!(
+                                        "Endpoint '{}' requires roles authentication but they are not set for this build.",
+                                        endpoint.id()
+                                    )));
+                                };
+
+                                let Ok(role_db) = databases.search(role_method.db_id()) else {
+                                    return Err(RequestError::Other(anyhow!(
+                                        "Cannot get the database connection for '{}'.",
+                                        role_method.db_id().unwrap_or("main".to_compact_string())
+                                    )));
+                                };
+
+                                let Ok(roles) = role_method.get_all(role_db, user_id).await else {
+                                    return Err(RequestError::Other(anyhow!(
+                                        "Cannot check the user's roles."
+                                    )));
+                                };
+
+                                roles
                             }
+                        };
+
+                        if checks_roles && roles.is_empty() {
+                            return Err(RequestError::Expected(
+                                StatusCode::UNAUTHORIZED,
+                                "Current user does not have any role.".to_compact_string(),
+                            ));
                         }
+
+                        // Inherited roles are allowed too, e.g. `admin` when `editor` is allowed.
+                        let grants = if api_key.is_some() {
+                            Grants::resolve_scopes(auth_config.roles(), &roles)
+                        } else {
+                            Grants::resolve(auth_config.roles(), &roles)
+                        };
+
+                        if !endpoint.allowed_roles().is_empty()
+                            && !endpoint
+                                .allowed_roles()
+                                .iter()
+                                .any(|role| grants.has_role(role))
+                        {
+                            return Err(RequestError::Expected(
+                                StatusCode::UNAUTHORIZED,
+                                "Current user does not have any of the allowed roles."
+                                    .to_compact_string(),
+                            ));
+                        }
+
+                        if let Some(permission) = endpoint
+                            .required_permissions()
+                            .iter()
+                            .find(|permission| !grants.has_permission(permission))
+                        {
+                            return Err(RequestError::Expected(
+                                StatusCode::FORBIDDEN,
+                                format!("Current user lacks the '{}' permission.", permission)
+                                    .to_compact_string(),
+                            ));
+                        }
+
+                        // Notifies the query whether the user can access other users' rows.
+                        if !endpoint.owner_bypass_roles().is_empty() {
+                            let bypass = endpoint
+                                .owner_bypass_roles()
+                                .iter()
+                                .any(|role| grants.has_role(role));
+
+                            request_params.insert(
+                                "owner_bypass".to_compact_string(),
+                                ExecuteParamValue::Internal(
+                                    if bypass { "1" } else { "0" }.to_compact_string(),
+                                ),
+                            );
+                        }
+
+                        inner
+                            .call((headers, endpoint, request_params, request_body))
+                            .await
                     }
                     None => Err(RequestError::Expected(
                         StatusCode::UNAUTHORIZED,
@@ -220,3 +350,31 @@ where
         })
     }
 }
+
+/// Whether the user is known by some authentication method and none of them has it disabled.
+pub async fn user_active(
+    auth_config: &Authentication,
+    databases: &DatabasesConnections,
+    user_id: UserId,
+) -> Result<bool, RequestError> {
+    let mut active = false;
+
+    for auth_method in auth_config.backends() {
+        let Ok(auth_db) = databases.search(auth_method.db_id()) else {
+            return Err(RequestError::Other(anyhow!(
+                "Cannot get the database connection for '{}'.",
+                auth_method.db_id().unwrap_or("main".to_compact_string())
+            )));
+        };
+
+        match auth_method.active(auth_db, user_id).await.map_err(|err| {
+            RequestError::Other(anyhow!("Cannot check whether the user is active. {}", err))
+        })? {
+            Some(true) => active = true,
+            Some(false) => return Ok(false),
+            None => {}
+        }
+    }
+
+    Ok(active)
+}
@@ -3,6 +3,8 @@
 
 use crate::*;
 
+use waveless_commons::mail::MailTemplate;
+
 /// TODO: add documentation.
 #[derive(Clone, Constructor, Debug)]
 pub struct SignUpCaptured;
@@ -83,6 +85,23 @@ impl Service<RequestParamsExtractorRequest> for SignUpCaptured {
                 }
             };
 
+            // Passwordless accounts must prove their address, so they're created through the magic link login.
+            if auth_method
+                .to_owned()
+                .into_arc_any()
+                .downcast::<SqlMagicLinkAuthenticationMethod>()
+                .is_ok()
+            {
+                return Err(RequestError::Expected(
+                    StatusCode::BAD_REQUEST,
+                    format!(
+                        "Accounts of '{}' are created through the magic link login.",
+                        auth_method.name()
+                    )
+                    .to_compact_string(),
+                ));
+            }
+
             let Ok(auth_db) = databases.search(auth_method.db_id()) else {
                 return Err(RequestError::Other(anyhow!(
                     "Cannot get the database connection for '{}'.",
@@ -91,58 +110,107 @@ impl Service<RequestParamsExtractorRequest> for SignUpCaptured {
             };
 
             // Create a new user.
-            match auth_method.new(auth_db, request_params).await {
-                Ok(user_id) => {
-                    // Create a new session.
-                    let session_method = auth_config.session();
-
-                    let Ok(session_db) = databases.search(session_method.db_id()) else {
-                        return Err(RequestError::Other(anyhow!(
-                            "Cannot get the database connection for '{}'.",
-                            session_method.db_id().unwrap_or("main".to_compact_string())
-                        )));
-                    };
-
-                    let session_token =
-                        session_method
-                            .new(session_db, user_id)
-                            .await
-                            .map_err(|err| {
-                                RequestError::Other(anyhow!(
-                                    "Cannot check the session token. {}",
-                                    err
-                                ))
-                            })?;
-
-
-                    let mut headers = HashMap::new();
-
-                    // TODO: should add the secure param to `Set-Cookie`.
-                    headers.insert(
-                        "Set-Cookie".to_compact_string(),
-                        format!(
-                            "Authorization={}; SameSite=Lax; Path=/; {}",
-                            session_token,
-                            session_method
-                                .max_age()
-                                .map(|max_age| format!("Max-Age={}", max_age))
-                                .unwrap_or_default()
-                        )
-                        .to_compact_string(),
-                    );
-
-                    Ok(ExecuteOutput::Json(
-                        Some(headers),
-                        json!({
-                            "token": session_token
-                        }),
-                    ))
-                },
-
-                Err(err) => Err(RequestError::Other(err))
-            }
+            let user_id = auth_method
+                .new(auth_db.to_owned(), request_params)
+                .await
+                .map_err(RequestError::Other)?;
+
+            assign_default_role(&auth_config, auth_method, auth_db.to_owned(), user_id).await?;
+
+            finish_signup(&auth_config, auth_method, auth_db, user_id, &headers).await
         }).into();
 
         future as Self::Future // Actually, this is not an error! https://github.com/rust-lang/rust/issues/92929
     }
 }
+
+/// Mails the verification link to the new user, which only gets a session when its email
+/// doesn't need to be verified first.
+pub async fn finish_signup(
+    auth_config: &Authentication,
+    auth_method: &Arc<dyn AnyAuthenticationMethod>,
+    auth_db: Arc<dyn AnyDatabaseConnection>,
+    user_id: UserId,
+    headers: &HeaderMap,
+) -> Result<ExecuteOutput, RequestError> {
+    // Mails the verification link, which can be sent again if it doesn't arrive.
+    let mailing = RuntimeCx::acquire()
+        .build()
+        .read()
+        .await
+        .config()
+        .mailing()
+        .to_owned();
+
+    if let (Some(settings), Some(mailing)) = (auth_config.email_verification(), mailing) {
+        let sent = match auth_method.email(auth_db, user_id).await {
+            Ok(Some(email)) => {
+                send_link(
+                    &mailing,
+                    MailTemplate::EmailVerification,
+                    auth_method,
+                    user_id,
+                    &email,
+                    settings.link(),
+                    *settings.max_age(),
+                )
+                .await
+            }
+            Ok(None) => Err(anyhow!("The user has no email.")),
+            Err(err) => Err(err),
+        };
+
+        if let Err(err) = sent {
+            error!(
+                "Cannot send the verification link of user {}. {}",
+                user_id, err
+            );
+        }
+
+        // Unverified users cannot log in, so they don't get a session either.
+        if *settings.required() {
+            return Ok(ExecuteOutput::Json(
+                None,
+                json!({ "verification_required": true }),
+            ));
+        }
+    }
+
+    new_session(auth_config, user_id, headers).await
+}
+
+/// Assigns the default role to the new user, which is rolled back if it cannot be set.
+pub async fn assign_default_role(
+    auth_config: &Authentication,
+    auth_method: &Arc<dyn AnyAuthenticationMethod>,
+    auth_db: Arc<dyn AnyDatabaseConnection>,
+    user_id: UserId,
+) -> Result<(), RequestError> {
+    let (Some(default_role), Some(role_method)) = (auth_config.default_role(), auth_config.role())
+    else {
+        return Ok(());
+    };
+
+    let Ok(role_db) = DATABASES_CONNS.get().unwrap().search(role_method.db_id()) else {
+        auth_method.delete(auth_db, user_id).await?;
+
+        return Err(RequestError::Other(anyhow!(
+            "Cannot get the database connection for '{}'.",
+            role_method.db_id().unwrap_or("main".to_compact_string())
+        )));
+    };
+
+    if let Err(err) = role_method
+        .set(role_db, user_id, default_role.to_owned())
+        .await
+    {
+        auth_method.delete(auth_db, user_id).await?;
+
+        return Err(RequestError::Other(anyhow!(
+            "Cannot assign the default role. {}",
+            err
+        )));
+    }
+
+    Ok(())
+}
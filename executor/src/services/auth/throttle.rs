@@ -0,0 +1,181 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+use crate::*;
+
+use waveless_commons::auth::throttle::*;
+
+/// Failed logins of this instance.
+static LOGIN_THROTTLE: LazyLock<LoginThrottle> = LazyLock::new(LoginThrottle::default);
+
+/// The key of the login's account, when the method tells which one it's for.
+pub fn login_account_key(
+    auth_method: &Arc<dyn AnyAuthenticationMethod>,
+    request_params: &HashMap<CompactString, CompactString>,
+) -> Option<CompactString> {
+    auth_method.login_account(request_params).map(|account| {
+        format!(
+            "account/{}/{}",
+            auth_method.name(),
+            account.trim().to_lowercase()
+        )
+        .to_compact_string()
+    })
+}
+
+/// The keys a login is throttled by along with their max failures: its account's, when the
+/// method tells which one it's for, and its client address'.
+fn login_keys(
+    auth_method: &Arc<dyn AnyAuthenticationMethod>,
+    request_params: &HashMap<CompactString, CompactString>,
+    headers: &HeaderMap,
+    settings: &LoginThrottlingSettings,
+) -> CheapVec<(CompactString, usize), 2> {
+    let mut keys = CheapVec::new();
+
+    if let Some(key) = login_account_key(auth_method, request_params) {
+        keys.push((key, *settings.account_max_failures()));
+    }
+
+    if let Some(address) = headers
+        .get(server::PEER_ADDR_HEADER)
+        .and_then(|address| address.to_str().ok())
+    {
+        keys.push((
+            format!("address/{}", address).to_compact_string(),
+            *settings.address_max_failures(),
+        ));
+    }
+
+    keys
+}
+
+/// Counts the login as failed before its credentials are checked, so concurrent logins cannot
+/// exceed the max failures, rejecting it while its account or its client address is throttled.
+/// NOTE: successful logins are given back through `record_successful_login`.
+pub fn check_login_throttle(
+    auth_method: &Arc<dyn AnyAuthenticationMethod>,
+    request_params: &HashMap<CompactString, CompactString>,
+    headers: &HeaderMap,
+    settings: &LoginThrottlingSettings,
+) -> Result<(), RequestError> {
+    let keys = login_keys(auth_method, request_params, headers, settings);
+
+    attempt_login(&keys[..], settings)
+}
+
+/// Counts the second factor of a login challenge as failed before its code is checked, against
+/// the same key as the account's logins, so guessing codes is throttled like guessing passwords.
+/// NOTE: successful logins are given back through `record_successful_second_factor`.
+pub fn check_second_factor_throttle(
+    account_key: Option<&CompactString>,
+    settings: &LoginThrottlingSettings,
+) -> Result<(), RequestError> {
+    let keys = account_key
+        .map(|key| (key.to_owned(), *settings.account_max_failures()))
+        .into_iter()
+        .collect::<Vec<_>>();
+
+    attempt_login(&keys, settings)
+}
+
+/// Counts an attempt of each key, giving them back if any of them is throttled.
+fn attempt_login(
+    keys: &[(CompactString, usize)],
+    settings: &LoginThrottlingSettings,
+) -> Result<(), RequestError> {
+    let now = Instant::now();
+
+    for (i, (key, max_failures)) in keys.iter().enumerate() {
+        match LOGIN_THROTTLE.attempt(key, *max_failures, settings, now) {
+            Ok(Some(delay)) if delay.as_secs() >= *settings.lockout() as u64 => {
+                warn!(
+                    "Logins of '{}' locked out for {}s after repeated failures.",
+                    key,
+                    delay.as_secs()
+                );
+            }
+            Ok(Some(delay)) => {
+                warn!(
+                    "Logins of '{}' delayed {}s after repeated failures.",
+                    key,
+                    delay.as_secs()
+                );
+            }
+            Ok(None) => {}
+            Err(retry_after) => {
+                // The attempts counted for the previous keys are given back, as it's not tried.
+                for (key, _) in &keys[..i] {
+                    LOGIN_THROTTLE.release(key);
+                }
+
+                return Err(RequestError::Throttled(
+                    retry_after,
+                    "Too many failed logins, try again later.".to_compact_string(),
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Forgets the failed logins of the account, its client address' are kept as other accounts
+/// may have been tried from it, so only this attempt is given back.
+pub fn record_successful_login(
+    auth_method: &Arc<dyn AnyAuthenticationMethod>,
+    request_params: &HashMap<CompactString, CompactString>,
+    headers: &HeaderMap,
+    settings: &LoginThrottlingSettings,
+) {
+    for (key, _) in login_keys(auth_method, request_params, headers, settings) {
+        if key.starts_with("account/") {
+            LOGIN_THROTTLE.succeed(&key);
+        } else {
+            LOGIN_THROTTLE.release(&key);
+        }
+    }
+}
+
+/// Gives back the login's attempt once its credentials are checked while its second factor is
+/// awaited, without forgetting the previous failures until the second factor succeeds too.
+pub fn release_login_attempt(
+    auth_method: &Arc<dyn AnyAuthenticationMethod>,
+    request_params: &HashMap<CompactString, CompactString>,
+    headers: &HeaderMap,
+    settings: &LoginThrottlingSettings,
+) {
+    for (key, _) in login_keys(auth_method, request_params, headers, settings) {
+        LOGIN_THROTTLE.release(&key);
+    }
+}
+
+/// Forgets the failed logins of the challenge's account once its second factor succeeds.
+pub fn record_successful_second_factor(account_key: Option<&CompactString>) {
+    if let Some(key) = account_key {
+        LOGIN_THROTTLE.succeed(key);
+    }
+}
+
+/// Removes the failed logins forgotten after the window periodically, so they aren't scanned
+/// while attempting logins. The build is read on every run so it follows reloads.
+pub async fn sweep_login_throttle() {
+    loop {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+
+        let Some(auth_config) = RuntimeCx::acquire()
+            .build()
+            .read()
+            .await
+            .config()
+            .authentication()
+            .to_owned()
+        else {
+            continue;
+        };
+
+        let size = LOGIN_THROTTLE.sweep(auth_config.login_throttling(), Instant::now());
+
+        debug!("Cleaned up the failed logins (size: {})", size);
+    }
+}
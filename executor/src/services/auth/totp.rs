@@ -0,0 +1,407 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+use crate::*;
+
+/// A login whose credentials have been checked but which awaits the user's TOTP code.
+#[derive(Clone, Debug)]
+struct LoginChallenge {
+    user_id: UserId,
+    auth_method: CompactString,
+    /// The throttling key of the login's account, so its second factor is throttled too.
+    account_key: Option<CompactString>,
+    expires_at: Instant,
+    attempts: usize,
+}
+
+static LOGIN_CHALLENGES: LazyLock<DashMap<CompactString, LoginChallenge>> =
+    LazyLock::new(DashMap::new);
+
+/// Secrets generated on enrollment which haven't been confirmed yet.
+static PENDING_ENROLLMENTS: LazyLock<DashMap<UserId, (CompactString, Instant)>> =
+    LazyLock::new(DashMap::new);
+
+/// Short-lived state kept while a login or an enrollment awaits its TOTP code.
+/// NOTE: it's kept in memory, so a challenge must be completed on the instance which issued it.
+pub struct TotpChallenges;
+
+impl TotpChallenges {
+    /// Issues a new login challenge for the given user.
+    pub fn issue(
+        user_id: UserId,
+        auth_method: CompactString,
+        account_key: Option<CompactString>,
+        settings: &TotpSettings,
+    ) -> CompactString {
+        let token = Alphanumeric
+            .sample_string(&mut rand::rng(), 32)
+            .to_compact_string();
+
+        LOGIN_CHALLENGES.insert(
+            token.to_owned(),
+            LoginChallenge {
+                user_id,
+                auth_method,
+                account_key,
+                expires_at: Instant::now()
+                    + Duration::from_secs(*settings.challenge_max_age() as u64),
+                attempts: 0,
+            },
+        );
+
+        token
+    }
+
+    /// Takes one of the challenge's attempts before its code is checked, so concurrent guesses
+    /// cannot exceed the max attempts. Expired and exhausted challenges are discarded.
+    fn attempt(token: &str, settings: &TotpSettings) -> Option<LoginChallenge> {
+        let mut exhausted = false;
+
+        let challenge = LOGIN_CHALLENGES.get_mut(token).and_then(|mut challenge| {
+            if challenge.expires_at <= Instant::now()
+                || challenge.attempts >= *settings.challenge_max_attempts()
+            {
+                exhausted = true;
+                return None;
+            }
+
+            challenge.attempts += 1;
+
+            Some(challenge.to_owned())
+        });
+
+        if exhausted {
+            LOGIN_CHALLENGES.remove(token);
+        }
+
+        challenge
+    }
+
+    fn enroll(user_id: UserId, secret: CompactString, settings: &TotpSettings) {
+        PENDING_ENROLLMENTS.insert(
+            user_id,
+            (
+                secret,
+                Instant::now() + Duration::from_secs(*settings.challenge_max_age() as u64),
+            ),
+        );
+    }
+
+    fn pending_enrollment(user_id: UserId) -> Option<CompactString> {
+        let (secret, expires_at) = PENDING_ENROLLMENTS.get(&user_id)?.to_owned();
+
+        if expires_at <= Instant::now() {
+            PENDING_ENROLLMENTS.remove(&user_id);
+            return None;
+        }
+
+        Some(secret)
+    }
+}
+
+/// Removes the expired login challenges and pending enrollments periodically, so they aren't
+/// scanned while issuing them.
+pub async fn sweep_totp_challenges() {
+    loop {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+
+        let now = Instant::now();
+
+        LOGIN_CHALLENGES.retain(|_, challenge| challenge.expires_at > now);
+        PENDING_ENROLLMENTS.retain(|_, (_, expires_at)| *expires_at > now);
+
+        debug!(
+            "Cleaned up the TOTP challenges (size: {})",
+            LOGIN_CHALLENGES.len()
+        );
+    }
+}
+
+/// Checks the `totp_code` or, if not given, the `recovery_code` parameter against the user's TOTP state.
+/// NOTE: used codes are consumed by replacing the user's TOTP state, which fails if another request
+/// has consumed a code in the meantime, so a code cannot be used twice.
+pub async fn check_second_factor(
+    auth_method: &Arc<dyn AnyAuthenticationMethod>,
+    auth_db: Arc<dyn AnyDatabaseConnection>,
+    user_id: UserId,
+    totp: TotpState,
+    request_params: &HashMap<CompactString, CompactString>,
+) -> Result<(), RequestError> {
+    let mut updated = totp.to_owned();
+
+    let valid = if let Some(code) = request_params.get("totp_code") {
+        updated.verify(code)?
+    } else if let Some(code) = request_params.get("recovery_code") {
+        updated.use_recovery_code(code)
+    } else {
+        return Err(RequestError::Expected(
+            StatusCode::BAD_REQUEST,
+            "Either `totp_code` or `recovery_code` is required.".to_compact_string(),
+        ));
+    };
+
+    let valid = valid
+        && auth_method
+            .replace_totp(auth_db, user_id, &totp, updated)
+            .await?;
+
+    if valid {
+        Ok(())
+    } else {
+        Err(RequestError::Expected(
+            StatusCode::FORBIDDEN,
+            "Login failed, invalid TOTP code.".to_compact_string(),
+        ))
+    }
+}
+
+/// Handles the TOTP's login second step, enrollment, confirmation and removal.
+#[derive(Clone, Constructor, Debug)]
+pub struct TotpCaptured;
+
+impl Service<RequestParamsExtractorRequest> for TotpCaptured {
+    type Response = ExecuteOutput;
+
+    type Error = RequestError;
+
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    #[instrument(skip_all)]
+    fn call(&mut self, cx: RequestParamsExtractorRequest) -> Self::Future {
+        let future: Pin<_> = Box::pin(async move {
+            let (headers, endpoint, request_params, _) = cx;
+
+            let (auth_config, project_name) = {
+                let _build_lock = RuntimeCx::acquire().build();
+                let build = _build_lock.read().await;
+
+                (
+                    build
+                        .config()
+                        .authentication()
+                        .to_owned()
+                        .ok_or(RequestError::Other(anyhow!(
+                            "Authentication is not set for the current build."
+                        )))?,
+                    build.config().name().to_owned(),
+                )
+            };
+
+            let settings = auth_config
+                .totp()
+                .to_owned()
+                .ok_or(RequestError::Other(anyhow!(
+                    "TOTP is not enabled for the current build."
+                )))?;
+
+            let client_params = request_params
+                .iter()
+                .filter_map(|entry| {
+                    if let (key, ExecuteParamValue::Client(Some(value))) = entry {
+                        Some((key.to_owned(), value.to_owned()))
+                    } else {
+                        None
+                    }
+                })
+                .collect::<HashMap<CompactString, CompactString>>();
+
+            let databases = DATABASES_CONNS.get().ok_or(RequestError::Other(anyhow!(
+                "The databases' connections haven't been loaded."
+            )))?;
+
+            // The second step of the login, which doesn't require a session.
+            if endpoint.id() == LOGIN_TOTP_ENDPOINT_ID {
+                let token = client_params
+                    .get("challenge")
+                    .ok_or(RequestError::Expected(
+                        StatusCode::BAD_REQUEST,
+                        "The login's `challenge` is required.".to_compact_string(),
+                    ))?;
+
+                let Some(challenge) = TotpChallenges::attempt(token, &settings) else {
+                    return Err(RequestError::Expected(
+                        StatusCode::FORBIDDEN,
+                        "Invalid or expired login challenge.".to_compact_string(),
+                    ));
+                };
+
+                let auth_method = auth_config
+                    .backends()
+                    .iter()
+                    .find(|auth_method| auth_method.name() == challenge.auth_method.as_str())
+                    .ok_or(RequestError::Other(anyhow!(
+                        "Cannot find the challenge's authentication method."
+                    )))?;
+
+                let Ok(auth_db) = databases.search(auth_method.db_id()) else {
+                    return Err(RequestError::Other(anyhow!(
+                        "Cannot get the database connection for '{}'.",
+                        auth_method.db_id().unwrap_or("main".to_compact_string())
+                    )));
+                };
+
+                let Some(totp) = auth_method
+                    .totp(auth_db.to_owned(), challenge.user_id)
+                    .await?
+                else {
+                    return Err(RequestError::Expected(
+                        StatusCode::FORBIDDEN,
+                        "TOTP has been disabled for the user.".to_compact_string(),
+                    ));
+                };
+
+                check_second_factor_throttle(
+                    challenge.account_key.as_ref(),
+                    auth_config.login_throttling(),
+                )?;
+
+                check_second_factor(
+                    auth_method,
+                    auth_db,
+                    challenge.user_id,
+                    totp,
+                    &client_params,
+                )
+                .await?;
+
+                LOGIN_CHALLENGES.remove(token.as_str());
+
+                let session = new_session(&auth_config, challenge.user_id, &headers).await?;
+
+                record_successful_second_factor(challenge.account_key.as_ref());
+
+                return Ok(session);
+            }
+
+            let user_id =
+                match request_params
+                    .get("user_id")
+                    .ok_or(RequestError::Other(anyhow!(
+                        "Cannot manage TOTP as there is no session active.",
+                    )))? {
+                    ExecuteParamValue::Internal(user_id) => Ok(user_id.to_owned()),
+                    _ => Err(RequestError::Expected(
+                        StatusCode::FORBIDDEN,
+                        "User id injection from the client is forbidden.".to_compact_string(),
+                    )),
+                }?
+                .parse::<UserId>()
+                .map_err(|_| {
+                    RequestError::Other(anyhow!(
+                        "Cannot convert user id to it's internal representation."
+                    ))
+                })?;
+
+            let auth_method = select_auth_method(&auth_config, &headers)?;
+
+            let Ok(auth_db) = databases.search(auth_method.db_id()) else {
+                return Err(RequestError::Other(anyhow!(
+                    "Cannot get the database connection for '{}'.",
+                    auth_method.db_id().unwrap_or("main".to_compact_string())
+                )));
+            };
+
+            match endpoint.id().as_str() {
+                TOTP_ENROLL_ENDPOINT_ID => {
+                    if auth_method
+                        .totp(auth_db.to_owned(), user_id)
+                        .await?
+                        .is_some()
+                    {
+                        return Err(RequestError::Expected(
+                            StatusCode::CONFLICT,
+                            "TOTP is already enabled. HINT: disable it before enrolling again."
+                                .to_compact_string(),
+                        ));
+                    }
+
+                    let secret = TotpState::generate_secret();
+
+                    let uri = TotpState::uri(
+                        &secret,
+                        settings
+                            .issuer()
+                            .as_deref()
+                            .unwrap_or(project_name.as_str()),
+                        client_params
+                            .get("account")
+                            .map(|account| account.as_str())
+                            .unwrap_or(&user_id.to_compact_string()),
+                    )?;
+
+                    TotpChallenges::enroll(user_id, secret.to_owned(), &settings);
+
+                    Ok(ExecuteOutput::Json(
+                        None,
+                        json!({
+                            "secret": secret,
+                            "uri": uri
+                        }),
+                    ))
+                }
+                TOTP_CONFIRM_ENDPOINT_ID => {
+                    let Some(secret) = TotpChallenges::pending_enrollment(user_id) else {
+                        return Err(RequestError::Expected(
+                            StatusCode::BAD_REQUEST,
+                            "There is no pending TOTP enrollment, it may have expired."
+                                .to_compact_string(),
+                        ));
+                    };
+
+                    let code = client_params
+                        .get("totp_code")
+                        .ok_or(RequestError::Expected(
+                            StatusCode::BAD_REQUEST,
+                            "The `totp_code` is required.".to_compact_string(),
+                        ))?;
+
+                    if !TotpState::verify_secret(&secret, code)? {
+                        return Err(RequestError::Expected(
+                            StatusCode::FORBIDDEN,
+                            "Invalid TOTP code.".to_compact_string(),
+                        ));
+                    }
+
+                    let (totp, recovery_codes) =
+                        TotpState::with_recovery_codes(secret, *settings.recovery_codes());
+
+                    auth_method.set_totp(auth_db, user_id, Some(totp)).await?;
+
+                    PENDING_ENROLLMENTS.remove(&user_id);
+
+                    Ok(ExecuteOutput::Json(
+                        None,
+                        json!({
+                            "recovery_codes": recovery_codes
+                        }),
+                    ))
+                }
+                TOTP_DISABLE_ENDPOINT_ID => {
+                    // The user must authenticate again.
+                    match auth_method.check(auth_db.to_owned(), client_params).await? {
+                        Some(checked_user_id) if checked_user_id == user_id => {
+                            auth_method.set_totp(auth_db, user_id, None).await?;
+
+                            Ok(ExecuteOutput::Json(None, json!({})))
+                        }
+                        _ => Err(RequestError::Expected(
+                            StatusCode::FORBIDDEN,
+                            "Re-authentication failed, invalid credentials.".to_compact_string(),
+                        )),
+                    }
+                }
+                _ => Err(RequestError::Other(anyhow!(
+                    "'{}' is not a TOTP endpoint.",
+                    endpoint.id()
+                ))),
+            }
+        })
+        .into();
+
+        future as Self::Future // Actually, this is not an error! https://github.com/rust-lang/rust/issues/92929
+    }
+}
@@ -67,58 +67,87 @@ where
         Box::pin(async move {
             let mut response = Response::builder()
                 .header("Content-Type", "application/json; charset=utf-8")
-                .header(
-                    "Cache-Control",
-                    format!(
-                        "max-age={}",
-                        (*RuntimeCx::acquire()
-                            .build()
-                            .read()
-                            .await
-                            .executor()
-                            .http_cache_time()) as u32
-                    ),
-                );
+                // Overwritten by the responses which may be cached.
+                .header("Cache-Control", "no-store");
 
             match fut.await {
                 Ok(output) => {
                     match output {
                         ExecuteOutput::Json(new_headers, value) => {
+                            let mut status = StatusCode::OK;
+
                             if let Some(new_headers) = new_headers {
+                                // Redirections (e.g. the OAuth login) keep their body for clients which don't follow them.
+                                if new_headers.contains_key("Location") {
+                                    status = StatusCode::FOUND;
+                                }
+
                                 let headers = response.headers_mut().unwrap();
 
                                 for (key, value) in new_headers {
-                                    headers.insert(HeaderName::from_bytes(key.as_bytes()).unwrap(), HeaderValue::from_bytes(value.as_bytes()).unwrap());
+                                    headers.insert(
+                                        HeaderName::from_bytes(key.as_bytes()).unwrap(),
+                                        HeaderValue::from_bytes(value.as_bytes()).unwrap(),
+                                    );
                                 }
                             }
-                        Ok(response
-                            .status(200)
-                            .body(serde_json::to_string_pretty(&value).unwrap()).unwrap()
-                        )
-                    },
-                    ExecuteOutput::Any(encode) => {
                             Ok(response
-                        .status(200)
-                        .body(serde_json::to_string_pretty(&json!({
-                                "data": encode.encode().unwrap()
-                            })).unwrap()).unwrap())
-                        },
+                                .status(status)
+                                .body(serde_json::to_string_pretty(&value).unwrap())
+                                .unwrap())
+                        }
+                        ExecuteOutput::Any(encode) => Ok(response
+                            .status(200)
+                            .body(
+                                serde_json::to_string_pretty(&json!({
+                                    "data": encode.encode().unwrap()
+                                }))
+                                .unwrap(),
+                            )
+                            .unwrap()),
                     }
-                },
-                Err(err) => Ok(response
-                    .status({
-                        match err {
-                            RequestError::Expected(status, _) => status,
-                            RequestError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+                }
+                Err(err) => {
+                    // Throttled clients are told when they may retry.
+                    match &err {
+                        RequestError::Throttled(retry_after, _) => {
+                            response = response.header(
+                                "Retry-After",
+                                (retry_after.as_secs_f64().ceil() as u64).to_string(),
+                            );
                         }
-                    })
-                    .body(serde_json::to_string_pretty(&json!({
-                        "error": match err {
-                            RequestError::Expected(_, err) => err,
-                            RequestError::Other(err) => format!("Unexpected error: {}", err).to_compact_string(),
+                        // Along with the limit they exceeded.
+                        RequestError::RateLimited(limit, reset, _) => {
+                            let reset = (reset.as_secs_f64().ceil() as u64).to_string();
+
+                            response = response
+                                .header("RateLimit-Limit", limit.to_string())
+                                .header("RateLimit-Remaining", "0")
+                                .header("RateLimit-Reset", reset.as_str())
+                                .header("Retry-After", reset);
                         }
-                    })).unwrap()).unwrap()
-                ),
+                        _ => {}
+                    }
+
+                    Ok(response
+                        .status({
+                            match err {
+                                RequestError::Expected(status, _) => status,
+                                RequestError::Throttled(_, _)
+                                | RequestError::RateLimited(_, _, _) => StatusCode::TOO_MANY_REQUESTS,
+                                RequestError::Other(_) => StatusCode::INTERNAL_SERVER_ERROR,
+                            }
+                        })
+                        .body(serde_json::to_string_pretty(&json!({
+                            "error": match err {
+                                RequestError::Expected(_, err) => err,
+                                RequestError::Throttled(_, err) => err,
+                                RequestError::RateLimited(_, _, err) => err,
+                                RequestError::Other(err) => format!("Unexpected error: {}", err).to_compact_string(),
+                            }
+                        })).unwrap()).unwrap()
+                    )
+                }
             }
         })
     }
@@ -5,6 +5,8 @@ use crate::*;
 
 use super::*;
 
+use waveless_commons::execute::mysql::MySQLExecute;
+
 /// TODO: add documentation.
 #[derive(Clone, Debug)]
 pub struct ExecuteHandler;
@@ -41,12 +43,23 @@ impl Service<RequestParamsExtractorRequest> for ExecuteHandler {
                 ));
             };
 
+            let input = ExecuteInput::new(request_params, request_body);
+
+            // MySQL queries go through the query cache when it's set.
+            let mysql_execute = execute_strategy
+                .to_owned()
+                .into_arc_any()
+                .downcast::<MySQLExecute>()
+                .ok();
+
+            if let (Some(query_cache), Some(mysql_execute)) = (query_cache().await?, mysql_execute)
+            {
+                return execute_cached(&endpoint, &mysql_execute, query_cache, db_conn, input)
+                    .await;
+            }
+
             execute_strategy
-                .execute(
-                    *endpoint.method(),
-                    db_conn,
-                    ExecuteInput::new(request_params, request_body),
-                )
+                .execute(*endpoint.method(), db_conn, input)
                 .await
         })
     }
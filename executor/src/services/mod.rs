@@ -3,13 +3,23 @@
 
 pub mod execute_wrapper;
 pub mod handler;
+pub mod query_cache;
+pub mod quota;
+pub mod rate_limit;
 pub mod request_params;
+pub mod response_cache;
 pub mod router;
+pub mod statistics;
 
 mod auth;
 
 pub use auth::*;
 pub use execute_wrapper::*;
 pub use handler::*;
+pub use query_cache::*;
+pub use quota::*;
+pub use rate_limit::*;
 pub use request_params::*;
+pub use response_cache::*;
 pub use router::*;
+pub use statistics::*;
@@ -0,0 +1,146 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+use crate::*;
+
+use waveless_commons::execute::{cache::*, mysql::MySQLExecute};
+use waveless_commons::store::*;
+
+/// Query cache of this instance, set up from the build's settings when it's first used.
+static QUERY_CACHE: OnceCell<Option<QueryCache>> = OnceCell::const_new();
+
+/// The query cache, if it's set for the build.
+pub async fn query_cache() -> Result<Option<&'static QueryCache>> {
+    QUERY_CACHE
+        .get_or_try_init(|| async {
+            let Some(settings) = RuntimeCx::acquire()
+                .build()
+                .read()
+                .await
+                .executor()
+                .query_cache()
+                .to_owned()
+            else {
+                return Ok(None);
+            };
+
+            // The tables' versions are kept next to the results.
+            let store = match settings.path() {
+                Some(path) => Some((
+                    EmbeddedStore::open(path, settings.table_name())?,
+                    EmbeddedStore::open(path, &format!("{}_versions", settings.table_name()))?,
+                )),
+                None => None,
+            };
+
+            Ok::<_, anyhow::Error>(Some(QueryCache::new(*settings.max_entries(), store)))
+        })
+        .await
+        .map(|query_cache| {
+            // Shared, so the writes of the authentication methods outdate its results too.
+            if let Some(query_cache) = query_cache {
+                query_cache.share();
+            }
+
+            query_cache.as_ref()
+        })
+}
+
+/// Executes the endpoint's query through the cache: reads are served from it when the endpoint
+/// sets a TTL, while successful writes invalidate the results of the tables they touch.
+pub async fn execute_cached(
+    endpoint: &Endpoint,
+    execute: &MySQLExecute,
+    query_cache: &QueryCache,
+    db_conn: Arc<dyn AnyDatabaseConnection>,
+    input: ExecuteInput,
+) -> Result<ExecuteOutput, RequestError> {
+    // Endpoints on the primary database may name it or not.
+    let database = endpoint
+        .target_database()
+        .to_owned()
+        .unwrap_or(DATABASES_CONNS.get().unwrap().primary_name().to_owned());
+
+    let (query, values) = execute.bind(*endpoint.method(), &input)?;
+
+    let tables = query_tables(&database, &query);
+
+    if !is_read_query(&query) {
+        let output = execute.execute_bound(db_conn, query, values).await?;
+
+        if let Err(err) = query_cache.invalidate(tables).await {
+            error!(
+                "Cannot invalidate the cached results of '{}'. {}",
+                endpoint.id(),
+                err
+            );
+        }
+
+        return Ok(output);
+    }
+
+    let Some(ttl) = endpoint.query_cache_ttl() else {
+        return execute.execute_bound(db_conn, query, values).await;
+    };
+
+    let key = QueryCache::key(&database, &query, &values);
+
+    // The database is queried when the cache cannot be read.
+    match query_cache.get(&key).await {
+        Ok(Some(result)) => return Ok(ExecuteOutput::Json(None, result)),
+        Ok(None) => {}
+        Err(err) => warn!(
+            "Cannot read the cached result of '{}'. {}",
+            endpoint.id(),
+            err
+        ),
+    }
+
+    // Taken before querying, so the writes made meanwhile outdate the result.
+    let versions = match query_cache.versions(tables).await {
+        Ok(versions) => Some(versions),
+        Err(err) => {
+            warn!(
+                "Cannot read the versions of the tables of '{}'. {}",
+                endpoint.id(),
+                err
+            );
+
+            None
+        }
+    };
+
+    let output = execute.execute_bound(db_conn, query, values).await?;
+
+    if let (ExecuteOutput::Json(_, result), Some(versions)) = (&output, versions) {
+        if let Err(err) = query_cache
+            .insert(key, versions, result, Duration::from_secs(*ttl))
+            .await
+        {
+            warn!("Cannot cache the result of '{}'. {}", endpoint.id(), err);
+        }
+    }
+
+    Ok(output)
+}
+
+/// Removes the expired and outdated results of the query cache periodically, if it's set.
+pub async fn sweep_query_cache() {
+    loop {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+
+        let query_cache = match query_cache().await {
+            Ok(Some(query_cache)) => query_cache,
+            Ok(None) => continue,
+            Err(err) => {
+                error!("Cannot set up the query cache. {}", err);
+                continue;
+            }
+        };
+
+        match query_cache.sweep().await {
+            Ok(removed) => debug!("Removed {} cached query results.", removed),
+            Err(err) => error!("Cannot sweep the query cache. {}", err),
+        }
+    }
+}
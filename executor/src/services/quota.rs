@@ -0,0 +1,510 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+//!
+//! Usage accounting and quotas of the authenticated consumers (API keys or users).
+//! Counters are kept on the embedded store per period, under `{period}/{consumer}` for the
+//! consumer's totals and `{period}/{consumer}/{endpoint}` for each endpoint, where periods are
+//! either days (`2026-10-18`) or months (`2026-10`) and consumers are `key:{id}` or `user:{id}`.
+//! Counters are updated in memory and flushed to the store periodically, so the usage of the last
+//! few seconds is lost if the process stops abruptly.
+//!
+
+use crate::*;
+
+use waveless_commons::project::{QuotaConsumer, QuotaLimit, QuotaPeriod, Quotas};
+use waveless_commons::store::*;
+
+use serde::{Deserialize, Serialize};
+
+/// Internal param injected by the session's watchdog with the request's consumer.
+pub const CONSUMER_PARAM: &str = "consumer";
+
+/// Response header with the requests left on the most restrictive quota.
+pub const QUOTA_REMAINING_HEADER: &str = "X-Quota-Remaining";
+
+/// How often the counters are flushed to the store.
+const USAGE_FLUSH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Usage of a consumer during a period.
+#[derive(Clone, Copy, PartialEq, Default, Serialize, Deserialize, Debug)]
+pub struct UsageEntry {
+    requests: u64,
+    rows: u64,
+}
+
+/// A counter loaded from the store, which is flushed back once it changes.
+#[derive(Clone, Copy, Default, Debug)]
+struct UsageCounter {
+    usage: UsageEntry,
+    dirty: bool,
+}
+
+/// Counters by their key, loaded on their first use.
+static USAGE_COUNTERS: LazyLock<DashMap<CompactString, UsageCounter>> = LazyLock::new(DashMap::new);
+
+/// Store of the counters, opened on its first use.
+static USAGE_STORE: OnceCell<EmbeddedStore<CompactString, UsageEntry>> = OnceCell::const_new();
+
+/// The counters' store, opened with the quotas' settings when it's first used.
+async fn usage_store(quotas: &Quotas) -> Result<&'static EmbeddedStore<CompactString, UsageEntry>> {
+    USAGE_STORE
+        .get_or_try_init(|| async { EmbeddedStore::open(quotas.path(), quotas.table_name()) })
+        .await
+}
+
+/// Gets the counter's usage, loading it from the store if it isn't in memory yet.
+async fn usage(
+    store: &EmbeddedStore<CompactString, UsageEntry>,
+    key: &CompactString,
+) -> Result<UsageEntry> {
+    if let Some(counter) = USAGE_COUNTERS.get(key) {
+        return Ok(counter.usage);
+    }
+
+    let usage = store.get(key.to_owned()).await?.unwrap_or_default();
+
+    // Another request may have loaded it meanwhile.
+    Ok(USAGE_COUNTERS
+        .entry(key.to_owned())
+        .or_insert(UsageCounter {
+            usage,
+            dirty: false,
+        })
+        .usage)
+}
+
+/// Writes the changed counters to the store, they're marked as changed again if it fails.
+async fn flush_usage(quotas: &Quotas) -> Result<()> {
+    let mut changed = HashMap::new();
+
+    for mut counter in USAGE_COUNTERS.iter_mut() {
+        if counter.dirty {
+            counter.dirty = false;
+            changed.insert(counter.key().to_owned(), counter.usage);
+        }
+    }
+
+    if changed.is_empty() {
+        return Ok(());
+    }
+
+    let keys = changed.keys().cloned().collect::<Vec<_>>();
+
+    let flushed = match usage_store(quotas).await {
+        Ok(store) => {
+            let values = changed.to_owned();
+
+            store
+                .update(keys, move |key, _| values[key])
+                .await
+                .map(|_| ())
+        }
+        Err(err) => Err(err),
+    };
+
+    if flushed.is_err() {
+        for key in changed.keys() {
+            if let Some(mut counter) = USAGE_COUNTERS.get_mut(key) {
+                counter.dirty = true;
+            }
+        }
+    }
+
+    flushed
+}
+
+/// Whether the limit applies to the consumer's request on the endpoint.
+fn limit_applies(limit: &QuotaLimit, consumer: &str, endpoint_id: &str) -> bool {
+    let kind = match limit.consumer() {
+        QuotaConsumer::ApiKey => "key:",
+        QuotaConsumer::User => "user:",
+    };
+
+    consumer.starts_with(kind)
+        && (limit.endpoints().is_empty()
+            || limit
+                .endpoints()
+                .iter()
+                .any(|endpoint| endpoint == endpoint_id))
+}
+
+/// Adds the usage to the counters, which must be loaded already.
+fn account(keys: &[CompactString], requests: i64, rows: u64) {
+    for key in keys {
+        if let Some(mut counter) = USAGE_COUNTERS.get_mut(key) {
+            counter.usage.requests = counter.usage.requests.saturating_add_signed(requests);
+            counter.usage.rows += rows;
+            counter.dirty = true;
+        }
+    }
+}
+
+/// Reserves the consumer's quotas before executing the endpoint and accounts its rows afterwards.
+#[derive(Clone, Constructor, Debug)]
+pub struct QuotaGuard<S>
+where
+    S: Service<RequestParamsExtractorRequest, Response = ExecuteOutput, Error = RequestError>,
+{
+    inner: S,
+}
+
+pub struct QuotaGuardLayer;
+
+impl<S> Layer<S> for QuotaGuardLayer
+where
+    S: Service<RequestParamsExtractorRequest, Response = ExecuteOutput, Error = RequestError>,
+{
+    type Service = QuotaGuard<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        QuotaGuard { inner }
+    }
+}
+
+impl<S> Service<RequestParamsExtractorRequest> for QuotaGuard<S>
+where
+    S: Service<RequestParamsExtractorRequest, Response = ExecuteOutput, Error = RequestError>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send + 'static,
+    S::Response: Send + 'static,
+    S::Error: Send + 'static,
+{
+    type Response = S::Response;
+
+    type Error = S::Error;
+
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    #[instrument(skip_all)]
+    fn call(&mut self, cx: RequestParamsExtractorRequest) -> Self::Future {
+        let mut inner = self.inner.to_owned();
+
+        Box::pin(async move {
+            let (headers, endpoint, request_params, request_body) = cx;
+
+            let quotas = RuntimeCx::acquire()
+                .build()
+                .read()
+                .await
+                .config()
+                .quotas()
+                .to_owned();
+
+            // Only authenticated requests have a consumer.
+            let consumer = match request_params.get(CONSUMER_PARAM) {
+                Some(ExecuteParamValue::Internal(consumer)) => Some(consumer.to_owned()),
+                _ => None,
+            };
+
+            let (Some(quotas), Some(consumer)) = (quotas, consumer) else {
+                return inner
+                    .call((headers, endpoint, request_params, request_body))
+                    .await;
+            };
+
+            let endpoint_id = endpoint.id().to_owned();
+
+            let store = usage_store(&quotas).await?;
+
+            let limits = quotas
+                .limits()
+                .iter()
+                .filter(|limit| limit_applies(limit, &consumer, &endpoint_id))
+                .cloned()
+                .collect::<CheapVec<QuotaLimit, 0>>();
+
+            // The request is accounted on the consumer's and endpoint's counters of every period.
+            let keys: Vec<CompactString> = [QuotaPeriod::Daily, QuotaPeriod::Monthly]
+                .iter()
+                .flat_map(|period| {
+                    let period = period.current();
+
+                    [
+                        format!("{}/{}", period, consumer).to_compact_string(),
+                        format!("{}/{}/{}", period, consumer, endpoint_id).to_compact_string(),
+                    ]
+                })
+                .collect();
+
+            // The counters must be loaded first, so the stored usage isn't overwritten.
+            for key in keys.iter() {
+                self::usage(store, key).await?;
+            }
+
+            // The request is reserved before checking the quotas, so concurrent requests can't
+            // overshoot them, and it's released if the quotas or the request fail.
+            account(&keys, 1, 0);
+
+            // The usage of each limit, summing the endpoints' counters when they're given.
+            let mut usages = CheapVec::<UsageEntry, 0>::new();
+
+            for limit in limits.iter() {
+                let period = limit.period().current();
+
+                let limit_keys = if limit.endpoints().is_empty() {
+                    vec![format!("{}/{}", period, consumer).to_compact_string()]
+                } else {
+                    limit
+                        .endpoints()
+                        .iter()
+                        .map(|endpoint| {
+                            format!("{}/{}/{}", period, consumer, endpoint).to_compact_string()
+                        })
+                        .collect()
+                };
+
+                let mut usage = UsageEntry::default();
+
+                for key in limit_keys {
+                    let entry = match self::usage(store, &key).await {
+                        Ok(entry) => entry,
+                        Err(err) => {
+                            account(&keys, -1, 0);
+                            return Err(err.into());
+                        }
+                    };
+
+                    usage.requests += entry.requests;
+                    usage.rows += entry.rows;
+                }
+
+                // The usage includes this request's reservation.
+                if limit
+                    .requests()
+                    .is_some_and(|requests| usage.requests > requests)
+                    || limit.rows().is_some_and(|rows| usage.rows >= rows)
+                {
+                    account(&keys, -1, 0);
+
+                    return Err(RequestError::Expected(
+                        StatusCode::TOO_MANY_REQUESTS,
+                        format!(
+                            "The {} quota of '{}' has been exceeded.",
+                            limit.period().to_string().to_lowercase(),
+                            consumer
+                        )
+                        .to_compact_string(),
+                    ));
+                }
+
+                usages.push(usage);
+            }
+
+            let mut output = inner
+                .call((headers, endpoint, request_params, request_body))
+                .await;
+
+            // Only successful requests are accounted, so failures don't consume the quotas.
+            match &output {
+                Ok(ExecuteOutput::Json(_, serde_json::Value::Array(rows))) => {
+                    account(&keys, 0, rows.len() as u64)
+                }
+                Ok(_) => {}
+                Err(_) => {
+                    account(&keys, -1, 0);
+                    return output;
+                }
+            }
+
+            // Notifies the requests left on the most restrictive quota.
+            let remaining = limits
+                .iter()
+                .zip(usages.iter())
+                .filter_map(|(limit, usage)| {
+                    limit
+                        .requests()
+                        .map(|requests| requests.saturating_sub(usage.requests))
+                })
+                .min();
+
+            if let (Some(remaining), Ok(ExecuteOutput::Json(headers, _))) =
+                (remaining, output.as_mut())
+            {
+                headers.get_or_insert_default().insert(
+                    QUOTA_REMAINING_HEADER.to_compact_string(),
+                    remaining.to_compact_string(),
+                );
+            }
+
+            output
+        })
+    }
+}
+
+/// Reports the usage per consumer and endpoint, only allowed to the admin roles.
+#[derive(Clone, Constructor, Debug)]
+pub struct UsageCaptured;
+
+impl Service<RequestParamsExtractorRequest> for UsageCaptured {
+    type Response = ExecuteOutput;
+
+    type Error = RequestError;
+
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    #[instrument(skip_all)]
+    fn call(&mut self, cx: RequestParamsExtractorRequest) -> Self::Future {
+        let future: Pin<_> = Box::pin(async move {
+            let (_, _, request_params, _) = cx;
+
+            let client_param = |param: &str| match request_params.get(param) {
+                Some(ExecuteParamValue::Client(Some(value))) => Some(value.to_owned()),
+                _ => None,
+            };
+
+            let quotas = RuntimeCx::acquire()
+                .build()
+                .read()
+                .await
+                .config()
+                .quotas()
+                .to_owned()
+                .ok_or(RequestError::Other(anyhow!(
+                    "Quotas are not set for the current build."
+                )))?;
+
+            // The current month by default.
+            let period = client_param("period").unwrap_or(QuotaPeriod::Monthly.current());
+
+            let consumer = client_param("consumer");
+
+            // The report reads the store, so the latest usage is flushed first.
+            flush_usage(&quotas).await?;
+
+            let prefix = format!("{}/", period);
+
+            let entries = usage_store(&quotas)
+                .await?
+                .entries(move |key, _| {
+                    key.strip_prefix(&prefix).is_some_and(|key| {
+                        consumer
+                            .as_ref()
+                            .is_none_or(|consumer| key.split('/').next() == Some(consumer.as_str()))
+                    })
+                })
+                .await?;
+
+            let mut consumers = serde_json::Map::new();
+
+            for (key, usage) in entries {
+                let mut parts = key.splitn(3, '/').skip(1);
+
+                let (Some(consumer), endpoint) = (parts.next(), parts.next()) else {
+                    continue;
+                };
+
+                let report = consumers
+                    .entry(consumer)
+                    .or_insert_with(|| json!({ "requests": 0, "rows": 0, "endpoints": {} }));
+
+                match endpoint {
+                    Some(endpoint) => {
+                        report["endpoints"][endpoint] = json!(usage);
+                    }
+                    None => {
+                        report["requests"] = json!(usage.requests);
+                        report["rows"] = json!(usage.rows);
+                    }
+                }
+            }
+
+            Ok(ExecuteOutput::Json(
+                None,
+                json!({ "period": period, "consumers": consumers }),
+            ))
+        })
+        .into();
+
+        future as Self::Future // Actually, this is not an error! https://github.com/rust-lang/rust/issues/92929
+    }
+}
+
+/// Flushes the changed counters to the store periodically.
+/// The build is read on every run so it follows reloads.
+#[instrument(skip_all)]
+pub async fn flush_usage_periodically() {
+    loop {
+        tokio::time::sleep(USAGE_FLUSH_INTERVAL).await;
+
+        let Some(quotas) = RuntimeCx::acquire()
+            .build()
+            .read()
+            .await
+            .config()
+            .quotas()
+            .to_owned()
+        else {
+            continue;
+        };
+
+        if let Err(err) = flush_usage(&quotas).await {
+            error!("Cannot flush the usage counters. {}", err);
+        }
+    }
+}
+
+/// Removes the counters of past periods daily, keeping a month of daily counters and a year of
+/// monthly ones. The build is read on every run so it follows reloads.
+#[instrument(skip_all)]
+pub async fn sweep_usage() {
+    loop {
+        tokio::time::sleep(Duration::from_secs(86400)).await;
+
+        let Some(quotas) = RuntimeCx::acquire()
+            .build()
+            .read()
+            .await
+            .config()
+            .quotas()
+            .to_owned()
+        else {
+            continue;
+        };
+
+        let now = chrono::Utc::now();
+
+        // Periods are compared as strings, as their format sorts chronologically.
+        let oldest_day = (now - chrono::Duration::days(31))
+            .format("%Y-%m-%d")
+            .to_compact_string();
+        let oldest_month = (now - chrono::Duration::days(366))
+            .format("%Y-%m")
+            .to_compact_string();
+
+        let current = move |key: &CompactString| {
+            let period = key.split('/').next().unwrap_or_default();
+
+            if period.len() == oldest_day.len() {
+                period >= oldest_day.as_str()
+            } else {
+                period >= oldest_month.as_str()
+            }
+        };
+
+        USAGE_COUNTERS.retain(|key, _| current(key));
+
+        let retained = match usage_store(&quotas).await {
+            Ok(store) => store.retain(move |key, _| current(&key)).await,
+            Err(err) => Err(err),
+        };
+
+        match retained {
+            Ok(removed) => debug!(
+                "{} usage counters of past periods have been removed.",
+                removed
+            ),
+            Err(err) => error!("Cannot remove the usage counters of past periods. {}", err),
+        }
+    }
+}
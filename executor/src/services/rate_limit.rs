@@ -0,0 +1,351 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+//!
+//! Rate limits of the endpoints, counted on fixed windows per rule and client, where clients are
+//! `ip:{address}`, `user:{id}` or `key:{id}`. Responses tell the most restrictive limit applied
+//! through the `RateLimit-*` headers, while rejected requests tell when to retry.
+//!
+
+use crate::*;
+
+use waveless_commons::project::{RateLimitKey, RateLimitRule};
+
+/// Requests of each rule's client during its current window, along with the window's start.
+/// NOTE: it's kept in memory, so each instance limits the requests it receives.
+static RATE_LIMIT_WINDOWS: LazyLock<DashMap<CompactString, (u64, Instant)>> =
+    LazyLock::new(DashMap::new);
+
+/// Whether the rule applies to the endpoint.
+fn rule_applies(rule: &RateLimitRule, endpoint: &Endpoint) -> bool {
+    (rule.endpoints().is_empty() && rule.tags().is_empty())
+        || rule.endpoints().iter().any(|id| id == endpoint.id())
+        || rule.tags().iter().any(|tag| endpoint.tags().contains(tag))
+}
+
+/// Identifies the rule by its settings rather than its position, so reloads which add, remove or
+/// reorder rules keep the windows of those which didn't change.
+fn rule_id(rule: &RateLimitRule) -> CompactString {
+    format!(
+        "{}:{}:{}:{}:{}",
+        rule.key(),
+        rule.requests(),
+        rule.period(),
+        rule.endpoints().join(","),
+        rule.tags().join(",")
+    )
+    .to_compact_string()
+}
+
+/// Counts a request on the window, which starts over once its period has passed, returning
+/// whether it's allowed along with the window's requests and its start.
+/// NOTE: rejected requests aren't counted, so they don't extend the limit.
+fn count_request(
+    window_key: CompactString,
+    limit: u64,
+    period: Duration,
+    now: Instant,
+) -> (bool, u64, Instant) {
+    let mut window = RATE_LIMIT_WINDOWS.entry(window_key).or_insert((0, now));
+
+    if now.saturating_duration_since(window.1) >= period {
+        *window = (0, now);
+    }
+
+    let allowed = window.0 < limit;
+
+    if allowed {
+        window.0 += 1;
+    }
+
+    (allowed, window.0, window.1)
+}
+
+/// Gives back the requests counted on the windows, unless they have started over meanwhile.
+fn uncount_requests(counted: &[(CompactString, Instant)]) {
+    for (window_key, started_at) in counted {
+        if let Some(mut window) = RATE_LIMIT_WINDOWS
+            .get_mut(window_key)
+            .filter(|window| window.1 == *started_at)
+        {
+            window.0 = window.0.saturating_sub(1);
+        }
+    }
+}
+
+/// The client the rule counts the request to, if it's known.
+fn rule_client(
+    rule: &RateLimitRule,
+    headers: &HeaderMap,
+    consumer: Option<&CompactString>,
+) -> Option<CompactString> {
+    match rule.key() {
+        RateLimitKey::Ip => headers
+            .get(server::PEER_ADDR_HEADER)
+            .and_then(|address| address.to_str().ok())
+            .map(|address| format!("ip:{}", address).to_compact_string()),
+        RateLimitKey::User => consumer
+            .filter(|consumer| consumer.starts_with("user:"))
+            .cloned(),
+        RateLimitKey::ApiKey => consumer
+            .filter(|consumer| consumer.starts_with("key:"))
+            .cloned(),
+    }
+}
+
+/// Removes the windows which have already ended every minute, the build is read on every run so
+/// it follows reloads.
+#[instrument(skip_all)]
+pub async fn sweep_rate_limits() {
+    loop {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+
+        let longest_period = RuntimeCx::acquire()
+            .build()
+            .read()
+            .await
+            .executor()
+            .rate_limiting()
+            .rules()
+            .iter()
+            .map(|rule| *rule.period())
+            .max()
+            .unwrap_or_default();
+
+        RATE_LIMIT_WINDOWS.retain(|_, (_, started_at)| {
+            started_at.elapsed() < Duration::from_secs(longest_period)
+        });
+
+        debug!(
+            "Cleaned up the rate limit windows (size: {})",
+            RATE_LIMIT_WINDOWS.len()
+        );
+    }
+}
+
+/// Counts the request on the rules applying to it, rejecting it when any of them is exceeded.
+/// The rules keyed by the client's IP are applied by their own guard before the session is
+/// checked, while the rest are applied once the request's consumer is known.
+#[derive(Clone, Constructor, Debug)]
+pub struct RateLimitGuard<S>
+where
+    S: Service<RequestParamsExtractorRequest, Response = ExecuteOutput, Error = RequestError>,
+{
+    inner: S,
+    by_ip: bool,
+}
+
+pub struct RateLimitGuardLayer {
+    by_ip: bool,
+}
+
+impl RateLimitGuardLayer {
+    /// Applies the rules keyed by the client's IP.
+    pub fn by_ip() -> Self {
+        Self { by_ip: true }
+    }
+
+    /// Applies the rules keyed by the request's consumer.
+    pub fn by_consumer() -> Self {
+        Self { by_ip: false }
+    }
+}
+
+impl<S> Layer<S> for RateLimitGuardLayer
+where
+    S: Service<RequestParamsExtractorRequest, Response = ExecuteOutput, Error = RequestError>,
+{
+    type Service = RateLimitGuard<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitGuard {
+            inner,
+            by_ip: self.by_ip,
+        }
+    }
+}
+
+impl<S> Service<RequestParamsExtractorRequest> for RateLimitGuard<S>
+where
+    S: Service<RequestParamsExtractorRequest, Response = ExecuteOutput, Error = RequestError>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send + 'static,
+    S::Response: Send + 'static,
+    S::Error: Send + 'static,
+{
+    type Response = S::Response;
+
+    type Error = S::Error;
+
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    #[instrument(skip_all)]
+    fn call(&mut self, cx: RequestParamsExtractorRequest) -> Self::Future {
+        let mut inner = self.inner.to_owned();
+
+        let by_ip = self.by_ip;
+
+        Box::pin(async move {
+            let (headers, endpoint, request_params, request_body) = cx;
+
+            let rules = RuntimeCx::acquire()
+                .build()
+                .read()
+                .await
+                .executor()
+                .rate_limiting()
+                .rules()
+                .to_owned();
+
+            let consumer = match request_params.get(CONSUMER_PARAM) {
+                Some(ExecuteParamValue::Internal(consumer)) => Some(consumer.to_owned()),
+                _ => None,
+            };
+
+            let now = Instant::now();
+
+            // The limit, the requests left and the time left of the most restrictive rule.
+            let mut most_restrictive: Option<(u64, u64, Duration)> = None;
+
+            // The windows the request has been counted on, so it's given back if it's rejected.
+            let mut counted = CheapVec::<(CompactString, Instant), 0>::new();
+
+            for rule in rules.iter() {
+                if !rule_applies(rule, &endpoint) || (*rule.key() == RateLimitKey::Ip) != by_ip {
+                    continue;
+                }
+
+                let Some(client) = rule_client(rule, &headers, consumer.as_ref()) else {
+                    continue;
+                };
+
+                let period = Duration::from_secs(*rule.period());
+
+                let window_key = format!("{}/{}", rule_id(rule), client).to_compact_string();
+
+                let (allowed, requests, started_at) =
+                    count_request(window_key.to_owned(), *rule.requests(), period, now);
+
+                let reset = period.saturating_sub(now.saturating_duration_since(started_at));
+
+                if !allowed {
+                    uncount_requests(&counted[..]);
+
+                    return Err(RequestError::RateLimited(
+                        *rule.requests(),
+                        reset,
+                        "Rate limit exceeded, try again later.".to_compact_string(),
+                    ));
+                }
+
+                counted.push((window_key, started_at));
+
+                let remaining = rule.requests().saturating_sub(requests);
+
+                if most_restrictive.is_none_or(|(_, most_remaining, _)| remaining < most_remaining)
+                {
+                    most_restrictive = Some((*rule.requests(), remaining, reset));
+                }
+            }
+
+            let mut output = inner
+                .call((headers, endpoint, request_params, request_body))
+                .await;
+
+            // Requests rejected by the rules of the inner guard aren't counted either.
+            if let Err(RequestError::RateLimited(..)) = output {
+                uncount_requests(&counted[..]);
+            }
+
+            // The inner guard may have told a more restrictive rule already.
+            let told = match &output {
+                Ok(ExecuteOutput::Json(Some(headers), _)) => headers
+                    .get("RateLimit-Remaining")
+                    .and_then(|told| told.parse::<u64>().ok()),
+                _ => None,
+            };
+
+            let most_restrictive = most_restrictive
+                .filter(|(_, remaining, _)| told.is_none_or(|told| *remaining < told));
+
+            if let (Some((limit, remaining, reset)), Ok(ExecuteOutput::Json(headers, _))) =
+                (most_restrictive, output.as_mut())
+            {
+                let headers = headers.get_or_insert_default();
+
+                headers.insert(
+                    "RateLimit-Limit".to_compact_string(),
+                    limit.to_compact_string(),
+                );
+                headers.insert(
+                    "RateLimit-Remaining".to_compact_string(),
+                    remaining.to_compact_string(),
+                );
+                headers.insert(
+                    "RateLimit-Reset".to_compact_string(),
+                    (reset.as_secs_f64().ceil() as u64).to_compact_string(),
+                );
+            }
+
+            output
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_windows() {
+        let now = Instant::now();
+
+        let period = Duration::from_secs(10);
+
+        let key = || "test/fixed_windows".to_compact_string();
+
+        // Requests are allowed up to the limit, while rejected ones aren't counted.
+        assert_eq!(count_request(key(), 2, period, now), (true, 1, now));
+        assert_eq!(count_request(key(), 2, period, now), (true, 2, now));
+        assert_eq!(
+            count_request(key(), 2, period, now + Duration::from_secs(9)),
+            (false, 2, now)
+        );
+
+        // The window starts over once its period has passed.
+        let later = now + Duration::from_secs(10);
+
+        assert_eq!(count_request(key(), 2, period, later), (true, 1, later));
+
+        // Requests rejected by other rules are given back, unless their window has started over.
+        uncount_requests(&[(key(), later)]);
+
+        assert_eq!(count_request(key(), 2, period, later), (true, 1, later));
+
+        uncount_requests(&[(key(), now)]);
+
+        assert_eq!(count_request(key(), 2, period, later), (true, 2, later));
+    }
+
+    #[test]
+    fn rules_are_identified_by_their_settings() {
+        let rule = |requests| {
+            RateLimitRule::new(
+                RateLimitKey::Ip,
+                requests,
+                60,
+                CheapVec::from_vec(vec!["login".to_compact_string()]),
+                CheapVec::new(),
+            )
+        };
+
+        assert_eq!(rule_id(&rule(10)), rule_id(&rule(10)));
+        assert_ne!(rule_id(&rule(10)), rule_id(&rule(20)));
+    }
+}
@@ -0,0 +1,626 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+//!
+//! Cache of the responses of the endpoints opting in. Responses are keyed by the endpoint, the
+//! client's params, the headers the endpoint varies by and, when it requires auth, the consumer,
+//! so they're never shared across users. Successful writes invalidate the cached responses of the
+//! endpoints whose routes start with the same literal segment or with any of the same tags (e.g.
+//! the target table's), by increasing their generations rather than scanning the cache.
+//!
+
+use crate::*;
+
+use std::collections::BTreeMap;
+
+/// Max responses cached, new ones aren't cached while it's full of unexpired ones.
+const MAX_CACHED_RESPONSES: usize = 4096;
+
+/// A cached response along with what invalidates it.
+#[derive(Clone, Debug)]
+struct CachedResponse {
+    headers: Option<HashMap<CompactString, CompactString>>,
+    value: serde_json::Value,
+    /// The scopes which invalidate it, along with their generation when it was computed.
+    scopes: CheapVec<CompactString, 0>,
+    generation: u64,
+    stored_at: Instant,
+    /// When it stops being served, stale or not.
+    expires_at: Instant,
+    /// Whether a request is already refreshing it.
+    refreshing: bool,
+}
+
+/// NOTE: it's kept in memory, so each instance caches (and invalidates) the responses it serves.
+static RESPONSE_CACHE: LazyLock<DashMap<CompactString, CachedResponse>> =
+    LazyLock::new(DashMap::new);
+
+/// Generation of each route prefix (`route/{segment}`) and tag (`tag/{tag}`), increased by the
+/// writes on them, so the responses computed before are outdated.
+static GENERATIONS: LazyLock<DashMap<CompactString, u64>> = LazyLock::new(DashMap::new);
+
+/// The endpoint's cache settings, the deprecated `http_cache_time` applies to the `GET` endpoints
+/// (but the internal ones) which don't set their own.
+fn endpoint_cache(endpoint: &Endpoint, http_cache_time: Option<u64>) -> Option<EndpointCache> {
+    match (endpoint.cache(), http_cache_time) {
+        (Some(cache), _) => Some(cache.to_owned()),
+        (None, Some(ttl)) if ttl > 0 && endpoint.execute().is_some() => {
+            Some(EndpointCache::new(ttl, 0, CheapVec::new()))
+        }
+        _ => None,
+    }
+}
+
+/// Identifies the response of the request.
+fn cache_key(
+    endpoint: &Endpoint,
+    cache: &EndpointCache,
+    headers: &HeaderMap,
+    request_params: &HashMap<CompactString, ExecuteParamValue>,
+) -> CompactString {
+    let consumer = match request_params.get(CONSUMER_PARAM) {
+        Some(ExecuteParamValue::Internal(consumer)) => Some(consumer),
+        _ => None,
+    };
+
+    let params = request_params
+        .iter()
+        .filter_map(|(key, value)| match value {
+            ExecuteParamValue::Client(value) => Some((key, value)),
+            ExecuteParamValue::Internal(_) => None,
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    let vary = cache
+        .vary_headers()
+        .iter()
+        .map(|name| {
+            headers
+                .get(name.as_str())
+                .and_then(|value| value.to_str().ok())
+        })
+        .collect::<CheapVec<_, 0>>();
+
+    format!("{}/{:?}/{:?}/{:?}", endpoint.id(), consumer, vary, params).to_compact_string()
+}
+
+/// The `Cache-Control` and `Vary` headers of the endpoint's responses.
+fn cache_headers(
+    endpoint: &Endpoint,
+    cache: &EndpointCache,
+) -> CheapVec<(CompactString, CompactString), 2> {
+    let mut cache_control = format!(
+        "{}, max-age={}",
+        if *endpoint.require_auth() {
+            "private"
+        } else {
+            "public"
+        },
+        cache.ttl()
+    );
+
+    if *cache.stale_while_revalidate() > 0 {
+        cache_control.push_str(&format!(
+            ", stale-while-revalidate={}",
+            cache.stale_while_revalidate()
+        ));
+    }
+
+    let mut vary = cache.vary_headers().to_owned();
+
+    // The consumer is identified by any of them.
+    if *endpoint.require_auth() {
+        vary.extend(
+            ["Authorization", "Cookie", API_KEY_HEADER].map(|name| name.to_compact_string()),
+        );
+    }
+
+    let mut headers = CheapVec::new();
+
+    headers.push((
+        "Cache-Control".to_compact_string(),
+        cache_control.to_compact_string(),
+    ));
+
+    if !vary.is_empty() {
+        headers.push((
+            "Vary".to_compact_string(),
+            vary.join(", ").to_compact_string(),
+        ));
+    }
+
+    headers
+}
+
+/// Whether the response may be cached, i.e. it neither sets state on the client nor sets its own caching.
+fn is_cacheable(headers: &HashMap<CompactString, CompactString>) -> bool {
+    !headers.keys().any(|name| {
+        ["set-cookie", "location", "cache-control"].contains(&name.to_lowercase().as_str())
+    })
+}
+
+/// The scopes which invalidate the endpoint's responses: the first literal segment of its route
+/// (e.g. `users` for both `users/{id}` and `{tenant}/users`), as params match any segment, and
+/// its tags.
+fn endpoint_scopes(endpoint: &Endpoint) -> CheapVec<CompactString, 0> {
+    let prefix = endpoint
+        .route()
+        .split('/')
+        .find(|segment| !segment.is_empty() && !segment.starts_with('{'))
+        .unwrap_or_default();
+
+    let mut scopes = CheapVec::new();
+
+    scopes.push(format!("route/{}", prefix).to_compact_string());
+
+    scopes.extend(
+        endpoint
+            .tags()
+            .iter()
+            .map(|tag| format!("tag/{}", tag).to_compact_string()),
+    );
+
+    scopes
+}
+
+/// The generation of the scopes, which increases whenever any of them is invalidated.
+fn scopes_generation(scopes: &[CompactString]) -> u64 {
+    scopes
+        .iter()
+        .map(|scope| GENERATIONS.get(scope).map_or(0, |generation| *generation))
+        .sum()
+}
+
+/// Caches the response, unless the cache is full or its scopes have been invalidated since the
+/// given `generation`, when the response started being computed.
+fn store_response(
+    key: CompactString,
+    cache: &EndpointCache,
+    scopes: CheapVec<CompactString, 0>,
+    headers: Option<HashMap<CompactString, CompactString>>,
+    value: serde_json::Value,
+    generation: u64,
+) {
+    let now = Instant::now();
+
+    // Invalidations which happen after this check outdate it once it's inserted.
+    if scopes_generation(&scopes[..]) != generation {
+        return;
+    }
+
+    if RESPONSE_CACHE.len() >= MAX_CACHED_RESPONSES && !RESPONSE_CACHE.contains_key(&key) {
+        RESPONSE_CACHE.retain(|_, cached| {
+            now < cached.expires_at && scopes_generation(&cached.scopes[..]) == cached.generation
+        });
+
+        if RESPONSE_CACHE.len() >= MAX_CACHED_RESPONSES {
+            debug!("The response cache is full, '{}' won't be cached.", key);
+            return;
+        }
+    }
+
+    RESPONSE_CACHE.insert(
+        key,
+        CachedResponse {
+            headers,
+            value,
+            scopes,
+            generation,
+            stored_at: now,
+            expires_at: now + Duration::from_secs(cache.ttl() + cache.stale_while_revalidate()),
+            refreshing: false,
+        },
+    );
+}
+
+/// Outdates the cached responses sharing any scope with the write endpoint (e.g. `users` and
+/// `users/{id}`, or its target table's tag), which stop being served right away.
+fn invalidate_responses(endpoint: &Endpoint) {
+    for scope in endpoint_scopes(endpoint).iter() {
+        *GENERATIONS.entry(scope.to_owned()).or_insert(0) += 1;
+    }
+}
+
+/// Serves the cached responses of the endpoints opting in, and invalidates them on successful writes.
+#[derive(Clone, Constructor, Debug)]
+pub struct ResponseCache<S>
+where
+    S: Service<RequestParamsExtractorRequest, Response = ExecuteOutput, Error = RequestError>,
+{
+    inner: S,
+}
+
+pub struct ResponseCacheLayer;
+
+impl<S> Layer<S> for ResponseCacheLayer
+where
+    S: Service<RequestParamsExtractorRequest, Response = ExecuteOutput, Error = RequestError>,
+{
+    type Service = ResponseCache<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ResponseCache { inner }
+    }
+}
+
+impl<S> Service<RequestParamsExtractorRequest> for ResponseCache<S>
+where
+    S: Service<RequestParamsExtractorRequest, Response = ExecuteOutput, Error = RequestError>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send + 'static,
+    S::Response: Send + 'static,
+    S::Error: Send + 'static,
+{
+    type Response = S::Response;
+
+    type Error = S::Error;
+
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    #[instrument(skip_all)]
+    fn call(&mut self, cx: RequestParamsExtractorRequest) -> Self::Future {
+        let mut inner = self.inner.to_owned();
+
+        Box::pin(async move {
+            let (headers, endpoint, request_params, request_body) = cx;
+
+            if *endpoint.method() != HttpMethod::Get {
+                let output = inner
+                    .call((headers, endpoint.to_owned(), request_params, request_body))
+                    .await;
+
+                // Internal endpoints (e.g. the logins or the admin ones) don't write the data of
+                // the cached ones.
+                if output.is_ok() && endpoint.execute().is_some() {
+                    invalidate_responses(&endpoint);
+                }
+
+                return output;
+            }
+
+            let http_cache_time = RuntimeCx::acquire()
+                .build()
+                .read()
+                .await
+                .executor()
+                .http_cache_time()
+                .to_owned();
+
+            let Some(cache) = endpoint_cache(&endpoint, http_cache_time) else {
+                return inner
+                    .call((headers, endpoint, request_params, request_body))
+                    .await;
+            };
+
+            let key = cache_key(&endpoint, &cache, &headers, &request_params);
+
+            let now = Instant::now();
+
+            let scopes = endpoint_scopes(&endpoint);
+
+            let generation = scopes_generation(&scopes[..]);
+
+            // Fresh responses are served right away, and stale ones while a single request refreshes them.
+            let cached = match RESPONSE_CACHE.get_mut(&key) {
+                Some(mut cached) if now < cached.expires_at && cached.generation == generation => {
+                    let stale = now.saturating_duration_since(cached.stored_at)
+                        >= Duration::from_secs(*cache.ttl());
+
+                    let refresh = stale && !cached.refreshing;
+
+                    if refresh {
+                        cached.refreshing = true;
+                    }
+
+                    Some((
+                        cached.headers.to_owned(),
+                        cached.value.to_owned(),
+                        cached.stored_at,
+                        refresh,
+                    ))
+                }
+                _ => None,
+            };
+
+            if let Some((mut cached_headers, value, stored_at, refresh)) = cached {
+                if refresh {
+                    tokio::spawn(async move {
+                        match inner
+                            .call((headers, endpoint.to_owned(), request_params, request_body))
+                            .await
+                        {
+                            Ok(ExecuteOutput::Json(mut headers, value))
+                                if headers.as_ref().is_none_or(is_cacheable) =>
+                            {
+                                headers
+                                    .get_or_insert_default()
+                                    .extend(cache_headers(&endpoint, &cache));
+
+                                store_response(key, &cache, scopes, headers, value, generation);
+                            }
+                            _ => {
+                                // Another request will try again.
+                                if let Some(mut cached) = RESPONSE_CACHE.get_mut(&key) {
+                                    cached.refreshing = false;
+                                }
+                            }
+                        }
+                    });
+                }
+
+                cached_headers.get_or_insert_default().insert(
+                    "Age".to_compact_string(),
+                    now.saturating_duration_since(stored_at)
+                        .as_secs()
+                        .to_compact_string(),
+                );
+
+                return Ok(ExecuteOutput::Json(cached_headers, value));
+            }
+
+            let mut output = inner
+                .call((headers, endpoint.to_owned(), request_params, request_body))
+                .await;
+
+            match output.as_mut() {
+                Ok(ExecuteOutput::Json(headers, value))
+                    if headers.as_ref().is_none_or(is_cacheable) =>
+                {
+                    headers
+                        .get_or_insert_default()
+                        .extend(cache_headers(&endpoint, &cache));
+
+                    store_response(
+                        key,
+                        &cache,
+                        scopes,
+                        headers.to_owned(),
+                        value.to_owned(),
+                        generation,
+                    );
+                }
+                _ => {}
+            }
+
+            output
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(id: &str, route: &str, method: HttpMethod, tags: &[&str]) -> Endpoint {
+        EndpointBuilder::default()
+            .id(id.to_compact_string())
+            .route(route.to_compact_string())
+            .method(method)
+            .tags(CheapVec::from_vec(
+                tags.iter().map(|tag| tag.to_compact_string()).collect(),
+            ))
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn cache_keys() {
+        let endpoint = endpoint("list_users", "users", HttpMethod::Get, &[]);
+
+        let cache = EndpointCache::new(
+            60,
+            0,
+            CheapVec::from_vec(vec!["Accept-Language".to_compact_string()]),
+        );
+
+        let params = |page: &str, consumer: Option<&str>| {
+            let mut params = HashMap::from([
+                (
+                    "page".to_compact_string(),
+                    ExecuteParamValue::Client(Some(page.to_compact_string())),
+                ),
+                (
+                    "limit".to_compact_string(),
+                    ExecuteParamValue::Client(Some("10".to_compact_string())),
+                ),
+            ]);
+
+            if let Some(consumer) = consumer {
+                params.insert(
+                    CONSUMER_PARAM.to_compact_string(),
+                    ExecuteParamValue::Internal(consumer.to_compact_string()),
+                );
+            }
+
+            params
+        };
+
+        let language = |language: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert("Accept-Language", HeaderValue::from_str(language).unwrap());
+            headers
+        };
+
+        let key = cache_key(&endpoint, &cache, &language("en"), &params("1", None));
+
+        // The same request has the same key, whatever the params' order.
+        assert_eq!(
+            key,
+            cache_key(&endpoint, &cache, &language("en"), &params("1", None))
+        );
+
+        // While the params, the headers it varies by and the consumer make a difference.
+        assert_ne!(
+            key,
+            cache_key(&endpoint, &cache, &language("en"), &params("2", None))
+        );
+        assert_ne!(
+            key,
+            cache_key(&endpoint, &cache, &language("es"), &params("1", None))
+        );
+        assert_ne!(
+            key,
+            cache_key(
+                &endpoint,
+                &cache,
+                &language("en"),
+                &params("1", Some("user:1"))
+            )
+        );
+    }
+
+    #[test]
+    fn cacheable_responses_and_their_headers() {
+        assert!(is_cacheable(&HashMap::new()));
+        assert!(!is_cacheable(&HashMap::from([(
+            "Set-Cookie".to_compact_string(),
+            "session=1".to_compact_string(),
+        )])));
+        assert!(!is_cacheable(&HashMap::from([(
+            "location".to_compact_string(),
+            "/".to_compact_string(),
+        )])));
+
+        let cache = EndpointCache::new(
+            60,
+            30,
+            CheapVec::from_vec(vec!["Accept-Language".to_compact_string()]),
+        );
+
+        let public = endpoint("list_users", "users", HttpMethod::Get, &[]);
+
+        assert_eq!(
+            cache_headers(&public, &cache).to_vec(),
+            vec![
+                (
+                    "Cache-Control".to_compact_string(),
+                    "public, max-age=60, stale-while-revalidate=30".to_compact_string()
+                ),
+                (
+                    "Vary".to_compact_string(),
+                    "Accept-Language".to_compact_string()
+                ),
+            ]
+        );
+
+        // Responses requiring auth vary by whatever identifies the consumer.
+        let private = EndpointBuilder::default()
+            .id("get_profile".to_compact_string())
+            .route("profile".to_compact_string())
+            .method(HttpMethod::Get)
+            .require_auth(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            cache_headers(&private, &cache).to_vec(),
+            vec![
+                (
+                    "Cache-Control".to_compact_string(),
+                    "private, max-age=60, stale-while-revalidate=30".to_compact_string()
+                ),
+                (
+                    "Vary".to_compact_string(),
+                    format!("Accept-Language, Authorization, Cookie, {}", API_KEY_HEADER)
+                        .to_compact_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn writes_invalidate_overlapping_routes_and_tags() {
+        let cache = EndpointCache::new(60, 0, CheapVec::new());
+
+        let cached = [
+            endpoint("test_list_users", "test_users", HttpMethod::Get, &[]),
+            endpoint("test_get_user", "test_users/{id}", HttpMethod::Get, &[]),
+            endpoint(
+                "test_list_orders",
+                "{tenant}/test_orders",
+                HttpMethod::Get,
+                &["test_orders"],
+            ),
+            endpoint(
+                "test_stats",
+                "test_stats",
+                HttpMethod::Get,
+                &["test_orders"],
+            ),
+        ];
+
+        let generation = |endpoint: &Endpoint| scopes_generation(&endpoint_scopes(endpoint)[..]);
+
+        for endpoint in &cached {
+            store_response(
+                endpoint.id().to_owned(),
+                &cache,
+                endpoint_scopes(endpoint),
+                None,
+                json!({}),
+                generation(endpoint),
+            );
+        }
+
+        let current = |id: &str| {
+            RESPONSE_CACHE
+                .get(id)
+                .is_some_and(|cached| scopes_generation(&cached.scopes[..]) == cached.generation)
+        };
+
+        let list_generation = generation(&cached[0]);
+
+        // Updating a user invalidates both the user and the list, but nothing else.
+        invalidate_responses(&endpoint(
+            "test_update_user",
+            "/test_users/{user_id}/",
+            HttpMethod::Put,
+            &[],
+        ));
+
+        assert!(!current("test_list_users"));
+        assert!(!current("test_get_user"));
+        assert!(current("test_list_orders"));
+
+        // Params don't match literal segments, so other routes starting with one aren't affected.
+        invalidate_responses(&endpoint(
+            "test_update_tenant",
+            "{tenant}/test_tenants",
+            HttpMethod::Put,
+            &[],
+        ));
+
+        assert!(current("test_list_orders"));
+
+        // Creating an order invalidates those sharing its tag too.
+        invalidate_responses(&endpoint(
+            "test_create_order",
+            "test_orders",
+            HttpMethod::Post,
+            &["test_orders"],
+        ));
+
+        assert!(!current("test_list_orders"));
+        assert!(!current("test_stats"));
+
+        // Responses computed before an invalidation aren't cached.
+        RESPONSE_CACHE.remove("test_list_users");
+
+        store_response(
+            "test_list_users".to_compact_string(),
+            &cache,
+            endpoint_scopes(&cached[0]),
+            None,
+            json!({}),
+            list_generation,
+        );
+
+        assert!(!RESPONSE_CACHE.contains_key("test_list_users"));
+    }
+}
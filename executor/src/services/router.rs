@@ -62,18 +62,7 @@ where
                 return Box::pin(async move {
                     let response = Response::builder()
                         .header("Content-Type", "application/json; charset=utf-8")
-                        .header(
-                            "Cache-Control",
-                            format!(
-                                "max-age={}",
-                                (*RuntimeCx::acquire()
-                                    .build()
-                                    .read()
-                                    .await
-                                    .executor()
-                                    .http_cache_time()) as u32
-                            ),
-                        );
+                        .header("Cache-Control", "no-store");
 
                     Ok(response
                         .status(404)
@@ -109,18 +98,7 @@ where
                 return Box::pin(async move {
                     let response = Response::builder()
                         .header("Content-Type", "application/json; charset=utf-8")
-                        .header(
-                            "Cache-Control",
-                            format!(
-                                "max-age={}",
-                                (*RuntimeCx::acquire()
-                                    .build()
-                                    .read()
-                                    .await
-                                    .executor()
-                                    .http_cache_time()) as u32
-                            ),
-                        );
+                        .header("Cache-Control", "no-store");
 
                     Ok(response
                             .status(404)
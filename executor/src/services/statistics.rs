@@ -0,0 +1,36 @@
+// Waveless
+// Copyright (C) 2026 Oscar Alvarez Gonzalez
+
+use crate::*;
+
+/// Reports the statistics of this instance, only allowed to the admin roles.
+#[derive(Clone, Constructor, Debug)]
+pub struct StatisticsCaptured;
+
+impl Service<RequestParamsExtractorRequest> for StatisticsCaptured {
+    type Response = ExecuteOutput;
+
+    type Error = RequestError;
+
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut std::task::Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    #[instrument(skip_all)]
+    fn call(&mut self, _cx: RequestParamsExtractorRequest) -> Self::Future {
+        let future: Pin<_> = Box::pin(async move {
+            // `null` when the query cache isn't set.
+            let query_cache = query_cache().await?.map(|query_cache| query_cache.stats());
+
+            Ok(ExecuteOutput::Json(
+                None,
+                json!({ "query_cache": query_cache }),
+            ))
+        })
+        .into();
+
+        future as Self::Future // Actually, this is not an error! https://github.com/rust-lang/rust/issues/92929
+    }
+}